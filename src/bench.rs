@@ -0,0 +1,116 @@
+use std::time::{Duration, Instant};
+
+use gittask::{Label, Task};
+
+use crate::util::error_message;
+
+const STATUSES: [&str; 3] = ["OPEN", "IN_PROGRESS", "CLOSED"];
+const LABELS: [&str; 4] = ["bug", "feature", "docs", "ui"];
+const ASSIGNEES: [&str; 3] = ["alice", "bob", "carol"];
+
+struct Setup {
+    name: &'static str,
+    sharded: bool,
+    index: bool,
+}
+
+const SETUPS: [Setup; 3] = [
+    Setup { name: "flat", sharded: false, index: false },
+    Setup { name: "sharded", sharded: true, index: false },
+    Setup { name: "sharded+index", sharded: true, index: true },
+];
+
+const OPERATIONS: [&str; 4] = ["create", "list", "filter", "export"];
+
+/// Generates `count` synthetic tasks in temporary repositories for every storage setup
+/// and prints throughput of the main operations as a comparison table.
+pub(crate) fn task_bench(count: usize) -> bool {
+    if count == 0 {
+        return error_message("Task count must be positive".to_string());
+    }
+
+    let mut results = vec![];
+    for setup in &SETUPS {
+        eprintln!("Running {} with {count} task(s)...", setup.name);
+        let result = gittask::TaskRepository::temporary().and_then(|repository| repository.run(|| bench_setup(setup, count)));
+        match result {
+            Ok(result) => results.push(result),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    print!("{:<10}", "operation");
+    SETUPS.iter().for_each(|setup| print!("{:>22}", setup.name));
+    println!();
+    for (n, operation) in OPERATIONS.iter().enumerate() {
+        print!("{operation:<10}");
+        for result in &results {
+            let (elapsed, items) = result[n];
+            print!("{:>22}", format!("{} ({}/s)", format_elapsed(elapsed), rate(items, elapsed)));
+        }
+        println!();
+    }
+
+    true
+}
+
+fn bench_setup(setup: &Setup, count: usize) -> Result<Vec<(Duration, usize)>, String> {
+    gittask::create_task(generate_task(0))?;
+    if setup.sharded {
+        gittask::set_layout(true)?;
+    }
+    if setup.index {
+        gittask::set_index(true)?;
+    }
+
+    let start = Instant::now();
+    for n in 1..count {
+        gittask::create_task(generate_task(n))?;
+    }
+    let create = (start.elapsed(), count - 1);
+
+    let start = Instant::now();
+    let listed = gittask::list_task_summaries()?.len();
+    let list = (start.elapsed(), listed);
+
+    let start = Instant::now();
+    let filter = ("status", vec!["IN_PROGRESS".to_string()]);
+    let filtered = match gittask::find_tasks_by_index(std::slice::from_ref(&filter))? {
+        Some(tasks) => tasks.len(),
+        None => gittask::list_task_summaries()?.iter().filter(|task| task.get_property("status").is_some_and(|status| filter.1.contains(status))).count(),
+    };
+    let filter = (start.elapsed(), filtered);
+
+    let start = Instant::now();
+    let tasks = gittask::list_tasks()?;
+    serde_json::to_string(&tasks).map_err(|e| e.to_string())?;
+    let export = (start.elapsed(), tasks.len());
+
+    Ok(vec![create, list, filter, export])
+}
+
+fn generate_task(n: usize) -> Task {
+    let mut builder = Task::builder(&format!("Synthetic task {}", n + 1))
+        .status(STATUSES[n % STATUSES.len()])
+        .description(&"Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(4))
+        .property("assignee", ASSIGNEES[n % ASSIGNEES.len()])
+        .label(Label::new(LABELS[n % LABELS.len()].to_string(), None, None));
+    if n.is_multiple_of(5) {
+        builder = builder.comment("First synthetic comment").comment("Second synthetic comment");
+    }
+    builder.build().unwrap()
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    match elapsed.as_millis() {
+        ms if ms >= 1000 => format!("{:.2}s", elapsed.as_secs_f64()),
+        ms => format!("{ms}ms"),
+    }
+}
+
+fn rate(items: usize, elapsed: Duration) -> u64 {
+    match elapsed.as_secs_f64() {
+        secs if secs > 0.0 => (items as f64 / secs) as u64,
+        _ => items as u64,
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static TESTCASE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<testcase\b([^>]*?)(?:/>|>(.*?)</testcase>)").unwrap()
+});
+
+static FAILURE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<(failure|error)\b([^>]*?)(?:/>|>(.*?)</(?:failure|error)>)").unwrap()
+});
+
+static ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+pub const DEFAULT_LOG_PATTERN: &str = r"(?i)\b(?:error|failed|panicked)\b";
+
+pub struct Failure {
+    pub name: String,
+    pub message: String,
+}
+
+/// Extracts failed and errored test cases from a JUnit XML report.
+pub fn parse_junit(input: &str) -> Vec<Failure> {
+    TESTCASE_REGEX.captures_iter(input).filter_map(|caps| {
+        let body = caps.get(2)?.as_str();
+        let failure = FAILURE_REGEX.captures(body)?;
+
+        let attributes = &caps[1];
+        let name = get_attribute(attributes, "name").unwrap_or_default();
+        let name = match get_attribute(attributes, "classname") {
+            Some(classname) if !classname.is_empty() => format!("{classname}::{name}"),
+            _ => name,
+        };
+
+        let mut message = format!("{}: {}", &failure[1], get_attribute(&failure[2], "message").unwrap_or_default());
+        if let Some(text) = failure.get(3).map(|text| unescape_xml(strip_cdata(text.as_str().trim()))).filter(|text| !text.is_empty()) {
+            message.push_str("\n\n");
+            message.push_str(&text);
+        }
+
+        Some(Failure { name, message })
+    }).collect()
+}
+
+/// Extracts lines matching the pattern from a log, the `name` group (or the whole line) becomes the failure name.
+pub fn parse_log(input: &str, pattern: &str) -> Result<Vec<Failure>, String> {
+    let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+    let mut result: Vec<Failure> = vec![];
+
+    for line in input.lines() {
+        if let Some(caps) = regex.captures(line) {
+            let name = caps.name("name").map(|m| m.as_str()).unwrap_or(line).trim().to_string();
+            if !name.is_empty() && !result.iter().any(|failure| failure.name == name) {
+                result.push(Failure { name, message: line.trim().to_string() });
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+fn get_attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE_REGEX.captures_iter(attributes)
+        .find(|caps| &caps[1] == name)
+        .map(|caps| unescape_xml(caps.get(2).or_else(|| caps.get(3)).unwrap().as_str()))
+}
+
+fn strip_cdata(text: &str) -> &str {
+    text.strip_prefix("<![CDATA[").and_then(|text| text.strip_suffix("]]>")).unwrap_or(text)
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_junit() {
+        let input = r#"<?xml version="1.0"?>
+<testsuite name="suite" tests="3">
+  <testcase classname="app.Login" name="passes"/>
+  <testcase classname="app.Login" name="fails" time="0.1">
+    <failure message="expected &quot;a&quot;" type="AssertionError"><![CDATA[stack trace]]></failure>
+  </testcase>
+  <testcase name="crashes"><error message="boom"/></testcase>
+  <testcase name="skipped"><skipped/></testcase>
+</testsuite>"#;
+        let failures = parse_junit(input);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].name, "app.Login::fails");
+        assert_eq!(failures[0].message, "failure: expected \"a\"\n\nstack trace");
+        assert_eq!(failures[1].name, "crashes");
+        assert_eq!(failures[1].message, "error: boom");
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let input = "running 3 tests\ntest a ... ok\ntest b ... FAILED\ntest b ... FAILED\nerror: build failed\n";
+        assert_eq!(parse_log(input, DEFAULT_LOG_PATTERN).unwrap().len(), 2);
+        let failures = parse_log(input, r"^test (?P<name>\S+) \.\.\. FAILED").unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, "b");
+        assert!(parse_log(input, "(").is_err());
+    }
+}
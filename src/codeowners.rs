@@ -0,0 +1,95 @@
+use regex::Regex;
+
+use gittask::Task;
+
+const CODEOWNERS_LOCATIONS: [&str; 4] = ["CODEOWNERS", ".github/CODEOWNERS", ".gitlab/CODEOWNERS", "docs/CODEOWNERS"];
+
+pub struct CodeOwners {
+    rules: Vec<(Regex, Vec<String>)>,
+}
+
+impl CodeOwners {
+    pub fn load() -> Option<CodeOwners> {
+        CODEOWNERS_LOCATIONS.iter()
+            .find_map(|location| gittask::read_repo_file(location).ok().flatten())
+            .map(|content| CodeOwners::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> CodeOwners {
+        let rules = content.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('['))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = pattern_to_regex(parts.next()?)?;
+                let owners = parts.take_while(|part| !part.starts_with('#')).map(|owner| owner.to_string()).collect();
+                Some((pattern, owners))
+            })
+            .collect();
+
+        CodeOwners { rules }
+    }
+
+    /// Returns owners of the path, the last matching rule takes precedence like in GitHub and Gitlab.
+    pub fn get_owners(&self, path: &str) -> Vec<String> {
+        self.rules.iter().rev()
+            .find(|(pattern, _)| pattern.is_match(path))
+            .map(|(_, owners)| owners.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Sets `assignee` to the first owner of the task `path` (or `component`) if it's not assigned yet.
+/// Controlled by `task.assign.auto` config parameter, enabled by default.
+pub fn auto_assign(task: &mut Task, codeowners: &Option<CodeOwners>) -> Option<String> {
+    if task.has_property("assignee") || gittask::get_config_value("task.assign.auto").is_ok_and(|value| value == "false") {
+        return None;
+    }
+
+    let path = task.get_property("path").or_else(|| task.get_property("component"))?.clone();
+    let owner = codeowners.as_ref()?.get_owners(&path).into_iter().next()?;
+    let owner = owner.trim_start_matches('@').to_string();
+    task.set_property("assignee", &owner);
+
+    Some(owner)
+}
+
+fn pattern_to_regex(pattern: &str) -> Option<Regex> {
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            },
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push_str("(?:/.*)?$");
+
+    Regex::new(&regex).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CODEOWNERS: &str = "# Owners\n* @core-team\n/src/frontend/ @frontend-team @alice\ndocs @writers # inline comment\n/src/**/api @backend\n";
+
+    #[test]
+    fn test_get_owners() {
+        let codeowners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(codeowners.get_owners("README.md"), vec!["@core-team"]);
+        assert_eq!(codeowners.get_owners("src/frontend"), vec!["@frontend-team", "@alice"]);
+        assert_eq!(codeowners.get_owners("src/frontend/app"), vec!["@frontend-team", "@alice"]);
+        assert_eq!(codeowners.get_owners("lib/docs"), vec!["@writers"]);
+        assert_eq!(codeowners.get_owners("src/server/v1/api"), vec!["@backend"]);
+        assert_eq!(codeowners.get_owners("src/frontend-old"), vec!["@core-team"]);
+    }
+}
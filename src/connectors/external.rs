@@ -0,0 +1,259 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use gittask::{Comment, Label, Task};
+use crate::connectors::{CommentWindow, RemoteConnector, RemoteTaskFilter, RemoteTaskState};
+use crate::util::{error_message_kind, ErrorKind};
+
+/// Executables named `git-task-connector-<name>` found on PATH are available as `--connector <name>`.
+pub(crate) const PLUGIN_PREFIX: &str = "git-task-connector-";
+/// Version of the JSON protocol sent with every request.
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Out-of-tree connector running as an external process. Every call spawns the executable,
+/// writes a single `{"version", "method", "params"}` request to its stdin and reads
+/// `{"result": ...}` or `{"error": "message"}` from its stdout. Tasks, comments and labels
+/// are in the `git task export` format, stderr of the plugin is passed through.
+pub struct ExternalRemoteConnector {
+    name: &'static str,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<String>,
+}
+
+impl ExternalRemoteConnector {
+    pub fn new(name: &str, path: PathBuf) -> ExternalRemoteConnector {
+        ExternalRemoteConnector { name: Box::leak(name.to_string().into_boxed_str()), path }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>, String> {
+        let request = json!({ "version": PROTOCOL_VERSION, "method": method, "params": params });
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Could not run {}: {e}", self.path.display()))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(request.to_string().as_bytes());
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+        match serde_json::from_slice::<Response<T>>(&output.stdout) {
+            Ok(Response { error: Some(error), .. }) => Err(error),
+            Ok(Response { result, .. }) if output.status.success() => Ok(result),
+            Ok(_) => Err(format!("{} {method} failed with {}", self.name, output.status)),
+            Err(e) => Err(format!("Invalid response of {} to {method}: {e}", self.name)),
+        }
+    }
+
+    /// Calls the method that must return a value.
+    fn call_for<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        self.call(method, params)?.ok_or_else(|| format!("{} returned no result for {method}", self.name))
+    }
+
+    /// Calls the method that returns nothing, a `null` result is fine.
+    fn call_unit(&self, method: &str, params: Value) -> Result<(), String> {
+        self.call::<Value>(method, params).map(|_| ())
+    }
+}
+
+fn state_name(state: &RemoteTaskState) -> &'static str {
+    match state {
+        RemoteTaskState::All => "all",
+        RemoteTaskState::Open => "open",
+        RemoteTaskState::Closed => "closed",
+    }
+}
+
+impl RemoteConnector for ExternalRemoteConnector {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn supports_remote(&self, url: &str) -> Option<(String, String)> {
+        match self.call("supports_remote", json!({ "url": url })) {
+            Ok(result) => result,
+            Err(e) => {
+                error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+                None
+            }
+        }
+    }
+
+    fn list_remote_tasks(&self, user: &String, repo: &String, with_comments: bool, comment_window: &CommentWindow, with_labels: bool, limit: Option<usize>, state: RemoteTaskState, filter: &RemoteTaskFilter, task_statuses: &Vec<String>) -> Result<Vec<Task>, String> {
+        let params = json!({
+            "user": user,
+            "repo": repo,
+            "with_comments": with_comments,
+            "comment_window": { "since": comment_window.since, "max": comment_window.max },
+            "with_labels": with_labels,
+            "limit": limit,
+            "state": state_name(&state),
+            "filter": { "updated_since": filter.updated_since, "labels": filter.labels, "author": filter.author },
+            "task_statuses": task_statuses,
+        });
+        self.call("list_remote_tasks", params).map(Option::unwrap_or_default)
+    }
+
+    fn get_remote_task(&self, user: &String, repo: &String, task_id: &String, with_comments: bool, comment_window: &CommentWindow, with_labels: bool, task_statuses: &Vec<String>) -> Option<Task> {
+        let params = json!({
+            "user": user,
+            "repo": repo,
+            "task_id": task_id,
+            "with_comments": with_comments,
+            "comment_window": { "since": comment_window.since, "max": comment_window.max },
+            "with_labels": with_labels,
+            "task_statuses": task_statuses,
+        });
+        match self.call("get_remote_task", params) {
+            Ok(task) => task,
+            Err(e) => {
+                error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+                None
+            }
+        }
+    }
+
+    fn create_remote_task(&self, user: &String, repo: &String, task: &Task) -> Result<String, String> {
+        self.call_for("create_remote_task", json!({ "user": user, "repo": repo, "task": task }))
+    }
+
+    fn create_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
+        self.call_for("create_remote_comment", json!({ "user": user, "repo": repo, "task_id": task_id, "comment": comment }))
+    }
+
+    fn create_remote_label(&self, user: &String, repo: &String, task_id: &String, label: &Label) -> Result<(), String> {
+        self.call_unit("create_remote_label", json!({ "user": user, "repo": repo, "task_id": task_id, "label": label }))
+    }
+
+    fn update_remote_task(&self, user: &String, repo: &String, task: &Task, labels: Option<&Vec<Label>>, state: RemoteTaskState) -> Result<(), String> {
+        self.call_unit("update_remote_task", json!({ "user": user, "repo": repo, "task": task, "labels": labels, "state": state_name(&state) }))
+    }
+
+    fn update_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String, text: &String) -> Result<(), String> {
+        self.call_unit("update_remote_comment", json!({ "user": user, "repo": repo, "task_id": task_id, "comment_id": comment_id, "text": text }))
+    }
+
+    fn delete_remote_task(&self, user: &String, repo: &String, task_id: &String) -> Result<(), String> {
+        self.call_unit("delete_remote_task", json!({ "user": user, "repo": repo, "task_id": task_id }))
+    }
+
+    fn delete_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String) -> Result<(), String> {
+        self.call_unit("delete_remote_comment", json!({ "user": user, "repo": repo, "task_id": task_id, "comment_id": comment_id }))
+    }
+
+    fn delete_remote_label(&self, user: &String, repo: &String, task_id: &String, name: &String) -> Result<(), String> {
+        self.call_unit("delete_remote_label", json!({ "user": user, "repo": repo, "task_id": task_id, "name": name }))
+    }
+
+    fn check_connection(&self, user: &str, repo: &str) -> Result<String, String> {
+        self.call_for("check_connection", json!({ "user": user, "repo": repo }))
+    }
+}
+
+/// Finds `git-task-connector-*` executables on PATH, the first one of each name wins.
+pub(crate) fn find_plugins() -> Vec<(String, PathBuf)> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return vec![];
+    };
+
+    let mut result: Vec<(String, PathBuf)> = vec![];
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found = entries.flatten()
+            .filter_map(|entry| Some((plugin_name(&entry.file_name().to_string_lossy())?, entry.path())))
+            .filter(|(_, path)| is_executable(path))
+            .collect::<Vec<_>>();
+        found.sort();
+        for (name, path) in found {
+            if !result.iter().any(|(known, _)| *known == name) {
+                result.push((name, path));
+            }
+        }
+    }
+
+    result
+}
+
+fn plugin_name(file_name: &str) -> Option<String> {
+    let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+    let name = if cfg!(windows) { name.strip_suffix(".exe").unwrap_or(name) } else { name };
+    match !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        true => Some(name.to_string()),
+        false => None,
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_plugin_name() {
+        assert_eq!(plugin_name("git-task-connector-redmine"), Some("redmine".to_string()));
+        assert_eq!(plugin_name("git-task-connector-azure_devops"), Some("azure_devops".to_string()));
+        assert_eq!(plugin_name("git-task-connector-"), None);
+        assert_eq!(plugin_name("git-task-connector-foo.sh~"), None);
+        assert_eq!(plugin_name("git-task"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_connector() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("git-task-connector-echo");
+        std::fs::write(&path, r#"#!/bin/sh
+read -r request
+case "$request" in
+  *'"method":"supports_remote"'*) echo '{"result":["team","project"]}' ;;
+  *'"method":"create_remote_task"'*) echo '{"result":"42"}' ;;
+  *'"method":"list_remote_tasks"'*) echo '{"result":[{"id":"7","props":{"name":"Remote","status":"OPEN"}}]}' ;;
+  *'"method":"delete_remote_task"'*) echo '{"result":null}' ;;
+  *) echo '{"error":"not supported"}'; exit 1 ;;
+esac
+"#).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let connector = ExternalRemoteConnector::new("echo", path);
+        let (user, repo) = connector.supports_remote("https://tracker.example.com/team/project").unwrap();
+        assert_eq!((user.as_str(), repo.as_str()), ("team", "project"));
+
+        let task = Task::builder("Crash").status("OPEN").build().unwrap();
+        assert_eq!(connector.create_remote_task(&user, &repo, &task), Ok("42".to_string()));
+        assert!(connector.delete_remote_task(&user, &repo, &"42".to_string()).is_ok());
+        assert_eq!(connector.check_connection(&user, &repo), Err("not supported".to_string()));
+
+        let tasks = connector.list_remote_tasks(&user, &repo, false, &CommentWindow::default(), false, None, RemoteTaskState::All, &RemoteTaskFilter::default(), &vec![]).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].get_property("name").map(String::as_str), Some("Remote"));
+    }
+}
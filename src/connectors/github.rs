@@ -1,33 +1,33 @@
-use std::collections::HashMap;
-use std::sync::{Arc, LazyLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
 
-use futures_util::{StreamExt, TryStreamExt};
 use graphql_client::{reqwest::post_graphql_blocking as post_graphql, GraphQLQuery};
-use octocrab::Octocrab;
-use octocrab::models::{CommentId, IssueState};
-use octocrab::params::State;
-use regex::Regex;
-use tokio::pin;
-use tokio::runtime::Runtime;
+use octocrab::models::issues::{Comment as IssueComment, Issue};
+use octocrab::models::{IssueState, Label as IssueLabel, Repository};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, Url};
+use serde::de::DeserializeOwned;
+use serde_json::json;
 
 use gittask::{Comment, Label, Task};
-use crate::connectors::{RemoteConnector, RemoteTaskState};
+use crate::connectors::http::HttpOptions;
+use crate::connectors::{parse_remote_url, type_from_labels, with_type_label, CommentWindow, LabelCache, RemoteConnector, RemoteTaskFilter, RemoteTaskState};
 use crate::util::color_str_to_rgb_str;
 
 pub struct GithubRemoteConnector;
 
-static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
-    Runtime::new().unwrap()
-});
+static LABEL_CACHE: LazyLock<LabelCache> = LazyLock::new(LabelCache::default);
 
 impl RemoteConnector for GithubRemoteConnector {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
     fn supports_remote(&self, url: &str) -> Option<(String, String)> {
-        match Regex::new("((https://)|(git@))github.com[/:](?P<user>[a-zA-Z0-9-]+)/(?P<repo>[a-zA-Z0-9-]+)(\\.git)?").unwrap().captures(url) {
-            Some(caps) if caps.len() >= 3 => {
-                let user = caps.name("user")?.as_str().to_string();
-                let repo = caps.name("repo")?.as_str().to_string();
-                Some((user, repo))
-            },
+        let url = parse_remote_url(url).filter(|url| url.is_on(&get_base_url()))?;
+        match url.segments().as_slice() {
+            [user, repo] => Some((user.to_string(), repo.to_string())),
             _ => None,
         }
     }
@@ -37,26 +37,18 @@ impl RemoteConnector for GithubRemoteConnector {
         user: &String,
         repo: &String,
         with_comments: bool,
+        comment_window: &CommentWindow,
         with_labels: bool,
         limit: Option<usize>,
         state: RemoteTaskState,
+        filter: &RemoteTaskFilter,
         task_statuses: &Vec<String>
-    ) -> Vec<Task> {
-        let state = match state {
-            RemoteTaskState::Open => State::Open,
-            RemoteTaskState::Closed => State::Closed,
-            RemoteTaskState::All => State::All,
-        };
-        RUNTIME.block_on(
-            list_issues(
-                user,
-                repo,
-                with_comments,
-                with_labels,
-                limit,
-                state,
-                task_statuses
-            ))
+    ) -> Result<Vec<Task>, String> {
+        if let Some(token) = get_token_from_env() {
+            return list_issues_batched(&token, user, repo, with_comments, comment_window, with_labels, limit, state, filter, task_statuses);
+        }
+
+        list_issues(user, repo, with_comments, comment_window, with_labels, limit, state, filter, task_statuses)
     }
 
     fn get_remote_task(
@@ -65,26 +57,23 @@ impl RemoteConnector for GithubRemoteConnector {
         repo: &String,
         task_id: &String,
         with_comments: bool,
+        comment_window: &CommentWindow,
         with_labels: bool,
         task_statuses: &Vec<String>
     ) -> Option<Task> {
-        RUNTIME.block_on(
-            get_issue(
-                &user, &repo, task_id.parse().unwrap(), with_comments, with_labels, task_statuses
-            )
-        )
+        get_issue(user, repo, task_id.parse().unwrap(), with_comments, comment_window, with_labels, task_statuses)
     }
 
     fn create_remote_task(&self, user: &String, repo: &String, task: &Task) -> Result<String, String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(create_issue(user, repo, task)),
+            Some(_) => create_issue(user, repo, task),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
 
     fn create_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(create_comment(user, repo, task_id, comment)),
+            Some(_) => create_comment(user, repo, task_id, comment),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
@@ -92,13 +81,7 @@ impl RemoteConnector for GithubRemoteConnector {
     #[allow(unused_variables)]
     fn create_remote_label(&self, user: &String, repo: &String, task_id: &String, label: &Label) -> Result<(), String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(
-                add_label(
-                    user,
-                    repo,
-                    task_id.parse().unwrap(),
-                    label
-                )),
+            Some(_) => add_label(user, repo, task_id.parse().unwrap(), label),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
@@ -114,19 +97,19 @@ impl RemoteConnector for GithubRemoteConnector {
         match get_token_from_env() {
             Some(_) => {
                 let state = match state {
-                    RemoteTaskState::Closed => IssueState::Closed,
-                    _ => IssueState::Open,
+                    RemoteTaskState::Closed => "closed",
+                    _ => "open",
                 };
-                RUNTIME.block_on(
-                    update_issue(
-                        user,
-                        repo,
-                        task.get_id().unwrap().parse().unwrap(),
-                        task.get_property("name").unwrap(),
-                        task.get_property("description").unwrap(),
-                        labels,
-                        state
-                    ))
+                let labels = labels.map(|labels| with_type_label(task, labels));
+                update_issue(
+                    user,
+                    repo,
+                    task.get_id().unwrap().parse().unwrap(),
+                    task.get_property("name").unwrap(),
+                    task.get_property("description").unwrap(),
+                    labels.as_ref(),
+                    state
+                )
             },
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
@@ -134,7 +117,7 @@ impl RemoteConnector for GithubRemoteConnector {
 
     fn update_remote_comment(&self, user: &String, repo: &String, _task_id: &String, comment_id: &String, text: &String) -> Result<(), String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(update_comment(user, repo, comment_id.parse().unwrap(), text)),
+            Some(_) => update_comment(user, repo, comment_id.parse().unwrap(), text),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
@@ -142,7 +125,7 @@ impl RemoteConnector for GithubRemoteConnector {
     fn delete_remote_task(&self, user: &String, repo: &String, task_id: &String) -> Result<(), String> {
         match get_token_from_env() {
             Some(token) => {
-                let issue_id = RUNTIME.block_on(get_issue_id(user, repo, task_id.parse().unwrap()));
+                let issue_id = get_issue_id(user, repo, task_id.parse().unwrap());
                 if issue_id.is_err() {
                     return Err("Could not match task ID with GitHub internal issue ID.".to_string());
                 }
@@ -151,17 +134,9 @@ impl RemoteConnector for GithubRemoteConnector {
                     issue_id,
                 };
 
-                let client = reqwest::blocking::Client::builder()
-                    .user_agent("git-task/".to_owned() + env!("CARGO_PKG_VERSION"))
-                    .default_headers(
-                        std::iter::once((
-                            reqwest::header::AUTHORIZATION,
-                            reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
-                        )).collect(),
-                    )
-                    .build().unwrap();
+                let client = get_graphql_client(&token)?;
 
-                let response_body = post_graphql::<DeleteIssue, _>(&client, "https://api.github.com/graphql", variables).expect("Failed to make GraphQL request");
+                let response_body = post_graphql::<DeleteIssue, _>(&client, &get_graphql_url(), variables).expect("Failed to make GraphQL request");
 
                 if let Some(errors) = response_body.errors {
                     if !errors.is_empty() {
@@ -186,14 +161,22 @@ impl RemoteConnector for GithubRemoteConnector {
 
     fn delete_remote_comment(&self, user: &String, repo: &String, _task_id: &String, comment_id: &String) -> Result<(), String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(delete_comment(user, repo, comment_id.parse().unwrap())),
+            Some(_) => delete_comment(user, repo, comment_id.parse().unwrap()),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
 
     fn delete_remote_label(&self, user: &String, repo: &String, task_id: &String, name: &String) -> Result<(), String> {
         match get_token_from_env() {
-            Some(_) => RUNTIME.block_on(delete_label(user, repo, task_id.parse().unwrap(), name)),
+            Some(_) => delete_label(user, repo, task_id.parse().unwrap(), name),
+            None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
+        }
+    }
+
+    fn check_connection(&self, user: &str, repo: &str) -> Result<String, String> {
+        match get_token_from_env() {
+            Some(_) => rest_get::<Repository>(&["repos", user, repo])
+                .map(|repository| format!("GitHub repository {} is accessible", repository.full_name.unwrap_or(repository.name))),
             None => Err("Could not find GITHUB_TOKEN environment variable.".to_string())
         }
     }
@@ -207,268 +190,606 @@ impl RemoteConnector for GithubRemoteConnector {
 )]
 struct DeleteIssue;
 
-async fn list_issues(
-    user: &String,
-    repo: &String,
+type DateTime = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "resources/github/schema.graphql",
+    query_path = "resources/github/list_issues.graphql",
+    variables_derives = "Clone",
+    response_derives = "Debug"
+)]
+struct ListIssues;
+
+#[allow(clippy::too_many_arguments)]
+fn list_issues(
+    user: &str,
+    repo: &str,
     with_comments: bool,
+    comment_window: &CommentWindow,
     with_labels: bool,
     limit: Option<usize>,
-    state: State,
-    task_statuses: &Vec<String>
-) -> Vec<Task> {
+    state: RemoteTaskState,
+    filter: &RemoteTaskFilter,
+    task_statuses: &[String]
+) -> Result<Vec<Task>, String> {
+    let state = match state {
+        RemoteTaskState::Open => "open",
+        RemoteTaskState::Closed => "closed",
+        RemoteTaskState::All => "all",
+    };
+    let mut query = vec![("state", state.to_string())];
+    if let Some(updated_since) = filter.updated_since.and_then(|since| chrono::DateTime::from_timestamp(since, 0)) {
+        query.push(("since", updated_since.to_rfc3339()));
+    }
+    if !filter.labels.is_empty() {
+        query.push(("labels", filter.labels.join(",")));
+    }
+    if let Some(author) = &filter.author {
+        query.push(("creator", author.clone()));
+    }
+
     let mut result = vec![];
-    let crab = get_octocrab_instance().await;
-    let stream = crab.issues(user, repo)
-        .list()
-        .state(state)
-        .per_page(100)
-        .send()
-        .await.unwrap()
-        .into_stream(&crab);
-    pin!(stream);
-    let mut count = 0;
-    while let Some(issue) = stream.try_next().await.unwrap() {
-        if limit.is_some() && count >= limit.unwrap() {
-            break;
-        }
-        count += 1;
+    for issue in rest_list::<Issue>(&["repos", user, repo, "issues"], &query, limit)? {
         let mut props = HashMap::new();
         props.insert(String::from("name"), issue.title);
-        props.insert(String::from("status"), if issue.state == IssueState::Open { task_statuses.get(0).unwrap().clone() } else { task_statuses.get(1).unwrap().clone() } );
-        props.insert(String::from("description"), issue.body.unwrap_or(String::new()));
+        props.insert(String::from("status"), if issue.state == IssueState::Open { task_statuses[0].clone() } else { task_statuses[1].clone() } );
+        props.insert(String::from("description"), issue.body.unwrap_or_default());
         props.insert(String::from("created"), issue.created_at.timestamp().to_string());
+        props.insert(String::from("updated"), issue.updated_at.timestamp().to_string());
         props.insert(String::from("author"), issue.user.login);
 
+        if let Some(task_type) = type_from_labels(issue.labels.iter().map(|l| l.name.as_str())) {
+            props.insert(String::from("type"), task_type);
+        }
+
         let mut task = Task::from_properties(issue.number.to_string(), props).unwrap();
 
         if with_comments {
-            let task_comments = list_issue_comments(&user, &repo, issue.number).await;
-            task.set_comments(task_comments);
+            task.set_comments(list_issue_comments(user, repo, issue.number, issue.comments, comment_window)?);
         }
 
-        if with_labels {
-            if !issue.labels.is_empty() {
-                let labels = issue.labels.iter()
-                    .map(|l| Label::new(
-                        l.name.clone(),
-                        Some(l.color.clone()),
-                        l.description.clone()
-                    ))
-                    .collect();
-                task.set_labels(labels);
-            }
+        if with_labels && !issue.labels.is_empty() {
+            task.set_labels(issue.labels.into_iter().map(|l| Label::new(l.name, Some(l.color), l.description)).collect());
         }
 
         result.push(task);
     }
 
-    result
+    Ok(result)
+}
+
+const GRAPHQL_PAGE_SIZE: usize = 100;
+
+fn get_graphql_client(token: &str) -> Result<Client, String> {
+    get_client(Some(token))
+}
+
+/// Client for both GraphQL and REST requests, set up with the `task.http.*` options.
+fn get_client(token: Option<&str>) -> Result<Client, String> {
+    let mut headers = HeaderMap::new();
+    headers.insert(reqwest::header::ACCEPT, HeaderValue::from_static("application/vnd.github+json"));
+    if let Some(token) = token {
+        headers.insert(reqwest::header::AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {token}")).map_err(|e| e.to_string())?);
+    }
+    let builder = Client::builder()
+        .user_agent("git-task/".to_owned() + env!("CARGO_PKG_VERSION"))
+        .default_headers(headers);
+    HttpOptions::load().configure(builder)?.build().map_err(|e| e.to_string())
 }
 
-async fn list_issue_comments(user: &String, repo: &String, n: u64) -> Vec<Comment> {
+/// Lists issues with GraphQL in pages of 100, fetching labels and the most recent comments
+/// in the same request. Comments are only requested separately for issues having more
+/// of them than fit into the page.
+#[allow(clippy::too_many_arguments)]
+fn list_issues_batched(
+    token: &str,
+    user: &str,
+    repo: &str,
+    with_comments: bool,
+    comment_window: &CommentWindow,
+    with_labels: bool,
+    limit: Option<usize>,
+    state: RemoteTaskState,
+    filter: &RemoteTaskFilter,
+    task_statuses: &[String]
+) -> Result<Vec<Task>, String> {
+    let client = get_graphql_client(token)?;
+    let states = match state {
+        RemoteTaskState::Open => Some(vec![list_issues::IssueState::OPEN]),
+        RemoteTaskState::Closed => Some(vec![list_issues::IssueState::CLOSED]),
+        RemoteTaskState::All => None,
+    };
+    let filter_by = list_issues::IssueFilters {
+        assignee: None,
+        created_by: filter.author.clone(),
+        labels: match filter.labels.is_empty() {
+            true => None,
+            false => Some(filter.labels.clone()),
+        },
+        mentioned: None,
+        milestone: None,
+        milestone_number: None,
+        since: filter.updated_since
+            .and_then(|since| chrono::DateTime::from_timestamp(since, 0))
+            .map(|since| since.to_rfc3339()),
+        states,
+        viewer_subscribed: None,
+    };
+    let comments_per_issue = match with_comments {
+        true => comment_window.max.unwrap_or(GRAPHQL_PAGE_SIZE).min(GRAPHQL_PAGE_SIZE),
+        false => 0,
+    };
+
     let mut result = vec![];
-    let crab = get_octocrab_instance().await;
-    let stream = crab.issues(user, repo)
-        .list_comments(n)
-        .per_page(100)
-        .send()
-        .await.unwrap()
-        .into_stream(&crab);
-    pin!(stream);
-    while let Some(comment) = stream.try_next().await.unwrap() {
-        let comment = Comment::new(comment.id.to_string(), HashMap::from([
+    let mut cursor = None;
+    loop {
+        let page_size = match limit {
+            Some(limit) => (limit - result.len()).min(GRAPHQL_PAGE_SIZE),
+            None => GRAPHQL_PAGE_SIZE,
+        };
+        if page_size == 0 {
+            break;
+        }
+
+        let variables = list_issues::Variables {
+            owner: user.to_string(),
+            name: repo.to_string(),
+            cursor,
+            page_size: page_size as i64,
+            filter_by: Some(filter_by.clone()),
+            comments: comments_per_issue as i64,
+        };
+        let response_body = post_graphql::<ListIssues, _>(&client, &get_graphql_url(), variables).map_err(|e| e.to_string())?;
+        if let Some(error) = response_body.errors.and_then(|errors| errors.into_iter().next()) {
+            return Err(error.message);
+        }
+        let issues = response_body.data
+            .and_then(|data| data.repository)
+            .ok_or_else(|| format!("Repository {user}/{repo} not found"))?
+            .issues;
+
+        for issue in issues.nodes.unwrap_or_default().into_iter().flatten() {
+            result.push(task_from_graphql_issue(user, repo, issue, with_comments, comment_window, with_labels, task_statuses)?);
+        }
+
+        match issues.page_info.has_next_page {
+            true => cursor = issues.page_info.end_cursor,
+            false => break,
+        }
+    }
+
+    Ok(result)
+}
+
+fn task_from_graphql_issue(
+    user: &str,
+    repo: &str,
+    issue: list_issues::ListIssuesRepositoryIssuesNodes,
+    with_comments: bool,
+    comment_window: &CommentWindow,
+    with_labels: bool,
+    task_statuses: &[String]
+) -> Result<Task, String> {
+    let labels = issue.labels.and_then(|labels| labels.nodes).unwrap_or_default().into_iter().flatten().collect::<Vec<_>>();
+
+    let mut props = HashMap::new();
+    props.insert(String::from("name"), issue.title);
+    props.insert(String::from("status"), if matches!(issue.state, list_issues::IssueState::OPEN) { task_statuses[0].clone() } else { task_statuses[1].clone() });
+    props.insert(String::from("description"), issue.body);
+    props.insert(String::from("created"), parse_graphql_timestamp(&issue.created_at));
+    props.insert(String::from("updated"), parse_graphql_timestamp(&issue.updated_at));
+    props.insert(String::from("author"), issue.author.map(|author| author.login).unwrap_or_default());
+
+    if let Some(task_type) = type_from_labels(labels.iter().map(|l| l.name.as_str())) {
+        props.insert(String::from("type"), task_type);
+    }
+
+    let n = issue.number as u64;
+    let mut task = Task::from_properties(n.to_string(), props).unwrap();
+
+    if with_comments {
+        let total = issue.comments.total_count as usize;
+        let comments = issue.comments.nodes.unwrap_or_default().into_iter().flatten()
+            .map(|comment| Comment::new(comment.database_id.unwrap_or_default().to_string(), HashMap::from([
+                ("author".to_string(), comment.author.map(|author| author.login).unwrap_or_default()),
+                ("created".to_string(), parse_graphql_timestamp(&comment.created_at)),
+            ]), comment.body))
+            .collect::<Vec<_>>();
+        let comments = match comments_complete(&comments, total, comment_window) {
+            true => comment_window.apply(comments),
+            false => list_issue_comments(user, repo, n, total as u32, comment_window)?,
+        };
+        task.set_comments(comments);
+    }
+
+    if with_labels && !labels.is_empty() {
+        task.set_labels(labels.into_iter().map(|l| Label::new(l.name, Some(l.color), l.description)).collect());
+    }
+
+    Ok(task)
+}
+
+/// Checks whether the most recent comments fetched along with an issue cover the comment window.
+fn comments_complete(comments: &[Comment], total: usize, window: &CommentWindow) -> bool {
+    if comments.len() >= total || window.max.is_some_and(|max| comments.len() >= max) {
+        return true;
+    }
+
+    let oldest = comments.first().and_then(|comment| comment.get_all_properties().get("created")).and_then(|created| created.parse::<i64>().ok());
+    matches!((window.since, oldest), (Some(since), Some(oldest)) if oldest < since)
+}
+
+fn parse_graphql_timestamp(value: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|value| value.timestamp().to_string())
+        .unwrap_or_default()
+}
+
+fn list_issue_comments(user: &str, repo: &str, n: u64, total: u32, window: &CommentWindow) -> Result<Vec<Comment>, String> {
+    let mut query = vec![];
+    if let Some(since) = window.since.and_then(|since| chrono::DateTime::from_timestamp(since, 0)) {
+        query.push(("since", since.to_rfc3339()));
+    }
+    if let (Some(max), None) = (window.max, window.since) {
+        // comments are listed oldest first, skip the pages before the most recent ones
+        query.push(("page", (total.saturating_sub(max as u32) / REST_PAGE_SIZE as u32 + 1).to_string()));
+    }
+    let comments = rest_list::<IssueComment>(&["repos", user, repo, "issues", &n.to_string(), "comments"], &query, None)?
+        .into_iter()
+        .map(|comment| Comment::new(comment.id.to_string(), HashMap::from([
             ("author".to_string(), comment.user.login),
             ("created".to_string(), comment.created_at.timestamp().to_string()),
-        ]), comment.body.unwrap());
-        result.push(comment);
-    }
+        ]), comment.body.unwrap_or_default()))
+        .collect();
 
-    result
+    Ok(window.apply(comments))
 }
 
-async fn get_issue(
-    user: &String,
-    repo: &String,
+fn get_issue(
+    user: &str,
+    repo: &str,
     n: u64,
     with_comments: bool,
+    comment_window: &CommentWindow,
     with_labels: bool,
-    task_statuses: &Vec<String>
+    task_statuses: &[String]
 ) -> Option<Task> {
-    let crab = get_octocrab_instance().await;
-    let issue = crab.issues(user, repo).get(n).await;
-    match issue {
-        Ok(issue) => {
-            let mut props = HashMap::new();
-            props.insert(String::from("name"), issue.title);
-            props.insert(String::from("status"), if issue.state == IssueState::Open { task_statuses.get(0).unwrap().clone() } else { task_statuses.get(1).unwrap().clone() } );
-            props.insert(String::from("description"), issue.body.unwrap_or(String::new()));
-            props.insert(String::from("created"), issue.created_at.timestamp().to_string());
-            props.insert(String::from("author"), issue.user.login);
-
-            let mut task = Task::from_properties(n.to_string(), props).unwrap();
-
-            if with_comments {
-                let task_comments = list_issue_comments(user, repo, issue.number).await;
-                task.set_comments(task_comments);
-            }
-
-            if with_labels {
-                let labels = issue.labels.iter()
-                    .map(|l| Label::new(
-                        l.name.to_string(),
-                        Some(l.color.to_string()),
-                        l.description.clone()
-                    )).collect();
-                task.set_labels(labels);
-            }
-
-            Some(task)
-        },
-        _ => None
+    let issue = rest_get::<Issue>(&["repos", user, repo, "issues", &n.to_string()]).ok()?;
+    let mut props = HashMap::new();
+    props.insert(String::from("name"), issue.title);
+    props.insert(String::from("status"), if issue.state == IssueState::Open { task_statuses[0].clone() } else { task_statuses[1].clone() } );
+    props.insert(String::from("description"), issue.body.unwrap_or_default());
+    props.insert(String::from("created"), issue.created_at.timestamp().to_string());
+    props.insert(String::from("updated"), issue.updated_at.timestamp().to_string());
+    props.insert(String::from("author"), issue.user.login);
+
+    if let Some(task_type) = type_from_labels(issue.labels.iter().map(|l| l.name.as_str())) {
+        props.insert(String::from("type"), task_type);
+    }
+
+    let mut task = Task::from_properties(n.to_string(), props).unwrap();
+
+    if with_comments {
+        task.set_comments(list_issue_comments(user, repo, issue.number, issue.comments, comment_window).ok()?);
+    }
+
+    if with_labels {
+        task.set_labels(issue.labels.into_iter().map(|l| Label::new(l.name, Some(l.color), l.description)).collect());
     }
+
+    Some(task)
 }
 
-async fn create_issue(user: &String, repo: &String, task: &Task) -> Result<String, String> {
-    let crab = get_octocrab_instance().await;
-    let crab_issues = crab.issues(user, repo);
-    let mut create_builder = crab_issues.create(task.get_property("name").unwrap());
-    if let Some(description) = task.get_property("description") {
-        create_builder = create_builder.body(description);
+fn create_issue(user: &str, repo: &str, task: &Task) -> Result<String, String> {
+    let mut body = json!({ "title": task.get_property("name").unwrap() });
+    if let Some(description) = get_issue_body(task)? {
+        body["body"] = json!(description);
     }
-    if let Some(labels) = task.get_labels() {
-        if !labels.is_empty() {
-            prepare_labels(user, repo, labels, &crab).await;
-            let labels = labels.iter().map(|l| l.get_name()).collect::<Vec<_>>();
-            create_builder = create_builder.labels(labels);
+    let labels = with_type_label(task, task.get_labels().as_deref().unwrap_or_default());
+    if !labels.is_empty() {
+        prepare_labels(user, repo, &labels)?;
+        body["labels"] = json!(labels.iter().map(|l| l.get_name()).collect::<Vec<_>>());
+    }
+    let issue = rest_request(Method::POST, &["repos", user, repo, "issues"], Some(body))?.json::<Issue>().map_err(|e| e.to_string())?;
+    Ok(issue.number.to_string())
+}
+
+fn create_comment(user: &str, repo: &str, task_id: &str, comment: &Comment) -> Result<String, String> {
+    let body = json!({ "body": comment.get_text() });
+    let comment = rest_request(Method::POST, &["repos", user, repo, "issues", task_id, "comments"], Some(body))?.json::<IssueComment>().map_err(|e| e.to_string())?;
+    Ok(comment.id.to_string())
+}
+
+fn add_label(user: &str, repo: &str, n: u64, label: &Label) -> Result<(), String> {
+    prepare_labels(user, repo, std::slice::from_ref(label))?;
+    let body = json!({ "labels": [label.get_name()] });
+    rest_request(Method::POST, &["repos", user, repo, "issues", &n.to_string(), "labels"], Some(body)).map(|_| ())
+}
+
+/// Creates the labels missing in the repository, existing ones are listed once per run.
+fn prepare_labels(user: &str, repo: &str, labels: &[Label]) -> Result<(), String> {
+    if LABEL_CACHE.get(user, repo).is_none() {
+        let existing_labels = rest_list::<IssueLabel>(&["repos", user, repo, "labels"], &[], None)?
+            .into_iter()
+            .map(|label| label.name)
+            .collect::<HashSet<_>>();
+        LABEL_CACHE.set(user, repo, existing_labels);
+    }
+    for l in LABEL_CACHE.missing(user, repo, labels).unwrap_or_default().iter() {
+        let body = json!({
+            "name": l.get_name(),
+            "color": color_str_to_rgb_str(&l.get_color()),
+            "description": l.get_description().unwrap_or_default(),
+        });
+        if rest_request(Method::POST, &["repos", user, repo, "labels"], Some(body)).is_ok() {
+            LABEL_CACHE.add(user, repo, &l.get_name());
         }
     }
-    match create_builder.send().await {
-        Ok(issue) => Ok(issue.number.to_string()),
-        Err(e) => Err(e.to_string())
+    Ok(())
+}
+
+fn update_issue(user: &str, repo: &str, n: u64, title: &str, body: &str, labels: Option<&Vec<Label>>, state: &str) -> Result<(), String> {
+    let mut request = json!({ "title": title, "body": body, "state": state });
+    if let Some(labels) = labels {
+        if !labels.is_empty() {
+            prepare_labels(user, repo, labels)?;
+        }
+        request["labels"] = json!(labels.iter().map(|l| l.get_name()).collect::<Vec<_>>());
     }
+    rest_request(Method::PATCH, &["repos", user, repo, "issues", &n.to_string()], Some(request)).map(|_| ())
+}
+
+fn update_comment(user: &str, repo: &str, n: u64, text: &str) -> Result<(), String> {
+    rest_request(Method::PATCH, &["repos", user, repo, "issues", "comments", &n.to_string()], Some(json!({ "body": text }))).map(|_| ())
+}
+
+fn delete_comment(user: &str, repo: &str, n: u64) -> Result<(), String> {
+    rest_request(Method::DELETE, &["repos", user, repo, "issues", "comments", &n.to_string()], None).map(|_| ())
+}
+
+pub fn delete_label(user: &str, repo: &str, n: u64, label_name: &str) -> Result<(), String> {
+    rest_request(Method::DELETE, &["repos", user, repo, "issues", &n.to_string(), "labels", label_name], None).map(|_| ())
 }
 
-async fn create_comment(user: &String, repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
-    let crab = get_octocrab_instance().await;
-    match crab.issues(user, repo).create_comment(task_id.parse().unwrap(), comment.get_text()).await {
-        Ok(comment) => Ok(comment.id.to_string()),
-        Err(e) => Err(e.to_string())
+fn get_issue_id(user: &str, repo: &str, n: u64) -> Result<String, String> {
+    rest_get::<Issue>(&["repos", user, repo, "issues", &n.to_string()]).map(|issue| issue.node_id)
+}
+
+const REST_PAGE_SIZE: usize = 100;
+
+/// REST API URL for the path segments, e.g. `["repos", user, repo]`, the segments are percent-encoded.
+fn get_rest_url(segments: &[&str]) -> Result<Url, String> {
+    let base_url = get_base_url();
+    let api_url = match get_host(&base_url) {
+        DEFAULT_HOST => "https://api.github.com".to_string(),
+        _ => format!("{base_url}/api/v3"),
+    };
+    let mut url = Url::parse(&api_url).map_err(|e| e.to_string())?;
+    url.path_segments_mut().map_err(|_| format!("Invalid GitHub API URL {api_url}"))?.pop_if_empty().extend(segments);
+    Ok(url)
+}
+
+fn rest_request(method: Method, segments: &[&str], body: Option<serde_json::Value>) -> Result<Response, String> {
+    let token = get_token_from_env();
+    let mut request = get_client(token.as_deref())?.request(method, get_rest_url(segments)?);
+    if let Some(body) = body {
+        request = request.json(&body);
     }
+    send_rest(request)
 }
 
-async fn add_label(
-    user: &String,
-    repo: &String,
-    n: u64,
-    label: &Label,
-) -> Result<(), String> {
-    let crab = get_octocrab_instance().await;
-    let _ = prepare_labels(user, repo, &vec![label.clone()], &crab).await;
-    let add_label_body = vec![label.get_name()];
-    crab
-        .issues(user, repo)
-        .add_labels(n, &add_label_body)
-        .await
-        .map(|_| ())
-        .map_err(|e| e.to_string())
-}
-
-async fn prepare_labels(
-    user: &String,
-    repo: &String,
-    labels: &Vec<Label>,
-    crab: &Arc<Octocrab>)
-{
-    let existing_labels_stream = crab
-        .issues(user, repo)
-        .list_labels_for_repo()
-        .per_page(100)
-        .send()
-        .await.unwrap()
-        .into_stream(&crab);
-    pin!(existing_labels_stream);
-    let mut labels_to_create = labels.clone();
-    while let Some(Ok(label)) = existing_labels_stream.next().await {
-        if let Some(pos) = labels_to_create.iter().position(|l| l.get_name() == label.name) {
-            labels_to_create.remove(pos);
+fn rest_get<T: DeserializeOwned>(segments: &[&str]) -> Result<T, String> {
+    rest_request(Method::GET, segments, None)?.json::<T>().map_err(|e| e.to_string())
+}
+
+/// Fetches all pages of a list following the `Link` headers, stops once the limit is reached.
+fn rest_list<T: DeserializeOwned>(segments: &[&str], query: &[(&str, String)], limit: Option<usize>) -> Result<Vec<T>, String> {
+    let token = get_token_from_env();
+    let client = get_client(token.as_deref())?;
+    let mut url = get_rest_url(segments)?;
+    url.query_pairs_mut()
+        .extend_pairs(query)
+        .append_pair("per_page", &REST_PAGE_SIZE.to_string());
+
+    let mut result = vec![];
+    let mut next = Some(url);
+    while let Some(url) = next.take() {
+        if limit.is_some_and(|limit| result.len() >= limit) {
+            break;
         }
+        let response = send_rest(client.get(url))?;
+        next = get_next_page(response.headers());
+        result.extend(response.json::<Vec<T>>().map_err(|e| e.to_string())?);
     }
-    for l in labels_to_create.iter() {
-        let _ = crab
-            .issues(user, repo)
-            .create_label(
-                l.get_name(),
-                color_str_to_rgb_str(&l.get_color()),
-                l.get_description().unwrap_or_else(|| "".to_string()),
-            )
-            .await;
+    if let Some(limit) = limit {
+        result.truncate(limit);
     }
+    Ok(result)
 }
 
-async fn update_issue(user: &String, repo: &String, n: u64, title: &String, body: &String, labels: Option<&Vec<Label>>, state: IssueState) -> Result<(), String> {
-    let crab = get_octocrab_instance().await;
-    let crab_issues = crab.issues(user, repo);
-    let mut update_builder = crab_issues.update(n).title(title).body(body).state(state);
-    let label_list;
-    if let Some(labels) = labels {
-        if !labels.is_empty() {
-            prepare_labels(user, repo, labels, &crab).await;
+/// Sends the request, unsuccessful responses become errors with the message GitHub returned.
+fn send_rest(request: RequestBuilder) -> Result<Response, String> {
+    let response = request.send().map_err(|e| e.to_string())?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response.json::<serde_json::Value>().ok()
+        .and_then(|body| body.get("message").and_then(|message| message.as_str()).map(str::to_string))
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or_default().to_string());
+    Err(format!("GitHub API error {}: {message}", status.as_u16()))
+}
+
+/// URL of the next page from a `Link: <...>; rel="next", <...>; rel="last"` header.
+fn get_next_page(headers: &HeaderMap) -> Option<Url> {
+    headers.get(reqwest::header::LINK)?.to_str().ok()?
+        .split(',')
+        .find(|link| link.contains("rel=\"next\""))
+        .and_then(|link| link.split(';').next())
+        .and_then(|url| Url::parse(url.trim().trim_start_matches('<').trim_end_matches('>')).ok())
+}
+
+const DEFAULT_HOST: &str = "github.com";
+
+/// Returns the GitHub web URL without a trailing slash, `https://github.com` unless
+/// a GitHub Enterprise Server is set with `task.github.url` or `GITHUB_URL`.
+fn get_base_url() -> String {
+    let mut result = match gittask::get_config_value("task.github.url") {
+        Ok(url) if !url.is_empty() => url,
+        _ => match std::env::var("GITHUB_URL") {
+            Ok(url) => url,
+            _ => format!("https://{DEFAULT_HOST}"),
         }
-        label_list = labels.iter().map(|l| l.get_name()).collect::<Vec<_>>();
-        update_builder = update_builder.labels(&label_list);
+    };
+
+    if !result.starts_with("http") {
+        result = "https://".to_string() + result.as_str();
     }
-    match update_builder.send().await {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.to_string())
+
+    result.trim_end_matches('/').to_string()
+}
+
+fn get_host(base_url: &str) -> &str {
+    base_url.split_once("://").map(|(_, host)| host).unwrap_or(base_url)
+}
+
+fn get_graphql_url() -> String {
+    let base_url = get_base_url();
+    match get_host(&base_url) {
+        DEFAULT_HOST => "https://api.github.com/graphql".to_string(),
+        _ => format!("{base_url}/api/graphql"),
     }
 }
 
-async fn update_comment(user: &String, repo: &String, n: u64, text: &String) -> Result<(), String> {
-    let crab = get_octocrab_instance().await;
-    match crab.issues(user, repo).update_comment(CommentId(n), text).await {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.to_string())
+const ISSUE_TEMPLATE_DIR: &str = ".github/ISSUE_TEMPLATE";
+
+/// Returns the body for a new issue, rendered through the template set in `task.github.template` if any.
+fn get_issue_body(task: &Task) -> Result<Option<String>, String> {
+    let template = match gittask::get_config_value("task.github.template") {
+        Ok(template) if !template.is_empty() => template,
+        _ => return Ok(task.get_property("description").cloned()),
+    };
+    let path = match template.contains('/') {
+        true => template,
+        false => format!("{ISSUE_TEMPLATE_DIR}/{template}"),
+    };
+    let content = gittask::read_repo_file(&path)?.ok_or_else(|| format!("Issue template not found: {path}"))?;
+    let props = task.get_all_properties();
+    match path.ends_with(".yml") || path.ends_with(".yaml") {
+        true => Ok(Some(render_issue_form(&content, props))),
+        false => render_markdown_template(&content, props).map(Some),
     }
 }
 
-async fn delete_comment(user: &String, repo: &String, n: u64) -> Result<(), String> {
-    let crab = get_octocrab_instance().await;
-    match crab.issues(user, repo).delete_comment(CommentId(n)).await {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.to_string())
+/// Fills `{{property}}` placeholders of a Markdown issue template. When the template
+/// has no `{{description}}` placeholder, the description is put before its sections.
+fn render_markdown_template(content: &str, props: &HashMap<String, String>) -> Result<String, String> {
+    let body = strip_front_matter(content);
+    let context = serde_json::to_value(props).map_err(|e| e.to_string())?;
+    let rendered = crate::template::render(body, &context)?;
+    match props.get("description").filter(|description| !description.is_empty() && !body.contains("{{description}}")) {
+        Some(description) => Ok(format!("{description}\n\n{rendered}")),
+        None => Ok(rendered),
     }
 }
 
-pub async fn delete_label(
-    user: &String,
-    repo: &String,
-    n: u64,
-    label_name: &str,
-) -> Result<(), String> {
-    let crab = get_octocrab_instance().await;
+fn strip_front_matter(content: &str) -> &str {
+    content.strip_prefix("---")
+        .and_then(|rest| rest.find("\n---").map(|end| &rest[end + 4..]))
+        .map(|rest| rest.trim_start_matches(['\r', '\n']))
+        .unwrap_or(content)
+}
+
+/// Renders an issue form the way GitHub does: one `### Label` section per input, filled
+/// from the task property with the same id. The description goes to the `description`
+/// input or, when there is none, the first empty textarea.
+fn render_issue_form(content: &str, props: &HashMap<String, String>) -> String {
+    let fields = parse_issue_form(content);
+    let description = props.get("description").filter(|description| !description.is_empty());
+    let mut description_used = description.is_none() || fields.iter().any(|field| field.id.as_deref() == Some("description"));
+    let mut sections = vec![];
+
+    for field in fields.iter().filter(|field| field.kind != "markdown") {
+        let value = match field.id.as_ref().and_then(|id| props.get(id)).filter(|value| !value.is_empty()) {
+            Some(value) => value.as_str(),
+            None if !description_used && field.kind == "textarea" => {
+                description_used = true;
+                description.unwrap()
+            },
+            None => "_No response_",
+        };
+        let label = field.label.as_ref().or(field.id.as_ref()).map(|label| label.as_str()).unwrap_or_default();
+        sections.push(format!("### {label}\n\n{value}"));
+    }
 
-    crab
-        .issues(user, repo)
-        .remove_label(n, label_name)
-        .await
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+    if !description_used {
+        sections.push(description.unwrap().clone());
+    }
+
+    sections.join("\n\n")
 }
 
-async fn get_issue_id(user: &String, repo: &String, n: u64) -> Result<String, String> {
-    let crab = get_octocrab_instance().await;
-    let issue = crab.issues(user, repo).get(n).await;
-    match issue {
-        Ok(issue) => Ok(issue.node_id),
-        Err(e) => Err(e.to_string()),
+struct FormField {
+    kind: String,
+    id: Option<String>,
+    label: Option<String>,
+}
+
+/// Extracts the `body` items of a YAML issue form. Only the keys needed to render
+/// the issue are read: `type`, `id` and the first `label` of every item.
+fn parse_issue_form(content: &str) -> Vec<FormField> {
+    let mut fields: Vec<FormField> = vec![];
+    let mut in_body = false;
+    let mut item_indent = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+
+        if indent == 0 && !trimmed.starts_with('-') {
+            in_body = trimmed.trim_end() == "body:";
+            continue;
+        }
+        if !in_body {
+            continue;
+        }
+
+        let entry = match trimmed.strip_prefix("- ") {
+            Some(entry) if item_indent.is_none() || item_indent == Some(indent) => {
+                item_indent = Some(indent);
+                fields.push(FormField { kind: String::new(), id: None, label: None });
+                entry
+            },
+            Some(entry) => entry,
+            None => trimmed,
+        };
+
+        let (Some(field), Some((key, value))) = (fields.last_mut(), entry.split_once(':')) else {
+            continue;
+        };
+        let value = unquote(value.trim());
+        match key.trim() {
+            "type" if field.kind.is_empty() => field.kind = value,
+            "id" if field.id.is_none() => field.id = Some(value),
+            "label" if field.label.is_none() && !value.is_empty() => field.label = Some(value),
+            _ => {},
+        }
     }
+
+    fields
 }
 
-async fn get_octocrab_instance() -> Arc<Octocrab> {
-    match get_token_from_env() {
-        Some(token) => Arc::new(Octocrab::builder().personal_token(token).build().unwrap()),
-        None => octocrab::instance()
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(value) = value.strip_prefix(quote).and_then(|value| value.strip_suffix(quote)) {
+            return value.to_string();
+        }
     }
+    value.to_string()
 }
 
 fn get_token_from_env() -> Option<String> {
@@ -483,7 +804,68 @@ mod test {
     fn test_remote_url() {
         let connector = GithubRemoteConnector {};
 
-        assert!(connector.supports_remote("git@github.com:VIK-777/java-telegram-meetup-bot.git").is_some());
-        assert!(connector.supports_remote("https://github.com/jhspetersson/fselect.git").is_some());
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            assert!(connector.supports_remote("git@github.com:VIK-777/java-telegram-meetup-bot.git").is_some());
+            assert!(connector.supports_remote("https://github.com/jhspetersson/fselect.git").is_some());
+            assert!(connector.supports_remote("ssh://git@github.com:443/jhspetersson/fselect.git").is_some());
+            assert!(connector.supports_remote("https://github.com/group/subgroup/project").is_none());
+            assert!(connector.supports_remote("https://github.com.evil.org/jhspetersson/fselect").is_none());
+            assert_eq!(get_graphql_url(), "https://api.github.com/graphql");
+            assert_eq!(get_rest_url(&["repos", "user", "repo"]).unwrap().as_str(), "https://api.github.com/repos/user/repo");
+
+            gittask::set_config_value("task.github.url", "github.acme.com/").unwrap();
+            assert_eq!(get_base_url(), "https://github.acme.com");
+            assert_eq!(get_graphql_url(), "https://github.acme.com/api/graphql");
+            assert_eq!(get_rest_url(&["repos", "user", "repo", "labels", "good first issue"]).unwrap().as_str(), "https://github.acme.com/api/v3/repos/user/repo/labels/good%20first%20issue");
+            assert!(connector.supports_remote("git@github.acme.com:team/project.git").is_some());
+            assert!(connector.supports_remote("https://github.com/jhspetersson/fselect.git").is_none());
+        });
+    }
+
+    #[test]
+    fn test_get_next_page() {
+        let mut headers = HeaderMap::new();
+        assert!(get_next_page(&headers).is_none());
+
+        headers.insert(reqwest::header::LINK, HeaderValue::from_static("<https://api.github.com/repositories/1/issues?page=2>; rel=\"next\", <https://api.github.com/repositories/1/issues?page=5>; rel=\"last\""));
+        assert_eq!(get_next_page(&headers).unwrap().as_str(), "https://api.github.com/repositories/1/issues?page=2");
+
+        headers.insert(reqwest::header::LINK, HeaderValue::from_static("<https://api.github.com/repositories/1/issues?page=4>; rel=\"prev\""));
+        assert!(get_next_page(&headers).is_none());
+    }
+
+    #[test]
+    fn test_comments_complete() {
+        let comments = vec![
+            Comment::new("2".to_string(), HashMap::from([("created".to_string(), "200".to_string())]), "second".to_string()),
+            Comment::new("3".to_string(), HashMap::from([("created".to_string(), "300".to_string())]), "third".to_string()),
+        ];
+
+        assert!(comments_complete(&comments, 2, &CommentWindow::default()));
+        assert!(!comments_complete(&comments, 3, &CommentWindow::default()));
+        assert!(comments_complete(&comments, 3, &CommentWindow { since: None, max: Some(2) }));
+        assert!(comments_complete(&comments, 3, &CommentWindow { since: Some(250), max: None }));
+        assert!(!comments_complete(&comments, 3, &CommentWindow { since: Some(150), max: None }));
+    }
+
+    #[test]
+    fn test_render_issue_form() {
+        let form = "name: Bug report\ndescription: File a bug\nlabels: [\"bug\"]\nbody:\n  - type: markdown\n    attributes:\n      value: Thanks for reporting!\n  - type: textarea\n    id: what-happened\n    attributes:\n      label: What happened?\n    validations:\n      required: true\n  - type: input\n    id: version\n    attributes:\n      label: \"Version\"\n  - type: checkboxes\n    id: terms\n    attributes:\n      label: Code of Conduct\n      options:\n        - label: I agree\n";
+        let props = HashMap::from([
+            ("description".to_string(), "It crashed".to_string()),
+            ("version".to_string(), "0.2.2".to_string()),
+        ]);
+        assert_eq!(render_issue_form(form, &props), "### What happened?\n\nIt crashed\n\n### Version\n\n0.2.2\n\n### Code of Conduct\n\n_No response_");
+    }
+
+    #[test]
+    fn test_render_markdown_template() {
+        let template = "---\nname: Bug report\nabout: Report a bug\n---\n## Version\n{{version}}\n";
+        let props = HashMap::from([
+            ("description".to_string(), "It crashed".to_string()),
+            ("version".to_string(), "0.2.2".to_string()),
+        ]);
+        assert_eq!(render_markdown_template(template, &props).unwrap(), "It crashed\n\n## Version\n0.2.2\n");
+        assert_eq!(render_markdown_template("## Steps\n{{description}}\n", &props).unwrap(), "## Steps\nIt crashed\n");
     }
 }
\ No newline at end of file
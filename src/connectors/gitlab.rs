@@ -1,18 +1,30 @@
 use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
+use gitlab::api::common::SortOrder;
 use gitlab::api::issues::{IssueScope, IssueState};
+use gitlab::api::projects::issues::notes::NoteOrderBy;
 use gitlab::api::projects::issues::IssueStateEvent;
+use gitlab::api::common::NameOrId;
+use gitlab::api::endpoint_prelude::{Cow, Endpoint, Method, QueryParams};
 use gitlab::api::{Pagination, Query};
 use gitlab::Gitlab;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use gittask::{Comment, Label, Task};
-use crate::connectors::{RemoteConnector, RemoteTaskState};
-use crate::util::{color_str_to_rgb_str, parse_datetime_to_seconds};
+use crate::connectors::http::HttpOptions;
+use crate::connectors::{create_missing, get_milestone, parse_remote_url, type_from_labels, with_type_label, CommentWindow, LabelCache, RemoteConnector, RemoteTaskFilter, RemoteTaskState, MILESTONE};
+use crate::util::{color_str_to_rgb_str, error_message_kind, parse_datetime_to_seconds, ErrorKind};
 
 pub struct GitlabRemoteConnector;
 
+static LABEL_CACHE: LazyLock<LabelCache> = LazyLock::new(LabelCache::default);
+/// User IDs by username, `None` for unknown users, so each assignee is looked up once per command.
+static USER_CACHE: LazyLock<Mutex<HashMap<String, Option<u64>>>> = LazyLock::new(Default::default);
+/// Milestone IDs by project path and title, `None` for missing milestones.
+static MILESTONE_CACHE: LazyLock<Mutex<HashMap<String, Option<u64>>>> = LazyLock::new(Default::default);
+
 #[derive(Serialize, Deserialize)]
 struct Author {
     username: String,
@@ -25,8 +37,38 @@ struct Issue {
     description: String,
     author: Author,
     created_at: String,
+    updated_at: String,
     state: String,
     labels: Vec<String>,
+    milestone: Option<GitlabMilestone>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GitlabMilestone {
+    id: u64,
+    title: String,
+}
+
+/// Project milestones with the exact title, the crate has no endpoint for listing them.
+struct ProjectMilestones<'a> {
+    project: NameOrId<'a>,
+    title: &'a str,
+}
+
+impl Endpoint for ProjectMilestones<'_> {
+    fn method(&self) -> Method {
+        Method::GET
+    }
+
+    fn endpoint(&self) -> Cow<'static, str> {
+        format!("projects/{}/milestones", self.project).into()
+    }
+
+    fn parameters(&self) -> QueryParams<'_> {
+        let mut params = QueryParams::default();
+        params.push("title", self.title);
+        params
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -44,6 +86,16 @@ struct GitlabLabel {
     description: String,
 }
 
+#[derive(Deserialize)]
+struct GitlabUser {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct GitlabProject {
+    path_with_namespace: String,
+}
+
 #[derive(Deserialize)]
 struct DeleteIssueResult {}
 
@@ -51,15 +103,16 @@ struct DeleteIssueResult {}
 struct DeleteIssueNoteResult {}
 
 impl RemoteConnector for GitlabRemoteConnector {
+    fn name(&self) -> &'static str {
+        "gitlab"
+    }
+
+    /// Accepts HTTPS and SSH remotes of projects in nested groups, the last path segment is the project
+    /// and everything before it is the namespace, e.g. `group/subgroup` and `project`.
     fn supports_remote(&self, url: &str) -> Option<(String, String)> {
-        match Regex::new(&(get_base_url() + "([a-z0-9-]+)/([a-z0-9-]+)\\.?")).unwrap().captures(url) {
-            Some(caps) if caps.len() == 3 => {
-                let user = caps.get(1)?.as_str().to_string();
-                let repo = caps.get(2)?.as_str().to_string();
-                Some((user, repo))
-            },
-            _ => None,
-        }
+        let url = parse_remote_url(url).filter(|url| url.is_on(&get_base_url()))?;
+        let (namespace, project) = url.path.rsplit_once('/')?;
+        Some((namespace.to_string(), project.to_string()))
     }
 
     fn list_remote_tasks(
@@ -67,11 +120,13 @@ impl RemoteConnector for GitlabRemoteConnector {
         user: &String,
         repo: &String,
         with_comments: bool,
+        comment_window: &CommentWindow,
         with_labels: bool,
         limit: Option<usize>,
         state: RemoteTaskState,
+        filter: &RemoteTaskFilter,
         task_statuses: &Vec<String>
-    ) -> Vec<Task> {
+    ) -> Result<Vec<Task>, String> {
         let state = match state {
             RemoteTaskState::Open => Some(IssueState::Opened),
             RemoteTaskState::Closed => Some(IssueState::Closed),
@@ -82,9 +137,9 @@ impl RemoteConnector for GitlabRemoteConnector {
         let labels = match with_labels {
             true => {
                 let mut endpoint = gitlab::api::projects::labels::Labels::builder();
-                let endpoint = endpoint.project(user.to_string() + "/" + repo);
+                let endpoint = endpoint.project(project_path(user, repo));
                 let endpoint = endpoint.build().unwrap();
-                let labels: Vec<GitlabLabel> = gitlab::api::paged(endpoint, Pagination::All).query(&client).unwrap();
+                let labels: Vec<GitlabLabel> = gitlab::api::paged(endpoint, Pagination::All).query(&client).map_err(|e| e.to_string())?;
                 let labels = labels.iter()
                     .map(|gl| Label::new(
                         gl.name.to_string(),
@@ -98,17 +153,26 @@ impl RemoteConnector for GitlabRemoteConnector {
         };
 
         let mut endpoint = gitlab::api::issues::ProjectIssues::builder();
-        let mut endpoint = endpoint.project(user.to_string() + "/" + repo).scope(IssueScope::All);
+        let mut endpoint = endpoint.project(project_path(user, repo)).scope(IssueScope::All);
         endpoint = match state {
             Some(state) => endpoint.state(state),
             None => endpoint
         };
+        if let Some(updated_since) = filter.updated_since.and_then(|since| chrono::DateTime::from_timestamp(since, 0)) {
+            endpoint = endpoint.updated_after(updated_since);
+        }
+        if !filter.labels.is_empty() {
+            endpoint = endpoint.labels(filter.labels.iter().map(|label| label.as_str()));
+        }
+        if let Some(author) = &filter.author {
+            endpoint = endpoint.author(author.as_str());
+        }
         let endpoint = endpoint.build().unwrap();
         let pagination = match limit {
             Some(limit) => Pagination::Limit(limit),
             None => Pagination::All
         };
-        let issues: Vec<Issue> = gitlab::api::paged(endpoint, pagination).query(&client).unwrap();
+        let issues: Vec<Issue> = gitlab::api::paged(endpoint, pagination).query(&client).map_err(|e| e.to_string())?;
         let mut result = vec![];
         for issue in issues {
             let mut props = HashMap::new();
@@ -116,12 +180,20 @@ impl RemoteConnector for GitlabRemoteConnector {
             props.insert(String::from("description"), issue.description);
             props.insert(String::from("status"), if issue.state == "opened" { task_statuses.get(0).unwrap().clone() } else { task_statuses.get(1).unwrap().clone() });
             props.insert(String::from("created"), parse_datetime_to_seconds(issue.created_at));
+            props.insert(String::from("updated"), parse_datetime_to_seconds(issue.updated_at));
             props.insert(String::from("author"), issue.author.username);
+            if let Some(milestone) = issue.milestone {
+                props.insert(String::from(MILESTONE), milestone.title);
+            }
+
+            if let Some(task_type) = type_from_labels(issue.labels.iter().map(|l| l.as_str())) {
+                props.insert(String::from("type"), task_type);
+            }
 
             let mut task = Task::from_properties(issue.iid.to_string(), props).unwrap();
 
             if with_comments {
-                let comments = list_issue_comments(&client, &user, &repo, &issue.iid.to_string());
+                let comments = list_issue_comments(&client, user, repo, &issue.iid.to_string(), comment_window);
                 task.set_comments(comments);
             }
 
@@ -138,7 +210,7 @@ impl RemoteConnector for GitlabRemoteConnector {
             result.push(task);
         }
 
-        result
+        Ok(result)
     }
 
     fn get_remote_task(
@@ -147,12 +219,13 @@ impl RemoteConnector for GitlabRemoteConnector {
         repo: &String,
         task_id: &String,
         with_comments: bool,
+        comment_window: &CommentWindow,
         with_labels: bool,
         task_statuses: &Vec<String>
     ) -> Option<Task> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::Issue::builder();
-        let mut endpoint = endpoint.project(user.to_string() + "/" + repo);
+        let mut endpoint = endpoint.project(project_path(user, repo));
         endpoint = endpoint.issue(task_id.parse().unwrap());
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
@@ -163,18 +236,26 @@ impl RemoteConnector for GitlabRemoteConnector {
                 props.insert(String::from("description"), issue.description);
                 props.insert(String::from("status"), if issue.state == "opened" { task_statuses.get(0).unwrap().clone() } else { task_statuses.get(1).unwrap().clone() });
                 props.insert(String::from("created"), parse_datetime_to_seconds(issue.created_at));
+                props.insert(String::from("updated"), parse_datetime_to_seconds(issue.updated_at));
                 props.insert(String::from("author"), issue.author.username);
+                if let Some(milestone) = issue.milestone {
+                    props.insert(String::from(MILESTONE), milestone.title);
+                }
+
+                if let Some(task_type) = type_from_labels(issue.labels.iter().map(|l| l.as_str())) {
+                    props.insert(String::from("type"), task_type);
+                }
 
                 let mut task = Task::from_properties(task_id.to_string(), props).unwrap();
 
                 if with_comments {
-                    let comments = list_issue_comments(&client, &user, &repo, task_id);
+                    let comments = list_issue_comments(&client, user, repo, task_id, comment_window);
                     task.set_comments(comments);
                 }
 
                 if with_labels {
                     let mut endpoint = gitlab::api::projects::labels::Labels::builder();
-                    let endpoint = endpoint.project(user.to_string() + "/" + repo);
+                    let endpoint = endpoint.project(project_path(user, repo));
                     let endpoint = endpoint.build().unwrap();
                     let labels: Vec<GitlabLabel> = gitlab::api::paged(endpoint, Pagination::All).query(&client).unwrap();
                     let labels = issue.labels.iter()
@@ -195,14 +276,21 @@ impl RemoteConnector for GitlabRemoteConnector {
     fn create_remote_task(&self, user: &String, repo: &String, task: &Task) -> Result<String, String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::CreateIssue::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo);
+        let endpoint = endpoint.project(project_path(user, repo));
         endpoint.title(task.get_property("name").unwrap());
         endpoint.description(task.get_property("description").unwrap());
-        if let Some(labels) = task.get_labels() {
+        let labels = with_type_label(task, task.get_labels().as_deref().unwrap_or_default());
+        if !labels.is_empty() {
             prepare_labels(&client, &user, &repo, &labels);
             let labels = labels.iter().map(|l| l.get_name()).collect::<Vec<_>>();
             endpoint.labels(labels);
         }
+        if let Some(assignee_id) = get_assignee_id(&client, task) {
+            endpoint.assignee_id(assignee_id);
+        }
+        if let Some(milestone_id) = get_milestone_id(&client, user, repo, task) {
+            endpoint.milestone_id(milestone_id);
+        }
         let endpoint = endpoint.build().unwrap();
         let issue: Issue = endpoint.query(&client).unwrap();
 
@@ -212,7 +300,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     fn create_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::notes::CreateIssueNote::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
         endpoint.body(comment.get_text().clone());
         let endpoint = endpoint.build().unwrap();
         let comment: GitlabComment = endpoint.query(&client).unwrap();
@@ -229,7 +317,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     ) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::Issue::builder();
-        let mut endpoint = endpoint.project(user.to_string() + "/" + repo);
+        let mut endpoint = endpoint.project(project_path(user, repo));
         endpoint = endpoint.issue(task_id.parse().unwrap());
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
@@ -239,7 +327,7 @@ impl RemoteConnector for GitlabRemoteConnector {
                 if !issue.labels.contains(&label_name) {
                     prepare_labels(&client, &user, &repo, &vec![label.clone()]);
                     let mut endpoint = gitlab::api::projects::issues::EditIssue::builder();
-                    let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+                    let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
                     endpoint.add_label(label_name);
                     let endpoint = endpoint.build().unwrap();
                     match endpoint.query(&client) {
@@ -267,14 +355,21 @@ impl RemoteConnector for GitlabRemoteConnector {
     ) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::EditIssue::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task.get_id().unwrap().parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task.get_id().unwrap().parse().unwrap());
         endpoint.title(task.get_property("name").unwrap());
         endpoint.description(task.get_property("description").unwrap());
         if let Some(labels) = labels {
+            let labels = with_type_label(task, labels);
             prepare_labels(&client, &user, &repo, &labels);
             let labels = labels.iter().map(|l| l.get_name()).collect::<Vec<_>>();
             endpoint.labels(labels);
         }
+        if let Some(assignee_id) = get_assignee_id(&client, task) {
+            endpoint.assignee_id(assignee_id);
+        }
+        if let Some(milestone_id) = get_milestone_id(&client, user, repo, task) {
+            endpoint.milestone_id(milestone_id);
+        }
         endpoint.state_event(if state == RemoteTaskState::Open { IssueStateEvent::Reopen } else { IssueStateEvent::Close });
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
@@ -289,7 +384,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     fn update_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String, text: &String) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::notes::EditIssueNote::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
         endpoint.note(comment_id.parse().unwrap());
         endpoint.body(text.as_str());
         let endpoint = endpoint.build().unwrap();
@@ -305,7 +400,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     fn delete_remote_task(&self, user: &String, repo: &String, task_id: &String) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::DeleteIssue::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
             Ok(result) => {
@@ -319,7 +414,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     fn delete_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::notes::DeleteIssueNote::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
         endpoint.note(comment_id.parse().unwrap());
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
@@ -340,7 +435,7 @@ impl RemoteConnector for GitlabRemoteConnector {
     ) -> Result<(), String> {
         let client = get_client(get_token_from_env().unwrap().as_str());
         let mut endpoint = gitlab::api::projects::issues::EditIssue::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+        let endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
         endpoint.remove_label(label_name);
         let endpoint = endpoint.build().unwrap();
         match endpoint.query(&client) {
@@ -351,15 +446,39 @@ impl RemoteConnector for GitlabRemoteConnector {
             Err(e) => Err(e.to_string())
         }
     }
+
+    fn check_connection(&self, user: &str, repo: &str) -> Result<String, String> {
+        let token = get_token_from_env().ok_or_else(|| "Could not find GITLAB_TOKEN environment variable.".to_string())?;
+        let client = try_get_client(&token)?;
+        let endpoint = gitlab::api::projects::Project::builder().project(project_path(user, repo)).build().unwrap();
+        match endpoint.query(&client) {
+            Ok(project) => {
+                let project: GitlabProject = project;
+                Ok(format!("Gitlab project {} is accessible", project.path_with_namespace))
+            },
+            Err(e) => Err(e.to_string())
+        }
+    }
 }
 
-fn list_issue_comments(client: &Gitlab, user: &String, repo: &String, task_id: &String) -> Vec<Comment> {
+fn list_issue_comments(client: &Gitlab, user: &str, repo: &str, task_id: &str, window: &CommentWindow) -> Vec<Comment> {
     let mut endpoint = gitlab::api::projects::issues::notes::IssueNotes::builder();
-    let endpoint = endpoint.project(user.to_string() + "/" + repo).issue(task_id.parse().unwrap());
+    let mut endpoint = endpoint.project(project_path(user, repo)).issue(task_id.parse().unwrap());
+    // newest first, so that the limit keeps the most recent comments
+    let pagination = match window.max {
+        Some(max) => {
+            endpoint = endpoint.order_by(NoteOrderBy::CreatedAt).sort(SortOrder::Descending);
+            Pagination::Limit(max)
+        },
+        None => Pagination::All
+    };
     let endpoint = endpoint.build().unwrap();
-    match gitlab::api::paged(endpoint, Pagination::All).query(client) {
+    match gitlab::api::paged(endpoint, pagination).query(client) {
         Ok(comments) => {
-            let comments: Vec<GitlabComment> = comments;
+            let mut comments: Vec<GitlabComment> = comments;
+            if window.max.is_some() {
+                comments.reverse();
+            }
             let mut result: Vec<Comment> = vec![];
             for comment in comments {
                 let comment = Comment::new(comment.id.to_string(), HashMap::from([
@@ -368,7 +487,7 @@ fn list_issue_comments(client: &Gitlab, user: &String, repo: &String, task_id: &
                 ]), comment.body);
                 result.push(comment);
             }
-            result
+            window.apply(result)
         },
         Err(e) => {
             eprintln!("{}", e);
@@ -378,19 +497,16 @@ fn list_issue_comments(client: &Gitlab, user: &String, repo: &String, task_id: &
 }
 
 fn prepare_labels(client: &Gitlab, user: &String, repo: &String, labels: &Vec<Label>) {
-    let mut endpoint = gitlab::api::projects::labels::Labels::builder();
-    let endpoint = endpoint.project(user.to_string() + "/" + repo);
-    let endpoint = endpoint.build().unwrap();
-    let existing_labels: Vec<GitlabLabel> = gitlab::api::paged(endpoint, Pagination::All).query(client).unwrap();
-    let mut labels_to_create = labels.clone();
-    for label in existing_labels {
-        if let Some(pos) = labels_to_create.iter().position(|l| l.get_name() == label.name) {
-            labels_to_create.remove(pos);
-        }
+    if LABEL_CACHE.get(user, repo).is_none() {
+        let mut endpoint = gitlab::api::projects::labels::Labels::builder();
+        let endpoint = endpoint.project(project_path(user, repo));
+        let endpoint = endpoint.build().unwrap();
+        let existing_labels: Vec<GitlabLabel> = gitlab::api::paged(endpoint, Pagination::All).query(client).unwrap();
+        LABEL_CACHE.set(user, repo, existing_labels.into_iter().map(|label| label.name).collect());
     }
-    for l in labels_to_create.iter() {
+    for l in LABEL_CACHE.missing(user, repo, labels).unwrap_or_default().iter() {
         let mut endpoint = gitlab::api::projects::labels::CreateLabel::builder();
-        let endpoint = endpoint.project(user.to_string() + "/" + repo);
+        let endpoint = endpoint.project(project_path(user, repo));
         endpoint.name(l.get_name());
         endpoint.color("#".to_string() + &color_str_to_rgb_str(&l.get_color()));
         if let Some(description) = l.get_description() {
@@ -398,16 +514,101 @@ fn prepare_labels(client: &Gitlab, user: &String, repo: &String, labels: &Vec<La
         }
         let endpoint = endpoint.build().unwrap();
         gitlab::api::ignore(endpoint).query(client).unwrap();
+        LABEL_CACHE.add(user, repo, &l.get_name());
+    }
+}
+
+/// Resolves the `assignee` property of the task to a GitLab user ID by username.
+fn get_assignee_id(client: &Gitlab, task: &Task) -> Option<u64> {
+    let username = task.get_property("assignee").map(|assignee| assignee.trim().trim_start_matches('@')).filter(|assignee| !assignee.is_empty())?;
+    let mut users = USER_CACHE.lock().unwrap();
+    if let Some(id) = users.get(username) {
+        return *id;
+    }
+
+    let endpoint = gitlab::api::users::Users::builder().username(username).build().unwrap();
+    let id = match endpoint.query(client) {
+        Ok(found) => {
+            let found: Vec<GitlabUser> = found;
+            found.first().map(|user| user.id)
+        },
+        Err(e) => {
+            error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+            None
+        },
+    };
+    if id.is_none() {
+        eprintln!("WARNING: GitLab user {username} not found, task {} is pushed without assignee", task.get_id().unwrap_or_default());
+    }
+    users.insert(username.to_string(), id);
+    id
+}
+
+/// Resolves the `milestone` property of the task to a project milestone ID by title,
+/// a missing milestone is created with `push --create-missing`.
+fn get_milestone_id(client: &Gitlab, user: &str, repo: &str, task: &Task) -> Option<u64> {
+    let title = get_milestone(task)?;
+    let project = project_path(user, repo);
+    let key = format!("{project}/{title}");
+    let mut milestones = MILESTONE_CACHE.lock().unwrap();
+    if let Some(id) = milestones.get(&key) {
+        return *id;
+    }
+
+    let endpoint = ProjectMilestones { project: NameOrId::from(project.as_str()), title: &title };
+    let mut id = match endpoint.query(client) {
+        Ok(found) => {
+            let found: Vec<GitlabMilestone> = found;
+            found.first().map(|milestone| milestone.id)
+        },
+        Err(e) => {
+            error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+            None
+        },
+    };
+    if id.is_none() && create_missing() {
+        let endpoint = gitlab::api::projects::milestones::CreateProjectMilestone::builder()
+            .project(project.as_str())
+            .title(title.as_str())
+            .build()
+            .unwrap();
+        match endpoint.query(client) {
+            Ok(created) => {
+                let created: GitlabMilestone = created;
+                println!("Milestone {title} created in {project}");
+                id = Some(created.id);
+            },
+            Err(e) => {
+                error_message_kind(ErrorKind::Remote, format!("ERROR: could not create milestone {title}: {e}"));
+            },
+        }
+    } else if id.is_none() {
+        eprintln!("WARNING: GitLab milestone {title} not found, task {} is pushed without milestone (use --create-missing to create it)", task.get_id().unwrap_or_default());
     }
+    milestones.insert(key, id);
+    id
+}
+
+/// Full path of the project in nested groups.
+fn project_path(namespace: &str, project: &str) -> String {
+    format!("{namespace}/{project}")
 }
 
 fn get_client(token: &str) -> Gitlab {
+    try_get_client(token).unwrap()
+}
+
+fn try_get_client(token: &str) -> Result<Gitlab, String> {
     let base_url = get_base_url();
     let gitlab_domain = match Regex::new("(https://)?(?P<domain>[^/]+)").unwrap().captures(&base_url) {
         Some(caps) if caps.name("domain").is_some() => caps.name("domain").unwrap().as_str().to_string(),
         _ => "gitlab.com".to_string(),
     };
-    Gitlab::new(gitlab_domain, token).unwrap()
+    let mut builder = Gitlab::builder(gitlab_domain, token);
+    if HttpOptions::load().insecure {
+        builder.cert_insecure();
+    }
+    builder.build().map_err(|e| e.to_string())
 }
 
 fn get_token_from_env() -> Option<String> {
@@ -442,17 +643,41 @@ mod test {
     fn test_remote_url() {
         let connector = GitlabRemoteConnector {};
 
-        gittask::set_config_value("task.gitlab.url", "https://gitlab.com/").unwrap();
-        assert!(connector.supports_remote("https://gitlab.com/jhspetersson/fselect").is_some());
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            gittask::set_config_value("task.gitlab.url", "https://gitlab.com/").unwrap();
+            assert!(connector.supports_remote("https://gitlab.com/jhspetersson/fselect").is_some());
+
+            let gitlab_url = get_base_url();
+            gittask::set_config_value("task.gitlab.url", "gitlab.kitware.com").unwrap();
 
-        let gitlab_url = get_base_url();
-        gittask::set_config_value("task.gitlab.url", "gitlab.kitware.com").unwrap();
+            let current_url = get_base_url();
+            assert_eq!(current_url, "https://gitlab.kitware.com/".to_string());
 
-        let current_url = get_base_url();
-        assert_eq!(current_url, "https://gitlab.kitware.com/".to_string());
+            assert!(connector.supports_remote("https://gitlab.kitware.com/jhspetersson/rust-gitlab.git").is_some());
+
+            gittask::set_config_value("task.gitlab.url", &gitlab_url).unwrap();
+        });
+    }
 
-        assert!(connector.supports_remote("https://gitlab.kitware.com/jhspetersson/rust-gitlab.git").is_some());
+    #[test]
+    fn test_project_milestones() {
+        let endpoint = ProjectMilestones { project: NameOrId::from("group/subgroup/project"), title: "1.0" };
+        assert_eq!(endpoint.endpoint(), "projects/group%2Fsubgroup%2Fproject/milestones");
+        assert_eq!(endpoint.method(), Method::GET);
+    }
 
-        gittask::set_config_value("task.gitlab.url", &gitlab_url).unwrap();
+    #[test]
+    fn test_nested_groups() {
+        let connector = GitlabRemoteConnector {};
+        let project = |namespace: &str, project: &str| Some((namespace.to_string(), project.to_string()));
+
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            gittask::set_config_value("task.gitlab.url", "https://gitlab.com/").unwrap();
+            assert_eq!(connector.supports_remote("https://gitlab.com/group/subgroup/project.git"), project("group/subgroup", "project"));
+            assert_eq!(connector.supports_remote("git@gitlab.com:group/subgroup/deep/project.git"), project("group/subgroup/deep", "project"));
+            assert_eq!(connector.supports_remote("ssh://git@gitlab.com:2222/group/project.git"), project("group", "project"));
+            assert_eq!(connector.supports_remote("https://gitlab.com/project"), None);
+            assert_eq!(connector.supports_remote("https://github.com/group/project"), None);
+        });
     }
-}
\ No newline at end of file
+}
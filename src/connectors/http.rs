@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::sync::Once;
+
+/// Proxy, CA bundle and certificate checks for the HTTP clients of connectors,
+/// `task.http.proxy`, `task.http.ca-cert` and `task.http.insecure` parameters.
+#[derive(Default, Debug, PartialEq)]
+pub(crate) struct HttpOptions {
+    pub proxy: Option<String>,
+    pub ca_cert: Option<PathBuf>,
+    pub insecure: bool,
+}
+
+static APPLY_ENV: Once = Once::new();
+
+impl HttpOptions {
+    pub(crate) fn load() -> HttpOptions {
+        let value = |param: &str| gittask::get_config_value(param).ok().map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        HttpOptions {
+            proxy: value("task.http.proxy"),
+            ca_cert: value("task.http.ca-cert").map(PathBuf::from),
+            insecure: value("task.http.insecure").is_some_and(|insecure| insecure == "true"),
+        }
+    }
+
+    /// Environment variables the clients built inside of the libraries pick up on their own:
+    /// reqwest-based ones (GitLab, Jira) use the proxy variables, OpenSSL and native roots of rustls read `SSL_CERT_FILE`.
+    fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut result = vec![];
+        if let Some(proxy) = &self.proxy {
+            result.extend(["HTTPS_PROXY", "HTTP_PROXY", "https_proxy", "http_proxy"].map(|var| (var, proxy.clone())));
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            result.push(("SSL_CERT_FILE", ca_cert.display().to_string()));
+        }
+        result
+    }
+
+    /// Exports the options to the environment once per process, before any client is created.
+    pub(crate) fn apply_env() {
+        APPLY_ENV.call_once(|| {
+            let options = HttpOptions::load();
+            for (var, value) in options.env_vars() {
+                std::env::set_var(var, value);
+            }
+        });
+    }
+
+    /// Applies the options to a client built by git-task itself.
+    pub(crate) fn configure(&self, mut builder: reqwest::blocking::ClientBuilder) -> Result<reqwest::blocking::ClientBuilder, String> {
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy).map_err(|e| format!("Invalid task.http.proxy: {e}"))?);
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            let pem = std::fs::read(ca_cert).map_err(|e| format!("Could not read task.http.ca-cert {}: {e}", ca_cert.display()))?;
+            for cert in reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| format!("Invalid task.http.ca-cert: {e}"))? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        Ok(builder.danger_accept_invalid_certs(self.insecure))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_http_options() {
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            assert_eq!(HttpOptions::load(), HttpOptions::default());
+            assert!(HttpOptions::default().env_vars().is_empty());
+
+            gittask::set_config_value("task.http.proxy", "http://proxy.example.com:3128").unwrap();
+            gittask::set_config_value("task.http.ca-cert", "/etc/ssl/corp.pem").unwrap();
+            gittask::set_config_value("task.http.insecure", "true").unwrap();
+            let options = HttpOptions::load();
+            assert_eq!(options, HttpOptions {
+                proxy: Some("http://proxy.example.com:3128".to_string()),
+                ca_cert: Some(PathBuf::from("/etc/ssl/corp.pem")),
+                insecure: true,
+            });
+            let vars = options.env_vars();
+            assert!(vars.contains(&("HTTPS_PROXY", "http://proxy.example.com:3128".to_string())));
+            assert!(vars.contains(&("SSL_CERT_FILE", "/etc/ssl/corp.pem".to_string())));
+
+            assert!(options.configure(reqwest::blocking::Client::builder()).is_err());
+            let options = HttpOptions { ca_cert: None, ..options };
+            assert!(options.configure(reqwest::blocking::Client::builder()).is_ok());
+        });
+    }
+}
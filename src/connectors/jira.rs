@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 use std::sync::LazyLock;
 use regex::Regex;
-use crate::connectors::{RemoteConnector, RemoteTaskState};
-use gittask::{Task, Comment, Label};
+use crate::connectors::http::HttpOptions;
+use crate::connectors::{create_missing, get_milestone, CommentWindow, LabelCache, RemoteConnector, RemoteTaskFilter, RemoteTaskState, MILESTONE};
+use crate::property::PropertyManager;
+use crate::util::{capitalize, error_message_kind, ErrorKind};
+use gittask::{Task, Comment, Label, PropertyValue};
 use jira_v3_openapi::{apis::configuration::Configuration, apis::issues_api};
-use jira_v3_openapi::apis::{issue_comments_api, issue_search_api};
+use jira_v3_openapi::apis::{issue_comments_api, issue_search_api, project_versions_api, projects_api};
 use tokio::runtime::Runtime;
 
 pub struct JiraRemoteConnector;
@@ -12,8 +15,16 @@ pub struct JiraRemoteConnector;
 static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
     Runtime::new().unwrap()
 });
+/// Names of the versions existing in Jira projects.
+static VERSION_CACHE: LazyLock<LabelCache> = LazyLock::new(LabelCache::default);
+/// Jira field the `milestone` property is synced with, unless `task.jira.fields` maps either of them.
+const MILESTONE_FIELD: &str = "fixVersions";
 
 impl RemoteConnector for JiraRemoteConnector {
+    fn name(&self) -> &'static str {
+        "jira"
+    }
+
     fn supports_remote(&self, _url: &str) -> Option<(String, String)> {
         if let Some(url) = get_base_url() {
             match Regex::new(r"https://([^/]+)\.atlassian\.net/jira/software/projects/([^/]+)").unwrap().captures(&url) {
@@ -34,11 +45,13 @@ impl RemoteConnector for JiraRemoteConnector {
         domain: &String,
         project: &String,
         with_comments: bool,
+        _comment_window: &CommentWindow,
         with_labels: bool,
         limit: Option<usize>,
         state: RemoteTaskState,
+        filter: &RemoteTaskFilter,
         _task_statuses: &Vec<String>
-    ) -> Vec<Task> {
+    ) -> Result<Vec<Task>, String> {
         let token = get_token_from_env().unwrap();
         let config = get_configuration(domain, token);
 
@@ -50,20 +63,31 @@ impl RemoteConnector for JiraRemoteConnector {
             eprintln!("Fetching labels is not yet supported by Jira connector.");
         }
 
-        let jql = match state {
+        let mut jql = match state {
             RemoteTaskState::Open => format!("project = {} AND status != Done", project),
             RemoteTaskState::Closed => format!("project = {} AND status = Done", project),
             RemoteTaskState::All => format!("project = {}", project),
         };
+        if let Some(updated_since) = filter.updated_since.and_then(|since| chrono::DateTime::from_timestamp(since, 0)) {
+            jql.push_str(&format!(" AND updated >= \"{}\"", updated_since.format("%Y/%m/%d %H:%M")));
+        }
+        for label in &filter.labels {
+            jql.push_str(&format!(" AND labels = {}", quote_jql(label)));
+        }
+        if let Some(author) = &filter.author {
+            jql.push_str(&format!(" AND creator = {}", quote_jql(author)));
+        }
 
-        let result = RUNTIME.block_on(async {
+        let status_map = get_status_map();
+        let field_map = get_field_map();
+        RUNTIME.block_on(async {
             let issues = issue_search_api::search_for_issues_using_jql(
                 &config,
                 Some(&jql),
                 None,
                 if let Some(limit) = limit { Some(limit as i32) } else { None },
                 None,
-                Some(vec!["summary".to_string(), "description".to_string(), "status".to_string(), "created".to_string(), "creator".to_string()]),
+                Some(get_requested_fields(&field_map)),
                 None,
                 None,
                 None,
@@ -71,27 +95,32 @@ impl RemoteConnector for JiraRemoteConnector {
             ).await;
             match issues {
                 Ok(response) => {
-                    response.issues.unwrap_or_default()
+                    Ok(response.issues.unwrap_or_default()
                         .into_iter()
                         .map(|issue| {
                             let mut props = HashMap::new();
                             if let Some(fields) = issue.fields {
                                 props.insert("name".to_string(), fields.get("summary").unwrap().as_str().unwrap().to_string());
                                 props.insert("description".to_string(), fields.get("description").unwrap().as_str().unwrap().to_string());
-                                props.insert("status".to_string(), fields.get("status").unwrap().as_str().unwrap().to_string());
+                                props.insert("status".to_string(), map_status(fields.get("status").unwrap().as_str().unwrap(), &status_map));
                                 props.insert("created".to_string(), fields.get("created").unwrap().as_str().unwrap().to_string());
                                 props.insert("author".to_string(), fields.get("creator").unwrap().as_str().unwrap().to_string());
+                                if let Some(updated) = fields.get("updated").and_then(|updated| updated.as_str()) {
+                                    props.insert("updated".to_string(), updated.to_string());
+                                }
+                                if let Some(issue_type) = fields.get("issuetype").and_then(|issue_type| issue_type.get("name")).and_then(|name| name.as_str()) {
+                                    props.insert("type".to_string(), issue_type.to_lowercase());
+                                }
+                                read_mapped_fields(&fields, &field_map, &mut props);
                             }
 
                             Task::from_properties(issue_key_to_task_id(&issue.key.unwrap()), props).unwrap()
                         })
-                        .collect()
+                        .collect())
                 },
-                Err(_) => vec![],
+                Err(e) => Err(e.to_string()),
             }
-        });
-
-        result
+        })
     }
 
     fn get_remote_task(
@@ -100,6 +129,7 @@ impl RemoteConnector for JiraRemoteConnector {
         project: &String,
         task_id: &String,
         with_comments: bool,
+        _comment_window: &CommentWindow,
         with_labels: bool,
         _task_statuses: &Vec<String>
     ) -> Option<Task> {
@@ -114,11 +144,13 @@ impl RemoteConnector for JiraRemoteConnector {
             eprintln!("Fetching labels is not yet supported by Jira connector.");
         }
 
+        let status_map = get_status_map();
+        let field_map = get_field_map();
         RUNTIME.block_on(async {
             match issues_api::get_issue(
                 &config,
                 task_id_to_issue_key(project, task_id).as_str(),
-                Some(vec!["summary".to_string(), "description".to_string(), "status".to_string(), "created".to_string(), "creator".to_string()]),
+                Some(get_requested_fields(&field_map)),
                 None,
                 None,
                 None,
@@ -130,9 +162,16 @@ impl RemoteConnector for JiraRemoteConnector {
                     if let Some(fields) = issue.fields {
                         props.insert("name".to_string(), fields.get("summary").unwrap().as_str().unwrap().to_string());
                         props.insert("description".to_string(), fields.get("description").unwrap().as_str().unwrap().to_string());
-                        props.insert("status".to_string(), fields.get("status").unwrap().as_str().unwrap().to_string());
+                        props.insert("status".to_string(), map_status(fields.get("status").unwrap().as_str().unwrap(), &status_map));
                         props.insert("created".to_string(), fields.get("created").unwrap().as_str().unwrap().to_string());
                         props.insert("author".to_string(), fields.get("creator").unwrap().as_str().unwrap().to_string());
+                        if let Some(updated) = fields.get("updated").and_then(|updated| updated.as_str()) {
+                            props.insert("updated".to_string(), updated.to_string());
+                        }
+                        if let Some(issue_type) = fields.get("issuetype").and_then(|issue_type| issue_type.get("name")).and_then(|name| name.as_str()) {
+                            props.insert("type".to_string(), issue_type.to_lowercase());
+                        }
+                        read_mapped_fields(&fields, &field_map, &mut props);
                     }
 
                     Some(Task::from_properties(issue_key_to_task_id(&issue.key.unwrap()), props).unwrap())
@@ -152,8 +191,7 @@ impl RemoteConnector for JiraRemoteConnector {
         let config = get_configuration(domain, token);
 
         RUNTIME.block_on(async {
-            let issue_details = jira_v3_openapi::models::IssueUpdateDetails {
-                fields: Some(std::collections::HashMap::from([
+            let mut fields = std::collections::HashMap::from([
                     ("project".to_string(), serde_json::json!({
                         "key": project
                     })),
@@ -164,9 +202,15 @@ impl RemoteConnector for JiraRemoteConnector {
                         task.get_property("description").unwrap()
                     )),
                     ("issuetype".to_string(), serde_json::json!({
-                        "name": "Task"
+                        "name": get_issue_type(task)
                     })),
-                ])),
+                ]);
+            let field_map = get_field_map();
+            write_mapped_fields(task, &field_map, &mut fields);
+            write_milestone(&config, domain, project, task, &field_map, &mut fields).await;
+
+            let issue_details = jira_v3_openapi::models::IssueUpdateDetails {
+                fields: Some(fields),
                 ..Default::default()
             };
 
@@ -305,6 +349,14 @@ impl RemoteConnector for JiraRemoteConnector {
             fields.insert("description".to_string(),
                           serde_json::json!(task.get_property("description").unwrap()));
 
+            if task.has_property("type") {
+                fields.insert("issuetype".to_string(), serde_json::json!({ "name": get_issue_type(task) }));
+            }
+
+            let field_map = get_field_map();
+            write_mapped_fields(task, &field_map, &mut fields);
+            write_milestone(&config, domain, project, task, &field_map, &mut fields).await;
+
             if let Some(labels) = labels {
                 fields.insert(
                     "labels".to_string(),
@@ -491,6 +543,17 @@ impl RemoteConnector for JiraRemoteConnector {
             }
         })
     }
+
+    fn check_connection(&self, domain: &str, project: &str) -> Result<String, String> {
+        let token = get_token_from_env().ok_or_else(|| "Could not find JIRA_TOKEN environment variable.".to_string())?;
+        let config = get_configuration(domain, token);
+        RUNTIME.block_on(async {
+            match projects_api::get_project(&config, project, None, None).await {
+                Ok(project) => Ok(format!("Jira project {} is accessible", project.key.unwrap_or_default())),
+                Err(e) => Err(format!("Failed to get project: {}", e))
+            }
+        })
+    }
 }
 
 fn get_token_from_env() -> Option<String> {
@@ -513,10 +576,15 @@ fn get_base_url() -> Option<String> {
     Some(result)
 }
 
-fn get_configuration(domain: &String, token: String) -> Configuration {
+fn get_configuration(domain: &str, token: String) -> Configuration {
     let mut config = Configuration::new();
     config.bearer_access_token = Some(token);
     config.base_path = format!("https://{}.atlassian.net", domain);
+    // proxy and CA bundle are picked up from the environment, see HttpOptions::apply_env
+    if HttpOptions::load().insecure {
+        let client = jira_reqwest::Client::builder().danger_accept_invalid_certs(true).build().unwrap();
+        config.client = reqwest_middleware::ClientBuilder::new(client).build();
+    }
     config
 }
 
@@ -526,4 +594,213 @@ fn issue_key_to_task_id(key: &String) -> String {
 
 fn task_id_to_issue_key(project: &String, id: &String) -> String {
     format!("{}-{}", project, id)
-}
\ No newline at end of file
+}
+/// Jira issue type name for the task `type` property, e.g. bug -> Bug, defaults to Task.
+fn get_issue_type(task: &Task) -> String {
+    match task.get_property("type").filter(|task_type| !task_type.is_empty()) {
+        Some(task_type) => capitalize(task_type),
+        None => "Task".to_string(),
+    }
+}
+
+/// Local status for the Jira status name according to `task.jira.status.map` ("To Do=OPEN, Done=CLOSED"),
+/// names without a mapping are kept as they are.
+fn map_status(status: &str, status_map: &Option<String>) -> String {
+    status_map.iter()
+        .flat_map(|status_map| status_map.split(','))
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(status))
+        .map(|(_, local)| local.trim().to_string())
+        .unwrap_or_else(|| status.to_string())
+}
+
+fn get_status_map() -> Option<String> {
+    gittask::get_config_value("task.jira.status.map").ok()
+}
+
+/// Local properties mapped to Jira fields with `task.jira.fields` ("components=customfield_10020, versions=fixVersions").
+fn get_field_map() -> Vec<(String, String)> {
+    gittask::get_config_value("task.jira.fields").ok().iter()
+        .flat_map(|fields| fields.split(','))
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(prop, field)| (prop.trim().to_string(), field.trim().to_string()))
+        .filter(|(prop, field)| !prop.is_empty() && !field.is_empty())
+        .collect()
+}
+
+fn get_requested_fields(field_map: &[(String, String)]) -> Vec<String> {
+    let mut requested = ["summary", "description", "status", "created", "updated", "creator", "issuetype"].map(String::from).to_vec();
+    requested.extend(field_map.iter().map(|(_, field)| field.clone()));
+    if syncs_milestone(field_map) {
+        requested.push(MILESTONE_FIELD.to_string());
+    }
+    requested
+}
+
+fn syncs_milestone(field_map: &[(String, String)]) -> bool {
+    !field_map.iter().any(|(prop, field)| prop == MILESTONE || field == MILESTONE_FIELD)
+}
+
+fn read_mapped_fields(fields: &HashMap<String, serde_json::Value>, field_map: &[(String, String)], props: &mut HashMap<String, String>) {
+    for (prop, field) in field_map {
+        if let Some(value) = fields.get(field).and_then(from_field_value) {
+            props.insert(prop.clone(), value);
+        }
+    }
+    if syncs_milestone(field_map) {
+        if let Some(milestone) = fields.get(MILESTONE_FIELD).and_then(read_milestone) {
+            props.insert(MILESTONE.to_string(), milestone);
+        }
+    }
+}
+
+/// A single fix version becomes a plain `milestone` value, several of them a list.
+fn read_milestone(value: &serde_json::Value) -> Option<String> {
+    let names = value.as_array()?.iter()
+        .filter_map(|version| version.get("name").and_then(|name| name.as_str()).map(String::from))
+        .collect::<Vec<_>>();
+    match names.len() {
+        0 => None,
+        1 => names.into_iter().next(),
+        _ => Some(gittask::format_list_value(&names)),
+    }
+}
+
+fn milestone_names(milestone: &str, is_list: bool) -> Vec<String> {
+    match is_list {
+        true => Vec::<String>::parse_value(milestone).unwrap_or_default(),
+        false => vec![milestone.to_string()],
+    }
+}
+
+/// Sets fix versions from the `milestone` property, the versions missing in the project
+/// are created with `push --create-missing` or skipped otherwise.
+async fn write_milestone(config: &Configuration, domain: &str, project: &str, task: &Task, field_map: &[(String, String)], fields: &mut HashMap<String, serde_json::Value>) {
+    let Some(milestone) = get_milestone(task).filter(|_| syncs_milestone(field_map)) else {
+        return;
+    };
+
+    if VERSION_CACHE.get(domain, project).is_none() {
+        match project_versions_api::get_project_versions(config, project, None).await {
+            Ok(versions) => VERSION_CACHE.set(domain, project, versions.into_iter().filter_map(|version| version.name).collect()),
+            Err(e) => {
+                error_message_kind(ErrorKind::Remote, format!("ERROR: could not list versions of {project}: {e}"));
+            },
+        }
+    }
+
+    let mut names = vec![];
+    for name in milestone_names(&milestone, PropertyManager::new().is_list(MILESTONE)) {
+        if VERSION_CACHE.contains(domain, project, &name) {
+            names.push(name);
+        } else if create_missing() {
+            let version = jira_v3_openapi::models::Version {
+                name: Some(name.clone()),
+                project: Some(project.to_string()),
+                ..Default::default()
+            };
+            match project_versions_api::create_version(config, version).await {
+                Ok(_) => {
+                    println!("Version {name} created in {project}");
+                    VERSION_CACHE.add(domain, project, &name);
+                    names.push(name);
+                },
+                Err(e) => {
+                    error_message_kind(ErrorKind::Remote, format!("ERROR: could not create version {name}: {e}"));
+                },
+            }
+        } else {
+            eprintln!("WARNING: Jira version {name} not found, task {} is pushed without it (use --create-missing to create it)", task.get_id().unwrap_or_default());
+        }
+    }
+
+    if !names.is_empty() {
+        fields.insert(MILESTONE_FIELD.to_string(), serde_json::Value::Array(names.iter().map(|name| serde_json::json!({ "name": name })).collect()));
+    }
+}
+
+fn write_mapped_fields(task: &Task, field_map: &[(String, String)], fields: &mut HashMap<String, serde_json::Value>) {
+    let prop_manager = PropertyManager::new();
+    for (prop, field) in field_map {
+        if let Some(value) = task.get_property(prop) {
+            let is_list = prop_manager.is_list(prop);
+            fields.insert(field.clone(), to_field_value(field, value, is_list));
+        }
+    }
+}
+
+/// Multi-select and other array fields become list values, options are referred by their values or names.
+fn from_field_value(value: &serde_json::Value) -> Option<String> {
+    let item_name = |item: &serde_json::Value| item.as_str()
+        .or_else(|| item.get("value").and_then(|value| value.as_str()))
+        .or_else(|| item.get("name").and_then(|name| name.as_str()))
+        .map(String::from);
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Number(number) => Some(number.to_string()),
+        serde_json::Value::Array(items) => Some(gittask::format_list_value(&items.iter().filter_map(item_name).collect::<Vec<_>>())),
+        value => item_name(value),
+    }
+}
+
+/// Options of custom multi-select fields are set by value, system fields like components by name.
+fn to_field_value(field: &str, value: &str, is_list: bool) -> serde_json::Value {
+    let key = if field.starts_with("customfield_") { "value" } else { "name" };
+    match is_list {
+        true => serde_json::Value::Array(Vec::<String>::parse_value(value).unwrap_or_default().iter()
+            .map(|item| serde_json::json!({ key: item }))
+            .collect()),
+        false => serde_json::json!(value),
+    }
+}
+
+fn quote_jql(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_map_status() {
+        let status_map = Some("To Do=OPEN, In Progress = IN_PROGRESS,Done=CLOSED".to_string());
+        assert_eq!(map_status("In Progress", &status_map), "IN_PROGRESS");
+        assert_eq!(map_status("done", &status_map), "CLOSED");
+        assert_eq!(map_status("Review", &status_map), "Review");
+        assert_eq!(map_status("Done", &None), "Done");
+    }
+
+    #[test]
+    fn test_field_values() {
+        let options = serde_json::json!([{ "value": "ui", "id": "1" }, { "value": "api", "id": "2" }]);
+        assert_eq!(from_field_value(&options), Some(r#"["ui","api"]"#.to_string()));
+        assert_eq!(from_field_value(&serde_json::json!({ "value": "High" })), Some("High".to_string()));
+        assert_eq!(from_field_value(&serde_json::json!(3)), Some("3".to_string()));
+        assert_eq!(from_field_value(&serde_json::Value::Null), None);
+
+        assert_eq!(to_field_value("customfield_10020", r#"["ui","api"]"#, true), serde_json::json!([{ "value": "ui" }, { "value": "api" }]));
+        assert_eq!(to_field_value("components", r#"["ui"]"#, true), serde_json::json!([{ "name": "ui" }]));
+        assert_eq!(to_field_value("customfield_10030", "5", false), serde_json::json!("5"));
+    }
+
+    #[test]
+    fn test_milestone() {
+        assert_eq!(read_milestone(&serde_json::json!([{ "id": "1", "name": "1.0" }])), Some("1.0".to_string()));
+        assert_eq!(read_milestone(&serde_json::json!([{ "name": "1.0" }, { "name": "1.1" }])), Some(r#"["1.0","1.1"]"#.to_string()));
+        assert_eq!(read_milestone(&serde_json::json!([])), None);
+        assert_eq!(milestone_names(r#"["1.0","1.1"]"#, true), vec!["1.0", "1.1"]);
+        assert_eq!(milestone_names("Sprint 5", true), vec!["Sprint 5"]);
+        assert_eq!(milestone_names("[beta] 2.0", false), vec!["[beta] 2.0"]);
+
+        assert!(syncs_milestone(&[("components".to_string(), "customfield_10020".to_string())]));
+        assert!(!syncs_milestone(&[("versions".to_string(), "fixVersions".to_string())]));
+        assert!(!syncs_milestone(&[("milestone".to_string(), "customfield_10040".to_string())]));
+    }
+
+    #[test]
+    fn test_quote_jql() {
+        assert_eq!(quote_jql("bug"), "\"bug\"");
+        assert_eq!(quote_jql("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+}
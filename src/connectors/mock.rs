@@ -0,0 +1,386 @@
+use std::path::{Path, PathBuf};
+
+use gittask::{Comment, Label, Task};
+use crate::connectors::{CommentWindow, RemoteConnector, RemoteTaskFilter, RemoteTaskState};
+use crate::util::error_message;
+
+/// Fixture file of the mock connector, `git-task-mock.json` in the current directory by default.
+pub(crate) const MOCK_FILE_ENV: &str = "GIT_TASK_MOCK_FILE";
+const DEFAULT_MOCK_FILE: &str = "git-task-mock.json";
+
+/// Remote connector backed by a local JSON fixture in the `git task export` format,
+/// for scripting and testing sync workflows without touching real trackers.
+pub struct MockRemoteConnector;
+
+impl RemoteConnector for MockRemoteConnector {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn supports_remote(&self, _url: &str) -> Option<(String, String)> {
+        None
+    }
+
+    fn list_remote_tasks(
+        &self,
+        _user: &String,
+        _repo: &String,
+        with_comments: bool,
+        comment_window: &CommentWindow,
+        with_labels: bool,
+        limit: Option<usize>,
+        state: RemoteTaskState,
+        filter: &RemoteTaskFilter,
+        task_statuses: &Vec<String>
+    ) -> Result<Vec<Task>, String> {
+        let tasks = load_fixture(&get_fixture_path())?;
+
+        Ok(tasks.into_iter()
+            .filter(|task| match state {
+                RemoteTaskState::All => true,
+                RemoteTaskState::Open => task.status() != task_statuses[1],
+                RemoteTaskState::Closed => task.status() == task_statuses[1],
+            })
+            .filter(|task| matches_filter(task, filter))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|task| prepare_remote_task(task, with_comments, comment_window, with_labels))
+            .collect())
+    }
+
+    fn get_remote_task(
+        &self,
+        _user: &String,
+        _repo: &String,
+        task_id: &String,
+        with_comments: bool,
+        comment_window: &CommentWindow,
+        with_labels: bool,
+        _task_statuses: &Vec<String>
+    ) -> Option<Task> {
+        load_fixture(&get_fixture_path()).ok()?
+            .into_iter()
+            .find(|task| task.get_id().as_ref() == Some(task_id))
+            .map(|task| prepare_remote_task(task, with_comments, comment_window, with_labels))
+    }
+
+    fn create_remote_task(&self, _user: &String, _repo: &String, task: &Task) -> Result<String, String> {
+        update_fixture(&get_fixture_path(), |tasks| {
+            let id = (tasks.iter().filter_map(|task| task.get_id()?.parse::<u64>().ok()).max().unwrap_or(0) + 1).to_string();
+            let mut task = prepare_remote_task(task.clone(), false, &CommentWindow::default(), true);
+            task.set_id(id.clone());
+            tasks.push(task);
+            Ok(id)
+        })
+    }
+
+    fn create_remote_comment(&self, _user: &String, _repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
+        update_fixture(&get_fixture_path(), |tasks| {
+            let id = (tasks.iter()
+                .flat_map(|task| task.get_comments().clone().unwrap_or_default())
+                .filter_map(|comment| comment.get_id()?.parse::<u64>().ok())
+                .max().unwrap_or(0) + 1).to_string();
+            let task = find_task(tasks, task_id)?;
+            task.add_comment(Some(id.clone()), comment.get_all_properties().clone(), comment.get_text());
+            Ok(id)
+        })
+    }
+
+    fn create_remote_label(&self, _user: &String, _repo: &String, task_id: &String, label: &Label) -> Result<(), String> {
+        update_fixture(&get_fixture_path(), |tasks| {
+            let task = find_task(tasks, task_id)?;
+            if task.get_label_by_name(&label.get_name()).is_none() {
+                task.add_label(label.get_name(), label.get_description(), Some(label.get_color()));
+            }
+            Ok(())
+        })
+    }
+
+    fn update_remote_task(
+        &self,
+        _user: &String,
+        _repo: &String,
+        task: &Task,
+        labels: Option<&Vec<Label>>,
+        _state: RemoteTaskState
+    ) -> Result<(), String> {
+        let task_id = task.get_id().ok_or_else(|| "Task has no ID".to_string())?;
+        update_fixture(&get_fixture_path(), |tasks| {
+            let remote_task = find_task(tasks, &task_id)?;
+            for (prop, value) in task.get_all_properties() {
+                remote_task.set_property(prop, value);
+            }
+            if let Some(labels) = labels {
+                remote_task.set_labels(labels.clone());
+            }
+            Ok(())
+        })
+    }
+
+    fn update_remote_comment(&self, _user: &String, _repo: &String, task_id: &String, comment_id: &String, text: &String) -> Result<(), String> {
+        update_fixture(&get_fixture_path(), |tasks| {
+            let task = find_task(tasks, task_id)?;
+            let mut comments = task.get_comments().clone().unwrap_or_default();
+            let comment = comments.iter_mut()
+                .find(|comment| comment.get_id().as_ref() == Some(comment_id))
+                .ok_or_else(|| format!("Comment ID {comment_id} not found"))?;
+            comment.set_text(text.clone());
+            task.set_comments(comments);
+            Ok(())
+        })
+    }
+
+    fn delete_remote_task(&self, _user: &String, _repo: &String, task_id: &String) -> Result<(), String> {
+        update_fixture(&get_fixture_path(), |tasks| {
+            let len = tasks.len();
+            tasks.retain(|task| task.get_id().as_ref() != Some(task_id));
+            match tasks.len() < len {
+                true => Ok(()),
+                false => Err(format!("Task ID {task_id} not found")),
+            }
+        })
+    }
+
+    fn delete_remote_comment(&self, _user: &String, _repo: &String, task_id: &String, comment_id: &String) -> Result<(), String> {
+        update_fixture(&get_fixture_path(), |tasks| find_task(tasks, task_id)?.delete_comment(comment_id))
+    }
+
+    fn delete_remote_label(&self, _user: &String, _repo: &String, task_id: &String, name: &String) -> Result<(), String> {
+        update_fixture(&get_fixture_path(), |tasks| find_task(tasks, task_id)?.delete_label(name))
+    }
+
+    fn check_connection(&self, _user: &str, _repo: &str) -> Result<String, String> {
+        let path = get_fixture_path();
+        let tasks = load_fixture(&path)?;
+        Ok(format!("Mock fixture {} has {} task(s)", path.display(), tasks.len()))
+    }
+}
+
+/// Wraps a real connector and saves every task it fetches into a fixture file,
+/// which can be replayed later with the mock connector.
+pub struct RecordingConnector {
+    inner: &'static dyn RemoteConnector,
+    path: PathBuf,
+}
+
+impl RecordingConnector {
+    pub fn new(inner: &'static dyn RemoteConnector, path: PathBuf) -> RecordingConnector {
+        RecordingConnector { inner, path }
+    }
+
+    fn record(&self, recorded: &[Task]) {
+        let result = update_fixture(&self.path, |tasks| {
+            for task in recorded {
+                match tasks.iter().position(|t| t.get_id() == task.get_id()) {
+                    Some(pos) => tasks[pos] = task.clone(),
+                    None => tasks.push(task.clone()),
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            error_message(format!("ERROR: could not record to {}: {e}", self.path.display()));
+        }
+    }
+}
+
+impl RemoteConnector for RecordingConnector {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn supports_remote(&self, url: &str) -> Option<(String, String)> {
+        self.inner.supports_remote(url)
+    }
+
+    fn list_remote_tasks(&self, user: &String, repo: &String, with_comments: bool, comment_window: &CommentWindow, with_labels: bool, limit: Option<usize>, state: RemoteTaskState, filter: &RemoteTaskFilter, task_statuses: &Vec<String>) -> Result<Vec<Task>, String> {
+        let tasks = self.inner.list_remote_tasks(user, repo, with_comments, comment_window, with_labels, limit, state, filter, task_statuses)?;
+        self.record(&tasks);
+        Ok(tasks)
+    }
+
+    fn get_remote_task(&self, user: &String, repo: &String, task_id: &String, with_comments: bool, comment_window: &CommentWindow, with_labels: bool, task_statuses: &Vec<String>) -> Option<Task> {
+        let task = self.inner.get_remote_task(user, repo, task_id, with_comments, comment_window, with_labels, task_statuses);
+        if let Some(task) = &task {
+            self.record(std::slice::from_ref(task));
+        }
+        task
+    }
+
+    fn create_remote_task(&self, user: &String, repo: &String, task: &Task) -> Result<String, String> {
+        self.inner.create_remote_task(user, repo, task)
+    }
+
+    fn create_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment: &Comment) -> Result<String, String> {
+        self.inner.create_remote_comment(user, repo, task_id, comment)
+    }
+
+    fn create_remote_label(&self, user: &String, repo: &String, task_id: &String, label: &Label) -> Result<(), String> {
+        self.inner.create_remote_label(user, repo, task_id, label)
+    }
+
+    fn update_remote_task(&self, user: &String, repo: &String, task: &Task, labels: Option<&Vec<Label>>, state: RemoteTaskState) -> Result<(), String> {
+        self.inner.update_remote_task(user, repo, task, labels, state)
+    }
+
+    fn update_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String, text: &String) -> Result<(), String> {
+        self.inner.update_remote_comment(user, repo, task_id, comment_id, text)
+    }
+
+    fn delete_remote_task(&self, user: &String, repo: &String, task_id: &String) -> Result<(), String> {
+        self.inner.delete_remote_task(user, repo, task_id)
+    }
+
+    fn delete_remote_comment(&self, user: &String, repo: &String, task_id: &String, comment_id: &String) -> Result<(), String> {
+        self.inner.delete_remote_comment(user, repo, task_id, comment_id)
+    }
+
+    fn delete_remote_label(&self, user: &String, repo: &String, task_id: &String, name: &String) -> Result<(), String> {
+        self.inner.delete_remote_label(user, repo, task_id, name)
+    }
+
+    fn check_connection(&self, user: &str, repo: &str) -> Result<String, String> {
+        self.inner.check_connection(user, repo)
+    }
+}
+
+fn get_fixture_path() -> PathBuf {
+    PathBuf::from(std::env::var(MOCK_FILE_ENV).unwrap_or_else(|_| DEFAULT_MOCK_FILE.to_string()))
+}
+
+fn load_fixture(path: &Path) -> Result<Vec<Task>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid fixture {}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn update_fixture<T>(path: &Path, f: impl FnOnce(&mut Vec<Task>) -> Result<T, String>) -> Result<T, String> {
+    let mut tasks = load_fixture(path)?;
+    let result = f(&mut tasks)?;
+    let content = serde_json::to_string_pretty(&tasks).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+fn find_task<'a>(tasks: &'a mut [Task], task_id: &String) -> Result<&'a mut Task, String> {
+    tasks.iter_mut()
+        .find(|task| task.get_id().as_ref() == Some(task_id))
+        .ok_or_else(|| format!("Task ID {task_id} not found"))
+}
+
+fn matches_filter(task: &Task, filter: &RemoteTaskFilter) -> bool {
+    let updated_since = filter.updated_since.is_none_or(|since| task.updated_at().is_none_or(|updated| updated.timestamp() >= since));
+    let author = filter.author.as_ref().is_none_or(|author| task.get_property("author") == Some(author));
+    let labels = filter.labels.iter().all(|label| task.get_label_by_name(label).is_some());
+    updated_since && author && labels
+}
+
+/// Returns a copy of the fixture task carrying only the requested parts.
+fn prepare_remote_task(task: Task, with_comments: bool, comment_window: &CommentWindow, with_labels: bool) -> Task {
+    let Ok(mut result) = Task::from_properties(task.get_id().unwrap_or_default(), task.get_all_properties().clone()) else {
+        return task;
+    };
+    if let Some(comments) = task.get_comments().as_ref().filter(|_| with_comments) {
+        result.set_comments(comment_window.apply(comments.clone()));
+    }
+    if let Some(labels) = task.get_labels().as_ref().filter(|labels| with_labels && !labels.is_empty()) {
+        result.set_labels(labels.clone());
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        assert!(load_fixture(&path).unwrap().is_empty());
+
+        let task = Task::builder("Crash").id("7").status("OPEN").label(Label::new("bug".to_string(), None, None)).build().unwrap();
+        let recorder = RecordingConnector::new(&MockRemoteConnector, path.clone());
+        recorder.record(&[task.clone(), task]);
+
+        let tasks = load_fixture(&path).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].get_id(), Some("7".to_string()));
+
+        let id = update_fixture(&path, |tasks| {
+            find_task(tasks, &"7".to_string())?.add_comment(Some("1".to_string()), HashMap::new(), "Confirmed".to_string());
+            Ok(tasks.len())
+        }).unwrap();
+        assert_eq!(id, 1);
+        assert_eq!(load_fixture(&path).unwrap()[0].get_comments().as_ref().unwrap().len(), 1);
+        assert!(update_fixture(&path, |tasks| find_task(tasks, &"8".to_string()).map(|_| ())).is_err());
+
+        std::fs::write(&path, "[{\"id\": \"7\"").unwrap();
+        assert!(load_fixture(&path).is_err());
+    }
+
+    /// Pulls and pushes through the mock connector the way `git task pull` and `git task push` do.
+    #[test]
+    fn test_pull_push() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("remote.json");
+        std::env::set_var(crate::connectors::CONNECTOR_ENV, "mock");
+        std::env::set_var(MOCK_FILE_ENV, &path);
+        let pull = || crate::operations::task_pull(None, &None, crate::operations::PullOptions::default());
+
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            std::fs::write(&path, "not json").unwrap();
+            assert!(!pull());
+            assert!(gittask::list_tasks().is_err());
+            assert_eq!(gittask::get_last_pull("mock/mock").unwrap(), None);
+
+            std::fs::write(&path, r#"[
+                {"id": "1", "props": {"name": "Crash", "status": "OPEN", "description": "Steps", "created": "1700000000", "updated": "1700000000", "author": "alice"},
+                 "comments": [{"id": "1", "props": {"author": "bob", "created": "1700000100"}, "text": "Confirmed"}],
+                 "labels": [{"name": "bug", "description": null, "color": "d73a4a"}]},
+                {"id": "2", "props": {"name": "Docs", "status": "CLOSED", "description": "", "created": "1700000000", "updated": "1700000000", "author": "alice"},
+                 "comments": null, "labels": null}
+            ]"#).unwrap();
+            assert!(pull());
+            let task = gittask::find_task("1").unwrap().unwrap();
+            assert_eq!(task.get_property("name").unwrap(), "Crash");
+            assert_eq!(task.get_comments().as_ref().unwrap()[0].get_text(), "Confirmed");
+            assert!(task.get_label_by_name("bug").is_some());
+            assert_eq!(gittask::find_task("2").unwrap().unwrap().get_property("status").unwrap(), "CLOSED");
+            assert!(gittask::get_last_pull("mock/mock").unwrap().is_some());
+
+            let mut task = task;
+            task.set_property("name", "Crash on start");
+            gittask::update_task(task).unwrap();
+            let id = gittask::create_task(Task::builder("New").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            assert!(crate::operations::task_push(format!("1,{id}"), &None, false, false, true, false, false));
+
+            let remote_tasks = load_fixture(&path).unwrap();
+            assert_eq!(remote_tasks.len(), 3);
+            assert_eq!(remote_tasks[0].get_property("name").unwrap(), "Crash on start");
+            assert_eq!(remote_tasks[2].get_id(), Some("3".to_string()));
+            assert_eq!(remote_tasks[2].get_property("name").unwrap(), "New");
+        });
+
+        std::env::remove_var(MOCK_FILE_ENV);
+        std::env::remove_var(crate::connectors::CONNECTOR_ENV);
+    }
+
+    #[test]
+    fn test_matches_filter() {
+        let task = Task::builder("Crash").status("OPEN")
+            .property("author", "alice")
+            .property("updated", "1000")
+            .label(Label::new("bug".to_string(), None, None))
+            .build().unwrap();
+
+        assert!(matches_filter(&task, &RemoteTaskFilter::default()));
+        assert!(matches_filter(&task, &RemoteTaskFilter { updated_since: Some(500), labels: vec!["bug".to_string()], author: Some("alice".to_string()) }));
+        assert!(!matches_filter(&task, &RemoteTaskFilter { updated_since: Some(1500), ..Default::default() }));
+        assert!(!matches_filter(&task, &RemoteTaskFilter { labels: vec!["bug".to_string(), "ui".to_string()], ..Default::default() }));
+        assert!(!matches_filter(&task, &RemoteTaskFilter { author: Some("bob".to_string()), ..Default::default() }));
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeSet;
+
+use serde_json::json;
+
+use gittask::Task;
+
+use crate::connectors::http::HttpOptions;
+use crate::status::StatusManager;
+use crate::template::render;
+
+pub(crate) const DEFAULT_EVENTS: &str = "created,status,deleted";
+const DEFAULT_TEMPLATE: &str = "{{summary}}";
+
+/// Bookkeeping properties that change with every update.
+const IGNORED_PROPERTIES: [&str; 2] = ["updated", "updated_by"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WebhookKind {
+    Slack,
+    Discord,
+}
+
+impl WebhookKind {
+    fn name(&self) -> &'static str {
+        match self {
+            WebhookKind::Slack => "slack",
+            WebhookKind::Discord => "discord",
+        }
+    }
+
+    /// Message size limit of the service, longer notifications are split.
+    fn max_length(&self) -> usize {
+        match self {
+            WebhookKind::Slack => 4000,
+            WebhookKind::Discord => 2000,
+        }
+    }
+
+    fn payload(&self, text: &str) -> serde_json::Value {
+        match self {
+            WebhookKind::Slack => json!({ "text": text }),
+            WebhookKind::Discord => json!({ "content": text }),
+        }
+    }
+}
+
+struct Webhook {
+    kind: WebhookKind,
+    url: String,
+    template: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EventKind {
+    Created,
+    Updated,
+    StatusChanged,
+    Deleted,
+}
+
+impl EventKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Updated => "updated",
+            EventKind::StatusChanged => "status",
+            EventKind::Deleted => "deleted",
+        }
+    }
+}
+
+struct TaskEvent {
+    kind: EventKind,
+    /// The saved task, or the last version of a deleted one
+    task: Task,
+    old_status: Option<String>,
+    changes: Vec<String>,
+}
+
+/// Sends changes made by a command to Slack and Discord webhooks (`task.notify.slack.webhook`, `task.notify.discord.webhook`).
+/// Events are found by comparing the tasks ref before and after the command, so every way of changing tasks is covered.
+pub(crate) struct Notifier {
+    webhooks: Vec<Webhook>,
+    events: Vec<String>,
+    labels: Vec<String>,
+    commit: Option<String>,
+}
+
+impl Notifier {
+    /// Remembers the current state of the tasks, `None` if no webhook is configured.
+    pub(crate) fn start() -> Option<Notifier> {
+        let value = |param: &str| gittask::get_config_value(param).ok().map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        let webhooks = [WebhookKind::Slack, WebhookKind::Discord].into_iter()
+            .filter_map(|kind| value(&format!("task.notify.{}.webhook", kind.name())).map(|url| Webhook {
+                kind,
+                url,
+                template: value(&format!("task.notify.{}.template", kind.name())).unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+            }))
+            .collect::<Vec<_>>();
+        if webhooks.is_empty() {
+            return None;
+        }
+
+        let split = |value: String| value.split(',').map(|item| item.trim().to_string()).filter(|item| !item.is_empty()).collect::<Vec<_>>();
+        Some(Notifier {
+            webhooks,
+            events: split(value("task.notify.events").unwrap_or_else(|| DEFAULT_EVENTS.to_string())),
+            labels: value("task.notify.labels").map(split).unwrap_or_default(),
+            commit: gittask::get_tasks_commit().ok()?,
+        })
+    }
+
+    /// Notifies about the changes since `start`, failures to deliver are only reported as warnings.
+    pub(crate) fn finish(self) {
+        if gittask::get_tasks_commit().ok().flatten() == self.commit {
+            return;
+        }
+
+        let old_tasks = match &self.commit {
+            Some(commit) => gittask::list_tasks_at(commit),
+            None => Ok(vec![]),
+        };
+        let (old_tasks, new_tasks) = match (old_tasks, gittask::list_tasks()) {
+            (Ok(old_tasks), Ok(new_tasks)) => (old_tasks, new_tasks),
+            (Err(e), _) | (_, Err(e)) => return eprintln!("WARNING: could not collect task changes for notifications: {e}"),
+        };
+
+        let status_manager = StatusManager::new();
+        let events = match get_events(&old_tasks, &new_tasks) {
+            Ok(events) => events,
+            Err(e) => return eprintln!("WARNING: could not collect task changes for notifications: {e}"),
+        };
+        let events = events.into_iter()
+            .filter(|event| matches_filter(event, &self.events, &self.labels, &status_manager))
+            .collect::<Vec<_>>();
+        if events.is_empty() {
+            return;
+        }
+
+        let client = match HttpOptions::load().configure(reqwest::blocking::Client::builder()).and_then(|builder| builder.build().map_err(|e| e.to_string())) {
+            Ok(client) => client,
+            Err(e) => return eprintln!("WARNING: could not send notifications: {e}"),
+        };
+        for webhook in &self.webhooks {
+            let messages = match events.iter().map(|event| render(&webhook.template, &get_context(event))).collect::<Result<Vec<_>, _>>() {
+                Ok(messages) => messages,
+                Err(e) => {
+                    eprintln!("WARNING: invalid task.notify.{}.template: {e}", webhook.kind.name());
+                    continue;
+                },
+            };
+            for text in join_messages(&messages, webhook.kind.max_length()) {
+                let result = client.post(&webhook.url).json(&webhook.kind.payload(&text)).send()
+                    .map_err(|e| e.to_string())
+                    .and_then(|response| response.error_for_status().map_err(|e| e.to_string()));
+                if let Err(e) = result {
+                    eprintln!("WARNING: could not send {} notification: {e}", webhook.kind.name());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Creations, deletions, status changes and other updates between two sets of tasks, ordered by task ID.
+fn get_events(old_tasks: &[Task], new_tasks: &[Task]) -> Result<Vec<TaskEvent>, String> {
+    let find = |tasks: &'_ [Task], id: &str| tasks.iter().find(|task| task.get_id().as_deref() == Some(id)).cloned();
+    let mut ids = old_tasks.iter().chain(new_tasks).filter_map(|task| task.get_id()).collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>();
+    gittask::sort_by_task_id(&mut ids, |id| Some(id.clone()))?;
+
+    Ok(ids.into_iter().filter_map(|id| {
+        let event = |kind, task, old_status, changes| Some(TaskEvent { kind, task, old_status, changes });
+        match (find(old_tasks, &id), find(new_tasks, &id)) {
+            (None, Some(task)) => event(EventKind::Created, task, None, vec![]),
+            (Some(task), None) => event(EventKind::Deleted, task, None, vec![]),
+            (Some(old_task), Some(new_task)) => {
+                let changes = get_changed_properties(&old_task, &new_task);
+                let old_status = old_task.get_property("status").cloned();
+                match (changes.is_empty(), changes.iter().any(|prop| prop == "status")) {
+                    (true, _) => None,
+                    (false, true) => event(EventKind::StatusChanged, new_task, old_status, changes),
+                    (false, false) => event(EventKind::Updated, new_task, None, changes),
+                }
+            },
+            (None, None) => None,
+        }
+    }).collect())
+}
+
+fn get_changed_properties(old_task: &Task, new_task: &Task) -> Vec<String> {
+    let mut result = old_task.get_all_properties().keys().chain(new_task.get_all_properties().keys())
+        .filter(|prop| !IGNORED_PROPERTIES.contains(&prop.as_str()) && old_task.get_property(prop) != new_task.get_property(prop))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let label_names = |task: &Task| task.get_labels().iter().flatten().map(|label| label.get_name()).collect::<Vec<_>>();
+    if label_names(old_task) != label_names(new_task) {
+        result.push("labels".to_string());
+    }
+    let comment_count = |task: &Task| task.get_comments().as_ref().map(|comments| comments.len()).unwrap_or(0);
+    if comment_count(old_task) != comment_count(new_task) {
+        result.push("comments".to_string());
+    }
+
+    result
+}
+
+/// Checks `task.notify.events` (`created`, `updated`, `deleted`, `status`, `status:NAME` or `done`)
+/// and `task.notify.labels`: the task must have one of the labels if any are given.
+fn matches_filter(event: &TaskEvent, events: &[String], labels: &[String], status_manager: &StatusManager) -> bool {
+    let status = event.task.get_property("status").cloned().unwrap_or_default();
+    let matches_event = events.iter().any(|filter| match (filter.as_str(), event.kind) {
+        ("done", EventKind::StatusChanged) => status_manager.is_done(&status),
+        (filter, EventKind::StatusChanged) if filter.starts_with("status:") => filter["status:".len()..].eq_ignore_ascii_case(&status),
+        (filter, kind) => filter == kind.name(),
+    });
+    let matches_labels = labels.is_empty() || event.task.get_labels().iter().flatten().any(|label| labels.iter().any(|name| name.eq_ignore_ascii_case(&label.get_name())));
+
+    matches_event && matches_labels
+}
+
+/// Values available in notification templates.
+fn get_context(event: &TaskEvent) -> serde_json::Value {
+    let task = &event.task;
+    let property = |prop: &str| task.get_property(prop).cloned().unwrap_or_default();
+    let id = task.get_id().unwrap_or_default();
+    let name = property("name");
+    let summary = match event.kind {
+        EventKind::Created => format!("Task #{id} created: {name}"),
+        EventKind::Updated => format!("Task #{id} {name} updated: {}", event.changes.join(", ")),
+        EventKind::StatusChanged => format!("Task #{id} {name}: {} -> {}", event.old_status.clone().unwrap_or_default(), property("status")),
+        EventKind::Deleted => format!("Task #{id} deleted: {name}"),
+    };
+
+    json!({
+        "event": event.kind.name(),
+        "id": id,
+        "name": name,
+        "status": property("status"),
+        "old_status": event.old_status,
+        "author": property("author"),
+        "assignee": property("assignee"),
+        "labels": task.get_labels().iter().flatten().map(|label| label.get_name()).collect::<Vec<_>>(),
+        "changes": event.changes,
+        "summary": summary,
+    })
+}
+
+/// Joins messages line by line into as few notifications as the size limit allows.
+fn join_messages(messages: &[String], max_length: usize) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for message in messages.iter().filter(|message| !message.trim().is_empty()) {
+        let message = message.chars().take(max_length).collect::<String>();
+        match result.last_mut() {
+            Some(last) if last.chars().count() + message.chars().count() < max_length => {
+                last.push('\n');
+                last.push_str(&message);
+            },
+            _ => result.push(message),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_events() {
+        let task = |id: &str, name: &str, status: &str| Task::builder(name).id(id).status(status).build().unwrap();
+        let mut released = task("3", "Release 1.0", "OPEN");
+        released.add_label("release".to_string(), None, None);
+        let old_tasks = vec![task("1", "Crash", "OPEN"), task("2", "Docs", "OPEN"), released.clone(), task("4", "Old", "OPEN")];
+        let mut renamed = task("2", "Write docs", "OPEN");
+        renamed.set_property("updated", "100");
+        let mut closed = released.clone();
+        closed.set_property("status", "CLOSED");
+        let new_tasks = vec![task("1", "Crash", "OPEN"), renamed, closed, task("5", "New", "OPEN")];
+
+        let events = get_events(&old_tasks, &new_tasks).unwrap();
+        let kinds = events.iter().map(|event| (event.task.get_id().unwrap(), event.kind)).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![
+            ("2".to_string(), EventKind::Updated),
+            ("3".to_string(), EventKind::StatusChanged),
+            ("4".to_string(), EventKind::Deleted),
+            ("5".to_string(), EventKind::Created),
+        ]);
+        assert_eq!(events[0].changes, vec!["name".to_string()]);
+        assert_eq!(get_context(&events[1])["summary"], "Task #3 Release 1.0: OPEN -> CLOSED");
+        assert_eq!(render("{{event}} {{id}} {{#each labels}}#{{this}}{{/each}}", &get_context(&events[1])).unwrap(), "status 3 #release");
+
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            let status_manager = StatusManager::new();
+            let filter = |events_filter: &[&str], labels: &[&str]| {
+                let events_filter = events_filter.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+                let labels = labels.iter().map(|item| item.to_string()).collect::<Vec<_>>();
+                events.iter().filter(|event| matches_filter(event, &events_filter, &labels, &status_manager)).map(|event| event.task.get_id().unwrap()).collect::<Vec<_>>()
+            };
+            assert_eq!(filter(&["created", "deleted"], &[]), vec!["4", "5"]);
+            assert_eq!(filter(&["done"], &[]), vec!["3"]);
+            assert_eq!(filter(&["status:closed"], &["release"]), vec!["3"]);
+            assert!(filter(&["status:IN_PROGRESS"], &[]).is_empty());
+            assert!(filter(&["created", "updated"], &["release"]).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_join_messages() {
+        let messages = vec!["a".repeat(8), "b".repeat(8), String::new(), "c".repeat(30)];
+        assert_eq!(join_messages(&messages, 20), vec![format!("{}\n{}", "a".repeat(8), "b".repeat(8)), "c".repeat(20)]);
+        assert_eq!(WebhookKind::Discord.payload("hi")["content"], "hi");
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use gittask::{Label, Task};
+
+use crate::property::PropertyManager;
+use crate::status::StatusManager;
+
+const MULTILINE_QUOTES: &str = "'''";
+
+pub fn task_to_document(task: &Task) -> String {
+    let mut result = String::new();
+    result.push_str(&format!("# Task ID {}\n", task.get_id().unwrap_or_else(|| "---".to_string())));
+    result.push_str("# Edit properties below, remove a line to delete the property.\n");
+    result.push_str("# Lines starting with # are ignored, comments are shown for reference only.\n\n");
+
+    let props = task.get_all_properties();
+    for key in ["name", "status"] {
+        if let Some(value) = props.get(key) {
+            result.push_str(&format!("{key} = {}\n", quote(value)));
+        }
+    }
+
+    let mut keys = props.keys().filter(|key| !["name", "status", "description"].contains(&key.as_str())).collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+        result.push_str(&format!("{key} = {}\n", quote(&props[key])));
+    }
+
+    let labels = task.get_labels().as_ref().map(|labels| labels.iter().map(|l| l.get_name()).collect::<Vec<_>>()).unwrap_or_default();
+    result.push_str(&format!("labels = {}\n", serde_json::to_string(&labels).unwrap()));
+
+    let description = props.get("description").cloned().unwrap_or_default();
+    if description.contains(MULTILINE_QUOTES) {
+        result.push_str(&format!("\ndescription = {}\n", quote(&description)));
+    } else {
+        result.push_str(&format!("\ndescription = {MULTILINE_QUOTES}\n{description}\n{MULTILINE_QUOTES}\n"));
+    }
+
+    if let Some(comments) = task.get_comments() {
+        for comment in comments {
+            let author = comment.get_all_properties().get("author").cloned().unwrap_or_default();
+            result.push_str(&format!("\n# --- Comment {} {author} ---\n", comment.get_id().unwrap_or_default()));
+            for line in comment.get_text().lines() {
+                result.push_str(&format!("# {line}\n"));
+            }
+        }
+    }
+
+    result
+}
+
+/// Parses the edited document back into a task. Status and property values are validated against the configuration,
+/// errors point to the line of the value. Multi-line values are kept verbatim.
+pub fn document_to_task(document: &str, source: &Task, prop_manager: &PropertyManager, status_manager: &StatusManager) -> Result<Task, String> {
+    let mut props = HashMap::new();
+    let mut labels = None;
+    let mut lines = document.split_inclusive('\n').enumerate();
+
+    while let Some((n, line)) = lines.next() {
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err(format!("Line {}: expected `key = value`", n + 1)),
+        };
+
+        if key.is_empty() {
+            return Err(format!("Line {}: empty property name", n + 1));
+        }
+
+        if key == "labels" {
+            let names: Vec<String> = serde_json::from_str(value).map_err(|e| format!("Line {}: {e}", n + 1))?;
+            labels = Some(names);
+            continue;
+        }
+
+        let value = if value == MULTILINE_QUOTES {
+            let mut text = String::new();
+            loop {
+                match lines.next() {
+                    Some((_, line)) if line.trim_end() == MULTILINE_QUOTES => break,
+                    Some((_, line)) => text.push_str(line),
+                    None => return Err(format!("Line {}: unterminated multi-line value", n + 1)),
+                }
+            }
+            // the line break before the closing quotes belongs to the document, not to the value
+            text.strip_suffix("\r\n").or_else(|| text.strip_suffix('\n')).map(str::to_string).unwrap_or(text)
+        } else {
+            serde_json::from_str::<String>(value).map_err(|e| format!("Line {}: {e}", n + 1))?
+        };
+
+        let value = match key {
+            "status" => check_status(&value, status_manager),
+            _ => prop_manager.validate_value(key, &value),
+        }.map_err(|e| format!("Line {}: {e}", n + 1))?;
+        props.insert(key.to_string(), value);
+    }
+
+    if props.get("name").is_none_or(|name| name.is_empty()) {
+        return Err("Task name can't be empty".to_string());
+    }
+
+    if props.get("status").is_none_or(|status| status.is_empty()) {
+        return Err("Task status can't be empty".to_string());
+    }
+
+    props.entry("description".to_string()).or_default();
+
+    let mut task = Task::from_properties(source.get_id().unwrap(), props)?;
+
+    if let Some(comments) = source.get_comments() {
+        task.set_comments(comments.clone());
+    }
+
+    if let Some(names) = labels {
+        let labels = names.into_iter().map(|name| match source.get_label_by_name(&name) {
+            Some(label) => label.clone(),
+            None => Label::new(name, None, None),
+        }).collect();
+        task.set_labels(labels);
+    }
+
+    Ok(task)
+}
+
+/// Resolves a status name or shortcut to the configured status name.
+fn check_status(status: &str, status_manager: &StatusManager) -> Result<String, String> {
+    if status.is_empty() {
+        return Ok(String::new());
+    }
+    let statuses = status_manager.get_statuses();
+    statuses.iter()
+        .find(|s| s.get_name().eq_ignore_ascii_case(status) || s.get_shortcut().eq_ignore_ascii_case(status))
+        .map(|s| s.get_name().to_string())
+        .ok_or_else(|| format!("unknown status {status}, expected one of: {}", statuses.iter().map(|s| s.get_name()).collect::<Vec<_>>().join(", ")))
+}
+
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample_task() -> Task {
+        let mut task = Task::from_properties("1".to_string(), HashMap::from([
+            ("name".to_string(), "Fix \"quoted\" bug".to_string()),
+            ("status".to_string(), "OPEN".to_string()),
+            ("description".to_string(), "First line\nSecond line".to_string()),
+            ("created".to_string(), "1700000000".to_string()),
+            ("priority".to_string(), "HIGH".to_string()),
+        ])).unwrap();
+        task.add_label("bug".to_string(), Some("Something is broken".to_string()), Some("red".to_string()));
+        task.add_comment(Some("1".to_string()), HashMap::new(), "A comment".to_string());
+        task
+    }
+
+    fn managers() -> (PropertyManager, StatusManager) {
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            let mut prop_manager = PropertyManager::new();
+            prop_manager.add_property("priority".to_string(), "string".to_string(), "Default".to_string(), None, Some(vec!["LOW".to_string(), "Green".to_string(), "HIGH".to_string(), "Red".to_string()]), None).unwrap();
+            prop_manager.add_property("estimate".to_string(), "integer".to_string(), "Default".to_string(), None, None, None).unwrap();
+            (prop_manager, StatusManager::new())
+        })
+    }
+
+    fn parse(document: &str, task: &Task) -> Result<Task, String> {
+        let (prop_manager, status_manager) = managers();
+        document_to_task(document, task, &prop_manager, &status_manager)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let task = sample_task();
+        let document = task_to_document(&task);
+        let parsed = parse(&document, &task).unwrap();
+        assert_eq!(parsed.get_all_properties(), task.get_all_properties());
+        assert_eq!(parsed.get_labels(), task.get_labels());
+        assert_eq!(parsed.get_comments().as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_edit_document() {
+        let task = sample_task();
+        let document = task_to_document(&task)
+            .replace("priority = \"HIGH\"\n", "")
+            .replace("labels = [\"bug\"]", "labels = [\"bug\", \"ui\"]")
+            .replace("Second line", "Changed line");
+        let parsed = parse(&document, &task).unwrap();
+        assert!(!parsed.has_property("priority"));
+        assert_eq!(parsed.get_property("description").unwrap(), "First line\nChanged line");
+        assert_eq!(parsed.get_label_by_name("bug").unwrap().get_color(), "red");
+        assert!(parsed.get_label_by_name("ui").is_some());
+    }
+
+    #[test]
+    fn test_invalid_document() {
+        let task = sample_task();
+        assert!(parse("name = \"\"\nstatus = \"OPEN\"", &task).is_err());
+        assert!(parse("name = unquoted\nstatus = \"OPEN\"", &task).is_err());
+        assert!(parse("name = \"A\"\ndescription = '''\nno end", &task).is_err());
+        assert_eq!(parse("name = \"A\"\n\nstatus = \"DONE\"", &task).err().unwrap(), "Line 3: unknown status DONE, expected one of: OPEN, IN_PROGRESS, CLOSED");
+        assert!(parse("name = \"A\"\nstatus = \"OPEN\"\npriority = \"URGENT\"", &task).err().unwrap().starts_with("Line 3: 'URGENT' is not a valid priority"));
+        assert!(parse("name = \"A\"\nstatus = \"OPEN\"\nestimate = \"many\"", &task).err().unwrap().starts_with("Line 3: "));
+    }
+
+    #[test]
+    fn test_normalize_values() {
+        let task = sample_task();
+        let parsed = parse("name = \"A\"\nstatus = \"i\"\npriority = \"high\"\nestimate = \"3\"", &task).unwrap();
+        assert_eq!(parsed.get_property("status").unwrap(), "IN_PROGRESS");
+        assert_eq!(parsed.get_property("priority").unwrap(), "HIGH");
+        assert_eq!(parsed.get_property("estimate").unwrap(), "3");
+    }
+
+    #[test]
+    fn test_description_verbatim() {
+        for description in ["Trailing newline\n", "Two\n\n", "\n  indented\n\tlines  \n", "Windows\r\nline breaks", ""] {
+            let mut task = sample_task();
+            task.set_property("description", description);
+            let parsed = parse(&task_to_document(&task), &task).unwrap();
+            assert_eq!(parsed.get_property("description").unwrap(), description);
+        }
+    }
+}
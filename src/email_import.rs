@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use regex::Regex;
+
+static ENCODED_WORD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"=\?([^?]+)\?([bBqQ])\?([^?]*)\?=").unwrap()
+});
+
+static PARAMETER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i);\s*([\w*-]+)\s*=\s*(?:"([^"]*)"|([^;\s]+))"#).unwrap()
+});
+
+static MBOX_SEPARATOR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^From \S+.*\n").unwrap()
+});
+
+static HTML_TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<(?:style|script)\b.*?</(?:style|script)>|<[^>]+>").unwrap()
+});
+
+pub struct Email {
+    pub message_id: Option<String>,
+    pub subject: String,
+    /// Display name of the sender, or the address if there's none
+    pub from: String,
+    pub date: Option<i64>,
+    pub body: String,
+    /// File names of the attachments, their contents are not kept
+    pub attachments: Vec<String>,
+}
+
+/// Splits an mbox file into messages, any other input is a single message.
+pub fn parse_mailbox(input: &str) -> Vec<Email> {
+    let input = input.replace("\r\n", "\n");
+    match input.starts_with("From ") {
+        true => MBOX_SEPARATOR_REGEX.split(&input).filter(|message| !message.trim().is_empty()).map(parse_eml).collect(),
+        false => vec![parse_eml(&input)],
+    }
+}
+
+/// Parses an RFC 822 message: headers with encoded words, the first text part of a MIME body
+/// (HTML is reduced to text if there's no plain one) and file names of attachments.
+pub fn parse_eml(input: &str) -> Email {
+    let input = input.replace("\r\n", "\n");
+    let (headers, body) = split_message(&input);
+
+    let mut text = None;
+    let mut html = None;
+    let mut attachments = vec![];
+    collect_parts(&headers, body, &mut text, &mut html, &mut attachments);
+
+    let header = |name: &str| headers.get(name).map(|value| decode_words(value).trim().to_string()).filter(|value| !value.is_empty());
+    Email {
+        message_id: header("message-id"),
+        subject: header("subject").unwrap_or_else(|| "(no subject)".to_string()),
+        from: header("from").map(|from| get_sender_name(&from)).unwrap_or_default(),
+        date: header("date").and_then(|date| chrono::DateTime::parse_from_rfc2822(&date).ok()).map(|date| date.timestamp()),
+        body: text.or_else(|| html.map(|html| html_to_text(&html))).unwrap_or_default().trim().to_string(),
+        attachments,
+    }
+}
+
+/// Headers with lowercase names (folded lines joined, first occurrence wins) and the body.
+fn split_message(message: &str) -> (HashMap<String, String>, &str) {
+    let (head, body) = message.split_once("\n\n").unwrap_or((message, ""));
+    let mut headers = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in head.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = current.as_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = current.take() {
+            headers.entry(name).or_insert(value);
+        }
+        current = line.split_once(':').map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()));
+    }
+    if let Some((name, value)) = current {
+        headers.entry(name).or_insert(value);
+    }
+
+    (headers, body)
+}
+
+fn collect_parts(headers: &HashMap<String, String>, body: &str, text: &mut Option<String>, html: &mut Option<String>, attachments: &mut Vec<String>) {
+    let content_type = headers.get("content-type").map(String::as_str).unwrap_or("text/plain");
+    let mime_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    let parameters = get_parameters(content_type);
+
+    if mime_type.starts_with("multipart/") {
+        if let Some(boundary) = parameters.get("boundary") {
+            for part in split_multipart(body, boundary) {
+                let (part_headers, part_body) = split_message(part);
+                collect_parts(&part_headers, part_body, text, html, attachments);
+            }
+        }
+        return;
+    }
+
+    let disposition = headers.get("content-disposition").map(String::as_str).unwrap_or_default();
+    let file_name = get_parameters(disposition).get("filename").or_else(|| parameters.get("name")).map(|name| decode_words(name));
+    if disposition.trim().to_lowercase().starts_with("attachment") || file_name.is_some() {
+        attachments.push(file_name.unwrap_or_else(|| "(unnamed)".to_string()));
+        return;
+    }
+
+    let decode = || {
+        let encoding = headers.get("content-transfer-encoding").map(|encoding| encoding.trim().to_lowercase()).unwrap_or_default();
+        let bytes = match encoding.as_str() {
+            "base64" => STANDARD.decode(body.split_whitespace().collect::<String>()).unwrap_or_default(),
+            "quoted-printable" => decode_quoted_printable(body, false),
+            _ => body.as_bytes().to_vec(),
+        };
+        decode_charset(&bytes, parameters.get("charset").map(String::as_str).unwrap_or("utf-8"))
+    };
+    match mime_type.as_str() {
+        "text/plain" if text.is_none() => *text = Some(decode()),
+        "text/html" if html.is_none() => *html = Some(decode()),
+        _ => {},
+    }
+}
+
+fn split_multipart<'a>(body: &'a str, boundary: &str) -> Vec<&'a str> {
+    let delimiter = format!("--{boundary}");
+    body.split(&delimiter)
+        .skip(1)
+        .take_while(|part| !part.starts_with("--"))
+        .map(|part| part.strip_prefix('\n').unwrap_or(part))
+        .collect()
+}
+
+fn get_parameters(header: &str) -> HashMap<String, String> {
+    PARAMETER_REGEX.captures_iter(header)
+        .map(|caps| (caps[1].to_lowercase(), caps.get(2).or_else(|| caps.get(3)).unwrap().as_str().to_string()))
+        .collect()
+}
+
+/// Decodes RFC 2047 encoded words like `=?UTF-8?B?...?=`, whitespace between adjacent ones is dropped.
+fn decode_words(value: &str) -> String {
+    let mut result = String::new();
+    let mut last_end = 0;
+    for caps in ENCODED_WORD_REGEX.captures_iter(value) {
+        let word = caps.get(0).unwrap();
+        let between = &value[last_end..word.start()];
+        if last_end == 0 || !between.trim().is_empty() {
+            result.push_str(between);
+        }
+        let bytes = match caps[2].to_lowercase().as_str() {
+            "b" => STANDARD.decode(&caps[3]).unwrap_or_default(),
+            _ => decode_quoted_printable(&caps[3], true),
+        };
+        result.push_str(&decode_charset(&bytes, &caps[1]));
+        last_end = word.end();
+    }
+    result.push_str(&value[last_end..]);
+    result
+}
+
+/// Decodes quoted-printable text, in encoded words underscores stand for spaces.
+fn decode_quoted_printable(text: &str, encoded_word: bool) -> Vec<u8> {
+    let text = text.replace("=\n", "");
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'=' => match bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()) {
+                Some(byte) => {
+                    result.push(byte);
+                    i += 3;
+                    continue;
+                },
+                None => result.push(b'='),
+            },
+            b'_' if encoded_word => result.push(b' '),
+            byte => result.push(byte),
+        }
+        i += 1;
+    }
+    result
+}
+
+/// UTF-8 and ASCII are decoded as such, everything else is treated as Latin-1.
+fn decode_charset(bytes: &[u8], charset: &str) -> String {
+    match charset.trim().to_lowercase().as_str() {
+        "utf-8" | "utf8" | "us-ascii" | "ascii" => String::from_utf8_lossy(bytes).to_string(),
+        _ => match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => bytes.iter().map(|byte| *byte as char).collect(),
+        },
+    }
+}
+
+/// `Jane Doe <jane@example.com>` becomes `Jane Doe`, a bare address is kept.
+fn get_sender_name(from: &str) -> String {
+    match from.split_once('<') {
+        Some((name, address)) => match name.trim().trim_matches('"').trim() {
+            "" => address.trim_end_matches('>').trim().to_string(),
+            name => name.to_string(),
+        },
+        None => from.trim().to_string(),
+    }
+}
+
+fn html_to_text(html: &str) -> String {
+    let text = html.replace("<br>", "\n").replace("<br/>", "\n").replace("<br />", "\n").replace("</p>", "\n\n");
+    HTML_TAG_REGEX.replace_all(&text, "").replace("&nbsp;", " ").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_eml() {
+        let input = "Message-ID: <abc@example.com>\r\n\
+From: =?UTF-8?B?SsO2cmc=?= Meier <joerg@example.com>\r\n\
+Subject: =?UTF-8?Q?Login_f=C3=A4llt?=\r\n  =?UTF-8?Q?_aus?=\r\n\
+Date: Tue, 1 Jul 2025 10:00:00 +0000\r\n\
+Content-Type: multipart/mixed; boundary=\"b1\"\r\n\
+\r\n\
+preamble\r\n\
+--b1\r\n\
+Content-Type: text/plain; charset=utf-8\r\n\
+Content-Transfer-Encoding: quoted-printable\r\n\
+\r\n\
+Can't log in since the upd=\r\nate, error =3D 500.\r\n\
+--b1\r\n\
+Content-Type: image/png; name=\"screen.png\"\r\n\
+Content-Disposition: attachment; filename=\"screen.png\"\r\n\
+Content-Transfer-Encoding: base64\r\n\
+\r\n\
+iVBORw0KGgo=\r\n\
+--b1--\r\n";
+
+        let email = parse_eml(input);
+        assert_eq!(email.message_id.as_deref(), Some("<abc@example.com>"));
+        assert_eq!(email.from, "Jörg Meier");
+        assert_eq!(email.subject, "Login fällt aus");
+        assert_eq!(email.date, Some(1751364000));
+        assert_eq!(email.body, "Can't log in since the update, error = 500.");
+        assert_eq!(email.attachments, vec!["screen.png".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_mailbox() {
+        let input = "From alice@example.com Tue Jul  1 10:00:00 2025\nFrom: alice@example.com\nSubject: First\n\nHello\n\n\
+From bob@example.com Tue Jul  1 11:00:00 2025\nFrom: \"Bob\" <bob@example.com>\nContent-Type: text/html; charset=iso-8859-1\n\n<p>Caf\u{e9} &amp; more</p>\n";
+        let emails = parse_mailbox(input);
+        assert_eq!(emails.len(), 2);
+        assert_eq!(emails[0].from, "alice@example.com");
+        assert_eq!(emails[0].body, "Hello");
+        assert!(emails[0].message_id.is_none());
+        assert_eq!(emails[1].from, "Bob");
+        assert_eq!(emails[1].subject, "(no subject)");
+        assert_eq!(emails[1].body, "Café & more");
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+/// Languages with a message catalog, English messages are the catalog keys.
+pub(crate) const LANGUAGES: [&str; 3] = ["en", "de", "ru"];
+
+pub(crate) const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Translations of user-facing messages, gettext-style: the English text with `{name}` placeholders is the message ID.
+const CATALOGS: [(&str, &[(&str, &str)]); 2] = [
+    ("de", &[
+        ("Task ID {id} created", "Aufgabe {id} erstellt"),
+        ("Task ID {id} updated", "Aufgabe {id} aktualisiert"),
+        ("Task ID {id} not found", "Aufgabe {id} nicht gefunden"),
+        ("Task(s) {ids} deleted", "Aufgabe(n) {ids} gelöscht"),
+        ("{count} task(s) deleted", "{count} Aufgabe(n) gelöscht"),
+        ("No matching tasks to delete", "Keine passenden Aufgaben zum Löschen"),
+        ("No changes", "Keine Änderungen"),
+        ("Name", "Name"),
+        ("Status", "Status"),
+        ("Created", "Erstellt"),
+        ("Updated", "Aktualisiert"),
+        ("Author", "Autor"),
+        ("Description", "Beschreibung"),
+        ("Labels", "Labels"),
+        ("Type", "Typ"),
+        ("Assignee", "Zuständig"),
+        ("Due", "Fällig"),
+        ("Priority", "Priorität"),
+        ("Comment ID", "Kommentar-ID"),
+        (" (edited)", " (bearbeitet)"),
+    ]),
+    ("ru", &[
+        ("Task ID {id} created", "Задача {id} создана"),
+        ("Task ID {id} updated", "Задача {id} обновлена"),
+        ("Task ID {id} not found", "Задача {id} не найдена"),
+        ("Task(s) {ids} deleted", "Удалены задачи: {ids}"),
+        ("{count} task(s) deleted", "Удалено задач: {count}"),
+        ("No matching tasks to delete", "Нет подходящих задач для удаления"),
+        ("No changes", "Изменений нет"),
+        ("Name", "Название"),
+        ("Status", "Статус"),
+        ("Created", "Создана"),
+        ("Updated", "Обновлена"),
+        ("Author", "Автор"),
+        ("Description", "Описание"),
+        ("Labels", "Метки"),
+        ("Type", "Тип"),
+        ("Assignee", "Исполнитель"),
+        ("Due", "Срок"),
+        ("Priority", "Приоритет"),
+        ("Comment ID", "ID комментария"),
+        (" (edited)", " (изменён)"),
+    ]),
+];
+
+/// Date and time formats of the languages, ISO-like for the rest.
+const DATE_FORMATS: [(&str, &str); 2] = [
+    ("de", "%d.%m.%Y %H:%M"),
+    ("ru", "%d.%m.%Y %H:%M"),
+];
+
+static CURRENT_LANG: OnceLock<String> = OnceLock::new();
+
+/// Language of the output: `task.lang` parameter, otherwise the `LC_ALL`, `LC_MESSAGES` or `LANG` locale.
+pub(crate) fn current_lang() -> &'static str {
+    CURRENT_LANG.get_or_init(|| {
+        gittask::get_config_value("task.lang").ok().filter(|value| !value.is_empty())
+            .or_else(|| ["LC_ALL", "LC_MESSAGES", "LANG"].iter().find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty())))
+            .and_then(|locale| parse_locale(&locale))
+            .unwrap_or_else(|| "en".to_string())
+    })
+}
+
+/// Language code of a locale like `de_DE.UTF-8`, `C` and `POSIX` are English.
+pub(crate) fn parse_locale(locale: &str) -> Option<String> {
+    let lang = locale.split(['_', '-', '.', '@']).next()?.trim().to_lowercase();
+    match lang.as_str() {
+        "" => None,
+        "c" | "posix" => Some("en".to_string()),
+        _ => Some(lang),
+    }
+}
+
+/// Translates the message to the output language and fills in its `{name}` placeholders.
+pub(crate) fn tr(message: &str, args: &[(&str, &dyn Display)]) -> String {
+    translate(current_lang(), message, args)
+}
+
+pub(crate) fn translate(lang: &str, message: &str, args: &[(&str, &dyn Display)]) -> String {
+    let translated = CATALOGS.iter()
+        .find(|(catalog_lang, _)| *catalog_lang == lang)
+        .and_then(|(_, messages)| messages.iter().find(|(id, _)| *id == message))
+        .map(|(_, translated)| *translated)
+        .unwrap_or(message);
+
+    args.iter().fold(translated.to_string(), |result, (name, value)| result.replace(&format!("{{{name}}}"), &value.to_string()))
+}
+
+/// Format of dates and times in the output language.
+pub(crate) fn date_format() -> &'static str {
+    get_date_format(current_lang())
+}
+
+fn get_date_format(lang: &str) -> &'static str {
+    DATE_FORMATS.iter().find(|(format_lang, _)| *format_lang == lang).map(|(_, format)| *format).unwrap_or(DEFAULT_DATE_FORMAT)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate("de", "Task ID {id} updated", &[("id", &5)]), "Aufgabe 5 aktualisiert");
+        assert_eq!(translate("ru", "Task(s) {ids} deleted", &[("ids", &"1, 2")]), "Удалены задачи: 1, 2");
+        assert_eq!(translate("en", "Task ID {id} updated", &[("id", &"5".to_string())]), "Task ID 5 updated");
+        assert_eq!(translate("fr", "Task ID {id} created", &[("id", &7)]), "Task ID 7 created");
+        assert_eq!(translate("de", "Unknown message", &[]), "Unknown message");
+    }
+
+    #[test]
+    fn test_catalogs_complete() {
+        let (_, reference) = CATALOGS[0];
+        for (lang, messages) in CATALOGS {
+            assert!(LANGUAGES.contains(&lang));
+            assert_eq!(messages.len(), reference.len(), "{lang} catalog");
+            assert!(reference.iter().all(|(id, _)| messages.iter().any(|(other, _)| other == id)), "{lang} catalog");
+        }
+    }
+
+    #[test]
+    fn test_locale() {
+        assert_eq!(parse_locale("de_DE.UTF-8"), Some("de".to_string()));
+        assert_eq!(parse_locale("ru-RU"), Some("ru".to_string()));
+        assert_eq!(parse_locale("C.UTF-8"), Some("en".to_string()));
+        assert_eq!(parse_locale(""), None);
+        assert_eq!(get_date_format("de"), "%d.%m.%Y %H:%M");
+        assert_eq!(get_date_format("en"), DEFAULT_DATE_FORMAT);
+    }
+}
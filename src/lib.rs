@@ -1,15 +1,53 @@
 use std::borrow::ToOwned;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::Deref;
+use std::rc::Rc;
+use std::sync::LazyLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 use git2::*;
+use regex::Regex;
 use serde_json;
-use serde::{Deserialize, Serialize};
-
-const NAME: &'static str = "name";
-const DESCRIPTION: &'static str = "description";
-const STATUS: &'static str = "status";
-const CREATED: &'static str = "created";
+use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+
+const NAME: &str = "name";
+const DESCRIPTION: &str = "description";
+const STATUS: &str = "status";
+const CREATED: &str = "created";
+const UPDATED: &str = "updated";
+const UPDATED_BY: &str = "updated_by";
+const MENTIONS: &str = "mentions";
+const EDITED: &str = "edited";
+const EDITED_BY: &str = "edited_by";
+const ORIGINAL_TEXT: &str = "original_text";
+const WATCHERS: &str = "watchers";
+
+/// Current version of the tasks tree format, stored in the `.format` entry of the tree.
+/// Trees without the entry are considered version 0.
+pub const FORMAT_VERSION: u32 = 3;
+const FORMAT_ENTRY: &str = ".format";
+/// Since format version 2 a task with comments is stored as a tree with the task blob
+/// and a subtree holding one blob per comment.
+const SPLIT_COMMENTS_VERSION: u32 = 2;
+/// Since format version 3 every task has the `updated` timestamp.
+const UPDATED_VERSION: u32 = 3;
+const TASK_ENTRY: &str = "task";
+const COMMENTS_ENTRY: &str = "comments";
+/// Optional index of task IDs by status, label and assignee, kept up to date by every write.
+const INDEX_ENTRY: &str = ".index";
+/// Tasks are stored either in a flat tree or sharded into subtrees by ID prefix, see `.layout` entry.
+const LAYOUT_ENTRY: &str = ".layout";
+const SHARDED_LAYOUT: &str = "sharded";
+const SHARD_PREFIX_LENGTH: usize = 2;
+/// Time of the last successful pull per remote, for incremental pulls.
+const PULLS_ENTRY: &str = ".pulls";
+/// Configuration shared through the tasks ref, see `SHARED_CONFIG_KEYS`.
+const CONFIG_ENTRY: &str = ".config";
+
+static MENTION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^A-Za-z0-9_.@])@([A-Za-z0-9_][A-Za-z0-9_-]*)").unwrap()
+});
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -17,6 +55,9 @@ pub struct Task {
     props: HashMap<String, String>,
     comments: Option<Vec<Comment>>,
     labels: Option<Vec<Label>>,
+    /// Entry the task was read from, updates are merged into the tasks ref against it.
+    #[serde(skip)]
+    source: Option<Oid>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -33,7 +74,137 @@ pub struct Label {
     description: Option<String>,
 }
 
+/// Task IDs by status, lowercase label name and lowercase assignee.
+#[derive(Default, Serialize, Deserialize)]
+struct TaskIndex {
+    status: BTreeMap<String, BTreeSet<String>>,
+    label: BTreeMap<String, BTreeSet<String>>,
+    assignee: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Lightweight task for list views, comments and description are skipped while parsing.
+#[derive(Deserialize)]
+pub struct TaskSummary {
+    id: Option<String>,
+    #[serde(deserialize_with = "deserialize_props_without_description")]
+    props: HashMap<String, String>,
+    labels: Option<Vec<Label>>,
+}
+
+/// Validating builder for tasks, see [`Task::builder`].
+#[derive(Clone, Default)]
+pub struct TaskBuilder {
+    id: Option<String>,
+    props: HashMap<String, String>,
+    labels: Vec<Label>,
+    comments: Vec<(HashMap<String, String>, String)>,
+}
+
+/// Rust types a property value can be read as with [`Task::get_typed`].
+pub trait PropertyValue: Sized {
+    /// Schema value types (`string`, `text`, `integer`, `datetime`, `list`) this type can be read from.
+    const VALUE_TYPES: &'static [&'static str];
+
+    fn parse_value(value: &str) -> Result<Self, String>;
+}
+
+impl PropertyValue for String {
+    const VALUE_TYPES: &'static [&'static str] = &["string", "text", "integer", "datetime", "list"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        Ok(value.to_string())
+    }
+}
+
+impl PropertyValue for i64 {
+    const VALUE_TYPES: &'static [&'static str] = &["integer", "datetime"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        value.trim().parse().map_err(|_| format!("'{value}' is not an integer"))
+    }
+}
+
+impl PropertyValue for u64 {
+    const VALUE_TYPES: &'static [&'static str] = &["integer", "datetime"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        value.trim().parse().map_err(|_| format!("'{value}' is not a non-negative integer"))
+    }
+}
+
+impl PropertyValue for bool {
+    const VALUE_TYPES: &'static [&'static str] = &["string"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(true),
+            "false" | "no" | "0" => Ok(false),
+            _ => Err(format!("'{value}' is not a boolean")),
+        }
+    }
+}
+
+/// List values are stored as JSON arrays of strings, any other value is a list of itself.
+impl PropertyValue for Vec<String> {
+    const VALUE_TYPES: &'static [&'static str] = &["list", "string"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "" => Ok(vec![]),
+            value if value.starts_with('[') => serde_json::from_str(value).map_err(|_| format!("'{value}' is not a list of strings")),
+            value => Ok(vec![value.to_string()]),
+        }
+    }
+}
+
+/// Formats values of a list property the way they are stored.
+pub fn format_list_value(values: &[String]) -> String {
+    serde_json::to_string(values).unwrap()
+}
+
+impl PropertyValue for chrono::DateTime<chrono::Utc> {
+    const VALUE_TYPES: &'static [&'static str] = &["datetime", "integer"];
+
+    fn parse_value(value: &str) -> Result<Self, String> {
+        i64::parse_value(value).ok()
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .ok_or_else(|| format!("'{value}' is not a Unix timestamp"))
+    }
+}
+
+/// Parses a property value as `T`, checking that the schema value type (if known) is compatible.
+pub fn parse_typed_value<T: PropertyValue>(prop: &str, value: &str, value_type: Option<&str>) -> Result<T, String> {
+    if let Some(value_type) = value_type {
+        if !T::VALUE_TYPES.contains(&value_type) {
+            return Err(format!("Property '{prop}' is of type {value_type}, expected one of: {}", T::VALUE_TYPES.join(", ")));
+        }
+    }
+    T::parse_value(value).map_err(|e| format!("Property '{prop}': {e}"))
+}
+
+/// Value type of the property from the `task.properties` schema, built-in properties are known without it.
+pub fn get_property_value_type(prop: &str) -> Option<String> {
+    let schema = get_config_value("task.properties").ok()
+        .and_then(|properties| serde_json::from_str::<Vec<serde_json::Value>>(&properties).ok())
+        .unwrap_or_default();
+    schema.iter()
+        .find(|property| property["name"] == prop)
+        .and_then(|property| property["value_type"].as_str().map(|value_type| value_type.to_lowercase()))
+        .or_else(|| match prop {
+            "id" => Some("integer".to_string()),
+            CREATED | UPDATED => Some("datetime".to_string()),
+            NAME | STATUS | UPDATED_BY => Some("string".to_string()),
+            DESCRIPTION => Some("text".to_string()),
+            _ => None,
+        })
+}
+
 impl Task {
+    /// Starts building a task with the given name, status must be set before calling `build()`.
+    pub fn builder(name: &str) -> TaskBuilder {
+        TaskBuilder::default().property(NAME, name)
+    }
+
     pub fn new(name: String, description: String, status: String) -> Result<Task, &'static str> {
         if !name.is_empty() && !status.is_empty() {
             Ok(Self::construct_task(name, description, status, None))
@@ -51,7 +222,7 @@ impl Task {
                 props.insert("created".to_string(), get_current_timestamp().to_string());
             }
 
-            Ok(Task{ id: Some(id), props, comments: None, labels: None })
+            Ok(Task{ id: Some(id), props, comments: None, labels: None, source: None })
         } else {
             Err("Name or status is empty")
         }
@@ -74,6 +245,7 @@ impl Task {
             props,
             comments: None,
             labels: None,
+            source: None,
         }
     }
 
@@ -92,6 +264,45 @@ impl Task {
         self.props.get(prop)
     }
 
+    pub fn name(&self) -> &str {
+        self.props.get(NAME).map_or("", |name| name.as_str())
+    }
+
+    pub fn description(&self) -> &str {
+        self.props.get(DESCRIPTION).map_or("", |description| description.as_str())
+    }
+
+    pub fn status(&self) -> &str {
+        self.props.get(STATUS).map_or("", |status| status.as_str())
+    }
+
+    /// Creation time, `None` if the property is missing or isn't a Unix timestamp.
+    pub fn created_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.props.get(CREATED).and_then(|created| chrono::DateTime::parse_value(created).ok())
+    }
+
+    /// Time of the last update, falls back to the creation time for never updated tasks.
+    pub fn updated_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.props.get(UPDATED).and_then(|updated| chrono::DateTime::parse_value(updated).ok())
+            .or_else(|| self.created_at())
+    }
+
+    fn touch(&mut self) {
+        self.props.insert(UPDATED.to_string(), get_current_timestamp().to_string());
+        match get_current_user() {
+            Ok(Some(current_user)) => self.props.insert(UPDATED_BY.to_string(), current_user),
+            _ => self.props.remove(UPDATED_BY),
+        };
+    }
+
+    /// Reads the property as `T` according to its schema value type, `Ok(None)` if it's missing.
+    pub fn get_typed<T: PropertyValue>(&self, prop: &str) -> Result<Option<T>, String> {
+        match self.props.get(prop) {
+            Some(value) => parse_typed_value(prop, value, get_property_value_type(prop).as_deref()).map(Some),
+            None => Ok(None),
+        }
+    }
+
     pub fn get_all_properties(&self) -> &HashMap<String, String> {
         &self.props
     }
@@ -200,11 +411,76 @@ impl Task {
         Ok(())
     }
 
+    /// Renames the label keeping its color and description, returns false if the task doesn't have it.
+    /// If the task already has a label with the new name, the old one is just removed.
+    pub fn rename_label(&mut self, name: &str, new_name: &str) -> bool {
+        let Some(labels) = self.labels.as_mut() else {
+            return false;
+        };
+        let Some(index) = labels.iter().position(|label| label.name == name) else {
+            return false;
+        };
+
+        match labels.iter().any(|label| label.name == new_name) {
+            true => { labels.remove(index); },
+            false => labels[index].name = new_name.to_string(),
+        }
+
+        true
+    }
+
     pub fn get_label_by_name(&self, name: &str) -> Option<&Label> {
         self.labels
             .as_ref()
             .and_then(|labels| labels.iter().find(|label| label.name == name))
     }
+
+    pub fn get_mentions(&self) -> Vec<String> {
+        split_list(self.props.get(MENTIONS))
+    }
+
+    pub fn refresh_mentions(&mut self) {
+        let mut mentions = extract_mentions(self.props.get(DESCRIPTION).map(|s| s.as_str()).unwrap_or(""));
+
+        if let Some(comments) = self.comments.as_mut() {
+            for comment in comments {
+                let comment_mentions = extract_mentions(&comment.text);
+                for mention in &comment_mentions {
+                    if !mentions.contains(mention) {
+                        mentions.push(mention.clone());
+                    }
+                }
+                set_list(&mut comment.props, MENTIONS, comment_mentions);
+            }
+        }
+
+        set_list(&mut self.props, MENTIONS, mentions);
+    }
+
+    pub fn get_watchers(&self) -> Vec<String> {
+        split_list(self.props.get(WATCHERS))
+    }
+
+    pub fn add_watcher(&mut self, name: &str) -> bool {
+        let mut watchers = self.get_watchers();
+        if watchers.iter().any(|w| w == name) {
+            return false;
+        }
+        watchers.push(name.to_string());
+        set_list(&mut self.props, WATCHERS, watchers);
+        true
+    }
+
+    pub fn remove_watcher(&mut self, name: &str) -> bool {
+        let mut watchers = self.get_watchers();
+        let count = watchers.len();
+        watchers.retain(|w| w != name);
+        if watchers.len() == count {
+            return false;
+        }
+        set_list(&mut self.props, WATCHERS, watchers);
+        true
+    }
 }
 
 impl Comment {
@@ -231,6 +507,10 @@ impl Comment {
         &self.props
     }
 
+    pub fn get_mentions(&self) -> Vec<String> {
+        split_list(self.props.get(MENTIONS))
+    }
+
     pub fn get_text(&self) -> String {
         self.text.to_string()
     }
@@ -238,6 +518,28 @@ impl Comment {
     pub fn set_text(&mut self, text: String) {
         self.text = text;
     }
+
+    /// Replaces the text as a local edit, recording `edited` timestamp and `edited_by` author.
+    /// The text of the very first version is kept in `original_text`.
+    pub fn edit_text(&mut self, text: String) {
+        if text == self.text {
+            return;
+        }
+
+        if !self.props.contains_key(ORIGINAL_TEXT) {
+            self.props.insert(ORIGINAL_TEXT.to_string(), self.text.clone());
+        }
+        self.props.insert(EDITED.to_string(), get_current_timestamp().to_string());
+        match get_current_user() {
+            Ok(Some(current_user)) => self.props.insert(EDITED_BY.to_string(), current_user),
+            _ => self.props.remove(EDITED_BY),
+        };
+        self.text = text;
+    }
+
+    pub fn is_edited(&self) -> bool {
+        self.props.contains_key(EDITED)
+    }
 }
 
 impl Label {
@@ -268,255 +570,1909 @@ impl Label {
     }
 }
 
-macro_rules! map_err {
-    ($expr:expr) => {
-        $expr.map_err(|e| e.message().to_owned())?
+impl TaskSummary {
+    pub fn get_id(&self) -> Option<String> {
+        self.id.clone()
     }
-}
-
-pub fn list_tasks() -> Result<Vec<Task>, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
-    let task_tree = map_err!(task_ref.peel_to_tree());
-
-    let mut result = vec![];
 
-    let _ = map_err!(task_tree.walk(TreeWalkMode::PreOrder, |_, entry| {
-        let oid = entry.id();
-        let blob = repo.find_blob(oid).unwrap();
-        let content = blob.content();
+    pub fn get_property(&self, prop: &str) -> Option<&String> {
+        self.props.get(prop)
+    }
 
-        let task = serde_json::from_slice(content).unwrap();
-        result.push(task);
+    pub fn get_all_properties(&self) -> &HashMap<String, String> {
+        &self.props
+    }
 
-        TreeWalkResult::Ok
-    }));
+    pub fn get_labels(&self) -> &Option<Vec<Label>> {
+        &self.labels
+    }
 
-    Ok(result)
+    /// Converts the summary into a task without description and comments.
+    pub fn into_task(self) -> Task {
+        Task { id: self.id, props: self.props, comments: None, labels: self.labels, source: None }
+    }
 }
 
-pub fn find_task(id: &str) -> Result<Option<Task>, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref = repo.find_reference(&get_ref_path());
-    match task_ref {
-        Ok(task_ref) => {
-            let task_tree = map_err!(task_ref.peel_to_tree());
-            let result = match task_tree.get_name(id) {
-                Some(entry) => {
-                    let oid = entry.id();
-                    let blob = map_err!(repo.find_blob(oid));
-                    let content = blob.content();
-                    let task = serde_json::from_slice(content).unwrap();
-
-                    Some(task)
-                },
-                None => None,
-            };
+impl TaskBuilder {
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
 
-            Ok(result)
-        },
-        Err(_) => Ok(None)
+    pub fn description(self, description: &str) -> Self {
+        self.property(DESCRIPTION, description)
     }
-}
 
-pub fn delete_tasks(ids: &[&str]) -> Result<(), String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
-    let task_tree = map_err!(task_ref.peel_to_tree());
+    pub fn status(self, status: &str) -> Self {
+        self.property(STATUS, status)
+    }
 
-    let mut treebuilder = map_err!(repo.treebuilder(Some(&task_tree)));
-    for id in ids {
-        map_err!(treebuilder.remove(id));
+    pub fn created_at(self, created: chrono::DateTime<chrono::Utc>) -> Self {
+        self.property(CREATED, &created.timestamp().to_string())
     }
-    let tree_oid = map_err!(treebuilder.write());
 
-    let parent_commit = map_err!(task_ref.peel_to_commit());
-    let parents = vec![parent_commit];
-    let me = &map_err!(repo.signature());
+    /// Sets the property, a property set again keeps the last value.
+    pub fn property(mut self, prop: &str, value: &str) -> Self {
+        self.props.insert(prop.to_string(), value.to_string());
+        self
+    }
 
-    let mut ids = ids.iter().map(|id| id.parse::<u64>().unwrap()).collect::<Vec<_>>();
-    ids.sort();
-    let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
-    map_err!(repo.commit(Some(&get_ref_path()), me, me, format!("Delete task {}", ids).as_str(), &map_err!(repo.find_tree(tree_oid)), &parents.iter().collect::<Vec<_>>()));
+    pub fn label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
 
-    Ok(())
-}
+    pub fn comment(self, text: &str) -> Self {
+        self.comment_with_properties(HashMap::new(), text)
+    }
 
-pub fn clear_tasks() -> Result<u64, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
-    let task_tree = map_err!(task_ref.peel_to_tree());
+    /// Adds a comment, `created` and `author` are filled in on build unless given.
+    pub fn comment_with_properties(mut self, props: HashMap<String, String>, text: &str) -> Self {
+        self.comments.push((props, text.to_string()));
+        self
+    }
 
-    let mut treebuilder = map_err!(repo.treebuilder(Some(&task_tree)));
-    let task_count = treebuilder.len() as u64;
-    map_err!(treebuilder.clear());
-    let tree_oid = map_err!(treebuilder.write());
+    /// Validates the collected values and builds the task: name and status are required,
+    /// property and label names must be non-empty, label names unique, `created` must be a Unix timestamp.
+    pub fn build(self) -> Result<Task, String> {
+        if self.props.get(NAME).is_none_or(|name| name.trim().is_empty()) {
+            return Err("Task name can't be empty".to_string());
+        }
 
-    let parent_commit = map_err!(task_ref.peel_to_commit());
-    let parents = vec![parent_commit];
-    let me = &map_err!(repo.signature());
+        if self.props.get(STATUS).is_none_or(|status| status.trim().is_empty()) {
+            return Err("Task status can't be empty".to_string());
+        }
 
-    map_err!(repo.commit(Some(&get_ref_path()), me, me, "Clear tasks", &map_err!(repo.find_tree(tree_oid)), &parents.iter().collect::<Vec<_>>()));
+        if self.props.keys().any(|prop| prop.trim().is_empty()) {
+            return Err("Property name can't be empty".to_string());
+        }
 
-    Ok(task_count)
-}
+        if let Some(created) = self.props.get(CREATED) {
+            if created.parse::<u64>().is_err() {
+                return Err(format!("Invalid creation time '{created}', expected Unix timestamp"));
+            }
+        }
 
-pub fn create_task(mut task: Task) -> Result<Task, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref_result = repo.find_reference(&get_ref_path());
-    let source_tree = match task_ref_result {
-        Ok(ref reference) => {
-            match reference.peel_to_tree() {
-                Ok(tree) => Some(tree),
-                _ => None
+        let mut label_names = HashSet::new();
+        for label in &self.labels {
+            if label.name.trim().is_empty() {
+                return Err("Label name can't be empty".to_string());
+            }
+            if !label_names.insert(label.name.to_lowercase()) {
+                return Err(format!("Duplicate label '{}'", label.name));
             }
         }
-        _ => { None }
-    };
 
-    if task.get_id().is_none() {
-        let id = get_next_id().unwrap_or_else(|_| "1".to_string());
-        task.set_id(id);
-    }
-    let string_content = serde_json::to_string(&task).unwrap();
-    let content = string_content.as_bytes();
-    let oid = map_err!(repo.blob(content));
-    let mut treebuilder = map_err!(repo.treebuilder(source_tree.as_ref()));
-    map_err!(treebuilder.insert(&task.get_id().unwrap(), oid, FileMode::Blob.into()));
-    let tree_oid = map_err!(treebuilder.write());
+        if self.id.as_ref().is_some_and(|id| id.is_empty() || id.contains('/') || id.starts_with('.')) {
+            return Err(format!("Invalid task ID '{}'", self.id.unwrap()));
+        }
 
-    let me = &map_err!(repo.signature());
-    let mut parents = vec![];
-    if task_ref_result.is_ok() {
-        let parent_commit = map_err!(task_ref_result).peel_to_commit();
-        if parent_commit.is_ok() {
-            parents.push(map_err!(parent_commit));
+        let mut props = self.props;
+        props.entry(DESCRIPTION.to_string()).or_default();
+        props.entry(CREATED.to_string()).or_insert_with(|| get_current_timestamp().to_string());
+        if !props.contains_key("author") {
+            if let Ok(Some(current_user)) = get_current_user() {
+                props.insert("author".to_string(), current_user);
+            }
         }
-    }
-    map_err!(repo.commit(Some(&get_ref_path()), me, me, format!("Create task {}", &task.get_id().unwrap_or_else(|| String::from("?"))).as_str(), &map_err!(repo.find_tree(tree_oid)), &parents.iter().collect::<Vec<_>>()));
 
-    Ok(task)
+        let mut task = Task { id: self.id, props, comments: None, labels: None, source: None };
+        if !self.labels.is_empty() {
+            task.set_labels(self.labels);
+        }
+        for (props, text) in self.comments {
+            task.add_comment(None, props, text);
+        }
+
+        Ok(task)
+    }
 }
 
-pub fn update_task(task: Task) -> Result<String, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref_result = map_err!(repo.find_reference(&get_ref_path()));
-    let parent_commit = map_err!(task_ref_result.peel_to_commit());
-    let source_tree = map_err!(task_ref_result.peel_to_tree());
-    let string_content = serde_json::to_string(&task).unwrap();
-    let content = string_content.as_bytes();
-    let oid = map_err!(repo.blob(content));
-    let mut treebuilder = map_err!(repo.treebuilder(Some(&source_tree)));
-    map_err!(treebuilder.insert(&task.get_id().unwrap(), oid, FileMode::Blob.into()));
-    let tree_oid = map_err!(treebuilder.write());
+impl TaskIndex {
+    fn add(&mut self, task: &Task) {
+        let id = task.get_id().unwrap();
+        if let Some(status) = task.get_property(STATUS) {
+            self.status.entry(status.clone()).or_default().insert(id.clone());
+        }
+        for label in task.get_labels().iter().flatten() {
+            self.label.entry(label.get_name().to_lowercase()).or_default().insert(id.clone());
+        }
+        if let Some(assignee) = task.get_property("assignee").filter(|assignee| !assignee.is_empty()) {
+            self.assignee.entry(assignee.to_lowercase()).or_default().insert(id);
+        }
+    }
 
-    let me = &map_err!(repo.signature());
-    let parents = vec![parent_commit];
-    map_err!(repo.commit(Some(&get_ref_path()), me, me, format!("Update task {}", &task.get_id().unwrap()).as_str(), &map_err!(repo.find_tree(tree_oid)), &parents.iter().collect::<Vec<_>>()));
+    fn remove(&mut self, id: &str) {
+        for map in [&mut self.status, &mut self.label, &mut self.assignee] {
+            map.values_mut().for_each(|ids| { ids.remove(id); });
+            map.retain(|_, ids| !ids.is_empty());
+        }
+    }
 
-    Ok(task.get_id().unwrap())
+    fn get_map(&self, key: &str) -> Option<&BTreeMap<String, BTreeSet<String>>> {
+        match key {
+            STATUS => Some(&self.status),
+            "label" => Some(&self.label),
+            "assignee" => Some(&self.assignee),
+            _ => None,
+        }
+    }
 }
 
-fn get_next_id() -> Result<String, String> {
-    let repo = map_err!(Repository::discover("."));
-    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
-    let task_tree = map_err!(task_ref.peel_to_tree());
+fn deserialize_props_without_description<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HashMap<String, String>, D::Error> {
+    struct PropsVisitor;
 
-    let mut result = 0;
+    impl<'de> Visitor<'de> for PropsVisitor {
+        type Value = HashMap<String, String>;
 
-    let _ = map_err!(task_tree.walk(TreeWalkMode::PreOrder, |_, entry| {
-        let entry_name = entry.name().unwrap();
-        match entry_name.parse::<i64>() {
-            Ok(id) => {
-                if id > result {
-                    result = id;
-                }
-            },
-            _ => return TreeWalkResult::Skip
-        };
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a map of task properties")
+        }
 
-        TreeWalkResult::Ok
-    }));
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut result = HashMap::new();
+            while let Some(key) = map.next_key::<String>()? {
+                if key == DESCRIPTION {
+                    map.next_value::<IgnoredAny>()?;
+                } else {
+                    result.insert(key, map.next_value()?);
+                }
+            }
+            Ok(result)
+        }
+    }
 
-    Ok((result + 1).to_string())
+    deserializer.deserialize_map(PropsVisitor)
 }
 
-pub fn update_task_id(id: &str, new_id: &str) -> Result<(), String> {
-    let mut task = find_task(&id)?.unwrap();
-    task.set_id(new_id.to_string());
-    create_task(task)?;
-    delete_tasks(&[&id])?;
-
-    Ok(())
+pub fn extract_mentions(text: &str) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for caps in MENTION_REGEX.captures_iter(text) {
+        let mention = caps[1].to_string();
+        if !result.contains(&mention) {
+            result.push(mention);
+        }
+    }
+    result
 }
 
-pub fn update_comment_id(task_id: &str, id: &str, new_id: &str) -> Result<(), String> {
-    let mut task = find_task(&task_id)?.unwrap().clone();
-    let comments = task.get_comments();
-    match comments {
-        Some(comments) => {
-            let updated_comments = comments.iter().map(|c| {
-                if c.get_id().unwrap() == id {
-                    let mut c = c.clone();
-                    c.set_id(new_id.to_string());
-                    c
-                } else {
-                    c.clone()
-                }
-            }).collect::<Vec<_>>();
-            task.set_comments(updated_comments);
-            update_task(task)?;
-        },
-        None => {}
+fn split_list(value: Option<&String>) -> Vec<String> {
+    match value {
+        Some(value) => value.split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect(),
+        None => vec![],
     }
-
-    Ok(())
 }
 
-pub fn list_remotes(remote: &Option<String>) -> Result<Vec<String>, String> {
-    let repo = map_err!(Repository::discover("."));
-    let remotes = map_err!(repo.remotes());
-    Ok(remotes.iter()
-        .filter(|s| remote.is_none() || remote.as_ref().unwrap().as_str() == s.unwrap())
-        .map(|s| repo.find_remote(s.unwrap()).unwrap().url().unwrap().to_owned())
-        .collect())
+fn set_list(props: &mut HashMap<String, String>, key: &str, values: Vec<String>) {
+    if values.is_empty() {
+        props.remove(key);
+    } else {
+        props.insert(key.to_string(), values.join(","));
+    }
 }
 
-fn get_current_timestamp() -> u64 {
-    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+macro_rules! map_err {
+    ($expr:expr) => {
+        $expr.map_err(|e| e.message().to_owned())?
+    }
 }
 
-fn get_current_user() -> Result<Option<String>, String> {
-    let repo = map_err!(Repository::discover("."));
-    let me = &map_err!(repo.signature());
-    match me.name() {
-        Some(name) => Ok(Some(String::from(name))),
-        _ => match me.email() {
-            Some(email) => Ok(Some(String::from(email))),
-            _ => Ok(None),
-        }
+/// Opens the repository the way git itself does, honoring `GIT_DIR`, `GIT_WORK_TREE` and `GIT_CEILING_DIRECTORIES`.
+/// Linked worktrees share refs and config with the main repository, bare repositories are opened as is.
+/// Inside [`TaskRepository::run`] the repository at an explicit path is opened instead, once for the whole run.
+fn open_repo() -> Result<Rc<Repository>, String> {
+    match REPOSITORY_PATH.with_borrow(|path| path.clone()) {
+        Some(path) => match REPOSITORY.with_borrow(|repo| repo.clone()) {
+            Some(repo) => Ok(repo),
+            None => {
+                let repo = Rc::new(map_err!(Repository::open(path)));
+                REPOSITORY.set(Some(repo.clone()));
+                Ok(repo)
+            },
+        },
+        None => Ok(Rc::new(map_err!(Repository::open_from_env()))),
     }
 }
 
-pub fn get_ref_path() -> String {
-    get_config_value("task.ref").unwrap_or_else(|_| "refs/tasks/tasks".to_string())
+thread_local! {
+    static REPOSITORY_PATH: RefCell<Option<std::path::PathBuf>> = const { RefCell::new(None) };
+    static REPOSITORY: RefCell<Option<Rc<Repository>>> = const { RefCell::new(None) };
 }
 
-pub fn get_config_value(key: &str) -> Result<String, String> {
-    let repo = map_err!(Repository::discover("."));
+/// Repository at an explicit path, for embedding the library or testing against throwaway repositories.
+/// The most common operations are available as methods, any other library function can be called within [`TaskRepository::run`].
+pub struct TaskRepository {
+    path: std::path::PathBuf,
+    _temp_dir: Option<tempfile::TempDir>,
+}
+
+impl TaskRepository {
+    /// Opens the repository containing the path, looking up parent directories like git does.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<TaskRepository, String> {
+        let repo = map_err!(Repository::discover(path.as_ref()));
+        Ok(TaskRepository { path: repo.path().to_path_buf(), _temp_dir: None })
+    }
+
+    /// Creates an empty repository at the path.
+    pub fn init(path: impl AsRef<std::path::Path>) -> Result<TaskRepository, String> {
+        let repo = map_err!(Repository::init(path.as_ref()));
+        Ok(TaskRepository { path: repo.path().to_path_buf(), _temp_dir: None })
+    }
+
+    /// Creates a repository with a configured committer in a temporary directory, removed on drop.
+    pub fn temporary() -> Result<TaskRepository, String> {
+        let temp_dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+        let repo = map_err!(Repository::init(temp_dir.path()));
+        let mut config = map_err!(repo.config());
+        map_err!(config.set_str("user.name", "git-task"));
+        map_err!(config.set_str("user.email", "git-task@example.com"));
+        Ok(TaskRepository { path: repo.path().to_path_buf(), _temp_dir: Some(temp_dir) })
+    }
+
+    /// Path of the `.git` directory or the bare repository.
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// Runs `f` with all library functions operating on this repository. Affects the current thread only.
+    /// The repository is opened on first use and shared by all calls until the outermost `run` returns.
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        struct Restore(Option<std::path::PathBuf>, Option<Rc<Repository>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                REPOSITORY_PATH.set(self.0.take());
+                REPOSITORY.set(self.1.take());
+            }
+        }
+
+        let previous_path = REPOSITORY_PATH.replace(Some(self.path.clone()));
+        let previous_repo = match previous_path.as_ref() == Some(&self.path) {
+            true => REPOSITORY.with_borrow(|repo| repo.clone()),
+            false => REPOSITORY.take(),
+        };
+        let _restore = Restore(previous_path, previous_repo);
+        f()
+    }
+
+    pub fn list_tasks(&self) -> Result<Vec<Task>, String> {
+        self.run(list_tasks)
+    }
+
+    pub fn list_task_summaries(&self) -> Result<Vec<TaskSummary>, String> {
+        self.run(list_task_summaries)
+    }
+
+    pub fn find_task(&self, id: &str) -> Result<Option<Task>, String> {
+        self.run(|| find_task(id))
+    }
+
+    pub fn create_task(&self, task: Task) -> Result<Task, String> {
+        self.run(|| create_task(task))
+    }
+
+    pub fn update_task(&self, task: Task) -> Result<String, String> {
+        self.run(|| update_task(task))
+    }
+
+    pub fn update_tasks(&self, tasks: Vec<Task>) -> Result<Vec<String>, String> {
+        self.run(|| update_tasks(tasks))
+    }
+
+    pub fn delete_tasks(&self, ids: &[&str]) -> Result<(), String> {
+        self.run(|| delete_tasks(ids))
+    }
+
+    pub fn get_config_value(&self, key: &str) -> Result<String, String> {
+        self.run(|| get_config_value(key))
+    }
+
+    pub fn set_config_value(&self, key: &str, value: &str) -> Result<(), String> {
+        self.run(|| set_config_value(key, value))
+    }
+}
+
+pub fn list_tasks() -> Result<Vec<Task>, String> {
+    load_tasks(true)
+}
+
+/// Lists tasks without loading their comments.
+pub fn list_tasks_without_comments() -> Result<Vec<Task>, String> {
+    load_tasks(false)
+}
+
+fn load_tasks(with_comments: bool) -> Result<Vec<Task>, String> {
+    let repo = open_repo()?;
+    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
+    let task_tree = map_err!(task_ref.peel_to_tree());
+
+    let mut result = vec![];
+
+    for entry in list_task_entries(&repo, &task_tree) {
+        result.push(read_task(&repo, &entry, with_comments)?);
+    }
+
+    Ok(result)
+}
+
+/// Lists tasks as they were at a revision of the tasks ref, e.g. a commit ID or `~3` for three commits back.
+pub fn list_tasks_at(revision: &str) -> Result<Vec<Task>, String> {
+    let repo = open_repo()?;
+    let revision = match revision.starts_with(['~', '^']) {
+        true => get_ref_path() + revision,
+        false => revision.to_string(),
+    };
+    let task_tree = map_err!(map_err!(repo.revparse_single(&revision)).peel_to_tree());
+
+    let mut result = vec![];
+
+    for entry in list_task_entries(&repo, &task_tree) {
+        let mut task = read_task(&repo, &entry, true)?;
+        // restoring an old version must overwrite the current one, not be merged into it
+        task.source = None;
+        result.push(task);
+    }
+
+    Ok(result)
+}
+
+/// Lists tasks for list views without parsing comments and descriptions.
+pub fn list_task_summaries() -> Result<Vec<TaskSummary>, String> {
+    let repo = open_repo()?;
+    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
+    let task_tree = map_err!(task_ref.peel_to_tree());
+
+    let mut result = vec![];
+
+    for entry in list_task_entries(&repo, &task_tree) {
+        let oid = match entry.kind() {
+            Some(ObjectType::Tree) => {
+                let tree = map_err!(repo.find_tree(entry.id()));
+                let task_entry = tree.get_name(TASK_ENTRY).ok_or_else(|| format!("Task entry {} is missing the {TASK_ENTRY} blob", entry.name().unwrap_or_default()))?;
+                task_entry.id()
+            },
+            _ => entry.id(),
+        };
+        let blob = map_err!(repo.find_blob(oid));
+        result.push(serde_json::from_slice(blob.content()).map_err(|e| e.to_string())?);
+    }
+
+    Ok(result)
+}
+
+fn read_task(repo: &Repository, entry: &TreeEntry, with_comments: bool) -> Result<Task, String> {
+    let mut task = read_task_object(repo, entry.id(), entry.kind(), entry.name().unwrap_or_default(), with_comments)?;
+    // without comments the task can't be merged, it's written as is
+    if with_comments {
+        task.source = Some(entry.id());
+    }
+    Ok(task)
+}
+
+fn read_task_object(repo: &Repository, oid: Oid, kind: Option<ObjectType>, name: &str, with_comments: bool) -> Result<Task, String> {
+    if kind != Some(ObjectType::Tree) {
+        let blob = map_err!(repo.find_blob(oid));
+        let mut task: Task = serde_json::from_slice(blob.content()).map_err(|e| e.to_string())?;
+        if !with_comments {
+            task.comments = None;
+        }
+        return Ok(task);
+    }
+
+    let tree = map_err!(repo.find_tree(oid));
+    let task_entry = tree.get_name(TASK_ENTRY).ok_or_else(|| format!("Task entry {name} is missing the {TASK_ENTRY} blob"))?;
+    let mut task: Task = serde_json::from_slice(map_err!(repo.find_blob(task_entry.id())).content()).map_err(|e| e.to_string())?;
+
+    if with_comments {
+        if let Some(comments_entry) = tree.get_name(COMMENTS_ENTRY) {
+            let comments_tree = map_err!(repo.find_tree(comments_entry.id()));
+            let mut comments = vec![];
+            for comment_entry in comments_tree.iter() {
+                let blob = map_err!(repo.find_blob(comment_entry.id()));
+                comments.push(serde_json::from_slice::<Comment>(blob.content()).map_err(|e| e.to_string())?);
+            }
+            task.comments = Some(comments);
+        }
+    }
+
+    Ok(task)
+}
+
+/// Writes the task as a single blob or, if `split_comments` is set, as a tree with one blob per comment.
+fn write_task(repo: &Repository, task: &Task, split_comments: bool) -> Result<(Oid, FileMode), String> {
+    let comments = task.comments.as_ref().filter(|comments| !comments.is_empty());
+    match comments {
+        Some(comments) if split_comments => {
+            let mut comments_builder = map_err!(repo.treebuilder(None));
+            for (n, comment) in comments.iter().enumerate() {
+                let oid = map_err!(repo.blob(serde_json::to_string(comment).unwrap().as_bytes()));
+                map_err!(comments_builder.insert(format!("{n:06}"), oid, FileMode::Blob.into()));
+            }
+
+            let mut task = task.clone();
+            task.comments = None;
+            let task_oid = map_err!(repo.blob(serde_json::to_string(&task).unwrap().as_bytes()));
+
+            let mut treebuilder = map_err!(repo.treebuilder(None));
+            map_err!(treebuilder.insert(TASK_ENTRY, task_oid, FileMode::Blob.into()));
+            map_err!(treebuilder.insert(COMMENTS_ENTRY, map_err!(comments_builder.write()), FileMode::Tree.into()));
+            Ok((map_err!(treebuilder.write()), FileMode::Tree))
+        },
+        _ => Ok((map_err!(repo.blob(serde_json::to_string(task).unwrap().as_bytes())), FileMode::Blob)),
+    }
+}
+
+/// Checks the tasks ref, returns `None` if it doesn't exist yet,
+/// otherwise the task count, the time of the latest commit and the problems found.
+pub fn check_tasks_ref() -> Result<Option<(usize, i64, Vec<String>)>, String> {
+    let repo = open_repo()?;
+    let task_ref = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => task_ref,
+        Err(_) => return Ok(None),
+    };
+    let commit = map_err!(task_ref.peel_to_commit());
+    let tree = map_err!(commit.tree());
+
+    let mut count = 0;
+    let mut problems = vec![];
+    for entry in list_task_entries(&repo, &tree) {
+        let name = entry.name().unwrap_or_default().to_string();
+        match read_task(&repo, &entry, true) {
+            Ok(task) if task.get_id().as_ref() == Some(&name) => count += 1,
+            Ok(task) => problems.push(format!("entry '{name}' contains task ID {}", task.get_id().unwrap_or_default())),
+            Err(e) => problems.push(format!("entry '{name}' can't be parsed: {e}")),
+        }
+    }
+
+    Ok(Some((count, commit.time().seconds(), problems)))
+}
+
+pub fn find_task(id: &str) -> Result<Option<Task>, String> {
+    let id = &resolve_task_id(id);
+    let repo = open_repo()?;
+    let task_ref = repo.find_reference(&get_ref_path());
+    match task_ref {
+        Ok(task_ref) => {
+            let task_tree = map_err!(task_ref.peel_to_tree());
+            let path = get_task_path(id, is_sharded(&repo, &task_tree));
+            let result = match task_tree.get_path(std::path::Path::new(&path)) {
+                Ok(entry) => Some(read_task(&repo, &entry, true)?),
+                Err(_) => None,
+            };
+
+            Ok(result)
+        },
+        Err(_) => Ok(None)
+    }
+}
+
+pub fn delete_tasks(ids: &[&str]) -> Result<(), String> {
+    check_writable()?;
+    let repo = open_repo()?;
+
+    let mut sorted_ids = ids.iter().map(|id| parse_task_id(id)).collect::<Result<Vec<_>, _>>()?;
+    sorted_ids.sort();
+    let sorted_ids = sorted_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
+
+    commit_tasks(&repo, |repo, tree| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let mut tree_oid = tree.id();
+        for id in ids {
+            tree_oid = remove_task(repo, &map_err!(repo.find_tree(tree_oid)), id)?;
+        }
+        Ok((tree_oid, commit_message("delete", format!("Delete task {}", sorted_ids), &[("id", &sorted_ids)])))
+    })
+}
+
+pub fn clear_tasks() -> Result<u64, String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    let mut task_count = 0;
+    commit_tasks(&repo, |repo, tree| {
+        task_count = tree.map(|tree| list_task_entries(repo, tree).len()).unwrap_or(0) as u64;
+        let mut treebuilder = map_err!(repo.treebuilder(tree));
+        map_err!(treebuilder.filter(is_meta_entry));
+        if treebuilder.get(INDEX_ENTRY).ok().flatten().is_some() {
+            write_index(repo, &mut treebuilder, &TaskIndex::default())?;
+        }
+        Ok((map_err!(treebuilder.write()), commit_message("clear", "Clear tasks".to_string(), &[("count", &task_count.to_string())])))
+    })?;
+
+    Ok(task_count)
+}
+
+/// Creates the tasks ref with no tasks in it, returns false if it already exists.
+pub fn init_tasks_ref() -> Result<bool, String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    if repo.find_reference(&get_ref_path()).is_ok() {
+        return Ok(false);
+    }
+
+    commit_tasks(&repo, |repo, tree| {
+        let mut treebuilder = map_err!(repo.treebuilder(tree));
+        if tree.is_none() {
+            let format_oid = map_err!(repo.blob(FORMAT_VERSION.to_string().as_bytes()));
+            map_err!(treebuilder.insert(FORMAT_ENTRY, format_oid, FileMode::Blob.into()));
+        }
+        Ok((map_err!(treebuilder.write()), commit_message("init", "Initialize tasks".to_string(), &[])))
+    })?;
+
+    Ok(true)
+}
+
+pub fn create_task(mut task: Task) -> Result<Task, String> {
+    check_writable()?;
+    let repo = open_repo()?;
+
+    let assign_id = task.get_id().is_none();
+    commit_tasks(&repo, |repo, tree| {
+        if assign_id {
+            task.set_id(get_next_id_in_tree(repo, tree));
+        }
+        task.refresh_mentions();
+        run_update_hook(repo, &task, "create")?;
+        let id = task.get_id().unwrap_or_else(|| String::from("?"));
+        let message = commit_message("create", format!("Create task {id}"), &[("id", &id), ("name", task.name())]);
+        Ok((insert_task(repo, tree, &task)?, message))
+    })?;
+
+    Ok(task)
+}
+
+/// Saves the task, bumping its `updated` timestamp and `updated_by` author.
+pub fn update_task(mut task: Task) -> Result<String, String> {
+    check_writable()?;
+    task.refresh_mentions();
+    task.touch();
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    let id = task.get_id().unwrap();
+    let message = commit_message("update", format!("Update task {id}"), &[("id", &id), ("name", task.name())]);
+    commit_tasks(&repo, |repo, tree| {
+        let task = merge_task(repo, tree, &task)?;
+        run_update_hook(repo, &task, "update")?;
+        Ok((insert_task(repo, tree, &task)?, message.clone()))
+    })?;
+
+    Ok(task.get_id().unwrap())
+}
+
+/// Updates several tasks in a single commit, bumping their `updated` timestamps like `update_task`.
+pub fn update_tasks(mut tasks: Vec<Task>) -> Result<Vec<String>, String> {
+    check_writable()?;
+    tasks.iter_mut().for_each(|task| {
+        task.refresh_mentions();
+        task.touch();
+    });
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    let ids = tasks.iter().map(|task| task.get_id().unwrap()).collect::<Vec<_>>();
+    let message = match ids.len() {
+        1 => commit_message("update", format!("Update task {}", ids[0]), &[("id", &ids[0]), ("name", tasks[0].name())]),
+        _ => commit_message("update", format!("Update tasks {}", ids.join(", ")), &[("id", &ids.join(", ")), ("name", "")]),
+    };
+
+    commit_tasks(&repo, |repo, tree| {
+        let mut tree_oid = None;
+        for task in &tasks {
+            let tree = match tree_oid {
+                Some(oid) => Some(map_err!(repo.find_tree(oid))),
+                None => tree.cloned(),
+            };
+            let task = merge_task(repo, tree.as_ref(), task)?;
+            run_update_hook(repo, &task, "update")?;
+            tree_oid = Some(insert_task(repo, tree.as_ref(), &task)?);
+        }
+        match tree_oid {
+            Some(tree_oid) => Ok((tree_oid, message.clone())),
+            None => Err("No tasks to update".to_string()),
+        }
+    })?;
+
+    Ok(ids)
+}
+
+/// Applies changes made to the task since it was read to its version in the tree, so that a concurrent update
+/// of other properties isn't lost. Fails if a property, the comments or the labels were changed differently by both.
+fn merge_task(repo: &Repository, tree: Option<&Tree>, task: &Task) -> Result<Task, String> {
+    let (Some(source), Some(tree)) = (task.source, tree) else {
+        return Ok(task.clone());
+    };
+    let id = task.get_id().unwrap();
+    let entry = match tree.get_path(std::path::Path::new(&get_task_path(&id, is_sharded(repo, tree)))) {
+        Ok(entry) if entry.id() != source => entry,
+        _ => return Ok(task.clone()),
+    };
+
+    let base = read_task_object(repo, source, map_err!(repo.find_object(source, None)).kind(), &id, true)?;
+    let mut result = read_task(repo, &entry, true)?;
+    let conflict = |what: &str| format!("Task {id} {what} changed, {CONFLICT_ERROR}");
+
+    let props = base.props.keys().chain(task.props.keys()).collect::<HashSet<_>>();
+    for prop in props {
+        let (base_value, value) = (base.props.get(prop), task.props.get(prop));
+        if value == base_value {
+            continue;
+        }
+        let current_value = result.props.get(prop);
+        if current_value != base_value && current_value != value && ![UPDATED, UPDATED_BY, MENTIONS].contains(&prop.as_str()) {
+            return Err(conflict(&format!("property '{prop}'")));
+        }
+        match value {
+            Some(value) => result.props.insert(prop.clone(), value.clone()),
+            None => result.props.remove(prop),
+        };
+    }
+
+    if task.comments != base.comments {
+        if result.comments != base.comments && result.comments != task.comments {
+            return Err(conflict("comments"));
+        }
+        result.comments = task.comments.clone();
+    }
+    if task.labels != base.labels {
+        if result.labels != base.labels && result.labels != task.labels {
+            return Err(conflict("labels"));
+        }
+        result.labels = task.labels.clone();
+    }
+
+    result.refresh_mentions();
+    Ok(result)
+}
+
+fn insert_task(repo: &Repository, tree: Option<&Tree>, task: &Task) -> Result<Oid, String> {
+    let split_comments = match tree {
+        Some(tree) => get_tree_format_version(repo, tree)? >= SPLIT_COMMENTS_VERSION,
+        None => true,
+    };
+    let (oid, mode) = write_task(repo, task, split_comments)?;
+    let id = task.get_id().unwrap();
+    let mut treebuilder = map_err!(repo.treebuilder(tree));
+    match tree {
+        Some(tree) if is_sharded(repo, tree) => {
+            let shard = get_shard(&id);
+            let shard_tree = get_shard_tree(repo, tree, &shard)?;
+            let mut shard_builder = map_err!(repo.treebuilder(shard_tree.as_ref()));
+            map_err!(shard_builder.insert(&id, oid, mode.into()));
+            map_err!(treebuilder.insert(&shard, map_err!(shard_builder.write()), FileMode::Tree.into()));
+        },
+        _ => {
+            map_err!(treebuilder.insert(&id, oid, mode.into()));
+        }
+    }
+    if tree.is_none() {
+        let format_oid = map_err!(repo.blob(FORMAT_VERSION.to_string().as_bytes()));
+        map_err!(treebuilder.insert(FORMAT_ENTRY, format_oid, FileMode::Blob.into()));
+    }
+    if let Some(mut index) = tree.map(|tree| read_index(repo, tree)).transpose()?.flatten() {
+        index.remove(&id);
+        index.add(task);
+        write_index(repo, &mut treebuilder, &index)?;
+    }
+    Ok(map_err!(treebuilder.write()))
+}
+
+fn read_index(repo: &Repository, tree: &Tree) -> Result<Option<TaskIndex>, String> {
+    match tree.get_name(INDEX_ENTRY) {
+        Some(entry) => {
+            let blob = map_err!(repo.find_blob(entry.id()));
+            Ok(Some(serde_json::from_slice(blob.content()).map_err(|e| format!("Can't parse {INDEX_ENTRY}: {e}"))?))
+        },
+        None => Ok(None),
+    }
+}
+
+fn write_index(repo: &Repository, treebuilder: &mut TreeBuilder, index: &TaskIndex) -> Result<(), String> {
+    let oid = map_err!(repo.blob(serde_json::to_string(index).unwrap().as_bytes()));
+    map_err!(treebuilder.insert(INDEX_ENTRY, oid, FileMode::Blob.into()));
+    Ok(())
+}
+
+/// Returns true if the tasks tree contains the property index.
+pub fn has_index() -> Result<bool, String> {
+    let repo = open_repo()?;
+    let result = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => map_err!(task_ref.peel_to_tree()).get_name(INDEX_ENTRY).is_some(),
+        Err(_) => false,
+    };
+    Ok(result)
+}
+
+/// Builds or drops the property index in a single commit.
+pub fn set_index(enabled: bool) -> Result<(), String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    commit_tasks(&repo, |repo, tree| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let mut treebuilder = map_err!(repo.treebuilder(Some(tree)));
+        if enabled {
+            let mut index = TaskIndex::default();
+            for entry in list_task_entries(repo, tree) {
+                index.add(&read_task(repo, &entry, false)?);
+            }
+            write_index(repo, &mut treebuilder, &index)?;
+            Ok((map_err!(treebuilder.write()), "Build tasks index".to_string()))
+        } else {
+            if treebuilder.get(INDEX_ENTRY).ok().flatten().is_some() {
+                map_err!(treebuilder.remove(INDEX_ENTRY));
+            }
+            Ok((map_err!(treebuilder.write()), "Drop tasks index".to_string()))
+        }
+    })
+}
+
+fn read_pulls(repo: &Repository, tree: &Tree) -> Result<BTreeMap<String, i64>, String> {
+    match tree.get_name(PULLS_ENTRY) {
+        Some(entry) => {
+            let blob = map_err!(repo.find_blob(entry.id()));
+            serde_json::from_slice(blob.content()).map_err(|e| format!("Can't parse {PULLS_ENTRY}: {e}"))
+        },
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Returns the time of the last successful pull from the remote (`user/repo`) as a Unix timestamp.
+pub fn get_last_pull(remote: &str) -> Result<Option<i64>, String> {
+    let repo = open_repo()?;
+    let result = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => read_pulls(&repo, &map_err!(task_ref.peel_to_tree()))?.get(remote).copied(),
+        Err(_) => None,
+    };
+    Ok(result)
+}
+
+/// Records the time of a successful pull from the remote (`user/repo`).
+pub fn set_last_pull(remote: &str, timestamp: i64) -> Result<(), String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    commit_tasks(&repo, |repo, tree| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let mut pulls = read_pulls(repo, tree)?;
+        pulls.insert(remote.to_string(), timestamp);
+        let oid = map_err!(repo.blob(serde_json::to_string(&pulls).unwrap().as_bytes()));
+        let mut treebuilder = map_err!(repo.treebuilder(Some(tree)));
+        map_err!(treebuilder.insert(PULLS_ENTRY, oid, FileMode::Blob.into()));
+        Ok((map_err!(treebuilder.write()), format!("Record pull from {remote}")))
+    })
+}
+
+/// Finds tasks (without comments) matching all filters using the property index.
+/// Each filter is a property (`status`, `label` or `assignee`) and a list of alternative values.
+/// Returns `None` if there is no index.
+pub fn find_tasks_by_index(filters: &[(&str, Vec<String>)]) -> Result<Option<Vec<Task>>, String> {
+    let repo = open_repo()?;
+    let tree = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => map_err!(task_ref.peel_to_tree()),
+        Err(_) => return Ok(None),
+    };
+    let Some(index) = read_index(&repo, &tree)? else {
+        return Ok(None);
+    };
+
+    let mut ids: Option<BTreeSet<String>> = None;
+    for (key, values) in filters {
+        let map = index.get_map(key).ok_or_else(|| format!("Property {key} is not indexed"))?;
+        let matching = values.iter()
+            .filter_map(|value| map.get(&if *key == STATUS { value.clone() } else { value.to_lowercase() }))
+            .flatten()
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        ids = Some(match ids {
+            Some(ids) => ids.intersection(&matching).cloned().collect(),
+            None => matching,
+        });
+    }
+
+    let sharded = is_sharded(&repo, &tree);
+    let mut result = vec![];
+    for id in ids.unwrap_or_default() {
+        if let Ok(entry) = tree.get_path(std::path::Path::new(&get_task_path(&id, sharded))) {
+            result.push(read_task(&repo, &entry, false)?);
+        }
+    }
+    Ok(Some(result))
+}
+
+fn remove_task(repo: &Repository, tree: &Tree, id: &str) -> Result<Oid, String> {
+    let mut treebuilder = map_err!(repo.treebuilder(Some(tree)));
+    if is_sharded(repo, tree) {
+        let shard = get_shard(id);
+        let shard_tree = get_shard_tree(repo, tree, &shard)?;
+        let mut shard_builder = map_err!(repo.treebuilder(shard_tree.as_ref()));
+        map_err!(shard_builder.remove(id));
+        if shard_builder.is_empty() {
+            map_err!(treebuilder.remove(&shard));
+        } else {
+            map_err!(treebuilder.insert(&shard, map_err!(shard_builder.write()), FileMode::Tree.into()));
+        }
+    } else {
+        map_err!(treebuilder.remove(id));
+    }
+    if let Some(mut index) = read_index(repo, tree)? {
+        index.remove(id);
+        write_index(repo, &mut treebuilder, &index)?;
+    }
+    Ok(map_err!(treebuilder.write()))
+}
+
+fn is_meta_entry(entry: &TreeEntry) -> bool {
+    entry.name().is_some_and(|name| name.starts_with('.'))
+}
+
+fn is_sharded(repo: &Repository, tree: &Tree) -> bool {
+    tree.get_name(LAYOUT_ENTRY)
+        .and_then(|entry| repo.find_blob(entry.id()).ok())
+        .is_some_and(|blob| String::from_utf8_lossy(blob.content()).trim() == SHARDED_LAYOUT)
+}
+
+fn get_shard(id: &str) -> String {
+    id.chars().take(SHARD_PREFIX_LENGTH).collect()
+}
+
+fn get_task_path(id: &str, sharded: bool) -> String {
+    match sharded {
+        true => format!("{}/{id}", get_shard(id)),
+        false => id.to_string(),
+    }
+}
+
+fn get_shard_tree<'a>(repo: &'a Repository, tree: &Tree, shard: &str) -> Result<Option<Tree<'a>>, String> {
+    match tree.get_name(shard) {
+        Some(entry) if entry.kind() == Some(ObjectType::Tree) => Ok(Some(map_err!(repo.find_tree(entry.id())))),
+        Some(_) => Err(format!("Entry {shard} is not a shard tree")),
+        None => Ok(None),
+    }
+}
+
+/// Returns entries of all tasks in the tree named by task ID, both flat and sharded layouts are supported.
+/// An entry is either a task blob or a tree with separately stored comments.
+fn list_task_entries(repo: &Repository, tree: &Tree) -> Vec<TreeEntry<'static>> {
+    let entries = tree.iter().filter(|entry| !is_meta_entry(entry));
+    if !is_sharded(repo, tree) {
+        return entries.map(|entry| entry.to_owned()).collect();
+    }
+
+    let mut result = vec![];
+    for shard in entries {
+        match repo.find_tree(shard.id()) {
+            Ok(shard_tree) => result.extend(shard_tree.iter().map(|entry| entry.to_owned())),
+            Err(_) => result.push(shard.to_owned()),
+        }
+    }
+    result
+}
+
+/// Returns the task ID for a path of a task blob in the tree, `None` for comments and other entries.
+fn get_task_id_from_path(path: &std::path::Path) -> Option<String> {
+    let components = path.iter().map(|component| component.to_string_lossy()).collect::<Vec<_>>();
+    match components.as_slice() {
+        [.., id, name] if name == TASK_ENTRY => Some(id.to_string()),
+        [.., comments, _] if comments == COMMENTS_ENTRY => None,
+        [.., id] => Some(id.to_string()),
+        [] => None,
+    }
+}
+
+/// Returns true if tasks are sharded into subtrees by ID prefix.
+pub fn is_sharded_layout() -> Result<bool, String> {
+    let repo = open_repo()?;
+    let sharded = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => is_sharded(&repo, &map_err!(task_ref.peel_to_tree())),
+        Err(_) => false,
+    };
+    Ok(sharded)
+}
+
+/// Rewrites the tasks tree into the flat or sharded layout in a single commit, returns the task count.
+pub fn set_layout(sharded: bool) -> Result<usize, String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    let mut task_count = 0;
+    commit_tasks(&repo, |repo, tree| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let entries = list_task_entries(repo, tree);
+        task_count = entries.len();
+
+        let mut treebuilder = map_err!(repo.treebuilder(None));
+        for entry in tree.iter().filter(|entry| is_meta_entry(entry) && entry.name() != Some(LAYOUT_ENTRY)) {
+            map_err!(treebuilder.insert(entry.name().unwrap(), entry.id(), entry.filemode()));
+        }
+
+        if sharded {
+            let mut shards = BTreeMap::<String, Vec<TreeEntry>>::new();
+            for entry in entries {
+                shards.entry(get_shard(entry.name().unwrap_or_default())).or_default().push(entry);
+            }
+            for (shard, entries) in shards {
+                let mut shard_builder = map_err!(repo.treebuilder(None));
+                for entry in entries {
+                    map_err!(shard_builder.insert(entry.name().unwrap(), entry.id(), entry.filemode()));
+                }
+                map_err!(treebuilder.insert(&shard, map_err!(shard_builder.write()), FileMode::Tree.into()));
+            }
+            let layout_oid = map_err!(repo.blob(SHARDED_LAYOUT.as_bytes()));
+            map_err!(treebuilder.insert(LAYOUT_ENTRY, layout_oid, FileMode::Blob.into()));
+        } else {
+            for entry in entries {
+                map_err!(treebuilder.insert(entry.name().unwrap(), entry.id(), entry.filemode()));
+            }
+        }
+
+        let layout = if sharded { SHARDED_LAYOUT } else { "flat" };
+        Ok((map_err!(treebuilder.write()), format!("Change tasks layout to {layout}")))
+    })?;
+
+    Ok(task_count)
+}
+
+fn get_tree_format_version(repo: &Repository, tree: &Tree) -> Result<u32, String> {
+    match tree.get_name(FORMAT_ENTRY) {
+        Some(entry) => {
+            let blob = map_err!(repo.find_blob(entry.id()));
+            String::from_utf8_lossy(blob.content()).trim().parse::<u32>().map_err(|_| format!("Unknown tasks format version in {FORMAT_ENTRY}"))
+        },
+        None => Ok(0),
+    }
+}
+
+/// Returns the format version of the tasks tree or `None` if there are no tasks yet.
+pub fn get_format_version() -> Result<Option<u32>, String> {
+    let repo = open_repo()?;
+    let task_ref = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => task_ref,
+        Err(_) => return Ok(None),
+    };
+    let tree = map_err!(task_ref.peel_to_tree());
+    Ok(Some(get_tree_format_version(&repo, &tree)?))
+}
+
+/// Upgrades the tasks tree to the current format in a single commit.
+/// Returns the original version and IDs of the changed tasks, nothing is written if `dry_run` is set.
+pub fn migrate_tasks(dry_run: bool) -> Result<(u32, Vec<String>), String> {
+    if !dry_run {
+        check_writable()?;
+    }
+    let repo = open_repo()?;
+    map_err!(repo.find_reference(&get_ref_path()));
+
+    let mut result = (0, vec![]);
+    let mut build_commit = |repo: &Repository, tree: Option<&Tree>| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let version = get_tree_format_version(repo, tree)?;
+        if version > FORMAT_VERSION {
+            return Err(format!("Tasks format version {version} is newer than supported version {FORMAT_VERSION}, please upgrade git-task"));
+        }
+
+        let mut treebuilder = map_err!(repo.treebuilder(Some(tree)));
+        let format_oid = map_err!(repo.blob(FORMAT_VERSION.to_string().as_bytes()));
+        map_err!(treebuilder.insert(FORMAT_ENTRY, format_oid, FileMode::Blob.into()));
+        let mut tree_oid = map_err!(treebuilder.write());
+
+        let mut changed = vec![];
+        for entry in list_task_entries(repo, tree) {
+            let mut task = read_task(repo, &entry, true).map_err(|e| format!("Task {}: {e}", entry.name().unwrap_or_default()))?;
+            if migrate_task(&mut task, version) {
+                tree_oid = insert_task(repo, Some(&map_err!(repo.find_tree(tree_oid))), &task)?;
+                changed.push(task.get_id().unwrap());
+            }
+        }
+
+        result = (version, changed);
+        Ok((tree_oid, format!("Migrate tasks format {version} -> {FORMAT_VERSION}")))
+    };
+
+    if dry_run {
+        let tree = map_err!(map_err!(repo.find_reference(&get_ref_path())).peel_to_tree());
+        build_commit(&repo, Some(&tree))?;
+    } else {
+        commit_tasks(&repo, build_commit)?;
+    }
+
+    Ok(result)
+}
+
+/// Applies all format upgrades newer than `version` to the task, returns true if it has to be rewritten.
+fn migrate_task(task: &mut Task, version: u32) -> bool {
+    let mut changed = false;
+    if version < SPLIT_COMMENTS_VERSION {
+        changed |= task.comments.as_ref().is_some_and(|comments| !comments.is_empty());
+    }
+    if version < 1 {
+        changed |= normalize_timestamp(&mut task.props);
+        if let Some(comments) = task.comments.as_mut() {
+            for comment in comments {
+                changed |= normalize_timestamp(&mut comment.props);
+            }
+        }
+    }
+    if version < UPDATED_VERSION {
+        changed |= backfill_updated(task);
+    }
+    changed
+}
+
+/// Sets a missing `updated` timestamp to the latest of the creation times of the task and its comments.
+fn backfill_updated(task: &mut Task) -> bool {
+    if task.props.contains_key(UPDATED) {
+        return false;
+    }
+    let comments = task.comments.iter().flatten().filter_map(|comment| comment.props.get(CREATED));
+    let Some(updated) = task.props.get(CREATED).into_iter().chain(comments).filter_map(|created| created.parse::<u64>().ok()).max() else {
+        return false;
+    };
+    task.props.insert(UPDATED.to_string(), updated.to_string());
+    true
+}
+
+/// Converts a `created` value stored as RFC 3339 date or in milliseconds to seconds since the epoch.
+fn normalize_timestamp(props: &mut HashMap<String, String>) -> bool {
+    let Some(created) = props.get(CREATED) else {
+        return false;
+    };
+    let seconds = match created.parse::<u64>() {
+        Ok(millis) if millis >= 100_000_000_000 => millis / 1000,
+        Ok(_) => return false,
+        Err(_) => match chrono::DateTime::parse_from_rfc3339(created).or_else(|_| chrono::DateTime::parse_from_str(created, "%Y-%m-%dT%H:%M:%S%.f%z")) {
+            Ok(datetime) => datetime.timestamp().max(0) as u64,
+            Err(_) => return false,
+        }
+    };
+    props.insert(CREATED.to_string(), seconds.to_string());
+    true
+}
+
+/// Squashes the tasks ref history older than `keep_days` into a single baseline commit,
+/// newer commits are rewritten on top of it. The old tip is kept under `tag` if given,
+/// otherwise the reflog of the tasks ref is dropped, so the old commits can be pruned.
+/// Returns the number of squashed and kept commits, nothing is written if `dry_run` is set.
+pub fn compact_history(keep_days: u64, tag: Option<&str>, dry_run: bool) -> Result<(usize, usize), String> {
+    let repo = open_repo()?;
+    let ref_path = get_ref_path();
+    let tip = map_err!(map_err!(repo.find_reference(&ref_path)).peel_to_commit());
+    let cutoff = get_current_timestamp().saturating_sub(keep_days * 24 * 60 * 60) as i64;
+
+    let mut kept = vec![];
+    let mut base = None;
+    let mut squashed = 0;
+    let mut commit = Some(tip.clone());
+    while let Some(current) = commit {
+        commit = current.parents().next();
+        match base {
+            Some(_) => squashed += 1,
+            None if current.time().seconds() <= cutoff => {
+                base = Some(current);
+                squashed += 1;
+            },
+            None => kept.push(current),
+        }
+    }
+
+    let base = match base {
+        Some(base) if squashed > 1 => base,
+        _ => return Ok((0, kept.len())),
+    };
+    if dry_run {
+        return Ok((squashed, kept.len()));
+    }
+    check_writable()?;
+    let tag_ref = tag.map(|tag| format!("refs/tags/{tag}"));
+    if let Some(tag_ref) = tag_ref.as_ref().filter(|tag_ref| repo.find_reference(tag_ref).is_ok()) {
+        return Err(format!("{tag_ref} already exists"));
+    }
+
+    let me = get_signature(&repo)?;
+    let baseline_date = chrono::DateTime::from_timestamp(base.time().seconds(), 0).map(|date| date.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+    let mut new_tip = map_err!(repo.commit(None, &me, &me, &format!("Compact tasks history up to {baseline_date}"), &map_err!(base.tree()), &[]));
+    for commit in kept.iter().rev() {
+        let parent = map_err!(repo.find_commit(new_tip));
+        new_tip = map_err!(repo.commit(None, &commit.author(), &commit.committer(), commit.message().unwrap_or_default(), &map_err!(commit.tree()), &[&parent]));
+    }
+
+    if let Err(e) = repo.reference_matching(&ref_path, new_tip, true, tip.id(), "Compact tasks history") {
+        return match e.code() {
+            ErrorCode::Modified | ErrorCode::Exists | ErrorCode::Locked => Err(format!("Could not update {ref_path}, {CONFLICT_ERROR}")),
+            _ => Err(e.message().to_owned()),
+        };
+    }
+    // the tag is created only once the history is replaced, the reflog still has the old tip if this fails
+    match tag_ref {
+        Some(tag_ref) => {
+            map_err!(repo.reference(&tag_ref, tip.id(), false, "Tasks history before compaction"));
+        },
+        None => map_err!(repo.reflog_delete(&ref_path)),
+    }
+
+    Ok((squashed, kept.len()))
+}
+
+/// Path of the `.git` directory or the bare repository.
+pub fn get_git_dir() -> Result<std::path::PathBuf, String> {
+    Ok(open_repo()?.path().to_path_buf())
+}
+
+const MAX_WRITE_ATTEMPTS: usize = 10;
+/// Part of the error message returned when the tasks ref couldn't be updated due to concurrent writes.
+pub const CONFLICT_ERROR: &str = "it's being modified concurrently";
+
+/// Commits a new tree on top of the tasks ref using compare-and-swap.
+/// `build_commit` returns a tree built on top of the given one and a commit message.
+/// If the ref has moved since it was read, the tree is rebuilt on top of the new tip and the commit is retried.
+fn commit_tasks<F>(repo: &Repository, mut build_commit: F) -> Result<(), String>
+where
+    F: FnMut(&Repository, Option<&Tree>) -> Result<(Oid, String), String>,
+{
+    let ref_path = get_ref_path();
+    let me = &get_signature(repo)?;
+
+    for _ in 0..MAX_WRITE_ATTEMPTS {
+        let parent_commit = match repo.find_reference(&ref_path) {
+            Ok(reference) => Some(map_err!(reference.peel_to_commit())),
+            Err(_) => None,
+        };
+        let source_tree = match &parent_commit {
+            Some(commit) => Some(map_err!(commit.tree())),
+            None => None,
+        };
+
+        let (tree_oid, message) = build_commit(repo, source_tree.as_ref())?;
+        let parents = parent_commit.iter().collect::<Vec<_>>();
+        let commit_oid = map_err!(repo.commit(None, me, me, &message, &map_err!(repo.find_tree(tree_oid)), &parents));
+
+        let result = match &parent_commit {
+            Some(parent_commit) => repo.reference_matching(&ref_path, commit_oid, true, parent_commit.id(), &message),
+            None => repo.reference(&ref_path, commit_oid, false, &message),
+        };
+
+        match result {
+            Ok(_) => return Ok(()),
+            Err(e) if matches!(e.code(), ErrorCode::Modified | ErrorCode::Exists | ErrorCode::Locked) => continue,
+            Err(e) => return Err(e.message().to_owned()),
+        }
+    }
+
+    Err(format!("Could not update {ref_path}, {CONFLICT_ERROR}"))
+}
+
+/// Identity for commits on the tasks ref, `task.commit.author` ("Name <email>") if configured.
+fn get_signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    match get_config_value("task.commit.author").ok().filter(|author| !author.is_empty()) {
+        Some(author) => {
+            let (name, email) = parse_identity(&author).ok_or_else(|| format!("Invalid task.commit.author: {author}, expected Name <email>"))?;
+            Ok(map_err!(Signature::now(name, email)))
+        },
+        None => Ok(map_err!(repo.signature())),
+    }
+}
+
+pub fn parse_identity(value: &str) -> Option<(&str, &str)> {
+    let (name, email) = value.trim().strip_suffix('>')?.split_once('<')?;
+    let (name, email) = (name.trim(), email.trim());
+    match name.is_empty() || email.is_empty() || email.contains(['<', '>']) {
+        true => None,
+        false => Some((name, email)),
+    }
+}
+
+/// Commit message from the `task.commit.message.<operation>` template if configured,
+/// `{placeholders}` are replaced with the given values.
+fn commit_message(operation: &str, default: String, values: &[(&str, &str)]) -> String {
+    let template = get_config_value(&format!("task.commit.message.{operation}")).ok();
+    match template {
+        Some(template) if !template.is_empty() => render_commit_message(&template, values),
+        _ => default,
+    }
+}
+
+fn render_commit_message(template: &str, values: &[(&str, &str)]) -> String {
+    values.iter().fold(template.to_string(), |message, (key, value)| message.replace(&format!("{{{key}}}"), value))
+}
+
+#[cfg(test)]
+fn get_next_id() -> Result<String, String> {
+    let repo = open_repo()?;
+    let task_ref = map_err!(repo.find_reference(&get_ref_path()));
+    let task_tree = map_err!(task_ref.peel_to_tree());
+
+    Ok(get_next_id_in_tree(&repo, Some(&task_tree)))
+}
+
+fn get_next_id_in_tree(repo: &Repository, tree: Option<&Tree>) -> String {
+    let mut result = 0;
+
+    if let Some(tree) = tree {
+        for entry in list_task_entries(repo, tree) {
+            if let Ok(id) = entry.name().unwrap_or_default().parse::<i64>() {
+                if id > result {
+                    result = id;
+                }
+            }
+        }
+    }
+
+    (result + 1).to_string()
+}
+
+pub fn update_task_id(id: &str, new_id: &str) -> Result<(), String> {
+    let mut task = find_task(&id)?.unwrap();
+    task.set_id(new_id.to_string());
+    create_task(task)?;
+    delete_tasks(&[&id])?;
+
+    Ok(())
+}
+
+pub fn update_comment_id(task_id: &str, id: &str, new_id: &str) -> Result<(), String> {
+    let mut task = find_task(&task_id)?.unwrap().clone();
+    let comments = task.get_comments();
+    match comments {
+        Some(comments) => {
+            let updated_comments = comments.iter().map(|c| {
+                if c.get_id().unwrap() == id {
+                    let mut c = c.clone();
+                    c.set_id(new_id.to_string());
+                    c
+                } else {
+                    c.clone()
+                }
+            }).collect::<Vec<_>>();
+            task.set_comments(updated_comments);
+            update_task(task)?;
+        },
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Git remote the tasks ref is published to and fetched from: `task.remote` if configured, `origin` otherwise.
+pub fn get_tasks_remote(remote: &Option<String>) -> String {
+    remote.clone().or_else(|| get_config_value("task.remote").ok()).unwrap_or_else(|| "origin".to_string())
+}
+
+/// Ref holding the tasks ref last fetched from the remote.
+pub fn get_remote_tracking_ref(remote: &str) -> String {
+    format!("refs/task-remotes/{remote}")
+}
+
+fn get_auto_fetch_refspec() -> String {
+    let ref_path = get_ref_path();
+    format!("{ref_path}:{ref_path}")
+}
+
+/// Returns true if a plain `git fetch` from the remote also fast-forwards the tasks ref.
+pub fn is_auto_fetch(remote: &str) -> Result<bool, String> {
+    let repo = open_repo()?;
+    let remote = map_err!(repo.find_remote(remote));
+    let refspec = get_auto_fetch_refspec();
+    Ok(map_err!(remote.fetch_refspecs()).iter().flatten().any(|spec| spec == refspec))
+}
+
+/// Adds or removes the fetch refspec of the tasks ref for the remote, returns true if anything was changed.
+pub fn set_auto_fetch(remote: &str, enabled: bool) -> Result<bool, String> {
+    let repo = open_repo()?;
+    if is_auto_fetch(remote)? == enabled {
+        return Ok(false);
+    }
+
+    let refspec = get_auto_fetch_refspec();
+    match enabled {
+        true => map_err!(repo.remote_add_fetch(remote, &refspec)),
+        false => {
+            let mut config = map_err!(repo.config());
+            map_err!(config.remove_multivar(&format!("remote.{remote}.fetch"), &format!("^{}$", regex::escape(&refspec))));
+        },
+    }
+    Ok(true)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FetchResult {
+    UpToDate,
+    Created,
+    FastForwarded,
+    Diverged,
+}
+
+/// Moves the tasks ref to the commit of `from_ref` unless local tasks have diverged from it, or `force` is set.
+pub fn fast_forward_tasks(from_ref: &str, force: bool) -> Result<FetchResult, String> {
+    check_writable()?;
+    let repo = open_repo()?;
+    let ref_path = get_ref_path();
+    let fetched = map_err!(map_err!(repo.find_reference(from_ref)).peel_to_commit()).id();
+
+    let local = match repo.find_reference(&ref_path) {
+        Ok(reference) => map_err!(reference.peel_to_commit()).id(),
+        Err(_) => {
+            map_err!(repo.reference(&ref_path, fetched, false, "Fetch tasks"));
+            return Ok(FetchResult::Created);
+        },
+    };
+
+    if local == fetched || map_err!(repo.graph_descendant_of(local, fetched)) {
+        return Ok(FetchResult::UpToDate);
+    }
+    if !force && !map_err!(repo.graph_descendant_of(fetched, local)) {
+        return Ok(FetchResult::Diverged);
+    }
+
+    let result = match repo.reference_matching(&ref_path, fetched, true, local, "Fetch tasks") {
+        Ok(_) => Ok(FetchResult::FastForwarded),
+        Err(e) if matches!(e.code(), ErrorCode::Modified | ErrorCode::Locked) => Err(format!("Could not update {ref_path}, {CONFLICT_ERROR}")),
+        Err(e) => Err(e.message().to_owned()),
+    };
+    result
+}
+
+pub fn list_remotes(remote: &Option<String>) -> Result<Vec<String>, String> {
+    let repo = open_repo()?;
+    let remotes = map_err!(repo.remotes());
+    Ok(remotes.iter()
+        .filter(|s| remote.is_none() || remote.as_ref().unwrap().as_str() == s.unwrap())
+        .map(|s| repo.find_remote(s.unwrap()).unwrap().url().unwrap().to_owned())
+        .collect())
+}
+
+fn get_current_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+pub fn get_current_user() -> Result<Option<String>, String> {
+    let repo = open_repo()?;
+    let me = &map_err!(repo.signature());
+    match me.name() {
+        Some(name) => Ok(Some(String::from(name))),
+        _ => match me.email() {
+            Some(email) => Ok(Some(String::from(email))),
+            _ => Ok(None),
+        }
+    }
+}
+
+pub fn is_readonly() -> bool {
+    readonly_flag(std::env::var("GIT_TASK_READONLY").ok(), get_config_value("task.readonly").ok())
+}
+
+fn readonly_flag(env_value: Option<String>, config_value: Option<String>) -> bool {
+    let is_true = |value: String| ["1", "true", "yes", "on"].contains(&value.trim().to_lowercase().as_str());
+    match env_value {
+        Some(value) => is_true(value),
+        None => config_value.is_some_and(is_true),
+    }
+}
+
+fn check_writable() -> Result<(), String> {
+    match is_readonly() {
+        true => Err("Read-only mode is enabled (task.readonly or GIT_TASK_READONLY), refusing to modify tasks".to_string()),
+        false => Ok(()),
+    }
+}
+
+/// Name of the executable hook validating tasks before they are saved.
+pub const UPDATE_HOOK: &str = "pre-task-update";
+
+/// Returns the directory git runs hooks from: `core.hooksPath` resolved against the working tree
+/// like git does, or `.git/hooks`.
+pub fn get_hooks_dir() -> Result<std::path::PathBuf, String> {
+    Ok(resolve_hooks_dir(&*open_repo()?, &["core.hooksPath"]))
+}
+
+/// The first directory configured by the keys, relative paths are resolved against the working tree.
+fn resolve_hooks_dir(repo: &Repository, keys: &[&str]) -> std::path::PathBuf {
+    let configured = repo.config().ok().and_then(|config| {
+        keys.iter().find_map(|key| config.get_path(key).ok().filter(|path| !path.as_os_str().is_empty()))
+    });
+    match configured {
+        Some(path) if path.is_relative() => repo.workdir().unwrap_or(repo.path()).join(path),
+        Some(path) => path,
+        None => repo.path().join("hooks"),
+    }
+}
+
+/// Looks for the `pre-task-update` hook in the `task.hooks.path` directory, `core.hooksPath` or `.git/hooks`.
+fn find_update_hook(repo: &Repository) -> Option<std::path::PathBuf> {
+    let hook_path = resolve_hooks_dir(repo, &["task.hooks.path", "core.hooksPath"]).join(UPDATE_HOOK);
+    let metadata = std::fs::metadata(&hook_path).ok().filter(|metadata| metadata.is_file())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return None;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    Some(hook_path)
+}
+
+/// Runs the `pre-task-update` hook with the task JSON on stdin, a non-zero exit code rejects the change.
+/// The operation (`create` or `update`) and the task ID are passed as `GIT_TASK_OPERATION` and `GIT_TASK_ID`.
+fn run_update_hook(repo: &Repository, task: &Task, operation: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let Some(hook_path) = find_update_hook(repo) else {
+        return Ok(());
+    };
+
+    let id = task.get_id().unwrap_or_default();
+    let json = serde_json::to_string(task).map_err(|e| e.to_string())?;
+    let mut child = std::process::Command::new(&hook_path)
+        .current_dir(repo.workdir().unwrap_or(repo.path()))
+        .env("GIT_TASK_OPERATION", operation)
+        .env("GIT_TASK_ID", &id)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run {}: {e}", hook_path.display()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the hook may exit without reading its input
+        let _ = stdin.write_all(json.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+    match output.status.success() {
+        true => Ok(()),
+        false => {
+            let reason = [&output.stderr, &output.stdout].iter()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+                .find(|text| !text.is_empty())
+                .unwrap_or_else(|| format!("exit status {}", output.status));
+            Err(format!("{UPDATE_HOOK} hook rejected task {id}: {reason}"))
+        },
+    }
+}
+
+/// Returns the path to the personal task store that is not tied to any project.
+/// It's taken from the `task.global.path` parameter of the global git config and defaults to `~/.git-task`.
+/// The bare repository is created on first use.
+pub fn get_global_repo_path() -> Result<String, String> {
+    let path = match Config::open_default().and_then(|config| config.get_path("task.global.path")) {
+        Ok(path) => path,
+        Err(_) => {
+            let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).map_err(|_| "Could not determine home directory".to_string())?;
+            std::path::Path::new(&home).join(".git-task")
+        }
+    };
+
+    if Repository::open_bare(&path).is_err() {
+        map_err!(Repository::init_bare(&path));
+    }
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Returns the current directory relative to the root of the working tree, if it's a subdirectory of it.
+pub fn get_current_path() -> Result<Option<String>, String> {
+    let repo = open_repo()?;
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir.canonicalize().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+    let current_dir = std::env::current_dir().and_then(|dir| dir.canonicalize()).map_err(|e| e.to_string())?;
+
+    match current_dir.strip_prefix(&workdir) {
+        Ok(path) if !path.as_os_str().is_empty() => {
+            Ok(Some(path.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>().join("/")))
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Resolves a path given on the command line against the current directory like git does,
+/// returns it relative to the root of the working tree.
+pub fn resolve_path(path: &str) -> Result<String, String> {
+    if std::path::Path::new(path).is_absolute() {
+        let repo = open_repo()?;
+        let workdir = repo.workdir().ok_or_else(|| "Repository has no working tree".to_string())?.canonicalize().map_err(|e| e.to_string())?;
+        let path = std::path::Path::new(path).canonicalize().map_err(|e| format!("{path}: {e}"))?;
+        return match path.strip_prefix(&workdir) {
+            Ok(path) => join_path("", &path.to_string_lossy()),
+            Err(_) => Err(format!("{} is outside of the working tree", path.display())),
+        };
+    }
+    join_path(&get_current_path()?.unwrap_or_default(), path)
+}
+
+/// Appends the relative path to the base one, `.` and `..` segments are resolved.
+fn join_path(base: &str, path: &str) -> Result<String, String> {
+    let mut result = vec![];
+    for segment in base.split('/').chain(path.split(['/', '\\'])) {
+        match segment {
+            "" | "." => {},
+            ".." => if result.pop().is_none() {
+                return Err(format!("{path} is outside of the working tree"));
+            },
+            segment => result.push(segment),
+        }
+    }
+    Ok(result.join("/"))
+}
+
+/// Returns task ID aliases (alias -> ID) stored in `task.aliases` config parameter.
+pub fn get_aliases() -> BTreeMap<String, String> {
+    get_config_value("task.aliases").ok().and_then(|aliases| serde_json::from_str(&aliases).ok()).unwrap_or_default()
+}
+
+pub fn set_aliases(aliases: &BTreeMap<String, String>) -> Result<(), String> {
+    set_config_value("task.aliases", &serde_json::to_string(aliases).unwrap())
+}
+
+/// Parses a task ID, task IDs are non-negative integers.
+pub fn parse_task_id(id: &str) -> Result<u64, String> {
+    id.parse::<u64>().map_err(|_| format!("'{id}' is not a valid task ID"))
+}
+
+/// Sorts items by their task IDs in ascending order, an item with a missing or invalid ID is an error.
+pub fn sort_by_task_id<T>(items: &mut [T], get_id: impl Fn(&T) -> Option<String>) -> Result<(), String> {
+    let parse = |item: &T| parse_task_id(&get_id(item).unwrap_or_default());
+    if let Some(e) = items.iter().find_map(|item| parse(item).err()) {
+        return Err(e);
+    }
+    items.sort_by_cached_key(|item| parse(item).unwrap_or_default());
+    Ok(())
+}
+
+/// Resolves a task alias to the task ID, numeric IDs and unknown aliases are returned as is.
+pub fn resolve_task_id(id: &str) -> String {
+    match parse_task_id(id) {
+        Ok(_) => id.to_string(),
+        Err(_) => get_aliases().get(id).cloned().unwrap_or_else(|| id.to_string()),
+    }
+}
+
+/// Returns status changes of every task found in the history of the tasks ref.
+/// Each task ID maps to (commit timestamp, status) pairs in chronological order.
+pub fn get_status_history() -> Result<HashMap<String, Vec<(u64, String)>>, String> {
+    get_property_history(STATUS)
+}
+
+/// Returns changes of the property of every task found in the history of the tasks ref.
+/// Each task ID maps to (commit timestamp, value) pairs in chronological order.
+pub fn get_property_history(prop: &str) -> Result<HashMap<String, Vec<(u64, String)>>, String> {
+    let repo = open_repo()?;
+    let mut revwalk = map_err!(repo.revwalk());
+    map_err!(revwalk.push_ref(&get_ref_path()));
+    map_err!(revwalk.simplify_first_parent());
+    map_err!(revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE));
+
+    let mut result = HashMap::<String, Vec<(u64, String)>>::new();
+
+    for oid in revwalk {
+        let commit = map_err!(repo.find_commit(map_err!(oid)));
+        let tree = map_err!(commit.tree());
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(map_err!(parent.tree())),
+            None => None,
+        };
+        let diff = map_err!(repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None));
+
+        for delta in diff.deltas().filter(|delta| delta.status() != Delta::Deleted) {
+            let file = delta.new_file();
+            let (Some(id), Ok(blob)) = (file.path().and_then(get_task_id_from_path), repo.find_blob(file.id())) else {
+                continue;
+            };
+            let Ok(task) = serde_json::from_slice::<Task>(blob.content()) else {
+                continue;
+            };
+
+            if let Some(value) = task.props.get(prop) {
+                let changes = result.entry(id).or_default();
+                if changes.last().is_none_or(|(_, last_value)| last_value != value) {
+                    changes.push((commit.time().seconds() as u64, value.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns versions of the comment found in the history of the tasks ref in chronological order,
+/// a new version is added whenever the comment text changes. If the first version is gone from the history,
+/// e.g. after `gc`, it's restored from the `original_text` snapshot.
+pub fn get_comment_history(task_id: &str, comment_id: &str) -> Result<Vec<Comment>, String> {
+    let task_id = &resolve_task_id(task_id);
+    let repo = open_repo()?;
+    let mut revwalk = map_err!(repo.revwalk());
+    map_err!(revwalk.push_ref(&get_ref_path()));
+    map_err!(revwalk.simplify_first_parent());
+    map_err!(revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE));
+
+    let mut result: Vec<Comment> = vec![];
+    let mut last_oid = None;
+
+    for oid in revwalk {
+        let commit = map_err!(repo.find_commit(map_err!(oid)));
+        let tree = map_err!(commit.tree());
+        let Ok(entry) = tree.get_path(std::path::Path::new(&get_task_path(task_id, is_sharded(&repo, &tree)))) else {
+            continue;
+        };
+        if last_oid == Some(entry.id()) {
+            continue;
+        }
+        last_oid = Some(entry.id());
+
+        let Ok(task) = read_task(&repo, &entry, true) else {
+            continue;
+        };
+        let comment = task.comments.unwrap_or_default().into_iter().find(|comment| comment.id.as_deref() == Some(comment_id));
+        if let Some(comment) = comment {
+            if result.last().is_none_or(|last| last.text != comment.text) {
+                result.push(comment);
+            }
+        }
+    }
+
+    if let Some(original_text) = result.first().and_then(|first| first.props.get(ORIGINAL_TEXT)).cloned() {
+        if result[0].text != original_text {
+            let mut original = result[0].clone();
+            original.text = original_text;
+            [EDITED, EDITED_BY, ORIGINAL_TEXT].iter().for_each(|prop| { original.props.remove(*prop); });
+            result.insert(0, original);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns files of the working tree tracked by git as pairs of relative and absolute paths.
+pub fn list_tracked_files() -> Result<Vec<(String, std::path::PathBuf)>, String> {
+    let repo = open_repo()?;
+    let workdir = repo.workdir().ok_or_else(|| "Repository has no working tree".to_string())?.to_path_buf();
+    let index = map_err!(repo.index());
+
+    Ok(index.iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+        .map(|path| {
+            let full_path = workdir.join(&path);
+            (path, full_path)
+        })
+        .collect())
+}
+
+/// Returns a stable fingerprint of the text (git blob hash).
+pub fn get_fingerprint(text: &str) -> String {
+    Oid::hash_object(ObjectType::Blob, text.as_bytes()).map(|oid| oid.to_string()).unwrap_or_default()
+}
+
+/// Returns the commit the tasks ref points to, `None` before the first task is saved.
+pub fn get_tasks_commit() -> Result<Option<String>, String> {
+    let repo = open_repo()?;
+    Ok(repo.refname_to_id(&get_ref_path()).ok().map(|oid| oid.to_string()))
+}
+
+/// Returns commit time of a git revision, e.g. a tag or a branch.
+pub fn get_revision_time(revision: &str) -> Result<u64, String> {
+    let repo = open_repo()?;
+    let commit = map_err!(map_err!(repo.revparse_single(revision)).peel_to_commit());
+    Ok(commit.time().seconds() as u64)
+}
+
+/// Reads a file from the working tree or, for bare repositories, from HEAD.
+pub fn read_repo_file(path: &str) -> Result<Option<String>, String> {
+    let repo = open_repo()?;
+    if let Some(workdir) = repo.workdir() {
+        return Ok(std::fs::read_to_string(workdir.join(path)).ok());
+    }
+
+    let tree = match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => tree,
+        Err(_) => return Ok(None),
+    };
+    match tree.get_path(std::path::Path::new(path)) {
+        Ok(entry) => {
+            let blob = map_err!(map_err!(entry.to_object(&repo)).peel_to_blob());
+            Ok(Some(String::from_utf8_lossy(blob.content()).to_string()))
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+pub fn get_ref_path() -> String {
+    get_config_value("task.ref").unwrap_or_else(|_| "refs/tasks/tasks".to_string())
+}
+
+/// Parameters that can be stored under the tasks ref to be shared along with the tasks.
+pub const SHARED_CONFIG_KEYS: [&str; 28] = [
+    "task.statuses",
+    "task.properties",
+    "task.status.open",
+    "task.status.closed",
+    "task.github.status.open",
+    "task.github.status.closed",
+    "task.gitlab.status.open",
+    "task.gitlab.status.closed",
+    "task.jira.status.map",
+    "task.jira.fields",
+    "task.types",
+    "task.list.columns",
+    "task.list.sort",
+    "task.list.done.style",
+    "task.show.layout",
+    "task.show.hidden",
+    "task.theme",
+    "task.theme.title",
+    "task.theme.separator",
+    "task.aliases",
+    "task.github.template",
+    "task.changelog.template",
+    "task.changelog.sections",
+    "task.commit.message.init",
+    "task.commit.message.create",
+    "task.commit.message.update",
+    "task.commit.message.delete",
+    "task.commit.message.clear",
+];
+
+/// Returns the config parameter, git config takes precedence over the configuration shared under the tasks ref.
+pub fn get_config_value(key: &str) -> Result<String, String> {
+    let repo = open_repo()?;
     let config = map_err!(repo.config());
-    Ok(map_err!(config.get_string(key)))
+    match config.get_string(key) {
+        Ok(value) => Ok(value),
+        Err(e) if SHARED_CONFIG_KEYS.contains(&key) => read_shared_config(&repo)?.remove(key).ok_or_else(|| e.message().to_owned()),
+        Err(e) => Err(e.message().to_owned()),
+    }
 }
 
+/// Sets the config parameter, shareable ones are written under the tasks ref if `task.config.shared` is on.
 pub fn set_config_value(key: &str, value: &str) -> Result<(), String> {
-    let repo = map_err!(Repository::discover("."));
+    let repo = open_repo()?;
     let mut config = map_err!(repo.config());
+    if SHARED_CONFIG_KEYS.contains(&key) && is_config_shared(&config) {
+        write_shared_config(&repo, &[(key.to_string(), value.to_string())])?;
+        return remove_local_config_value(&repo, key);
+    }
     map_err!(config.set_str(key, value));
     Ok(())
 }
 
+/// Returns all `task.*` parameters, shared ones included, with git config values taking precedence.
+pub fn list_config_values() -> Result<BTreeMap<String, String>, String> {
+    let repo = open_repo()?;
+    let mut values = read_shared_config(&repo)?;
+    let config = map_err!(repo.config());
+    let mut entries = map_err!(config.entries(Some("^task\\.")));
+    while let Some(entry) = entries.next() {
+        let entry = map_err!(entry);
+        if let (Some(name), Some(value)) = (entry.name(), entry.value()) {
+            values.insert(name.to_string(), value.to_string());
+        }
+    }
+    Ok(values)
+}
+
+/// Returns where the parameter is set: git config level (`local`, `global`, etc.) or `shared` for the tasks ref.
+pub fn get_config_source(key: &str) -> Result<Option<String>, String> {
+    let repo = open_repo()?;
+    let config = map_err!(repo.config());
+    if let Ok(entry) = config.get_entry(key) {
+        let level = match entry.level() {
+            ConfigLevel::ProgramData => "programdata",
+            ConfigLevel::System => "system",
+            ConfigLevel::XDG => "xdg",
+            ConfigLevel::Global => "global",
+            ConfigLevel::Local => "local",
+            ConfigLevel::Worktree => "worktree",
+            ConfigLevel::App | ConfigLevel::Highest => "app",
+        };
+        return Ok(Some(level.to_string()));
+    }
+
+    match read_shared_config(&repo)?.contains_key(key) {
+        true => Ok(Some("shared".to_string())),
+        false => Ok(None),
+    }
+}
+
+fn is_config_shared(config: &Config) -> bool {
+    config.get_bool("task.config.shared").unwrap_or(false)
+}
+
+/// Turns sharing on or off. Sharing moves shareable parameters of the local git config under the tasks ref
+/// and returns their names, parameters already shared are kept as they are when sharing is turned off.
+pub fn set_config_shared(shared: bool) -> Result<Vec<String>, String> {
+    let repo = open_repo()?;
+    let mut moved = vec![];
+    if shared {
+        let local_config = map_err!(map_err!(repo.config()).open_level(ConfigLevel::Local));
+        let values = SHARED_CONFIG_KEYS.iter()
+            .filter_map(|key| local_config.get_string(key).ok().map(|value| (key.to_string(), value)))
+            .collect::<Vec<_>>();
+        if !values.is_empty() {
+            write_shared_config(&repo, &values)?;
+        }
+        for (key, _) in values {
+            remove_local_config_value(&repo, &key)?;
+            moved.push(key);
+        }
+    }
+    map_err!(map_err!(repo.config()).set_bool("task.config.shared", shared));
+    Ok(moved)
+}
+
+fn remove_local_config_value(repo: &Repository, key: &str) -> Result<(), String> {
+    let mut local_config = map_err!(map_err!(repo.config()).open_level(ConfigLevel::Local));
+    match local_config.remove(key) {
+        Ok(_) => Ok(()),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(()),
+        Err(e) => Err(e.message().to_owned()),
+    }
+}
+
+fn read_shared_config(repo: &Repository) -> Result<BTreeMap<String, String>, String> {
+    let tree = match repo.find_reference(&get_ref_path()) {
+        Ok(task_ref) => map_err!(task_ref.peel_to_tree()),
+        Err(_) => return Ok(BTreeMap::new()),
+    };
+    let config = match tree.get_name(CONFIG_ENTRY) {
+        Some(entry) => {
+            let blob = map_err!(repo.find_blob(entry.id()));
+            serde_json::from_slice(blob.content()).map_err(|e| format!("Can't parse {CONFIG_ENTRY}: {e}"))
+        },
+        None => Ok(BTreeMap::new()),
+    };
+    config
+}
+
+fn write_shared_config(repo: &Repository, values: &[(String, String)]) -> Result<(), String> {
+    check_writable()?;
+    if repo.find_reference(&get_ref_path()).is_err() {
+        return Err("No tasks ref found, run git task init first".to_string());
+    }
+
+    let keys = values.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>().join(", ");
+    commit_tasks(repo, |repo, tree| {
+        let tree = tree.ok_or_else(|| "No tasks found".to_string())?;
+        let mut config = match tree.get_name(CONFIG_ENTRY) {
+            Some(entry) => serde_json::from_slice(map_err!(repo.find_blob(entry.id())).content()).map_err(|e| format!("Can't parse {CONFIG_ENTRY}: {e}"))?,
+            None => BTreeMap::new(),
+        };
+        config.extend(values.iter().cloned());
+        let oid = map_err!(repo.blob(serde_json::to_string_pretty(&config).unwrap().as_bytes()));
+        let mut treebuilder = map_err!(repo.treebuilder(Some(tree)));
+        map_err!(treebuilder.insert(CONFIG_ENTRY, oid, FileMode::Blob.into()));
+        Ok((map_err!(treebuilder.write()), format!("Update config {keys}")))
+    })
+}
+
 pub fn set_ref_path(ref_path: &str, move_ref: bool) -> Result<(), String> {
-    let repo = map_err!(Repository::discover("."));
+    if move_ref {
+        check_writable()?;
+    }
+    let repo = open_repo()?;
 
     let current_reference = repo.find_reference(&get_ref_path());
     if let Ok(current_reference) = &current_reference {
@@ -539,138 +2495,867 @@ mod test {
     use std::collections::HashMap;
     use crate::*;
 
+    fn with_temp_repo<T>(f: impl FnOnce() -> T) -> T {
+        TaskRepository::temporary().unwrap().run(f)
+    }
+
+    #[test]
+    fn test_task_repository() {
+        let first = TaskRepository::temporary().unwrap();
+        let second = TaskRepository::temporary().unwrap();
+
+        let id = first.create_task(Task::builder("First").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+        assert!(first.find_task(&id).unwrap().is_some());
+        assert!(second.find_task(&id).unwrap().is_none());
+        assert!(second.run(|| find_task(&id)).unwrap().is_none());
+
+        first.set_config_value("task.test", "first").unwrap();
+        assert_eq!(first.get_config_value("task.test").unwrap(), "first");
+        assert!(second.get_config_value("task.test").is_err());
+
+        let workdir = first.path().parent().unwrap().join("nested");
+        std::fs::create_dir(&workdir).unwrap();
+        let opened = TaskRepository::open(&workdir).unwrap();
+        assert_eq!(opened.list_tasks().unwrap().len(), 1);
+        assert!(opened.delete_tasks(&[&id]).is_ok());
+        assert!(first.list_task_summaries().unwrap().is_empty());
+        assert!(TaskRepository::open(std::env::temp_dir().join("git-task-missing-repository")).is_err());
+    }
+
+    #[test]
+    fn test_task_repository_opened_once() {
+        let first = TaskRepository::temporary().unwrap();
+        let second = TaskRepository::temporary().unwrap();
+
+        first.run(|| {
+            let repo = open_repo().unwrap();
+            let id = create_task(Task::builder("First").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            assert!(find_task(&id).unwrap().is_some());
+            assert!(Rc::ptr_eq(&repo, &open_repo().unwrap()));
+
+            second.run(|| {
+                assert!(!Rc::ptr_eq(&repo, &open_repo().unwrap()));
+                assert!(find_task(&id).unwrap().is_none());
+            });
+            first.run(|| assert!(Rc::ptr_eq(&repo, &open_repo().unwrap())));
+            assert!(Rc::ptr_eq(&repo, &open_repo().unwrap()));
+        });
+    }
+
     #[test]
     fn test_ref_path() {
-        let ref_path = get_ref_path();
-        assert!(set_ref_path("refs/heads/test-git-task", true).is_ok());
-        assert_eq!(get_ref_path(), "refs/heads/test-git-task");
-        assert!(set_ref_path(&ref_path, true).is_ok());
-        assert_eq!(get_ref_path(), ref_path);
+        with_temp_repo(|| {
+            let ref_path = get_ref_path();
+            assert!(set_ref_path("refs/heads/test-git-task", true).is_ok());
+            assert_eq!(get_ref_path(), "refs/heads/test-git-task");
+            assert!(set_ref_path(&ref_path, true).is_ok());
+            assert_eq!(get_ref_path(), ref_path);
+        });
     }
 
     #[test]
     fn test_create_update_delete_task() {
-        let id = get_next_id().unwrap_or_else(|_| "1".to_string());
-        let task = Task::construct_task("Test task".to_string(), "Description goes here".to_string(), "OPEN".to_string(), Some(get_current_timestamp()));
-        let create_result = create_task(task);
-        assert!(create_result.is_ok());
-        let mut task = create_result.unwrap();
-        assert_eq!(task.get_id(), Some(id.clone()));
-        assert_eq!(task.get_property("name").unwrap(), "Test task");
-        assert_eq!(task.get_property("description").unwrap(), "Description goes here");
-        assert_eq!(task.get_property("status").unwrap(), "OPEN");
-        assert!(task.has_property("created"));
-
-        task.set_property("description", "Updated description");
-        let comment_props = HashMap::from([("author".to_string(), "Some developer".to_string())]);
-        task.add_comment(None, comment_props, "This is a comment".to_string());
-        task.set_property("custom_prop", "Custom content");
-        let update_result = update_task(task);
-        assert!(update_result.is_ok());
-        assert_eq!(update_result.unwrap(), id.clone());
-
-        let find_result = find_task(&id);
-        assert!(find_result.is_ok());
-        let task = find_result.unwrap();
-        assert!(task.is_some());
-        let task = task.unwrap();
-        assert_eq!(task.get_id(), Some(id.clone()));
-        assert_eq!(task.get_property("description").unwrap(), "Updated description");
-        let comments = task.get_comments().clone();
-        assert!(comments.is_some());
-        let comments = comments.unwrap();
-        assert_eq!(comments.len(), 1);
-        let comment = comments.first().unwrap();
-        assert_eq!(comment.get_text(), "This is a comment".to_string());
-        let comment_props = comment.clone().props;
-        assert_eq!(comment_props.get("author").unwrap(), &"Some developer".to_string());
-        assert_eq!(task.get_property("custom_prop").unwrap(), "Custom content");
-
-        let delete_result = delete_tasks(&[&id]);
-        assert!(delete_result.is_ok());
-
-        let find_result = find_task(&id);
-        assert!(find_result.is_ok());
-        let task = find_result.unwrap();
-        assert!(task.is_none());
+        with_temp_repo(|| {
+            let id = get_next_id().unwrap_or_else(|_| "1".to_string());
+            let task = Task::construct_task("Test task".to_string(), "Description goes here".to_string(), "OPEN".to_string(), Some(get_current_timestamp()));
+            let create_result = create_task(task);
+            assert!(create_result.is_ok());
+            let mut task = create_result.unwrap();
+            assert_eq!(task.get_id(), Some(id.clone()));
+            assert_eq!(task.get_property("name").unwrap(), "Test task");
+            assert_eq!(task.get_property("description").unwrap(), "Description goes here");
+            assert_eq!(task.get_property("status").unwrap(), "OPEN");
+            assert!(task.has_property("created"));
+
+            task.set_property("description", "Updated description");
+            let comment_props = HashMap::from([("author".to_string(), "Some developer".to_string())]);
+            task.add_comment(None, comment_props, "This is a comment".to_string());
+            task.set_property("custom_prop", "Custom content");
+            let update_result = update_task(task);
+            assert!(update_result.is_ok());
+            assert_eq!(update_result.unwrap(), id.clone());
+
+            let find_result = find_task(&id);
+            assert!(find_result.is_ok());
+            let task = find_result.unwrap();
+            assert!(task.is_some());
+            let task = task.unwrap();
+            assert_eq!(task.get_id(), Some(id.clone()));
+            assert_eq!(task.get_property("description").unwrap(), "Updated description");
+            let comments = task.get_comments().clone();
+            assert!(comments.is_some());
+            let comments = comments.unwrap();
+            assert_eq!(comments.len(), 1);
+            let comment = comments.first().unwrap();
+            assert_eq!(comment.get_text(), "This is a comment".to_string());
+            let comment_props = comment.clone().props;
+            assert_eq!(comment_props.get("author").unwrap(), &"Some developer".to_string());
+            assert_eq!(task.get_property("custom_prop").unwrap(), "Custom content");
+
+            let delete_result = delete_tasks(&[&id]);
+            assert!(delete_result.is_ok());
+
+            let find_result = find_task(&id);
+            assert!(find_result.is_ok());
+            let task = find_result.unwrap();
+            assert!(task.is_none());
+        });
     }
 
     #[test]
     fn test_update_comment_id() {
-        // Create a task first
-        let id = get_next_id().unwrap_or_else(|_| "1".to_string());
-        let task = Task::construct_task(
-            "Test task".to_string(),
-            "Description goes here".to_string(),
-            "OPEN".to_string(),
-            Some(get_current_timestamp())
-        );
-        let create_result = create_task(task);
-        assert!(create_result.is_ok());
-        let mut task = create_result.unwrap();
-
-        // Add a comment to the task
-        let comment_props = HashMap::from([("author".to_string(), "Some developer".to_string())]);
-        let comment = task.add_comment(Some("1".to_string()), comment_props, "Test comment".to_string());
-        assert_eq!(comment.get_id().unwrap(), "1");
-        let update_result = update_task(task);
-        assert!(update_result.is_ok());
-
-        // Update the comment ID
-        let result = update_comment_id(&id, "1", "2");
-        assert!(result.is_ok());
-
-        // Verify the comment ID was updated
-        let updated_task = find_task(&id).unwrap().unwrap();
-        let updated_comments = updated_task.get_comments().as_ref().unwrap();
-        assert_eq!(updated_comments.len(), 1);
-        assert_eq!(updated_comments[0].get_id().unwrap(), "2");
-
-        // Clean up
-        let delete_result = delete_tasks(&[&id]);
-        assert!(delete_result.is_ok());
+        with_temp_repo(|| {
+            // Create a task first
+            let id = get_next_id().unwrap_or_else(|_| "1".to_string());
+            let task = Task::construct_task(
+                "Test task".to_string(),
+                "Description goes here".to_string(),
+                "OPEN".to_string(),
+                Some(get_current_timestamp())
+            );
+            let create_result = create_task(task);
+            assert!(create_result.is_ok());
+            let mut task = create_result.unwrap();
+
+            // Add a comment to the task
+            let comment_props = HashMap::from([("author".to_string(), "Some developer".to_string())]);
+            let comment = task.add_comment(Some("1".to_string()), comment_props, "Test comment".to_string());
+            assert_eq!(comment.get_id().unwrap(), "1");
+            let update_result = update_task(task);
+            assert!(update_result.is_ok());
+
+            // Update the comment ID
+            let result = update_comment_id(&id, "1", "2");
+            assert!(result.is_ok());
+
+            // Verify the comment ID was updated
+            let updated_task = find_task(&id).unwrap().unwrap();
+            let updated_comments = updated_task.get_comments().as_ref().unwrap();
+            assert_eq!(updated_comments.len(), 1);
+            assert_eq!(updated_comments[0].get_id().unwrap(), "2");
+
+            // Clean up
+            let delete_result = delete_tasks(&[&id]);
+            assert!(delete_result.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_comment_history() {
+        with_temp_repo(|| {
+            let mut task = create_task(Task::construct_task("Test task".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()))).unwrap();
+            let id = task.get_id().unwrap();
+            task.add_comment(Some("1".to_string()), HashMap::new(), "First version".to_string());
+            update_task(task).unwrap();
+
+            for text in ["Second version", "Third version"] {
+                let mut task = find_task(&id).unwrap().unwrap();
+                let mut comments = task.get_comments().clone().unwrap();
+                comments[0].edit_text(text.to_string());
+                task.set_comments(comments);
+                update_task(task).unwrap();
+            }
+
+            let comment = find_task(&id).unwrap().unwrap().get_comments().clone().unwrap().remove(0);
+            assert!(comment.is_edited());
+            assert_eq!(comment.get_all_properties().get(ORIGINAL_TEXT).unwrap(), "First version");
+
+            let history = get_comment_history(&id, "1").unwrap();
+            assert_eq!(history.iter().map(|comment| comment.get_text()).collect::<Vec<_>>(), vec!["First version", "Second version", "Third version"]);
+            assert!(!history[0].is_edited());
+        });
+    }
+
+    #[test]
+    fn test_rename_label() {
+        let mut task = Task::construct_task("Test task".to_string(), String::new(), "OPEN".to_string(), None);
+        assert!(!task.rename_label("bug", "defect"));
+
+        task.add_label("bug".to_string(), Some("Something is broken".to_string()), Some("d73a4a".to_string()));
+        task.add_label("ui".to_string(), None, None);
+        assert!(task.rename_label("bug", "defect"));
+        let label = &task.get_labels().as_ref().unwrap()[0];
+        assert_eq!((label.get_name(), label.get_color(), label.get_description()), ("defect".to_string(), "d73a4a".to_string(), Some("Something is broken".to_string())));
+
+        assert!(task.rename_label("defect", "ui"));
+        assert_eq!(task.get_labels().as_ref().unwrap().iter().map(|label| label.get_name()).collect::<Vec<_>>(), vec!["ui".to_string()]);
+    }
+
+    #[test]
+    fn test_mentions() {
+        assert_eq!(extract_mentions("@alice please check, cc @bob-2 and @alice"), vec!["alice".to_string(), "bob-2".to_string()]);
+        assert!(extract_mentions("mail me at someone@example.com").is_empty());
+
+        let mut task = Task::construct_task("Test task".to_string(), "Ping @alice".to_string(), "OPEN".to_string(), Some(get_current_timestamp()));
+        task.add_comment(None, HashMap::new(), "And @carol too".to_string());
+        task.refresh_mentions();
+        assert_eq!(task.get_mentions(), vec!["alice".to_string(), "carol".to_string()]);
+        assert_eq!(task.get_comments().as_ref().unwrap()[0].get_mentions(), vec!["carol".to_string()]);
+
+        task.set_property("description", "No more mentions");
+        task.set_comments(vec![]);
+        task.refresh_mentions();
+        assert!(!task.has_property("mentions"));
+    }
+
+    #[test]
+    fn test_watchers() {
+        let mut task = Task::new("Watched".to_string(), String::new(), "OPEN".to_string()).unwrap();
+        assert!(task.add_watcher("alice"));
+        assert!(!task.add_watcher("alice"));
+        assert!(task.add_watcher("bob"));
+        assert_eq!(task.get_watchers(), vec!["alice".to_string(), "bob".to_string()]);
+        assert!(task.remove_watcher("alice"));
+        assert!(!task.remove_watcher("alice"));
+        assert!(task.remove_watcher("bob"));
+        assert!(!task.has_property("watchers"));
+    }
+
+    #[test]
+    fn test_readonly() {
+        assert!(!readonly_flag(None, None));
+        assert!(readonly_flag(None, Some("true".to_string())));
+        assert!(readonly_flag(Some("1".to_string()), None));
+        assert!(!readonly_flag(Some("0".to_string()), Some("true".to_string())));
+        assert!(!readonly_flag(None, Some("false".to_string())));
+    }
+
+    #[test]
+    fn test_update_tasks() {
+        with_temp_repo(|| {
+            let first = create_task(Task::construct_task("First".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()))).unwrap();
+            let second = create_task(Task::construct_task("Second".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()))).unwrap();
+            let (first_id, second_id) = (first.get_id().unwrap(), second.get_id().unwrap());
+            assert!(!first.has_property(UPDATED));
+
+            let tasks = [first, second].into_iter().map(|mut task| {
+                task.add_comment(None, HashMap::new(), "Batch comment".to_string());
+                task
+            }).collect();
+            assert_eq!(update_tasks(tasks).unwrap(), vec![first_id.clone(), second_id.clone()]);
+
+            for id in [&first_id, &second_id] {
+                let task = find_task(id).unwrap().unwrap();
+                assert_eq!(task.get_comments().as_ref().unwrap()[0].get_text(), "Batch comment");
+                assert!(task.get_property(UPDATED).is_some_and(|updated| updated.parse::<u64>().unwrap() >= task.get_property(CREATED).unwrap().parse::<u64>().unwrap()));
+            }
+
+            assert!(delete_tasks(&[&first_id, &second_id]).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_last_pull() {
+        with_temp_repo(|| {
+            let task = create_task(Task::construct_task("Pulled".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()))).unwrap();
+            assert_eq!(get_last_pull("test-user/test-repo").unwrap(), None);
+            assert!(set_last_pull("test-user/test-repo", 1700000000).is_ok());
+            assert_eq!(get_last_pull("test-user/test-repo").unwrap(), Some(1700000000));
+            assert!(find_task(&task.get_id().unwrap()).unwrap().is_some());
+
+            let repo = open_repo().unwrap();
+            commit_tasks(&repo, |repo, tree| {
+                let mut treebuilder = map_err!(repo.treebuilder(tree));
+                map_err!(treebuilder.remove(PULLS_ENTRY));
+                Ok((map_err!(treebuilder.write()), "Drop test pulls".to_string()))
+            }).unwrap();
+            assert!(delete_tasks(&[&task.get_id().unwrap()]).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_commit_message() {
+        assert_eq!(render_commit_message("task: create {id} {name}", &[("id", "5"), ("name", "Crash")]), "task: create 5 Crash");
+        assert_eq!(parse_identity("Task Bot <bot@example.com>"), Some(("Task Bot", "bot@example.com")));
+        assert_eq!(parse_identity("bot@example.com"), None);
+        assert_eq!(parse_identity("<bot@example.com>"), None);
+
+        with_temp_repo(|| {
+            set_config_value("task.commit.message.create", "task: create {id} {name}").unwrap();
+            set_config_value("task.commit.author", "Task Bot <bot@example.com>").unwrap();
+            let id = create_task(Task::builder("Crash").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+
+            let repo = open_repo().unwrap();
+            let commit = repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap();
+            assert_eq!(commit.message(), Some(format!("task: create {id} Crash").as_str()));
+            assert_eq!(commit.author().name(), Some("Task Bot"));
+            assert_eq!(commit.committer().email(), Some("bot@example.com"));
+
+            set_config_shared(true).unwrap();
+            let local_config = repo.config().unwrap().open_level(ConfigLevel::Local).unwrap();
+            assert!(local_config.get_string("task.commit.message.create").is_err());
+            let id = create_task(Task::builder("Shared").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            let commit = repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap();
+            assert_eq!(commit.message(), Some(format!("task: create {id} Shared").as_str()));
+
+            set_config_value("task.commit.author", "nobody").unwrap();
+            assert!(create_task(Task::builder("Rejected").status("OPEN").build().unwrap()).is_err());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_hook() {
+        use std::os::unix::fs::PermissionsExt;
+
+        with_temp_repo(|| {
+            let id = create_task(Task::builder("Before hook").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+
+            let hook_path = get_git_dir().unwrap().join("hooks").join(UPDATE_HOOK);
+            std::fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+            std::fs::write(&hook_path, "#!/bin/sh\ngrep -q '\"name\":\"PROJ-' || { echo \"$GIT_TASK_OPERATION: name must start with PROJ-\" >&2; exit 1; }\n").unwrap();
+            std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+            let error = create_task(Task::builder("Crash").status("OPEN").build().unwrap()).err().unwrap();
+            assert_eq!(error, "pre-task-update hook rejected task 2: create: name must start with PROJ-");
+            assert!(create_task(Task::builder("PROJ-1 Crash").status("OPEN").build().unwrap()).is_ok());
+
+            let mut task = find_task(&id).unwrap().unwrap();
+            task.set_property("status", "CLOSED");
+            assert!(update_task(task.clone()).is_err());
+            assert!(update_tasks(vec![task.clone()]).is_err());
+            assert_eq!(find_task(&id).unwrap().unwrap().get_property("status").unwrap(), "OPEN");
+
+            std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            assert!(update_task(task).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_get_hooks_dir() {
+        with_temp_repo(|| {
+            let repo = open_repo().unwrap();
+            assert_eq!(get_hooks_dir().unwrap(), repo.path().join("hooks"));
+
+            set_config_value("core.hooksPath", ".githooks").unwrap();
+            assert_eq!(get_hooks_dir().unwrap(), repo.workdir().unwrap().join(".githooks"));
+
+            let absolute = std::env::temp_dir().join("shared-hooks");
+            set_config_value("core.hooksPath", absolute.to_str().unwrap()).unwrap();
+            assert_eq!(get_hooks_dir().unwrap(), absolute);
+        });
+    }
+
+    #[test]
+    fn test_compact_history() {
+        with_temp_repo(|| {
+            let first = create_task(Task::builder("First").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            let second = create_task(Task::builder("Second").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            let mut task = find_task(&first).unwrap().unwrap();
+            task.set_property("status", "CLOSED");
+            update_task(task).unwrap();
+
+            assert_eq!(compact_history(1, None, false).unwrap(), (0, 3));
+            set_config_value("task.readonly", "true").unwrap();
+            assert_eq!(compact_history(0, Some("tasks-before-gc"), true).unwrap(), (3, 0));
+            assert!(compact_history(0, Some("tasks-before-gc"), false).is_err());
+            set_config_value("task.readonly", "false").unwrap();
+            assert!(open_repo().unwrap().find_reference("refs/tags/tasks-before-gc").is_err());
+            assert_eq!(compact_history(0, Some("tasks-before-gc"), false).unwrap(), (3, 0));
+
+            let repo = open_repo().unwrap();
+            let tip = repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap();
+            assert_eq!(tip.parent_count(), 0);
+            assert!(tip.message().unwrap().starts_with("Compact tasks history"));
+            assert_eq!(repo.find_reference("refs/tags/tasks-before-gc").unwrap().peel_to_commit().unwrap().parent_count(), 1);
+            assert_eq!(find_task(&first).unwrap().unwrap().get_property("status").unwrap(), "CLOSED");
+            assert!(find_task(&second).unwrap().is_some());
+
+            assert_eq!(compact_history(0, None, false).unwrap(), (0, 0));
+            update_task(find_task(&second).unwrap().unwrap()).unwrap();
+            update_task(find_task(&second).unwrap().unwrap()).unwrap();
+            assert_eq!(compact_history(0, Some("tasks-before-gc"), false).unwrap_err(), "refs/tags/tasks-before-gc already exists");
+            assert_eq!(repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap().parent_count(), 1);
+        });
+    }
+
+    #[test]
+    fn test_init_tasks_ref() {
+        with_temp_repo(|| {
+            assert!(init_tasks_ref().unwrap());
+            assert!(!init_tasks_ref().unwrap());
+            assert!(list_tasks().unwrap().is_empty());
+            assert_eq!(get_format_version().unwrap(), Some(FORMAT_VERSION));
+            let task = create_task(Task::builder("First").status("OPEN").build().unwrap()).unwrap();
+            assert_eq!(task.get_id(), Some("1".to_string()));
+        });
+    }
+
+    #[test]
+    fn test_shared_config() {
+        with_temp_repo(|| {
+            init_tasks_ref().unwrap();
+            set_config_value("task.types", "bug,task").unwrap();
+            set_config_value("task.remote", "upstream").unwrap();
+            assert_eq!(set_config_shared(true).unwrap(), vec!["task.types".to_string()]);
+
+            let repo = open_repo().unwrap();
+            let local_config = repo.config().unwrap().open_level(ConfigLevel::Local).unwrap();
+            assert!(local_config.get_string("task.types").is_err());
+            assert_eq!(get_config_value("task.types").unwrap(), "bug,task");
+            assert_eq!(get_config_value("task.remote").unwrap(), "upstream");
+
+            set_config_value("task.list.sort", "name").unwrap();
+            assert!(local_config.get_string("task.list.sort").is_err());
+            assert_eq!(read_shared_config(&repo).unwrap()["task.list.sort"], "name");
+
+            repo.config().unwrap().set_str("task.list.sort", "id").unwrap();
+            assert_eq!(get_config_value("task.list.sort").unwrap(), "id");
+
+            let values = list_config_values().unwrap();
+            assert_eq!(values["task.types"], "bug,task");
+            assert_eq!(values["task.list.sort"], "id");
+            assert_eq!(values["task.remote"], "upstream");
+
+            assert_eq!(get_config_source("task.types").unwrap(), Some("shared".to_string()));
+            assert_eq!(get_config_source("task.list.sort").unwrap(), Some("local".to_string()));
+            assert_eq!(get_config_source("task.jira.url").unwrap(), None);
+
+            set_config_shared(false).unwrap();
+            set_config_value("task.types", "bug").unwrap();
+            assert_eq!(read_shared_config(&repo).unwrap()["task.types"], "bug,task");
+            assert_eq!(get_config_value("task.types").unwrap(), "bug");
+        });
+    }
+
+    #[test]
+    fn test_fetch_tasks() {
+        with_temp_repo(|| {
+            let repo = open_repo().unwrap();
+            repo.remote("upstream", "https://example.com/project.git").unwrap();
+            assert!(!is_auto_fetch("upstream").unwrap());
+            assert!(set_auto_fetch("upstream", true).unwrap());
+            assert!(!set_auto_fetch("upstream", true).unwrap());
+            assert!(is_auto_fetch("upstream").unwrap());
+            assert!(set_auto_fetch("upstream", false).unwrap());
+            assert!(!is_auto_fetch("upstream").unwrap());
+            assert_eq!(get_tasks_remote(&None), "origin");
+            set_config_value("task.remote", "upstream").unwrap();
+            assert_eq!(get_tasks_remote(&None), "upstream");
+
+            let tracking_ref = get_remote_tracking_ref("upstream");
+            create_task(Task::builder("First").status("OPEN").build().unwrap()).unwrap();
+            let first = repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap().id();
+            create_task(Task::builder("Second").status("OPEN").build().unwrap()).unwrap();
+            let second = repo.find_reference(&get_ref_path()).unwrap().peel_to_commit().unwrap().id();
+
+            repo.reference(&tracking_ref, first, true, "test").unwrap();
+            assert_eq!(fast_forward_tasks(&tracking_ref, false).unwrap(), FetchResult::UpToDate);
+
+            repo.reference(&get_ref_path(), first, true, "test").unwrap();
+            repo.reference(&tracking_ref, second, true, "test").unwrap();
+            assert_eq!(fast_forward_tasks(&tracking_ref, false).unwrap(), FetchResult::FastForwarded);
+            assert_eq!(list_tasks().unwrap().len(), 2);
+
+            repo.reference(&get_ref_path(), first, true, "test").unwrap();
+            create_task(Task::builder("Local").status("OPEN").build().unwrap()).unwrap();
+            assert_eq!(fast_forward_tasks(&tracking_ref, false).unwrap(), FetchResult::Diverged);
+            assert_eq!(fast_forward_tasks(&tracking_ref, true).unwrap(), FetchResult::FastForwarded);
+            assert!(list_tasks().unwrap().iter().all(|task| task.name() != "Local"));
+        });
+    }
+
+    #[test]
+    fn test_normalize_timestamp() {
+        let mut props = HashMap::from([(CREATED.to_string(), "2024-01-02T03:04:05.000+0000".to_string())]);
+        assert!(normalize_timestamp(&mut props));
+        assert_eq!(props[CREATED], "1704164645");
+
+        props.insert(CREATED.to_string(), "1704164645123".to_string());
+        assert!(normalize_timestamp(&mut props));
+        assert_eq!(props[CREATED], "1704164645");
+
+        assert!(!normalize_timestamp(&mut props));
+    }
+
+    #[test]
+    fn test_task_path() {
+        assert_eq!(get_task_path("12345", true), "12/12345");
+        assert_eq!(get_task_path("7", true), "7/7");
+        assert_eq!(get_task_path("12345", false), "12345");
+    }
+
+    #[test]
+    fn test_join_path() {
+        assert_eq!(join_path("src", ".").unwrap(), "src");
+        assert_eq!(join_path("src/frontend", "../backend/").unwrap(), "src/backend");
+        assert_eq!(join_path("", "./src//frontend").unwrap(), "src/frontend");
+        assert_eq!(join_path("src", "..").unwrap(), "");
+        assert_eq!(join_path("", "src\\backend").unwrap(), "src/backend");
+        assert!(join_path("src", "../..").is_err());
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        with_temp_repo(|| {
+            let workdir = open_repo().unwrap().workdir().unwrap().to_path_buf();
+            std::fs::create_dir_all(workdir.join("src/frontend")).unwrap();
+            assert_eq!(resolve_path("src/frontend").unwrap(), "src/frontend");
+            assert_eq!(resolve_path(workdir.join("src").to_str().unwrap()).unwrap(), "src");
+            assert!(resolve_path(std::env::temp_dir().to_str().unwrap()).is_err());
+        });
+    }
+
+    #[test]
+    fn test_set_layout() {
+        with_temp_repo(|| {
+            let ids = ["Alpha", "Beta", "Gamma"].map(|name| create_task(Task::builder(name).status("OPEN").build().unwrap()).unwrap().get_id().unwrap());
+            update_task_id(&ids[2], "1234").unwrap();
+
+            assert_eq!(set_layout(true).unwrap(), 3);
+            assert!(is_sharded_layout().unwrap());
+            assert_eq!(find_task("1234").unwrap().unwrap().get_property("name").unwrap(), "Gamma");
+            let mut listed = list_tasks().unwrap().iter().map(|task| task.get_id().unwrap()).collect::<Vec<_>>();
+            listed.sort();
+            assert_eq!(listed, vec!["1", "1234", "2"]);
+            assert_eq!(get_next_id().unwrap(), "1235");
+
+            let task = create_task(Task::builder("Delta").status("OPEN").build().unwrap()).unwrap();
+            assert_eq!(task.get_id().unwrap(), "1235");
+            delete_tasks(&[&ids[0]]).unwrap();
+            assert!(find_task(&ids[0]).unwrap().is_none());
+
+            assert_eq!(set_layout(false).unwrap(), 3);
+            assert!(!is_sharded_layout().unwrap());
+            assert_eq!(find_task("1235").unwrap().unwrap().get_property("name").unwrap(), "Delta");
+            assert_eq!(list_tasks().unwrap().len(), 3);
+            assert_eq!(get_next_id().unwrap(), "1236");
+        });
+    }
+
+    #[test]
+    fn test_task_id_from_path() {
+        use std::path::Path;
+        assert_eq!(get_task_id_from_path(Path::new("42")), Some("42".to_string()));
+        assert_eq!(get_task_id_from_path(Path::new("42/4210/task")), Some("4210".to_string()));
+        assert_eq!(get_task_id_from_path(Path::new("7/comments/000001")), None);
+    }
+
+    #[test]
+    fn test_split_comments() {
+        with_temp_repo(|| {
+            let mut task = Task::construct_task("Split".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()));
+            task.add_comment(None, HashMap::new(), "First".to_string());
+            task.add_comment(None, HashMap::new(), "Second".to_string());
+            let id = create_task(task).unwrap().get_id().unwrap();
+
+            let repo = open_repo().unwrap();
+            let tree = repo.find_reference(&get_ref_path()).unwrap().peel_to_tree().unwrap();
+            assert!(get_tree_format_version(&repo, &tree).unwrap() >= SPLIT_COMMENTS_VERSION);
+            let entry = tree.get_path(std::path::Path::new(&get_task_path(&id, is_sharded(&repo, &tree)))).unwrap();
+            assert_eq!(entry.kind(), Some(ObjectType::Tree));
+            let comments = repo.find_tree(entry.id()).unwrap().get_name(COMMENTS_ENTRY).unwrap().to_object(&repo).unwrap().peel_to_tree().unwrap();
+            assert_eq!(comments.len(), 2);
+
+            let task = find_task(&id).unwrap().unwrap();
+            let texts = task.get_comments().as_ref().unwrap().iter().map(|comment| comment.get_text()).collect::<Vec<_>>();
+            assert_eq!(texts, vec!["First", "Second"]);
+
+            let summary = list_tasks_without_comments().unwrap().into_iter().find(|task| task.get_id() == Some(id.clone())).unwrap();
+            assert!(summary.get_comments().is_none());
+
+            assert!(delete_tasks(&[&id]).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_migrate_updated() {
+        with_temp_repo(|| {
+            let id = create_task(Task::builder("Old").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+
+            // rewrite the task the way format 2 stored it, without `updated`
+            let repo = open_repo().unwrap();
+            commit_tasks(&repo, |repo, tree| {
+                let mut task = Task::construct_task("Old".to_string(), String::new(), "OPEN".to_string(), Some(1700000000));
+                task.set_id(id.clone());
+                task.add_comment(None, HashMap::from([(CREATED.to_string(), "1700000500".to_string())]), "Later".to_string());
+                let tree_oid = insert_task(repo, tree, &task)?;
+                let mut treebuilder = map_err!(repo.treebuilder(Some(&map_err!(repo.find_tree(tree_oid)))));
+                map_err!(treebuilder.insert(FORMAT_ENTRY, map_err!(repo.blob(b"2")), FileMode::Blob.into()));
+                Ok((map_err!(treebuilder.write()), "Format 2".to_string()))
+            }).unwrap();
+            assert!(find_task(&id).unwrap().unwrap().get_property(UPDATED).is_none());
+
+            assert_eq!(migrate_tasks(true).unwrap(), (2, vec![id.clone()]));
+            assert!(find_task(&id).unwrap().unwrap().get_property(UPDATED).is_none());
+            assert_eq!(migrate_tasks(false).unwrap(), (2, vec![id.clone()]));
+            assert_eq!(get_format_version().unwrap(), Some(FORMAT_VERSION));
+            assert_eq!(find_task(&id).unwrap().unwrap().get_property(UPDATED).unwrap(), "1700000500");
+            assert_eq!(migrate_tasks(false).unwrap(), (FORMAT_VERSION, vec![]));
+        });
+    }
+
+    #[test]
+    fn test_get_property_history() {
+        with_temp_repo(|| {
+            let mut task = create_task(Task::builder("Assigned").status("OPEN").property("assignee", "alice").build().unwrap()).unwrap();
+            let id = task.get_id().unwrap();
+            task.set_property("name", "Renamed");
+            update_task(task.clone()).unwrap();
+            task.set_property("assignee", "bob");
+            update_task(task).unwrap();
+
+            let history = get_property_history("assignee").unwrap();
+            let values = history[&id].iter().map(|(_, value)| value.as_str()).collect::<Vec<_>>();
+            assert_eq!(values, vec!["alice", "bob"]);
+            assert_eq!(get_status_history().unwrap()[&id].len(), 1);
+        });
+    }
+
+    #[test]
+    fn test_parse_typed_value() {
+        assert_eq!(parse_typed_value::<i64>("estimate", "-3", Some("integer")), Ok(-3));
+        assert_eq!(parse_typed_value::<u64>("estimate", "42", None), Ok(42));
+        assert!(parse_typed_value::<u64>("estimate", "many", Some("integer")).is_err());
+        assert!(parse_typed_value::<i64>("name", "42", Some("string")).is_err());
+        assert_eq!(parse_typed_value::<bool>("flag", "Yes", None), Ok(true));
+        assert!(parse_typed_value::<bool>("flag", "maybe", None).is_err());
+        assert_eq!(parse_typed_value::<String>("name", "Task", Some("string")), Ok("Task".to_string()));
+        let created = parse_typed_value::<chrono::DateTime<chrono::Utc>>("created", "1700000000", Some("datetime")).unwrap();
+        assert_eq!(created.timestamp(), 1700000000);
+        assert!(parse_typed_value::<chrono::DateTime<chrono::Utc>>("created", "2024-01-01", Some("datetime")).is_err());
+    }
+
+    #[test]
+    fn test_sort_by_task_id() {
+        assert_eq!(parse_task_id("42"), Ok(42));
+        assert_eq!(parse_task_id("abc"), Err("'abc' is not a valid task ID".to_string()));
+
+        let mut ids = vec!["10".to_string(), "9".to_string(), "100".to_string()];
+        sort_by_task_id(&mut ids, |id| Some(id.clone())).unwrap();
+        assert_eq!(ids, vec!["9", "10", "100"]);
+
+        let mut ids = vec!["2".to_string(), "x1".to_string(), "1".to_string()];
+        assert_eq!(sort_by_task_id(&mut ids, |id| Some(id.clone())), Err("'x1' is not a valid task ID".to_string()));
+        assert_eq!(ids, vec!["2", "x1", "1"]);
+    }
+
+    #[test]
+    fn test_list_value() {
+        let components = vec!["ui".to_string(), "api".to_string()];
+        assert_eq!(format_list_value(&components), r#"["ui","api"]"#);
+        assert_eq!(parse_typed_value::<Vec<String>>("components", r#"["ui","api"]"#, Some("list")), Ok(components));
+        assert_eq!(parse_typed_value::<Vec<String>>("components", "ui", None), Ok(vec!["ui".to_string()]));
+        assert_eq!(parse_typed_value::<Vec<String>>("components", "", Some("list")), Ok(vec![]));
+        assert!(parse_typed_value::<Vec<String>>("components", "[1, 2]", Some("list")).is_err());
+        assert!(parse_typed_value::<Vec<String>>("estimate", "3", Some("integer")).is_err());
+    }
+
+    #[test]
+    fn test_task_builder() {
+        let created = chrono::DateTime::from_timestamp(1700000000, 0).unwrap();
+        let task = Task::builder("Builder task")
+            .status("OPEN")
+            .created_at(created)
+            .property("priority", "HIGH")
+            .label(Label::new("bug".to_string(), Some("red".to_string()), None))
+            .comment("First comment")
+            .build()
+            .unwrap();
+        assert_eq!(task.name(), "Builder task");
+        assert_eq!(task.status(), "OPEN");
+        assert_eq!(task.description(), "");
+        assert_eq!(task.created_at(), Some(created));
+        assert_eq!(task.get_property("priority").unwrap(), "HIGH");
+        assert_eq!(task.get_label_by_name("bug").unwrap().get_color(), "red");
+        assert_eq!(task.get_comments().as_ref().unwrap()[0].get_text(), "First comment");
+
+        assert!(Task::builder("").status("OPEN").build().is_err());
+        assert!(Task::builder("No status").build().is_err());
+        assert!(Task::builder("Bad time").status("OPEN").property("created", "yesterday").build().is_err());
+        assert!(Task::builder("Bad ID").status("OPEN").id("../1").build().is_err());
+        let label = Label::new("bug".to_string(), None, None);
+        assert!(Task::builder("Twice").status("OPEN").label(label.clone()).label(label).build().is_err());
+        let task = Task::builder("Twice").status("OPEN").property("priority", "LOW").status("CLOSED").property("priority", "HIGH").build().unwrap();
+        assert_eq!(task.status(), "CLOSED");
+        assert_eq!(task.get_property("priority").unwrap(), "HIGH");
+    }
+
+    #[test]
+    fn test_task_summary() {
+        let json = r#"{"id":"5","props":{"name":"Summary","description":"A very long text","status":"OPEN"},"comments":[{"id":"1","props":{},"text":"Skipped"}],"labels":[{"name":"bug","color":null,"description":null}]}"#;
+        let summary: TaskSummary = serde_json::from_str(json).unwrap();
+        assert_eq!(summary.get_id(), Some("5".to_string()));
+        assert_eq!(summary.get_property("name").unwrap(), "Summary");
+        assert!(summary.get_property("description").is_none());
+        assert_eq!(summary.get_labels().as_ref().unwrap()[0].get_name(), "bug");
+
+        let task = summary.into_task();
+        assert!(task.get_comments().is_none());
+        assert_eq!(task.get_property("status").unwrap(), "OPEN");
+    }
+
+    #[test]
+    fn test_index_in_sync() {
+        with_temp_repo(|| {
+            let label = |name: &str| Label::new(name.to_string(), None, None);
+            let first = create_task(Task::builder("First").status("OPEN").property("assignee", "Alice").label(label("Bug")).build().unwrap()).unwrap().get_id().unwrap();
+            let second = create_task(Task::builder("Second").status("CLOSED").label(label("ui")).build().unwrap()).unwrap().get_id().unwrap();
+            assert!(!has_index().unwrap());
+            assert!(find_tasks_by_index(&[(STATUS, vec!["OPEN".to_string()])]).unwrap().is_none());
+            set_index(true).unwrap();
+            assert!(has_index().unwrap());
+
+            let queries = [
+                vec![(STATUS, vec!["OPEN".to_string()])],
+                vec![(STATUS, vec!["OPEN".to_string(), "CLOSED".to_string()])],
+                vec![("label", vec!["bug".to_string()])],
+                vec![("label", vec!["UI".to_string()]), (STATUS, vec!["CLOSED".to_string()])],
+                vec![("assignee", vec!["alice".to_string()])],
+            ];
+            let assert_in_sync = || {
+                let tasks = list_tasks().unwrap();
+                for query in &queries {
+                    let matches = |task: &&Task| query.iter().all(|(key, values)| values.iter().any(|value| match *key {
+                        STATUS => task.get_property(STATUS) == Some(value),
+                        "label" => task.get_labels().iter().flatten().any(|label| label.get_name().eq_ignore_ascii_case(value)),
+                        _ => task.get_property(key).is_some_and(|assignee| assignee.eq_ignore_ascii_case(value)),
+                    }));
+                    let mut expected = tasks.iter().filter(matches).map(|task| task.get_id().unwrap()).collect::<Vec<_>>();
+                    let mut found = find_tasks_by_index(query).unwrap().unwrap().iter().map(|task| task.get_id().unwrap()).collect::<Vec<_>>();
+                    expected.sort();
+                    found.sort();
+                    assert_eq!(found, expected, "{query:?}");
+                }
+            };
+            assert_in_sync();
+
+            let third = create_task(Task::builder("Third").status("OPEN").property("assignee", "alice").label(label("ui")).build().unwrap()).unwrap().get_id().unwrap();
+            assert_in_sync();
+
+            let mut task = find_task(&first).unwrap().unwrap();
+            task.set_property(STATUS, "CLOSED");
+            task.delete_label("Bug").unwrap();
+            task.add_label("ui".to_string(), None, None);
+            update_task(task).unwrap();
+            let mut task = find_task(&second).unwrap().unwrap();
+            task.set_property(STATUS, "OPEN");
+            task.add_label("bug".to_string(), None, None);
+            update_tasks(vec![task]).unwrap();
+            assert_in_sync();
+            assert_eq!(find_tasks_by_index(&[("label", vec!["bug".to_string()])]).unwrap().unwrap().len(), 1);
+
+            delete_tasks(&[&third]).unwrap();
+            assert_in_sync();
+            assert!(find_tasks_by_index(&[("assignee", vec!["alice".to_string()])]).unwrap().unwrap().iter().all(|task| task.get_id().unwrap() == first));
+
+            set_index(false).unwrap();
+            assert!(find_tasks_by_index(&[(STATUS, vec!["OPEN".to_string()])]).unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_task_index() {
+        let mut task = Task::from_properties("3".to_string(), HashMap::from([
+            (NAME.to_string(), "Indexed".to_string()),
+            (STATUS.to_string(), "OPEN".to_string()),
+            ("assignee".to_string(), "Alice".to_string()),
+        ])).unwrap();
+        task.add_label("Bug".to_string(), None, None);
+
+        let mut index = TaskIndex::default();
+        index.add(&task);
+        assert!(index.status["OPEN"].contains("3"));
+        assert!(index.label["bug"].contains("3"));
+        assert!(index.assignee["alice"].contains("3"));
+
+        index.remove("3");
+        assert!(index.status.is_empty() && index.label.is_empty() && index.assignee.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_write() {
+        with_temp_repo(|| {
+            let repo = open_repo().unwrap();
+            let mut first_attempt = true;
+            let mut concurrent_id = None;
+            let mut task = Task::construct_task("Slow writer".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()));
+
+            commit_tasks(&repo, |repo, tree| {
+                if first_attempt {
+                    first_attempt = false;
+                    let concurrent = Task::construct_task("Fast writer".to_string(), String::new(), "OPEN".to_string(), Some(get_current_timestamp()));
+                    concurrent_id = create_task(concurrent).unwrap().get_id();
+                }
+                task.set_id(get_next_id_in_tree(repo, tree));
+                Ok((insert_task(repo, tree, &task)?, "Create task".to_string()))
+            }).unwrap();
+
+            let concurrent_id = concurrent_id.unwrap();
+            let id = task.get_id().unwrap();
+            assert_ne!(id, concurrent_id);
+            assert!(find_task(&concurrent_id).unwrap().is_some());
+            assert_eq!(find_task(&id).unwrap().unwrap().get_property("name").unwrap(), "Slow writer");
+
+            assert!(delete_tasks(&[&id, &concurrent_id]).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_concurrent_update() {
+        with_temp_repo(|| {
+            let id = create_task(Task::builder("Shared").status("OPEN").build().unwrap()).unwrap().get_id().unwrap();
+            let mut first = find_task(&id).unwrap().unwrap();
+            let mut second = find_task(&id).unwrap().unwrap();
+
+            first.set_property("priority", "HIGH");
+            update_task(first).unwrap();
+            second.set_property("assignee", "bob");
+            update_task(second.clone()).unwrap();
+
+            let task = find_task(&id).unwrap().unwrap();
+            assert_eq!(task.get_property("priority").unwrap(), "HIGH");
+            assert_eq!(task.get_property("assignee").unwrap(), "bob");
+
+            second.set_property("priority", "LOW");
+            assert!(update_task(second.clone()).unwrap_err().contains(CONFLICT_ERROR));
+            assert!(update_tasks(vec![second]).unwrap_err().contains(CONFLICT_ERROR));
+            assert_eq!(find_task(&id).unwrap().unwrap().get_property("priority").unwrap(), "HIGH");
+        });
     }
 
     #[test]
     fn test_clear_tasks() {
-        let id = get_next_id().unwrap_or_else(|_| "1".to_string());
-        let task = Task::construct_task("Test task".to_string(), "Description goes here".to_string(), "OPEN".to_string(), Some(get_current_timestamp()));
-        let create_result = create_task(task);
-        assert!(create_result.is_ok());
-        let task = create_result.unwrap();
-        assert_eq!(task.get_id(), Some(id.clone()));
-
-        let id = get_next_id().unwrap_or_else(|_| "2".to_string());
-        let task2 = Task::construct_task("Another task".to_string(), "Another description".to_string(), "IN_PROGRESS".to_string(), Some(get_current_timestamp()));
-        let create_result2 = create_task(task2);
-        assert!(create_result2.is_ok());
-        let task2 = create_result2.unwrap();
-        assert_eq!(task2.get_id(), Some(id.clone()));
-
-        let id = get_next_id().unwrap_or_else(|_| "3".to_string());
-        let task3 = Task::construct_task("Third task".to_string(), "Third description".to_string(), "CLOSED".to_string(), Some(get_current_timestamp()));
-        let create_result3 = create_task(task3);
-        assert!(create_result3.is_ok());
-        let task3 = create_result3.unwrap();
-        assert_eq!(task3.get_id(), Some(id.clone()));
-
-        let clear_result = crate::clear_tasks();
-        assert!(clear_result.is_ok());
-        assert_eq!(clear_result.unwrap(), 3);
-
-        let find_result = find_task(&id);
-        assert!(find_result.is_ok());
-        let task = find_result.unwrap();
-        assert!(task.is_none());
-
-        let find_result = find_task(&task2.get_id().unwrap());
-        assert!(find_result.is_ok());
-        let task = find_result.unwrap();
-        assert!(task.is_none());
-
-        let find_result = find_task(&task3.get_id().unwrap());
-        assert!(find_result.is_ok());
-        let task = find_result.unwrap();
-        assert!(task.is_none());
+        with_temp_repo(|| {
+            let id = get_next_id().unwrap_or_else(|_| "1".to_string());
+            let task = Task::construct_task("Test task".to_string(), "Description goes here".to_string(), "OPEN".to_string(), Some(get_current_timestamp()));
+            let create_result = create_task(task);
+            assert!(create_result.is_ok());
+            let task = create_result.unwrap();
+            assert_eq!(task.get_id(), Some(id.clone()));
+
+            let id = get_next_id().unwrap_or_else(|_| "2".to_string());
+            let task2 = Task::construct_task("Another task".to_string(), "Another description".to_string(), "IN_PROGRESS".to_string(), Some(get_current_timestamp()));
+            let create_result2 = create_task(task2);
+            assert!(create_result2.is_ok());
+            let task2 = create_result2.unwrap();
+            assert_eq!(task2.get_id(), Some(id.clone()));
+
+            let id = get_next_id().unwrap_or_else(|_| "3".to_string());
+            let task3 = Task::construct_task("Third task".to_string(), "Third description".to_string(), "CLOSED".to_string(), Some(get_current_timestamp()));
+            let create_result3 = create_task(task3);
+            assert!(create_result3.is_ok());
+            let task3 = create_result3.unwrap();
+            assert_eq!(task3.get_id(), Some(id.clone()));
+
+            let clear_result = crate::clear_tasks();
+            assert!(clear_result.is_ok());
+            assert_eq!(clear_result.unwrap(), 3);
+
+            let find_result = find_task(&id);
+            assert!(find_result.is_ok());
+            let task = find_result.unwrap();
+            assert!(task.is_none());
+
+            let find_result = find_task(&task2.get_id().unwrap());
+            assert!(find_result.is_ok());
+            let task = find_result.unwrap();
+            assert!(task.is_none());
+
+            let find_result = find_task(&task3.get_id().unwrap());
+            assert!(find_result.is_ok());
+            let task = find_result.unwrap();
+            assert!(task.is_none());
+        });
     }
 }
\ No newline at end of file
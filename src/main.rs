@@ -1,25 +1,72 @@
+#[cfg(feature = "bench")]
+mod bench;
+mod ci_import;
+mod codeowners;
 mod connectors;
+mod document;
+mod email_import;
+mod i18n;
 mod operations;
+mod palette;
+mod picker;
 mod property;
+mod report;
 mod status;
+mod template;
+mod theme;
 mod util;
+mod wizard;
 
 extern crate gittask;
 
 use std::process::ExitCode;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 
-use crate::operations::{task_clear, task_create, task_delete, task_edit, task_export, task_get, task_import, task_list, task_pull, task_push, task_replace, task_set, task_show, task_stats, task_status, task_unset};
+use crate::connectors::notify::Notifier;
+use crate::operations::{task_append, task_changelog, task_clear, task_close, task_create, task_delete, task_edit, task_export, task_gc, task_get, task_import, task_inbox, task_init, task_list, task_migrate, task_pull, task_push, task_replace, task_set, task_show, task_stats, task_status, task_unset, ListOptions, PullOptions, ReplaceOptions};
+use crate::operations::alias::*;
+use crate::operations::calendar::*;
 use crate::operations::comment::*;
 use crate::operations::config::*;
 use crate::operations::config::properties::*;
 use crate::operations::config::status::*;
+use crate::operations::config::theme::*;
+use crate::operations::diff::*;
+use crate::operations::doctor::*;
 use crate::operations::label::*;
+use crate::operations::remote::*;
+use crate::operations::scan::*;
+use crate::operations::snooze::*;
+use crate::operations::watch::*;
+use crate::property::PropertyManager;
+use crate::util::{error_message_kind, get_exit_code, set_color_mode, set_exit_code, set_json_errors, set_plain_output, split_args, ColorMode, ErrorKind, ListEdit};
 
 #[derive(Parser)]
-#[command(version, about = "Local-first task manager/bug tracker within your git repository which can sync issues from/to GitHub or Gitlab.", arg_required_else_help(true))]
+#[command(version, about = "Local-first task manager/bug tracker within your git repository which can sync issues from/to GitHub or Gitlab.", arg_required_else_help(true), args_override_self(true))]
 struct Args {
+    /// Run as if git-task was started in this directory
+    #[arg(short = 'C', value_name = "PATH")]
+    path: Option<String>,
+    /// Path to the repository (".git" directory or a bare repository)
+    #[arg(long, value_name = "PATH")]
+    git_dir: Option<String>,
+    /// Use the personal task store (task.global.path, ~/.git-task by default)
+    #[arg(short, long, conflicts_with = "git_dir")]
+    global: bool,
+    /// Use only this remote connector: github, gitlab, jira, "mock" working with a local JSON fixture (GIT_TASK_MOCK_FILE)
+    /// or a git-task-connector-NAME plugin found on PATH
+    #[arg(long, value_name = "NAME")]
+    connector: Option<String>,
+    /// Error output format: text or json
+    #[arg(long, value_name = "FORMAT", value_parser = ["text", "json"], default_value = "text")]
+    error_format: String,
+    /// When to colorize the output: auto (only on a terminal, respecting NO_COLOR and color.ui), always or never
+    #[arg(long = "color", value_name = "WHEN", value_parser = ["auto", "always", "never"], default_value = "auto", global = true)]
+    color_mode: String,
+    /// ASCII-only output without colors and decorations, friendly to screen readers
+    #[arg(long, global = true)]
+    plain: bool,
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -40,9 +87,27 @@ enum Command {
         /// Older than date, YYYY-MM-DD, inclusive
         #[arg(short, long)]
         until: Option<String>,
+        /// Updated (or created, if never updated) since date, YYYY-MM-DD, inclusive
+        #[arg(long)]
+        updated_since: Option<String>,
         /// Filter by author
         #[arg(long)]
         author: Option<String>,
+        /// Filter by one or more labels
+        #[arg(long = "label", value_delimiter = ',')]
+        labels: Option<Vec<String>>,
+        /// Filter by assignee
+        #[arg(long)]
+        assignee: Option<String>,
+        /// Filter by task type, e.g. bug or feature
+        #[arg(long = "type")]
+        task_type: Option<String>,
+        /// Filter by path of the task (subdirectory in a monorepo)
+        #[arg(long)]
+        path: Option<String>,
+        /// Filter by property: KEY=VALUE, KEY!=VALUE or KEY~=REGEX, list properties have to contain a matching value
+        #[arg(long = "prop", visible_alias = "property", value_name = "KEY=VALUE")]
+        properties: Option<Vec<String>>,
         /// Comma-separated list of columns
         #[arg(short, long, value_delimiter = ',')]
         columns: Option<Vec<String>>,
@@ -52,6 +117,18 @@ enum Command {
         /// Limit displayed task count
         #[arg(short, long)]
         limit: Option<usize>,
+        /// Show snoozed tasks too
+        #[arg(long)]
+        include_snoozed: bool,
+        /// Show only tasks not updated in the number of days, `task.stale.days` (30) by default
+        #[arg(long, value_name = "DAYS", num_args = 0..=1)]
+        stale: Option<Option<i64>>,
+        /// Print only the number of matching tasks
+        #[arg(long, conflicts_with_all = ["columns", "ids_only"])]
+        count: bool,
+        /// Print only the IDs of matching tasks, one per line
+        #[arg(long, conflicts_with = "columns")]
+        ids_only: bool,
         /// Disable colors
         #[arg(long)]
         no_color: bool,
@@ -59,7 +136,17 @@ enum Command {
     /// Show a task with all properties
     Show {
         /// task ID
-        id: String,
+        #[arg(required_unless_present = "pick")]
+        id: Option<String>,
+        /// Pick a task interactively
+        #[arg(long, conflicts_with = "id")]
+        pick: bool,
+        /// Output format: text or json (the full task with comments, computed properties and property configuration)
+        #[arg(short, long, value_parser = ["text", "json"])]
+        format: Option<String>,
+        /// Prettify JSON output
+        #[arg(short, long)]
+        pretty: bool,
         /// Disable colors
         #[arg(long)]
         no_color: bool,
@@ -74,6 +161,30 @@ enum Command {
         /// Skip editing description in the editor
         #[arg(short, long, conflicts_with = "description")]
         no_desc: bool,
+        /// Read task description from stdin
+        #[arg(long, conflicts_with_all = ["description", "no_desc"])]
+        from_stdin: bool,
+        /// Set a property, e.g. --prop priority=HIGH
+        #[arg(long = "prop", visible_alias = "property", value_name = "KEY=VALUE")]
+        properties: Option<Vec<String>>,
+        /// Add a label
+        #[arg(long = "label")]
+        labels: Option<Vec<String>>,
+        /// Set assignee
+        #[arg(long)]
+        assignee: Option<String>,
+        /// Set priority
+        #[arg(long)]
+        priority: Option<String>,
+        /// Set due date, YYYY-MM-DD
+        #[arg(long)]
+        due: Option<String>,
+        /// Set task type, e.g. bug or feature
+        #[arg(long = "type")]
+        task_type: Option<String>,
+        /// Associate task with a subdirectory, current directory by default
+        #[arg(long)]
+        path: Option<String>,
         /// Also push task to the remote source (e.g., GitHub)
         #[arg(short, long)]
         push: bool,
@@ -89,6 +200,9 @@ enum Command {
         /// status (by default: o - OPEN, i - IN_PROGRESS, c - CLOSED)
         #[clap(required = true)]
         status: String,
+        /// Exceed the WIP limit of the status even if it is strict
+        #[arg(short, long)]
+        force: bool,
         /// Also push task(s) to the remote source (e.g., GitHub)
         #[arg(short, long)]
         push: bool,
@@ -99,6 +213,20 @@ enum Command {
         #[arg(long)]
         no_color: bool,
     },
+    /// Close tasks with an optional closing comment
+    Close {
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
+        /// Closing comment added to every task
+        #[arg(short, long)]
+        comment: Option<String>,
+        /// Also close the remote issues and post the comment there (e.g., on GitHub)
+        #[arg(short, long)]
+        push: bool,
+        /// Use this remote if there are several of them
+        #[arg(short, long)]
+        remote: Option<String>,
+    },
     /// Get a property
     Get {
         /// task ID
@@ -113,8 +241,27 @@ enum Command {
         ids: String,
         /// property name
         prop_name: String,
-        /// property value
-        value: String,
+        /// property value, comma separated values for a list property
+        #[arg(required_unless_present = "pick")]
+        value: Option<String>,
+        /// Pick one of the enum values of the property interactively
+        #[arg(long, conflicts_with = "value")]
+        pick: bool,
+        /// Add values to a list property
+        #[arg(long, conflicts_with = "remove")]
+        add: bool,
+        /// Remove values from a list property
+        #[arg(long)]
+        remove: bool,
+        /// Show changes without saving them
+        #[arg(long, conflicts_with = "confirm")]
+        preview: bool,
+        /// Show changes and ask before saving them
+        #[arg(long)]
+        confirm: bool,
+        /// Exceed the WIP limit of the status even if it is strict
+        #[arg(short, long)]
+        force: bool,
         /// Also push task to the remote source (e.g., GitHub)
         #[arg(short, long)]
         push: bool,
@@ -130,18 +277,36 @@ enum Command {
         /// one or more task IDs (comma separated, including ranges like 1..10)
         #[clap(required = true)]
         ids: String,
-        /// property name
+        /// property name, several can be separated by commas (omitted if --props is used)
         #[clap(required = true)]
         prop_name: String,
         /// string to search
         #[clap(required = true)]
         search: String,
         /// replace with
-        #[clap(required = true)]
-        replace: String,
-        /// Treat search and replace strings as regular expressions
+        #[arg(required_unless_present = "props")]
+        replace: Option<String>,
+        /// Comma-separated property names, e.g. --props name,description
+        #[arg(long, value_delimiter = ',')]
+        props: Option<Vec<String>>,
+        /// Treat search string as a regular expression, replacement can refer to capture groups like $1
         #[arg(alias = "rx", long)]
         regex: bool,
+        /// Case-insensitive search
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Multi-line mode for regular expressions: ^ and $ match at line breaks
+        #[arg(short, long, requires = "regex")]
+        multiline: bool,
+        /// Also replace in comments
+        #[arg(long)]
+        include_comments: bool,
+        /// Show changes without saving them
+        #[arg(long, conflicts_with = "confirm")]
+        preview: bool,
+        /// Show changes and ask before saving them
+        #[arg(long)]
+        confirm: bool,
         /// Also push task(s) to the remote source (e.g., GitHub)
         #[arg(short, long)]
         push: bool,
@@ -152,6 +317,13 @@ enum Command {
         #[arg(long)]
         no_color: bool,
     },
+    /// Hide tasks from the list until the date (YYYY-MM-DD)
+    Snooze {
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
+        /// date to wake up the tasks on
+        until: String,
+    },
     /// Delete a property
     Unset {
         /// one or more task IDs (comma separated, including ranges like 1..10)
@@ -159,12 +331,44 @@ enum Command {
         /// property name
         prop_name: String,
     },
-    /// Edit a property
+    /// Edit a property or the whole task
     Edit {
+        /// task ID
+        #[arg(required_unless_present = "pick")]
+        id: Option<String>,
+        /// property name (edit the whole task if omitted)
+        prop_name: Option<String>,
+        /// Pick a task interactively and edit it as a whole
+        #[arg(long, conflicts_with_all = ["id", "prop_name"])]
+        pick: bool,
+    },
+    /// Append text to the task description
+    Append {
         /// task ID
         id: String,
-        /// property name
-        prop_name: String,
+        /// text to append
+        #[clap(required_unless_present = "body_file")]
+        text: Option<String>,
+        /// Read text from a file, use - for stdin
+        #[arg(long, conflicts_with = "text")]
+        body_file: Option<String>,
+        /// Separator between existing description and the new text
+        #[arg(long, default_value = "\\n")]
+        separator: String,
+    },
+    /// Prepend text to the task description
+    Prepend {
+        /// task ID
+        id: String,
+        /// text to prepend
+        #[clap(required_unless_present = "body_file")]
+        text: Option<String>,
+        /// Read text from a file, use - for stdin
+        #[arg(long, conflicts_with = "text")]
+        body_file: Option<String>,
+        /// Separator between the new text and existing description
+        #[arg(long, default_value = "\\n")]
+        separator: String,
     },
     /// Add or delete comments
     Comment {
@@ -177,13 +381,43 @@ enum Command {
         #[command(subcommand)]
         subcommand: LabelCommand,
     },
+    /// Assign memorable aliases to task IDs
+    Alias {
+        #[command(subcommand)]
+        subcommand: AliasCommand,
+    },
+    /// Watch tasks to follow their changes in the inbox
+    Watch {
+        #[command(subcommand)]
+        subcommand: WatchCommand,
+    },
+    /// Find TODO/FIXME/HACK comments in the working tree and create tasks for them
+    Scan {
+        /// Scan only this path
+        path: Option<String>,
+        /// Comma-separated keywords to look for, TODO,FIXME,HACK by default
+        #[arg(short, long)]
+        keywords: Option<String>,
+        /// Create tasks for new items
+        #[arg(short, long)]
+        apply: bool,
+    },
     /// Import tasks from a source
     Import {
         /// one or more task IDs (comma separated, including ranges like 1..10)
         ids: Option<String>,
-        /// Input format (only JSON is currently supported)
+        /// Input format: json (default), junit, log or eml (an email message or an mbox file)
         #[arg(short, long)]
         format: Option<String>,
+        /// Regular expression to match error lines in log format, `name` group sets the task name
+        #[arg(short, long)]
+        pattern: Option<String>,
+        /// Import tasks with their whole history from a git bundle made by export --with-history
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["ids", "format", "pattern"])]
+        from_bundle: Option<String>,
+        /// Replace local tasks that have diverged from the bundle
+        #[arg(long, requires = "from_bundle")]
+        force: bool,
     },
     /// Export tasks
     Export {
@@ -192,15 +426,31 @@ enum Command {
         /// Filter by status (by default: o - OPEN, i - IN_PROGRESS, c - CLOSED)
         #[arg(short, long, value_delimiter = ',')]
         status: Option<Vec<String>>,
+        /// Filter by property: KEY=VALUE, KEY!=VALUE or KEY~=REGEX, list properties have to contain a matching value
+        #[arg(long = "prop", visible_alias = "property", value_name = "KEY=VALUE")]
+        properties: Option<Vec<String>>,
         /// Limit exported task count
         #[arg(short, long)]
         limit: Option<usize>,
-        /// Output format (only JSON is currently supported)
+        /// Output format: json or html (a printable report with a page per task, print it or save as PDF from a browser)
         #[arg(short, long)]
         format: Option<String>,
         /// Prettify output
         #[arg(short, long)]
         pretty: bool,
+        /// Export the tasks ref with its whole history into a git bundle
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["ids", "status", "properties", "limit", "format", "pretty"])]
+        with_history: Option<String>,
+    },
+    /// Show tasks created, deleted and changed between two revisions of the tasks ref
+    Diff {
+        /// revision of the tasks ref (e.g. a commit ID or ~3 for three commits back) or a bundle made by export --with-history
+        rev1: String,
+        /// the other revision or bundle, the current tasks by default
+        rev2: Option<String>,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
     },
     /// Pull tasks from a remote source (e.g., GitHub)
     Pull {
@@ -215,12 +465,36 @@ enum Command {
         /// Use this remote if there are several of them
         #[arg(short, long)]
         remote: Option<String>,
+        /// Import only issues having all of these labels
+        #[arg(long = "label", value_delimiter = ',', conflicts_with = "ids")]
+        labels: Option<Vec<String>>,
+        /// Import only issues created by this user
+        #[arg(long, conflicts_with = "ids")]
+        author: Option<String>,
+        /// Import only issues updated since the last complete pull
+        #[arg(short, long, conflicts_with = "ids")]
+        incremental: bool,
         /// Don't import task comments
         #[arg(long, aliases = ["nc"])]
         no_comments: bool,
+        /// Import only comments created since date, YYYY-MM-DD, inclusive
+        #[arg(long, conflicts_with = "no_comments")]
+        comments_since: Option<String>,
+        /// Import only this many most recent comments per task
+        #[arg(long, conflicts_with = "no_comments")]
+        max_comments: Option<usize>,
         /// Don't import task labels
         #[arg(long, aliases = ["nl"])]
         no_labels: bool,
+        /// Import only IDs, names and statuses, the rest is fetched when a task is shown
+        #[arg(long, conflicts_with_all = ["comments_since", "max_comments"])]
+        shallow: bool,
+        /// Continue an interrupted pull of the same remote and tasks, skipping the tasks already imported
+        #[arg(long)]
+        resume: bool,
+        /// Mirror remote properties exactly, removing the properties that exist only locally
+        #[arg(long)]
+        prune_properties: bool,
     },
     /// Push task status to the remote source (e.g., GitHub)
     Push {
@@ -238,13 +512,64 @@ enum Command {
         /// Disable colors
         #[arg(long)]
         no_color: bool,
+        /// Continue an interrupted push of the same tasks, skipping the tasks already pushed
+        #[arg(long)]
+        resume: bool,
+        /// Create milestones (GitLab) and versions (Jira) the remote doesn't have yet
+        #[arg(long)]
+        create_missing: bool,
+    },
+    /// List tasks where you were mentioned, assigned or watched tasks changed since the last check
+    Inbox {
+        /// Show all mentions and assignments, don't update the last check time
+        #[arg(short, long)]
+        all: bool,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
     },
     /// Show total task count and count by status
     Stats {
+        /// Break down by label, milestone or any other property
+        #[arg(long)]
+        by: Option<String>,
+        /// Cross-tabulate with another property, e.g. --by status --cross priority
+        #[arg(long, requires = "by")]
+        cross: Option<String>,
+        /// Show lead time and cycle time of closed tasks, can be broken down --by label, month or a property
+        #[arg(long, conflicts_with = "cross")]
+        cycle_time: bool,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Show a month calendar with due and overdue task counts per day, or a week agenda
+    Calendar {
+        /// Month to show (YYYY-MM), the current one by default
+        #[arg(short, long, conflicts_with = "week")]
+        month: Option<String>,
+        /// List tasks due in the week containing the date (YYYY-MM-DD), the current week by default
+        #[arg(short, long, value_name = "DATE")]
+        week: Option<Option<String>>,
         /// Disable colors
         #[arg(long)]
         no_color: bool,
     },
+    /// Generate release notes from tasks closed in the period
+    Changelog {
+        /// Start of the period: date (YYYY-MM-DD) or git revision (e.g. a tag)
+        #[arg(short, long)]
+        since: String,
+        /// End of the period: date (YYYY-MM-DD) or git revision
+        #[arg(short, long)]
+        until: Option<String>,
+        /// Group tasks by label (default) or by a property
+        #[arg(short, long)]
+        group_by: Option<String>,
+        /// Path to a Handlebars-style template
+        #[arg(short, long)]
+        template: Option<String>,
+    },
     /// Delete one or several tasks at once
     #[clap(visible_aliases(["del", "remove", "rem"]))]
     Delete {
@@ -252,8 +577,11 @@ enum Command {
         #[clap(required = true)]
         ids: Option<String>,
         /// Delete by status (by default: o - OPEN, i - IN_PROGRESS, c - CLOSED)
-        #[arg(short, long, value_delimiter = ',', conflicts_with = "ids", required_unless_present = "ids")]
+        #[arg(short, long, value_delimiter = ',', conflicts_with = "ids", required_unless_present_any = ["ids", "properties"])]
         status: Option<Vec<String>>,
+        /// Delete by property: KEY=VALUE, KEY!=VALUE or KEY~=REGEX, combined with --status if both are given
+        #[arg(long = "prop", visible_alias = "property", value_name = "KEY=VALUE", conflicts_with = "ids")]
+        properties: Option<Vec<String>>,
         /// Also delete task from the remote source (e.g., GitHub)
         #[arg(short, long)]
         push: bool,
@@ -263,12 +591,95 @@ enum Command {
     },
     /// Delete all tasks
     Clear,
+    /// Upgrade tasks stored in an older format
+    Migrate {
+        /// Store tasks in a flat tree or sharded into subtrees by ID prefix (for large task counts)
+        #[arg(long, value_parser = ["flat", "sharded"])]
+        layout: Option<String>,
+        /// Build (or rebuild) the index of tasks by status, label and assignee, or drop it
+        #[arg(long, value_parser = ["on", "off"])]
+        index: Option<String>,
+        /// Only show what would be changed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Create the tasks ref and default configuration
+    Init {
+        /// Git remote for publish and fetch
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Let a plain git fetch fast-forward the tasks ref too
+        #[arg(long)]
+        auto_fetch: bool,
+        /// Install a pre-push hook that publishes tasks along with every push
+        #[arg(long)]
+        hooks: bool,
+    },
+    /// Configure the git remote tasks are shared through
+    Remote {
+        #[command(subcommand)]
+        subcommand: RemoteCommand,
+    },
+    /// Push the tasks ref to the git remote
+    Publish {
+        /// Git remote (task.remote or origin by default)
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Overwrite remote tasks even if they have diverged
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Fetch the tasks ref from the git remote and fast-forward local tasks
+    Fetch {
+        /// Git remote (task.remote or origin by default)
+        #[arg(short, long)]
+        remote: Option<String>,
+        /// Replace local tasks even if they have diverged
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Squash old history of the tasks ref into a single baseline commit and repack
+    Gc {
+        /// Keep commits made during the last N days as they are
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        keep_days: u64,
+        /// Preserve the old history under this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Don't run git gc afterwards
+        #[arg(long)]
+        no_repack: bool,
+        /// Only show what would be changed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check the setup and suggest fixes for found problems
+    Doctor {
+        /// Don't check connections to remote services
+        #[arg(long)]
+        offline: bool,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Measure throughput of the main operations on synthetic tasks with different storage setups
+    #[cfg(feature = "bench")]
+    Bench {
+        /// Number of tasks to generate
+        #[arg(short, long, default_value_t = 1000)]
+        count: usize,
+    },
     /// Set configuration parameters
     #[clap(visible_aliases(["cfg"]))]
     Config {
         #[command(subcommand)]
         subcommand: ConfigCommand,
     },
+    /// Run commands interactively in a single session, end a line with ? or Tab to list completions
+    Do,
+    /// User-defined command alias, `task.alias.<name>` parameter
+    #[command(external_subcommand)]
+    External(Vec<String>),
 }
 
 #[derive(Subcommand)]
@@ -276,8 +687,8 @@ enum CommentCommand {
     /// Add a comment
     #[clap(visible_aliases(["create", "new"]))]
     Add {
-        /// task ID
-        task_id: String,
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
         /// comment text
         text: Option<String>,
         /// Also push comment to the remote source (e.g., GitHub)
@@ -300,6 +711,16 @@ enum CommentCommand {
         #[arg(short, long)]
         remote: Option<String>,
     },
+    /// Show previous versions of an edited comment
+    History {
+        /// task ID
+        task_id: String,
+        /// comment ID
+        comment_id: String,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
     /// Delete a comment
     #[clap(visible_aliases(["del", "remove", "rem"]))]
     Delete {
@@ -314,6 +735,44 @@ enum CommentCommand {
         #[arg(short, long)]
         remote: Option<String>,
     },
+    /// Search and replace within comments of one or more tasks
+    Replace {
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
+        /// string to search
+        search: String,
+        /// replace with
+        replace: String,
+        /// Treat search string as a regular expression, replacement can refer to capture groups like $1
+        #[arg(alias = "rx", long)]
+        regex: bool,
+        /// Case-insensitive search
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Show changes without saving them
+        #[arg(long, visible_alias = "preview")]
+        dry_run: bool,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Search comments
+    #[clap(visible_aliases(["find", "grep"]))]
+    Search {
+        /// string to search
+        pattern: String,
+        /// one or more task IDs (comma separated, including ranges like 1..10), all tasks by default
+        ids: Option<String>,
+        /// Treat search string as a regular expression
+        #[arg(alias = "rx", long)]
+        regex: bool,
+        /// Case-insensitive search
+        #[arg(short, long)]
+        ignore_case: bool,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -321,8 +780,8 @@ enum LabelCommand {
     /// Add a label
     #[clap(visible_aliases(["create", "new"]))]
     Add {
-        /// task ID
-        task_id: String,
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
         /// label name
         name: String,
         /// label color
@@ -340,8 +799,8 @@ enum LabelCommand {
     /// Delete a label
     #[clap(visible_aliases(["del", "remove", "rem"]))]
     Delete {
-        /// task ID
-        task_id: String,
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
         /// label name
         name: String,
         /// Also delete label from the remote source (e.g., GitHub)
@@ -351,6 +810,96 @@ enum LabelCommand {
         #[arg(short, long)]
         remote: Option<String>,
     },
+    /// List labels of a task or all labels in use with task counts
+    #[clap(visible_aliases(["ls"]))]
+    List {
+        /// task ID
+        task_id: Option<String>,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
+    /// Rename a label in every task that has it
+    Rename {
+        /// label name
+        name: String,
+        /// new label name
+        new_name: String,
+        /// Also rename the label in tasks with final statuses
+        #[arg(short, long)]
+        all: bool,
+        /// Also rename label in the remote source (e.g., GitHub)
+        #[arg(short, long)]
+        push: bool,
+        /// Use this remote if there are several of them
+        #[arg(short, long)]
+        remote: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasCommand {
+    /// Add an alias
+    #[clap(visible_aliases(["create", "new"]))]
+    Add {
+        /// task ID
+        task_id: String,
+        /// alias, e.g. login-bug
+        alias: String,
+    },
+    /// Delete an alias
+    #[clap(visible_aliases(["del", "remove", "rem"]))]
+    Delete {
+        /// alias
+        alias: String,
+    },
+    /// List aliases
+    #[clap(visible_aliases(["ls"]))]
+    List {
+        /// Show only aliases of this task
+        task_id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum WatchCommand {
+    /// Start watching tasks
+    Add {
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
+        /// Watcher name (current user by default)
+        #[arg(short, long)]
+        user: Option<String>,
+    },
+    /// Stop watching tasks
+    #[clap(visible_aliases(["del", "delete", "rem"]))]
+    Remove {
+        /// one or more task IDs (comma separated, including ranges like 1..10)
+        ids: String,
+        /// Watcher name (current user by default)
+        #[arg(short, long)]
+        user: Option<String>,
+    },
+    /// List task watchers
+    #[clap(visible_aliases(["ls"]))]
+    List {
+        /// task ID
+        task_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RemoteCommand {
+    /// Choose the git remote for publish and fetch
+    Setup {
+        /// Git remote (origin by default)
+        remote: Option<String>,
+        /// Let a plain git fetch fast-forward the tasks ref too
+        #[arg(long)]
+        auto_fetch: bool,
+    },
+    /// Show the git remote and fetch settings
+    Show,
 }
 
 #[derive(Subcommand)]
@@ -365,13 +914,36 @@ enum ConfigCommand {
         /// parameter name
         param: String,
         /// parameter value
+        #[arg(allow_hyphen_values = true)]
         value: String,
         /// Remove old tasks ref after update
         #[arg(long = "move")]
         move_ref: bool,
     },
     /// List configuration parameters
-    List,
+    List {
+        /// Show effective values including defaults and where they come from
+        #[arg(short, long)]
+        all: bool,
+    },
+    /// Import task.* configuration parameters from JSON
+    Import {
+        /// Also import shell aliases and task.hooks.path that run commands
+        #[arg(long)]
+        allow_exec: bool,
+    },
+    /// Export all task.* configuration parameters except secrets to JSON
+    Export {
+        /// Prettify output
+        #[arg(short, long)]
+        pretty: bool,
+    },
+    /// Define statuses and properties interactively
+    Wizard {
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
     /// Configure task statuses
     Status {
         #[command(subcommand)]
@@ -383,6 +955,30 @@ enum ConfigCommand {
         #[command(subcommand)]
         subcommand: PropertiesCommand,
     },
+    /// Configure the color theme
+    Theme {
+        #[command(subcommand)]
+        subcommand: ThemeCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ThemeCommand {
+    /// List available themes, the current one is marked with *
+    List,
+    /// Switch to a theme and recolor statuses and default properties
+    Set {
+        /// theme name (dark, light, solarized or custom)
+        name: String,
+    },
+    /// Show sample output in the colors of a theme
+    Preview {
+        /// theme name, the current one by default
+        name: Option<String>,
+        /// Disable colors
+        #[arg(long)]
+        no_color: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -424,6 +1020,13 @@ enum StatusCommand {
         /// parameter value
         value: String,
     },
+    /// Move a status to another position in the workflow order
+    Move {
+        /// status name or shortcut
+        name: String,
+        /// new position, starting from 1
+        position: usize,
+    },
     /// List task statuses
     List,
     /// Import task statuses from JSON
@@ -445,7 +1048,7 @@ enum PropertiesCommand {
     Add {
         /// property name
         name: String,
-        /// property value type (string, text, datetime or integer)
+        /// property value type (string, text, datetime, integer or list)
         value_type: String,
         /// property color
         color: String,
@@ -458,6 +1061,9 @@ enum PropertiesCommand {
         /// property conditional formatting
         #[arg(long = "cond_format", num_args = 1..)]
         cond_format: Option<Vec<String>>,
+        /// expression computing the value from other properties, it's not stored in the tasks
+        #[arg(long)]
+        formula: Option<String>,
     },
     /// Delete a property
     #[clap(visible_aliases(["del", "remove", "rem"]))]
@@ -472,14 +1078,14 @@ enum PropertiesCommand {
     Get {
         /// property name
         name: String,
-        /// property parameter (name, color or value_type)
+        /// property parameter (name, color, style, value_type or formula)
         param: String,
     },
     /// Set task property parameter
     Set {
         /// property name
         name: String,
-        /// property parameter (name, color or value_type)
+        /// property parameter (name, color, style, value_type or formula)
         param: String,
         /// property value
         value: String,
@@ -498,6 +1104,17 @@ enum PropertiesCommand {
     },
     /// List task properties
     List,
+    /// Show all parameters of a property and how many tasks have it
+    Describe {
+        /// property name
+        name: String,
+    },
+    /// Print JSON Schema of tasks in the export format based on configured properties and statuses
+    Schema {
+        /// Prettify output
+        #[arg(short, long)]
+        pretty: bool,
+    },
     /// Import task properties from JSON
     Import,
     /// Export task properties
@@ -577,6 +1194,33 @@ enum PropertiesCondFormatCommand {
         cond_format_color: String,
         /// conditional formatting style (e.g., bold or underline)
         cond_format_style: Option<String>,
+        /// Highlight the whole row in the list instead of the property value
+        #[arg(long)]
+        row: bool,
+    },
+    /// Replace a property conditional formatting by its number
+    Set {
+        /// property name
+        name: String,
+        /// number of the conditional formatting as listed, starting from 1
+        number: usize,
+        /// conditional formatting expression
+        cond_format_expr: String,
+        /// conditional formatting color
+        cond_format_color: String,
+        /// conditional formatting style (e.g., bold or underline)
+        cond_format_style: Option<String>,
+        /// Highlight the whole row in the list instead of the property value
+        #[arg(long)]
+        row: bool,
+    },
+    /// Delete a property conditional formatting by its number
+    #[clap(visible_aliases(["del", "remove", "rem"]))]
+    Delete {
+        /// property name
+        name: String,
+        /// number of the conditional formatting as listed, starting from 1
+        number: usize,
     },
     /// Clear conditional formatting of a property
     Clear {
@@ -588,43 +1232,316 @@ enum PropertiesCondFormatCommand {
 fn main() -> ExitCode {
     let _ = enable_ansi_support::enable_ansi_support();
     let args = Args::parse();
-    let success = match args.command {
-        Some(Command::List { status, keyword, from, until, author, columns, sort, limit, no_color }) => task_list(status, keyword, from, until, author, columns, sort, limit, no_color),
-        Some(Command::Show { id, no_color }) => task_show(id, no_color),
-        Some(Command::Create { name, description, no_desc, push, remote }) => task_create(name, description, no_desc, push, &remote),
-        Some(Command::Status { ids, status, push, remote, no_color }) => task_status(ids, status, push, &remote, no_color),
+    set_json_errors(args.error_format == "json");
+    set_color_mode(args.color_mode.parse().unwrap_or(ColorMode::Auto));
+    if let Some(path) = &args.path {
+        if let Err(e) = std::env::set_current_dir(path) {
+            error_message_kind(ErrorKind::NotFound, format!("ERROR: cannot change to '{path}': {e}"));
+            return ExitCode::from(get_exit_code());
+        }
+    }
+    if let Some(git_dir) = &args.git_dir {
+        std::env::set_var("GIT_DIR", git_dir);
+    }
+    if let Some(connector) = &args.connector {
+        let names = connectors::list_connector_names();
+        if !names.contains(connector) {
+            error_message_kind(ErrorKind::Validation, format!("ERROR: Unknown connector '{connector}', available: {}", names.join(", ")));
+            return ExitCode::from(get_exit_code());
+        }
+        std::env::set_var(connectors::CONNECTOR_ENV, connector);
+    }
+    if args.global {
+        match gittask::get_global_repo_path() {
+            Ok(path) => std::env::set_var("GIT_DIR", path),
+            Err(e) => {
+                error_message_kind(ErrorKind::General, format!("ERROR: {e}"));
+                return ExitCode::from(get_exit_code());
+            }
+        }
+    }
+    // resolved once, the repository is known only after the options above
+    set_plain_output(args.plain || gittask::get_config_value("task.output.ascii").is_ok_and(|value| value == "true"));
+    let command = match get_default_flags(std::env::args().collect()) {
+        Some(args) => match Args::try_parse_from(args) {
+            Ok(args) => args.command,
+            Err(e) => e.exit(),
+        },
+        None => args.command,
+    };
+    let notifier = Notifier::start();
+    let success = run_command(command);
+    if let Some(notifier) = notifier {
+        notifier.finish();
+    }
+    if success { ExitCode::SUCCESS } else { ExitCode::from(get_exit_code()) }
+}
+
+fn run_command(command: Option<Command>) -> bool {
+    match command {
+        Some(Command::List { status, keyword, from, until, updated_since, author, labels, assignee, task_type, path, properties, columns, sort, limit, include_snoozed, stale, count, ids_only, no_color }) => {
+            let options = ListOptions { status, keyword, from, until, updated_since, author, labels, assignee, task_type, path, properties, include_snoozed, stale };
+            task_list(options, columns, sort, limit, count, ids_only, no_color)
+        },
+        Some(Command::Show { id, pick: _, format, pretty, no_color }) => with_task_id(id, |id| task_show(id, format, pretty, no_color)),
+        Some(Command::Create { name, description, no_desc, from_stdin, properties, labels, assignee, priority, due, task_type, path, push, remote }) => {
+            let properties = collect_properties(properties, [("assignee", assignee), ("priority", priority), ("due", due), ("type", task_type), ("path", path)]);
+            task_create(name, description, no_desc, from_stdin, properties, labels, push, &remote)
+        },
+        Some(Command::Status { ids, status, force, push, remote, no_color }) => task_status(ids, status, force, push, &remote, no_color),
+        Some(Command::Close { ids, comment, push, remote }) => task_close(ids, comment, push, &remote),
         Some(Command::Get { id, prop_name }) => task_get(id, prop_name),
-        Some(Command::Set { ids, prop_name, value, push, remote, no_color }) => task_set(ids, prop_name, value, push, &remote, no_color),
-        Some(Command::Replace { ids, prop_name, search, replace, regex, push, remote, no_color }) => task_replace(ids, prop_name, search, replace, regex, push, &remote, no_color),
+        Some(Command::Set { ids, prop_name, value, pick: _, add, remove, preview, confirm, force, push, remote, no_color }) => {
+            let list_edit = match (add, remove) {
+                (true, _) => ListEdit::Add,
+                (_, true) => ListEdit::Remove,
+                _ => ListEdit::Set,
+            };
+            with_property_value(&prop_name.clone(), value, |value| task_set(ids, prop_name, value, list_edit, preview, confirm, force, push, &remote, no_color))
+        },
+        Some(Command::Replace { ids, prop_name, search, replace, props, regex, ignore_case, multiline, include_comments, preview, confirm, push, remote, no_color }) => {
+            let (prop_names, search, replace) = match (props, replace) {
+                (Some(props), None) => (props, prop_name, search),
+                (Some(_), Some(_)) => return error_message_kind(ErrorKind::Validation, "ERROR: property name can't be used together with --props".to_string()),
+                (None, replace) => (prop_name.split(',').map(|s| s.trim().to_string()).collect(), search, replace.unwrap()),
+            };
+            let options = ReplaceOptions { regex, ignore_case, multiline, include_comments };
+            task_replace(ids, prop_names, search, replace, options, preview, confirm, push, &remote, no_color)
+        },
         Some(Command::Unset { ids, prop_name }) => task_unset(ids, prop_name),
-        Some(Command::Edit { id, prop_name }) => task_edit(id, prop_name),
+        Some(Command::Snooze { ids, until }) => task_snooze(ids, until),
+        Some(Command::Edit { id, prop_name, pick: _ }) => with_task_id(id, |id| task_edit(id, prop_name)),
+        Some(Command::Append { id, text, body_file, separator }) => task_append(id, text, body_file, separator, false),
+        Some(Command::Prepend { id, text, body_file, separator }) => task_append(id, text, body_file, separator, true),
         Some(Command::Comment { subcommand }) => task_comment(subcommand),
         Some(Command::Label { subcommand }) => task_label(subcommand),
-        Some(Command::Import { ids, format }) => task_import(ids, format),
-        Some(Command::Export { ids, status, limit, format, pretty }) => task_export(ids, status, limit, format, pretty),
-        Some(Command::Pull { ids, limit, status, remote, no_comments, no_labels }) => task_pull(ids, limit, status, &remote, no_comments, no_labels),
-        Some(Command::Push { ids, remote, no_comments, no_labels, no_color }) => task_push(ids, &remote, no_comments, no_labels, no_color),
-        Some(Command::Stats { no_color }) => task_stats(no_color),
-        Some(Command::Delete { ids, status, push, remote }) => task_delete(ids, status, push, &remote),
+        Some(Command::Alias { subcommand }) => task_alias(subcommand),
+        Some(Command::Watch { subcommand }) => task_watch(subcommand),
+        Some(Command::Scan { path, keywords, apply }) => task_scan(path, keywords, apply),
+        Some(Command::Import { from_bundle: Some(path), force, .. }) => task_import_bundle(path, force),
+        Some(Command::Import { ids, format, pattern, .. }) => task_import(ids, format, pattern),
+        Some(Command::Export { with_history: Some(path), .. }) => task_export_bundle(path),
+        Some(Command::Export { ids, status, properties, limit, format, pretty, .. }) => task_export(ids, status, properties, limit, format, pretty),
+        Some(Command::Diff { rev1, rev2, no_color }) => task_diff(rev1, rev2, no_color),
+        Some(Command::Pull { ids, limit, status, remote, labels, author, incremental, no_comments, comments_since, max_comments, no_labels, shallow, resume, prune_properties }) => {
+            let options = PullOptions { limit, status, labels, author, incremental, no_comments, comments_since, max_comments, no_labels, shallow, resume, prune_properties };
+            task_pull(ids, &remote, options)
+        },
+        Some(Command::Push { ids, remote, no_comments, no_labels, no_color, resume, create_missing }) => task_push(ids, &remote, no_comments, no_labels, no_color, resume, create_missing),
+        Some(Command::Inbox { all, no_color }) => task_inbox(all, no_color),
+        Some(Command::Stats { by, cross, cycle_time, no_color }) => task_stats(by, cross, cycle_time, no_color),
+        Some(Command::Calendar { month, week, no_color }) => task_calendar(month, week, no_color),
+        Some(Command::Changelog { since, until, group_by, template }) => task_changelog(since, until, group_by, template),
+        Some(Command::Delete { ids, status, properties, push, remote }) => task_delete(ids, status, properties, push, &remote),
         Some(Command::Clear) => task_clear(),
+        Some(Command::Migrate { layout, index, dry_run }) => task_migrate(dry_run, layout, index),
+        Some(Command::Init { remote, auto_fetch, hooks }) => task_init(remote, auto_fetch, hooks),
+        Some(Command::Remote { subcommand }) => task_remote(subcommand),
+        Some(Command::Publish { remote, force }) => task_publish(remote, force),
+        Some(Command::Fetch { remote, force }) => task_fetch(remote, force),
+        Some(Command::Gc { keep_days, tag, no_repack, dry_run }) => task_gc(keep_days, tag, no_repack, dry_run),
+        Some(Command::Doctor { offline, no_color }) => task_doctor(offline, no_color),
+        #[cfg(feature = "bench")]
+        Some(Command::Bench { count }) => bench::task_bench(count),
         Some(Command::Config { subcommand }) => task_config(subcommand),
+        Some(Command::Do) => task_do(),
+        Some(Command::External(words)) => run_command_alias(words, &mut vec![]),
         None => false
+    }
+}
+
+/// Expands a user-defined command alias and runs it with the rest of the arguments appended,
+/// aliases can refer to other aliases, those starting with `!` run as shell commands.
+fn run_command_alias(mut words: Vec<String>, seen: &mut Vec<String>) -> bool {
+    let name = words.remove(0);
+    let Some(alias) = get_command_alias(&name) else {
+        return error_message_kind(ErrorKind::Validation, format!("Unknown command or alias: {name}"));
+    };
+    if seen.contains(&name) {
+        return error_message_kind(ErrorKind::Validation, format!("Alias loop: {} -> {name}", seen.join(" -> ")));
+    }
+    seen.push(name.clone());
+
+    if let Some(command) = alias.strip_prefix('!') {
+        return match run_shell_alias(&name, command, &words) {
+            Ok(0) => true,
+            Ok(code) => {
+                set_exit_code(code.clamp(1, 255) as u8);
+                false
+            },
+            Err(e) => error_message_kind(ErrorKind::General, format!("ERROR: {e}")),
+        };
+    }
+
+    let expanded = match split_args(&alias) {
+        Ok(expanded) => expanded,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("Invalid alias {name}: {e}")),
     };
-    if success { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+    match parse_with_default_flags(std::iter::once("git-task".to_string()).chain(expanded).chain(words).collect()) {
+        Ok(Args { command: Some(Command::External(words)), .. }) => run_command_alias(words, seen),
+        Ok(args) => run_command(args.command),
+        Err(e) => {
+            let _ = e.print();
+            false
+        },
+    }
+}
+
+/// Runs `git task do` session, every line is parsed and dispatched like the command line arguments.
+fn parse_with_default_flags(args: Vec<String>) -> Result<Args, clap::Error> {
+    Args::try_parse_from(get_default_flags(args.clone()).unwrap_or(args))
+}
+
+/// Inserts the default flags of the command, `GIT_TASK_DEFAULTS_<COMMAND>` environment variable or `task.defaults.<command>` parameter,
+/// right after the command name, so the flags typed by the user come later and take precedence. Subcommands are joined
+/// with dots (underscores for the environment), e.g. `task.defaults.label.list`. `None` if there are no default flags.
+fn get_default_flags(mut args: Vec<String>) -> Option<Vec<String>> {
+    let mut command = Args::command();
+    let mut path = vec![];
+    let mut position = 0;
+    let mut index = 1;
+    while index < args.len() {
+        let word = &args[index];
+        if let Some(option) = word.strip_prefix('-').filter(|option| !option.is_empty() && !option.contains('=')) {
+            let takes_value = command.get_arguments().find(|arg| match option.strip_prefix('-') {
+                Some(long) => arg.get_long() == Some(long),
+                None => option.chars().count() == 1 && arg.get_short() == option.chars().next(),
+            }).is_some_and(|arg| arg.get_action().takes_values());
+            index += if takes_value { 2 } else { 1 };
+            continue;
+        }
+        match command.find_subcommand(word) {
+            Some(subcommand) => {
+                command = subcommand.clone();
+                path.push(command.get_name().to_string());
+                position = index;
+                index += 1;
+            },
+            None => break,
+        }
+    }
+
+    if path.is_empty() {
+        return None;
+    }
+
+    let env_name = format!("GIT_TASK_DEFAULTS_{}", path.join("_").to_uppercase().replace('-', "_"));
+    let defaults = std::env::var(env_name).ok()
+        .or_else(|| gittask::get_config_value(&format!("task.defaults.{}", path.join("."))).ok())
+        .filter(|defaults| !defaults.trim().is_empty())?;
+    match split_args(&defaults) {
+        Ok(defaults) => {
+            args.splice(position + 1..position + 1, defaults);
+            Some(args)
+        },
+        Err(e) => {
+            eprintln!("WARNING: ignoring default flags of {}: {e}", path.join(" "));
+            None
+        },
+    }
+}
+
+fn task_do() -> bool {
+    let commands = Args::command().get_subcommands()
+        .flat_map(|command| std::iter::once(command.get_name()).chain(command.get_visible_aliases()))
+        .map(String::from)
+        .collect::<Vec<_>>();
+
+    let result = palette::run_palette(commands, |words| {
+        match parse_with_default_flags(std::iter::once("git-task".to_string()).chain(words).collect()) {
+            Ok(Args { command: Some(Command::Do), .. }) => error_message_kind(ErrorKind::Validation, "Already in the command palette".to_string()),
+            Ok(args) => run_command(args.command),
+            Err(e) => {
+                let _ = e.print();
+                false
+            },
+        }
+    });
+
+    match result {
+        Ok(_) => true,
+        Err(e) => error_message_kind(ErrorKind::General, format!("ERROR: {e}")),
+    }
+}
+
+fn with_task_id(id: Option<String>, action: impl FnOnce(String) -> bool) -> bool {
+    match id {
+        Some(id) => action(id),
+        None => match picker::pick_task() {
+            Ok(Some(id)) => action(id),
+            Ok(None) => false,
+            Err(e) => error_message_kind(ErrorKind::General, format!("ERROR: {e}")),
+        }
+    }
+}
+
+fn with_property_value(prop_name: &str, value: Option<String>, action: impl FnOnce(String) -> bool) -> bool {
+    let prop_manager = PropertyManager::new();
+    let enum_names = prop_manager.get_enum_names(prop_name);
+    match value {
+        Some(value) => action(value),
+        None if enum_names.is_empty() => error_message_kind(ErrorKind::Validation, format!("Property {prop_name} has no enum values to pick from")),
+        None => match picker::pick_value(&enum_names) {
+            Ok(Some(value)) => action(value),
+            Ok(None) => false,
+            Err(e) => error_message_kind(ErrorKind::General, format!("ERROR: {e}")),
+        }
+    }
+}
+
+fn collect_properties<const N: usize>(properties: Option<Vec<String>>, named: [(&str, Option<String>); N]) -> Vec<String> {
+    let mut result = properties.unwrap_or_default();
+    for (key, value) in named {
+        if let Some(value) = value {
+            result.push(format!("{key}={value}"));
+        }
+    }
+    result
 }
 
 fn task_comment(subcommand: CommentCommand) -> bool {
     match subcommand {
-        CommentCommand::Add { task_id, text, push, remote } => task_comment_add(task_id, text, push, &remote),
+        CommentCommand::Add { ids, text, push, remote } => task_comment_add(ids, text, push, &remote),
         CommentCommand::Edit { task_id, comment_id, push, remote } => task_comment_edit(task_id, comment_id, push, &remote),
+        CommentCommand::History { task_id, comment_id, no_color } => task_comment_history(task_id, comment_id, no_color),
         CommentCommand::Delete { task_id, comment_id, push, remote } => task_comment_delete(task_id, comment_id, push, &remote),
+        CommentCommand::Replace { ids, search, replace, regex, ignore_case, dry_run, no_color } => {
+            let options = ReplaceOptions { regex, ignore_case, multiline: false, include_comments: true };
+            task_replace(ids, vec![], search, replace, options, dry_run, false, false, &None, no_color)
+        },
+        CommentCommand::Search { pattern, ids, regex, ignore_case, no_color } => task_comment_search(pattern, ids, regex, ignore_case, no_color),
     }
 }
 
 fn task_label(subcommand: LabelCommand) -> bool {
     match subcommand {
-        LabelCommand::Add { task_id, name, color, description, push, remote } => task_label_add(task_id, name, color, description, push, &remote),
-        LabelCommand::Delete { task_id, name, push, remote } => task_label_delete(task_id, name, push, &remote),
+        LabelCommand::Add { ids, name, color, description, push, remote } => task_label_add(ids, name, color, description, push, &remote),
+        LabelCommand::Delete { ids, name, push, remote } => task_label_delete(ids, name, push, &remote),
+        LabelCommand::List { task_id, no_color } => task_label_list(task_id, no_color),
+        LabelCommand::Rename { name, new_name, all, push, remote } => task_label_rename(name, new_name, all, push, &remote),
+    }
+}
+
+fn task_alias(subcommand: AliasCommand) -> bool {
+    match subcommand {
+        AliasCommand::Add { task_id, alias } => task_alias_add(task_id, alias),
+        AliasCommand::Delete { alias } => task_alias_delete(alias),
+        AliasCommand::List { task_id } => task_alias_list(task_id),
+    }
+}
+
+fn task_watch(subcommand: WatchCommand) -> bool {
+    match subcommand {
+        WatchCommand::Add { ids, user } => task_watch_add(ids, user),
+        WatchCommand::Remove { ids, user } => task_watch_remove(ids, user),
+        WatchCommand::List { task_id } => task_watch_list(task_id),
+    }
+}
+
+fn task_remote(subcommand: RemoteCommand) -> bool {
+    match subcommand {
+        RemoteCommand::Setup { remote, auto_fetch } => task_remote_setup(remote, auto_fetch),
+        RemoteCommand::Show => task_remote_show(),
     }
 }
 
@@ -632,9 +1549,21 @@ fn task_config(subcommand: ConfigCommand) -> bool {
     match subcommand {
         ConfigCommand::Get { param } => task_config_get(param),
         ConfigCommand::Set { param, value, move_ref } => task_config_set(param, value, move_ref),
-        ConfigCommand::List => task_config_list(),
+        ConfigCommand::List { all } => task_config_list(all),
+        ConfigCommand::Import { allow_exec } => task_config_import(allow_exec),
+        ConfigCommand::Wizard { no_color } => task_config_wizard(no_color),
+        ConfigCommand::Export { pretty } => task_config_export(pretty),
         ConfigCommand::Status { subcommand } => task_config_status(subcommand),
         ConfigCommand::Properties { subcommand } => task_config_properties(subcommand),
+        ConfigCommand::Theme { subcommand } => task_config_theme(subcommand),
+    }
+}
+
+fn task_config_theme(subcommand: ThemeCommand) -> bool {
+    match subcommand {
+        ThemeCommand::List => task_config_theme_list(),
+        ThemeCommand::Set { name } => task_config_theme_set(name),
+        ThemeCommand::Preview { name, no_color } => task_config_theme_preview(name, no_color),
     }
 }
 
@@ -644,6 +1573,7 @@ fn task_config_status(subcommand: StatusCommand) -> bool {
         StatusCommand::Delete { name, force } => task_config_status_delete(name, force),
         StatusCommand::Get { name, param } => task_config_status_get(name, param),
         StatusCommand::Set { name, param, value } => task_config_status_set(name, param, value),
+        StatusCommand::Move { name, position } => task_config_status_move(name, position),
         StatusCommand::List => task_config_status_list(),
         StatusCommand::Import => task_config_status_import(),
         StatusCommand::Export { pretty } => task_config_status_export(pretty),
@@ -653,13 +1583,15 @@ fn task_config_status(subcommand: StatusCommand) -> bool {
 
 fn task_config_properties(subcommand: PropertiesCommand) -> bool {
     match subcommand {
-        PropertiesCommand::Add { name, value_type, color, style, enum_values, cond_format } => task_config_properties_add(name, value_type, color, style, enum_values, cond_format),
+        PropertiesCommand::Add { name, value_type, color, style, enum_values, cond_format, formula } => task_config_properties_add(name, value_type, color, style, enum_values, cond_format, formula),
         PropertiesCommand::Delete { name, force } => task_config_properties_delete(name, force),
         PropertiesCommand::Get { name, param } => task_config_properties_get(name, param),
         PropertiesCommand::Set { name, param, value } => task_config_properties_set(name, param, value),
         PropertiesCommand::Enum { subcommand } => task_config_properties_enum(subcommand),
         PropertiesCommand::CondFormat { subcommand } => task_config_properties_cond_format(subcommand),
         PropertiesCommand::List => task_config_properties_list(),
+        PropertiesCommand::Describe { name } => task_config_properties_describe(name),
+        PropertiesCommand::Schema { pretty } => task_config_properties_schema(pretty),
         PropertiesCommand::Import => task_config_properties_import(),
         PropertiesCommand::Export { pretty } => task_config_properties_export(pretty),
         PropertiesCommand::Reset => task_config_properties_reset(),
@@ -679,7 +1611,9 @@ fn task_config_properties_enum(subcommand: PropertiesEnumCommand) -> bool {
 fn task_config_properties_cond_format(subcommand: PropertiesCondFormatCommand) -> bool {
     match subcommand {
         PropertiesCondFormatCommand::List { name } => task_config_properties_cond_format_list(name),
-        PropertiesCondFormatCommand::Add { name, cond_format_expr, cond_format_color, cond_format_style } => task_config_properties_cond_format_add(name, cond_format_expr, cond_format_color, cond_format_style),
+        PropertiesCondFormatCommand::Add { name, cond_format_expr, cond_format_color, cond_format_style, row } => task_config_properties_cond_format_add(name, cond_format_expr, cond_format_color, cond_format_style, row),
+        PropertiesCondFormatCommand::Set { name, number, cond_format_expr, cond_format_color, cond_format_style, row } => task_config_properties_cond_format_set(name, number, cond_format_expr, cond_format_color, cond_format_style, row),
+        PropertiesCondFormatCommand::Delete { name, number } => task_config_properties_cond_format_delete(name, number),
         PropertiesCondFormatCommand::Clear { name } => task_config_properties_cond_format_clear(name),
     }
 }
\ No newline at end of file
@@ -1,68 +1,218 @@
+pub(crate) mod alias;
+pub(crate) mod calendar;
 pub(crate) mod comment;
 pub(crate) mod config;
+pub(crate) mod diff;
+pub(crate) mod doctor;
+pub(crate) mod filter;
 pub(crate) mod label;
+pub(crate) mod remote;
+pub(crate) mod scan;
+pub(crate) mod stale;
+pub(crate) mod snooze;
+pub(crate) mod sync;
+pub(crate) mod watch;
+pub(crate) mod wip;
 
 use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use chrono::{Local, TimeZone};
-use nu_ansi_term::Color::DarkGray;
-use regex::Regex;
+use chrono::{Local, NaiveDate, TimeZone};
+use regex::{NoExpand, RegexBuilder};
 
-use gittask::{Comment, Label, Task};
+use gittask::{extract_mentions, parse_typed_value, Comment, Label, PropertyValue, Task};
 
-use crate::connectors::{get_matching_remote_connectors, RemoteConnector, RemoteTaskState};
+use crate::ci_import::{parse_junit, parse_log, Failure, DEFAULT_LOG_PATTERN};
+use crate::codeowners::{auto_assign, CodeOwners};
+use crate::connectors::{get_matching_remote_connectors, set_create_missing, CommentWindow, RemoteConnector, RemoteTaskFilter, RemoteTaskState};
+use crate::document::{document_to_task, task_to_document};
+use crate::email_import::{parse_mailbox, Email};
+use crate::i18n::tr;
+use crate::operations::filter::PropertyFilter;
+use crate::operations::sync::{get_local_only_properties, merge_comments, SyncCheckpoint, SyncOutcome, SyncReport};
 use crate::property::PropertyManager;
+use crate::report::tasks_to_html;
 use crate::status::StatusManager;
-use crate::util::{capitalize, colorize_string, error_message, get_text_from_editor, parse_date, parse_ids, read_from_pipe, str_to_color, success_message};
+use crate::template::render;
+use crate::theme::Theme;
+use crate::util::{capitalize, colorize_string, edit_list_value, error_message, error_message_kind, ErrorKind, format_datetime, format_display_datetime, format_duration, get_text_from_editor, is_color_enabled, is_in_path, is_plain_output, join_text, ListEdit, parse_date, parse_ids, parse_key_value, print_diff, read_from_file, read_from_pipe, str_to_color, success_message, unescape};
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn task_create(name: String, description: Option<String>, no_desc: bool, from_stdin: bool, properties: Vec<String>, labels: Option<Vec<String>>, push: bool, remote: &Option<String>) -> bool {
+    let mut properties = match properties.iter().map(|p| parse_key_value(p)).collect::<Result<Vec<_>, _>>() {
+        Ok(properties) => properties,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    match properties.iter_mut().find(|(key, _)| key == "path") {
+        Some((_, path)) => match gittask::resolve_path(path) {
+            Ok(resolved) => *path = resolved,
+            Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+        },
+        None => if let Ok(Some(path)) = gittask::get_current_path() {
+            println!("Path: {path} (from current directory)");
+            properties.push(("path".to_string(), path));
+        },
+    }
+
+    if !properties.iter().any(|(key, _)| key == "component") {
+        let component = properties.iter().find(|(key, _)| key == "path").and_then(|(_, path)| path.rsplit('/').next()).filter(|c| !c.is_empty()).map(|c| c.to_string());
+        if let Some(component) = component {
+            properties.push(("component".to_string(), component));
+        }
+    }
+
+    if let Some((_, due)) = properties.iter().find(|(key, _)| key == "due") {
+        if NaiveDate::parse_from_str(due, "%Y-%m-%d").is_err() {
+            return error_message_kind(ErrorKind::Validation, format!("ERROR: Invalid due date '{due}', expected YYYY-MM-DD"));
+        }
+    }
+
+    let prop_manager = PropertyManager::new();
+    for (key, value) in properties.iter_mut() {
+        match prop_manager.check_enum_value(key, value) {
+            Ok(checked) => *value = checked,
+            Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+        }
+    }
 
-pub(crate) fn task_create(name: String, description: Option<String>, no_desc: bool, push: bool, remote: &Option<String>) -> bool {
     let description = match description {
         Some(description) => description,
-        None => match no_desc {
-            true => String::from(""),
-            false => get_text_from_editor(None).unwrap_or_else(|| String::from(""))
+        None => match (no_desc, from_stdin) {
+            (true, _) => String::from(""),
+            (_, true) => match read_from_pipe() {
+                Some(description) => description,
+                None => return error_message("Can't read from pipe".to_string()),
+            },
+            _ => get_text_from_editor(None).unwrap_or_else(|| String::from(""))
         }
     };
 
     let status_manager = StatusManager::new();
-    let task = Task::new(name, description, status_manager.get_starting_status());
+    let mut task = match Task::new(name, description, status_manager.get_starting_status()) {
+        Ok(task) => task,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    for (key, value) in properties {
+        task.set_property(&key, &value);
+    }
+
+    for label in labels.unwrap_or_default() {
+        task.add_label(label, None, None);
+    }
+
+    if let Some(assignee) = auto_assign(&mut task, &CodeOwners::load()) {
+        println!("Assignee: {assignee} (from CODEOWNERS)");
+    }
 
-    match gittask::create_task(task.unwrap()) {
+    match gittask::create_task(task) {
         Ok(task) => {
-            println!("Task ID {} created", task.get_id().unwrap());
-            let mut success = false;
-            if push {
-                match get_user_repo(remote) {
-                    Ok((connector, user, repo)) => {
-                        match connector.create_remote_task(&user, &repo, &task) {
-                            Ok(id) => {
-                                println!("Sync: Created REMOTE task ID {id}");
-                                match gittask::update_task_id(&task.get_id().unwrap(), &id) {
-                                    Ok(_) => {
-                                        println!("Task ID {} -> {} updated", task.get_id().unwrap(), id);
-                                        success = true;
-                                    },
-                                    Err(e) => eprintln!("ERROR: {e}")
-                                }
-                            },
-                            Err(e) => eprintln!("ERROR: {e}")
-                        }
-                    },
-                    Err(e) => eprintln!("ERROR: {e}")
-                }
+            println!("{}", tr("Task ID {id} created", &[("id", &task.get_id().unwrap())]));
+            if !push {
+                return true;
+            }
+            match get_user_repo(remote) {
+                Ok((connector, user, repo)) => {
+                    match connector.create_remote_task(&user, &repo, &task) {
+                        Ok(id) => {
+                            println!("Sync: Created REMOTE task ID {id}");
+                            match gittask::update_task_id(&task.get_id().unwrap(), &id) {
+                                Ok(_) => success_message(format!("Task ID {} -> {} updated", task.get_id().unwrap(), id)),
+                                Err(e) => error_message(format!("ERROR: {e}"))
+                            }
+                        },
+                        Err(e) => error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"))
+                    }
+                },
+                Err(e) => error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"))
             }
-            success
         },
         Err(e) => error_message(format!("ERROR: {e}"))
     }
 }
 
-pub(crate) fn task_status(ids: String, status: String, push: bool, remote: &Option<String>, no_color: bool) -> bool {
+pub(crate) fn task_status(ids: String, status: String, force: bool, push: bool, remote: &Option<String>, no_color: bool) -> bool {
     let status_manager = StatusManager::new();
     let status = status_manager.get_full_status_name(&status);
 
-    task_set(ids, "status".to_string(), status.clone(), push, remote, no_color)
+    task_set(ids, "status".to_string(), status.clone(), ListEdit::Set, false, false, force, push, remote, no_color)
+}
+
+/// Sets the final status and adds the closing comment to all tasks in a single commit,
+/// with `push` closes the remote issues and posts the comment there too.
+pub(crate) fn task_close(ids: String, comment: Option<String>, push: bool, remote: &Option<String>) -> bool {
+    let status = StatusManager::new().get_final_status();
+
+    let mut tasks = vec![];
+    for task_id in parse_ids(ids) {
+        match gittask::find_task(&task_id) {
+            Ok(Some(task)) => tasks.push(task),
+            Ok(None) => return error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    let comments = tasks.iter_mut().filter_map(|task| {
+        task.set_property("status", &status);
+        comment.as_ref().map(|text| (task.get_id().unwrap(), task.add_comment(None, HashMap::new(), text.clone())))
+    }).collect::<Vec<_>>();
+    let closed = tasks.clone();
+
+    if let Err(e) = gittask::update_tasks(tasks) {
+        return error_message(format!("ERROR: {e}"));
+    }
+    closed.iter().for_each(|task| println!("Task ID {} closed", task.get_id().unwrap()));
+
+    if !push {
+        return true;
+    }
+
+    let (connector, user, repo) = match get_user_repo(remote) {
+        Ok(user_repo) => user_repo,
+        Err(e) => return error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+    };
+
+    let mut success = true;
+    for task in &closed {
+        let task_id = task.get_id().unwrap();
+        match connector.update_remote_task(&user, &repo, task, None, RemoteTaskState::Closed) {
+            Ok(_) => println!("Sync: REMOTE task ID {task_id} has been closed"),
+            Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR closing REMOTE task ID {task_id}: {e}")),
+        }
+    }
+
+    // local comments get the IDs of the remote ones, again in a single commit
+    let mut remote_comment_ids = HashMap::new();
+    for (task_id, comment) in comments {
+        match connector.create_remote_comment(&user, &repo, &task_id, &comment) {
+            Ok(remote_comment_id) => {
+                println!("Created REMOTE comment ID {remote_comment_id}");
+                remote_comment_ids.insert(task_id, (comment.get_id().unwrap(), remote_comment_id));
+            },
+            Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR creating REMOTE comment: {e}")),
+        }
+    }
+
+    if !remote_comment_ids.is_empty() {
+        let tasks = closed.into_iter().filter_map(|mut task| {
+            let (comment_id, remote_comment_id) = remote_comment_ids.get(&task.get_id().unwrap())?;
+            let comments = task.get_comments().iter().flatten().map(|comment| {
+                let mut comment = comment.clone();
+                if comment.get_id().as_ref() == Some(comment_id) {
+                    comment.set_id(remote_comment_id.clone());
+                }
+                comment
+            }).collect();
+            task.set_comments(comments);
+            Some(task)
+        }).collect::<Vec<_>>();
+        if let Err(e) = gittask::update_tasks(tasks) {
+            success = error_message(format!("ERROR: {e}"));
+        }
+    }
+
+    success
 }
 
 pub(crate) fn task_get(id: String, prop_name: String) -> bool {
@@ -70,25 +220,112 @@ pub(crate) fn task_get(id: String, prop_name: String) -> bool {
         Ok(Some(task)) => {
             match task.get_property(&prop_name) {
                 Some(value) => success_message(format!("{value}")),
-                None => error_message(format!("Task property {prop_name} not found"))
+                None => error_message_kind(ErrorKind::NotFound, format!("Task property {prop_name} not found"))
             }
         },
-        Ok(None) => error_message(format!("Task ID {id} not found")),
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
 
-pub(crate) fn task_set(ids: String, prop_name: String, value: String, push: bool, remote: &Option<String>, no_color: bool) -> bool {
+#[derive(PartialEq)]
+enum Review {
+    Apply,
+    Skip,
+    Quit,
+}
+
+/// Shows changes of the task before they are saved, with `confirm` asks whether to apply them.
+struct ChangeReview {
+    preview: bool,
+    confirm: bool,
+    apply_all: bool,
+    no_color: bool,
+}
+
+impl ChangeReview {
+    fn new(preview: bool, confirm: bool, no_color: bool) -> ChangeReview {
+        ChangeReview { preview, confirm, apply_all: false, no_color: check_no_color(no_color) }
+    }
+
+    fn review(&mut self, id: &str, changes: &[(String, String, String)]) -> Review {
+        if !self.preview && (!self.confirm || self.apply_all) {
+            return Review::Apply;
+        }
+
+        for (prop_name, old_value, new_value) in changes {
+            println!("{}", colorize_string(&format!("Task ID {id}: {prop_name}"), Theme::current().title(), self.no_color));
+            print_diff(old_value, new_value, self.no_color);
+        }
+
+        if self.preview {
+            return Review::Skip;
+        }
+
+        loop {
+            print!("Apply changes to task ID {id}? [y]es, [n]o, [a]ll, [q]uit: ");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+            let mut answer = String::new();
+            if std::io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+                return Review::Quit;
+            }
+            match answer.trim().to_lowercase().as_str() {
+                "y" | "yes" => return Review::Apply,
+                "n" | "no" => return Review::Skip,
+                "a" | "all" => {
+                    self.apply_all = true;
+                    return Review::Apply;
+                },
+                "q" | "quit" => return Review::Quit,
+                _ => {},
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn task_set(ids: String, prop_name: String, value: String, list_edit: ListEdit, preview: bool, confirm: bool, force: bool, push: bool, remote: &Option<String>, no_color: bool) -> bool {
+    let prop_manager = PropertyManager::new();
+    if prop_manager.is_computed(&prop_name) {
+        return error_message_kind(ErrorKind::Validation, format!("{prop_name} is computed with a formula and can't be set"));
+    }
+    if list_edit != ListEdit::Set && prop_name == "id" {
+        return error_message_kind(ErrorKind::Validation, "Task ID is not a list".to_string());
+    }
+    let is_list = list_edit != ListEdit::Set || prop_manager.is_list(&prop_name);
+    let value = match list_edit {
+        ListEdit::Remove => Ok(value),
+        _ if is_list => edit_list_value("", &value, ListEdit::Set).and_then(|list| prop_manager.check_enum_value(&prop_name, &list)),
+        _ => prop_manager.check_enum_value(&prop_name, &value),
+    };
+    let value = match value {
+        Ok(value) => value,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+
     let ids = parse_ids(ids);
+    if prop_name == "status" {
+        if let Err(e) = wip::check_wip_limit(&ids, &value, force) {
+            return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}"));
+        }
+    }
+
+    let mut review = ChangeReview::new(preview, confirm, no_color);
     match prop_name.as_str() {
         "id" => {
             for id in &ids {
+                match review.review(id, &[("id".to_string(), id.to_string(), value.clone())]) {
+                    Review::Apply => {},
+                    Review::Skip => continue,
+                    Review::Quit => break,
+                }
+
                 match gittask::update_task_id(&id, &value) {
                     Ok(_) => {
                         println!("Task ID {id} -> {value} updated");
 
                         if push {
-                            task_push(value.clone(), remote, false, false, no_color);
+                            task_push(value.clone(), remote, false, false, no_color, false, false);
                         }
                     },
                     Err(e) => {
@@ -101,14 +338,31 @@ pub(crate) fn task_set(ids: String, prop_name: String, value: String, push: bool
             for id in &ids {
                 match gittask::find_task(&id) {
                     Ok(Some(mut task)) => {
+                        let old_value = task.get_property(&prop_name).cloned().unwrap_or_default();
+                        let value = match is_list {
+                            true => match edit_list_value(&old_value, &value, list_edit) {
+                                Ok(value) => value,
+                                Err(e) => {
+                                    error_message_kind(ErrorKind::Validation, format!("ERROR: Task ID {id}: {e}"));
+                                    continue;
+                                }
+                            },
+                            false => value.clone(),
+                        };
+                        match review.review(id, &[(prop_name.clone(), old_value, value.clone())]) {
+                            Review::Apply => {},
+                            Review::Skip => continue,
+                            Review::Quit => break,
+                        }
+
                         task.set_property(&prop_name, &value);
 
                         match gittask::update_task(task) {
                             Ok(_) => {
-                                println!("Task ID {id} updated");
+                                println!("{}", tr("Task ID {id} updated", &[("id", &id)]));
 
                                 if push {
-                                    task_push(id.to_string(), remote, false, false, no_color);
+                                    task_push(id.to_string(), remote, false, false, no_color, false, false);
                                 }
                             },
                             Err(e) => {
@@ -117,7 +371,7 @@ pub(crate) fn task_set(ids: String, prop_name: String, value: String, push: bool
                         }
                     },
                     Ok(None) => {
-                        error_message(format!("Task ID {id} not found"));
+                        error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)]));
                     },
                     Err(e) =>{
                         error_message(format!("ERROR: {e}"));
@@ -130,64 +384,133 @@ pub(crate) fn task_set(ids: String, prop_name: String, value: String, push: bool
     true
 }
 
-pub(crate) fn task_replace(ids: String, prop_name: String, search: String, replace: String, regex: bool, push: bool, remote: &Option<String>, no_color: bool) -> bool {
+pub(crate) struct ReplaceOptions {
+    pub regex: bool,
+    pub ignore_case: bool,
+    pub multiline: bool,
+    pub include_comments: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn task_replace(ids: String, prop_names: Vec<String>, search: String, replace: String, options: ReplaceOptions, preview: bool, confirm: bool, push: bool, remote: &Option<String>, no_color: bool) -> bool {
     let ids = parse_ids(ids);
-    let regex = match regex {
-        true => Some(Box::new(Regex::new(search.as_str()).unwrap())),
+    let mut review = ChangeReview::new(preview, confirm, no_color);
+    let mut success = true;
+    let matcher = match options.regex || options.ignore_case {
+        true => {
+            let pattern = match options.regex {
+                true => search.clone(),
+                false => regex::escape(&search),
+            };
+            match RegexBuilder::new(&pattern).case_insensitive(options.ignore_case).multi_line(options.multiline).build() {
+                Ok(regex) => Some(regex),
+                Err(e) => return error_message(format!("ERROR: {e}")),
+            }
+        },
         false => None
     };
+    let replace_text = |value: &str| -> String {
+        match &matcher {
+            Some(regex) if options.regex => regex.replace_all(value, replace.as_str()).to_string(),
+            Some(regex) => regex.replace_all(value, NoExpand(&replace)).to_string(),
+            None => value.replace(&search, &replace),
+        }
+    };
+
     for id in ids {
         match gittask::find_task(&id) {
             Ok(Some(mut task)) => {
-                if let Some(value) = task.get_property(&prop_name) {
-                    let new_value = match regex {
-                        Some(ref regex) => regex.replace_all(value.as_str(), search.as_str()).to_string(),
-                        None => value.replace(&search, &replace)
-                    };
-                    task.set_property(&prop_name, &new_value);
-                    match gittask::update_task(task) {
-                        Ok(_) => {
-                            println!("Task ID {id} updated");
-                            if push {
-                                task_push(id.to_string(), remote, false, false, no_color);
-                            }
-                        },
-                        Err(e) => eprintln!("ERROR: {e}")
+                let mut changes = vec![];
+
+                if !prop_names.iter().any(|prop_name| task.has_property(prop_name)) && !options.include_comments {
+                    eprintln!("Task ID {id}: property not found");
+                    continue;
+                }
+
+                for prop_name in &prop_names {
+                    if let Some(value) = task.get_property(prop_name) {
+                        let new_value = replace_text(value);
+                        if new_value != *value {
+                            changes.push((prop_name.clone(), value.clone(), new_value));
+                        }
                     }
-                } else {
-                    eprintln!("Task ID {id}: property not found")
+                }
+
+                let mut comments = task.get_comments().clone().unwrap_or_default();
+                if options.include_comments {
+                    for comment in comments.iter_mut() {
+                        let text = comment.get_text();
+                        let new_text = replace_text(&text);
+                        if new_text != text {
+                            changes.push((format!("comment {}", comment.get_id().unwrap_or_default()), text, new_text.clone()));
+                            comment.edit_text(new_text);
+                        }
+                    }
+                }
+
+                if changes.is_empty() {
+                    continue;
+                }
+
+                match review.review(&id, &changes) {
+                    Review::Apply => {},
+                    Review::Skip => continue,
+                    Review::Quit => break,
+                }
+
+                for (prop_name, _, new_value) in changes.iter().filter(|(prop_name, _, _)| prop_names.contains(prop_name)) {
+                    task.set_property(prop_name, new_value);
+                }
+                if options.include_comments && !comments.is_empty() {
+                    task.set_comments(comments);
+                }
+
+                match gittask::update_task(task) {
+                    Ok(_) => {
+                        println!("{}", tr("Task ID {id} updated", &[("id", &id)]));
+                        if push {
+                            task_push(id.to_string(), remote, false, false, no_color, false, false);
+                        }
+                    },
+                    Err(e) => success = error_message(format!("ERROR: {e}")),
                 }
             },
             _ => {}
         }
     }
 
-    true
+    success
 }
 
 pub(crate) fn task_unset(ids: String, prop_name: String) -> bool {
     let ids = parse_ids(ids);
+    let mut success = true;
     for id in ids {
         match gittask::find_task(&id) {
             Ok(Some(mut task)) => {
                 if task.delete_property(&prop_name) {
                     match gittask::update_task(task) {
-                        Ok(_) => println!("Task ID {id} updated"),
-                        Err(e) => eprintln!("ERROR: {e}")
+                        Ok(_) => println!("{}", tr("Task ID {id} updated", &[("id", &id)])),
+                        Err(e) => success = error_message(format!("ERROR: {e}")),
                     }
                 } else {
                     eprintln!("Task ID {id}: property not found")
                 }
             },
-            Ok(None) => eprintln!("Task ID {id} not found"),
-            Err(e) => eprintln!("ERROR: {e}")
+            Ok(None) => success = error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
+            Err(e) => success = error_message(format!("ERROR: {e}")),
         }
     };
 
-    true
+    success
 }
 
-pub(crate) fn task_edit(id: String, prop_name: String) -> bool {
+pub(crate) fn task_edit(id: String, prop_name: Option<String>) -> bool {
+    if prop_name.is_none() {
+        return task_edit_document(id);
+    }
+    let prop_name = prop_name.unwrap();
+
     match gittask::find_task(&id) {
         Ok(Some(mut task)) => {
             match prop_name.as_str() {
@@ -198,10 +521,10 @@ pub(crate) fn task_edit(id: String, prop_name: String) -> bool {
                             match gittask::update_task(task) {
                                 Ok(_) => {
                                     println!("Task ID {id} -> {text} updated");
-                                    if let Err(e) = gittask::delete_tasks(&[&id]) {
-                                        eprintln!("ERROR: {e}");
+                                    match gittask::delete_tasks(&[&id]) {
+                                        Ok(_) => true,
+                                        Err(e) => error_message(format!("ERROR: {e}")),
                                     }
-                                    true
                                 },
                                 Err(e) => error_message(format!("ERROR: {e}")),
                             }
@@ -216,42 +539,194 @@ pub(crate) fn task_edit(id: String, prop_name: String) -> bool {
                                 Some(text) => {
                                     task.set_property(&prop_name, &text);
                                     match gittask::update_task(task) {
-                                        Ok(_) => success_message(format!("Task ID {id} updated")),
+                                        Ok(_) => success_message(tr("Task ID {id} updated", &[("id", &id)])),
                                         Err(e) => error_message(format!("ERROR: {e}")),
                                     }
                                 },
                                 None => error_message("Editing failed".to_string()),
                             }
                         },
-                        None => error_message(format!("Task property {prop_name} not found"))
+                        None => error_message_kind(ErrorKind::NotFound, format!("Task property {prop_name} not found"))
                     }
                 }
             }
         },
-        Ok(None) => error_message(format!("Task ID {id} not found")),
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
 
-pub(crate) fn task_import(ids: Option<String>, format: Option<String>) -> bool {
-    if let Some(format) = format {
-        if format.to_lowercase() != "json" {
-            return error_message("Only JSON format is supported".to_string());
-        }
+fn task_edit_document(id: String) -> bool {
+    match gittask::find_task(&id) {
+        Ok(Some(task)) => {
+            let document = task_to_document(&task);
+            match get_text_from_editor(Some(&document)) {
+                Some(text) if text == document => success_message("Nothing to update".to_string()),
+                Some(text) => {
+                    match document_to_task(&text, &task, &PropertyManager::new(), &StatusManager::new()) {
+                        Ok(task) => {
+                            match gittask::update_task(task) {
+                                Ok(_) => success_message(tr("Task ID {id} updated", &[("id", &id)])),
+                                Err(e) => error_message(format!("ERROR: {e}")),
+                            }
+                        },
+                        Err(e) => error_message(format!("ERROR: {e}")),
+                    }
+                },
+                None => error_message("Editing failed".to_string()),
+            }
+        },
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_append(id: String, text: Option<String>, body_file: Option<String>, separator: String, prepend: bool) -> bool {
+    let text = match body_file {
+        Some(body_file) => match read_from_file(&body_file) {
+            Some(text) => text,
+            None => return error_message(format!("Can't read from {body_file}")),
+        },
+        None => text.unwrap_or_default(),
+    };
+
+    match gittask::find_task(&id) {
+        Ok(Some(mut task)) => {
+            let description = task.get_property("description").cloned().unwrap_or_default();
+            let description = join_text(&description, &text, &unescape(&separator), prepend);
+            task.set_property("description", &description);
+            match gittask::update_task(task) {
+                Ok(_) => success_message(tr("Task ID {id} updated", &[("id", &id)])),
+                Err(e) => error_message(format!("ERROR: {e}")),
+            }
+        },
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_import(ids: Option<String>, format: Option<String>, pattern: Option<String>) -> bool {
+    let format = format.unwrap_or_else(|| "json".to_string()).to_lowercase();
+    if !["json", "junit", "log", "eml"].contains(&format.as_str()) {
+        return error_message_kind(ErrorKind::Validation, "Only JSON, JUnit, log and eml formats are supported".to_string());
     }
 
     if let Some(input) = read_from_pipe() {
-        import_from_input(ids, &input)
+        match format.as_str() {
+            "junit" => import_failures(parse_junit(&input)),
+            "log" => match parse_log(&input, pattern.as_deref().unwrap_or(DEFAULT_LOG_PATTERN)) {
+                Ok(failures) => import_failures(failures),
+                Err(e) => error_message(format!("ERROR: {e}")),
+            },
+            "eml" => import_emails(parse_mailbox(&input)),
+            _ => import_from_input(ids, &input),
+        }
     } else {
         error_message("Can't read from pipe".to_string())
     }
 }
 
+/// Creates a task per email, messages already imported are recognized by the fingerprint of their Message-ID.
+fn import_emails(emails: Vec<Email>) -> bool {
+    // without a tasks ref there's nothing imported yet
+    let tasks = gittask::get_tasks_commit().and_then(|commit| match commit {
+        Some(_) => gittask::list_tasks(),
+        None => Ok(vec![]),
+    });
+    let mut tasks = match tasks {
+        Ok(tasks) => tasks,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+    let status_manager = StatusManager::new();
+    let mut success = true;
+
+    for email in emails {
+        let key = email.message_id.clone().unwrap_or_else(|| format!("{}\n{}\n{}", email.from, email.subject, email.date.unwrap_or_default()));
+        let fingerprint = gittask::get_fingerprint(&format!("email\n{key}"));
+        if let Some(task) = tasks.iter().find(|task| task.get_property("fingerprint") == Some(&fingerprint)) {
+            println!("Task ID {} already imported from this email", task.get_id().unwrap());
+            continue;
+        }
+
+        let mut task = match Task::new(email.subject, email.body, status_manager.get_starting_status()) {
+            Ok(task) => task,
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        };
+        if !email.from.is_empty() {
+            task.set_property("author", &email.from);
+        }
+        if let Some(date) = email.date {
+            task.set_property("created", &date.to_string());
+        }
+        if let Some(message_id) = &email.message_id {
+            task.set_property("message_id", message_id);
+        }
+        if !email.attachments.is_empty() {
+            task.set_property("attachments", &serde_json::to_string(&email.attachments).unwrap());
+        }
+        task.set_property("fingerprint", &fingerprint);
+        task.add_label("email".to_string(), None, None);
+
+        match gittask::create_task(task) {
+            Ok(task) => {
+                println!("{}", tr("Task ID {id} created", &[("id", &task.get_id().unwrap())]));
+                tasks.push(task);
+            },
+            Err(e) => success = error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    success
+}
+
+fn import_failures(failures: Vec<Failure>) -> bool {
+    let mut tasks = gittask::list_tasks().unwrap_or_default();
+    let status_manager = StatusManager::new();
+    let mut success = true;
+
+    for failure in failures {
+        let fingerprint = gittask::get_fingerprint(&format!("failure\n{}", failure.name));
+        let existing = tasks.iter_mut().find(|task| task.get_property("fingerprint") == Some(&fingerprint));
+
+        let result = match existing {
+            Some(task) => {
+                let count = task.get_property("failure_count").and_then(|count| count.parse::<u64>().ok()).unwrap_or(1) + 1;
+                task.set_property("failure_count", &count.to_string());
+                gittask::update_task(task.clone()).map(|id| format!("Task ID {id} updated, failed {count} times"))
+            },
+            None => {
+                let mut task = match Task::new(failure.name.clone(), failure.message, status_manager.get_starting_status()) {
+                    Ok(task) => task,
+                    Err(e) => return error_message(format!("ERROR: {e}")),
+                };
+                task.set_property("test_name", &failure.name);
+                task.set_property("fingerprint", &fingerprint);
+                task.set_property("failure_count", "1");
+                task.add_label("test-failure".to_string(), None, None);
+                gittask::create_task(task).map(|task| {
+                    let message = tr("Task ID {id} created", &[("id", &task.get_id().unwrap())]);
+                    tasks.push(task);
+                    message
+                })
+            }
+        };
+
+        match result {
+            Ok(message) => println!("{message}"),
+            Err(e) => success = error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    success
+}
+
 fn import_from_input(ids: Option<String>, input: &String) -> bool {
     if let Ok(tasks) = serde_json::from_str::<Vec<Task>>(input) {
         let ids = ids.map(parse_ids);
+        let codeowners = CodeOwners::load();
+        let mut success = true;
 
-        for task in tasks {
+        for mut task in tasks {
             let id = task.get_id().unwrap().to_string();
 
             if let Some(ids) = &ids {
@@ -260,51 +735,126 @@ fn import_from_input(ids: Option<String>, input: &String) -> bool {
                 }
             }
 
+            auto_assign(&mut task, &codeowners);
+
             match gittask::create_task(task) {
                 Ok(_) => println!("Task ID {id} imported"),
-                Err(e) => eprintln!("ERROR: {e}"),
+                Err(e) => success = error_message(format!("ERROR: {e}")),
             }
         }
-        true
+        success
     } else {
         error_message("Can't deserialize input".to_string())
     }
 }
 
-pub(crate) fn task_pull(
-    ids: Option<String>,
-    limit: Option<usize>,
-    status: Option<String>,
-    remote: &Option<String>,
-    no_comments: bool,
-    no_labels: bool,
-) -> bool {
+#[derive(Default)]
+pub(crate) struct PullOptions {
+    pub limit: Option<usize>,
+    pub status: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub author: Option<String>,
+    pub incremental: bool,
+    pub no_comments: bool,
+    pub comments_since: Option<String>,
+    pub max_comments: Option<usize>,
+    pub no_labels: bool,
+    pub shallow: bool,
+    pub resume: bool,
+    pub prune_properties: bool,
+}
+
+impl PullOptions {
+    /// Filters narrowing down the pulled tasks, a checkpoint is resumed only with the same ones.
+    fn checkpoint_filters(&self) -> String {
+        [
+            ("status", self.status.clone()),
+            ("label", self.labels.as_ref().map(|labels| labels.join(","))),
+            ("limit", self.limit.map(|limit| limit.to_string())),
+            ("author", self.author.clone()),
+        ].into_iter()
+            .filter_map(|(name, value)| value.map(|value| format!(" {name}={value}")))
+            .collect()
+    }
+
+    /// Filters and options shaping the pulled tasks, an unchanged issue is skipped only if pulled with the same ones.
+    fn pull_options(&self) -> Option<String> {
+        let options = [
+            ("comments", self.no_comments.then(|| "off".to_string())),
+            ("comments-since", self.comments_since.clone()),
+            ("max-comments", self.max_comments.map(|max| max.to_string())),
+            ("labels", self.no_labels.then(|| "off".to_string())),
+            ("prune-properties", self.prune_properties.then(|| "on".to_string())),
+        ].into_iter()
+            .filter_map(|(name, value)| value.map(|value| format!(" {name}={value}")))
+            .collect::<String>();
+        let options = format!("{}{options}", self.checkpoint_filters());
+        match options.trim() {
+            "" => None,
+            options => Some(options.to_string()),
+        }
+    }
+}
+
+pub(crate) fn task_pull(ids: Option<String>, remote: &Option<String>, options: PullOptions) -> bool {
+    let filters = options.checkpoint_filters();
+    let pull_options = options.pull_options();
+    let PullOptions { limit, status, labels, author, incremental, no_comments, comments_since, max_comments, no_labels, shallow, resume, prune_properties } = options;
+    // shallow tasks are imported without descriptions, comments and labels, `show` completes them later
+    let (no_comments, no_labels) = (no_comments || shallow, no_labels || shallow);
+    let prepare = |task: Task| {
+        let mut task = if shallow { make_shallow(task) } else { task };
+        if let Some(pull_options) = &pull_options {
+            task.set_property(PULL_OPTIONS, pull_options);
+        }
+        task
+    };
+
     match get_user_repo(remote) {
         Ok((connector, user, repo)) => {
             println!("Pulling tasks from {user}/{repo}...");
 
+            let comment_window = CommentWindow {
+                since: parse_date(comments_since).map(|date| date.earliest().unwrap().timestamp()),
+                max: max_comments,
+            };
+
+            let key = match &ids {
+                Some(ids) => format!("{user}/{repo} {ids}{filters}"),
+                None => format!("{user}/{repo}{filters}"),
+            };
+            let mut checkpoint = match SyncCheckpoint::start("pull", &key, resume) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => return error_message_kind(ErrorKind::NotFound, format!("ERROR: {e}")),
+            };
             let ids = ids.map(parse_ids);
 
             let status_manager = StatusManager::new();
-            let task_statuses = vec![
-                status_manager.get_starting_status(),
-                status_manager.get_final_status(),
-            ];
+            let task_statuses = status_manager.get_remote_statuses(connector.name());
 
+            let mut report = SyncReport::default();
             if ids.is_some() {
                 for id in ids.unwrap() {
-                    match connector.get_remote_task(&user, &repo, &id, !no_comments, !no_labels, &task_statuses) {
+                    if checkpoint.is_done(&id) {
+                        continue;
+                    }
+                    match connector.get_remote_task(&user, &repo, &id, !no_comments, &comment_window, !no_labels, &task_statuses) {
                         Some(task) => {
-                            match import_remote_task(task, no_comments) {
-                                Ok(Some(id)) => println!("Task ID {id} updated"),
-                                Ok(None) => println!("Task ID {id} skipped, nothing to update"),
-                                Err(e) => eprintln!("ERROR: {e}"),
+                            let outcome = print_pull_outcome(&id, import_remote_task(prepare(task), &status_manager, no_comments, prune_properties));
+                            if !matches!(outcome, SyncOutcome::Failed(_)) {
+                                checkpoint.mark_done(&id);
                             }
+                            report.add(&id, outcome);
                         },
-                        None => eprintln!("Task ID {id} not found")
+                        None => {
+                            eprintln!("{}", tr("Task ID {id} not found", &[("id", &id)]));
+                            report.add(&id, SyncOutcome::Failed("not found".to_string()));
+                        }
                     }
                 }
-                true
+                report.print(check_no_color(false));
+                checkpoint.finish(&report);
+                pull_result(&report)
             } else {
                 let state = match status {
                     Some(s) => {
@@ -315,67 +865,203 @@ pub(crate) fn task_pull(
                     None => RemoteTaskState::All
                 };
 
-                let tasks = connector.list_remote_tasks(&user, &repo, !no_comments, !no_labels, limit, state, &task_statuses);
+                let remote_key = format!("{user}/{repo}");
+                let mut filter = RemoteTaskFilter {
+                    labels: labels.unwrap_or_default(),
+                    author,
+                    ..Default::default()
+                };
+                if incremental {
+                    match gittask::get_last_pull(&remote_key) {
+                        Ok(Some(last_pull)) => {
+                            println!("Pulling tasks updated since {}", format_display_datetime(last_pull as u64));
+                            filter.updated_since = Some(last_pull);
+                        },
+                        Ok(None) => println!("No previous pull found, pulling all tasks"),
+                        Err(e) => return error_message(format!("ERROR: {e}")),
+                    }
+                }
+
+                // only a pull of all tasks can be the starting point for the next incremental one
+                let complete = limit.is_none() && state == RemoteTaskState::All && filter.labels.is_empty() && filter.author.is_none();
+                let tasks = match connector.list_remote_tasks(&user, &repo, !no_comments, &comment_window, !no_labels, limit, state, &filter, &task_statuses) {
+                    Ok(tasks) => tasks,
+                    Err(e) => {
+                        // recorded under the remote, the run fails without touching the pull watermark
+                        report.add(&remote_key, print_pull_outcome(&remote_key, Err(format!("could not list tasks: {e}"))));
+                        report.print(check_no_color(false));
+                        checkpoint.finish(&report);
+                        return pull_result(&report);
+                    },
+                };
 
                 if tasks.is_empty() {
-                    success_message("No tasks found".to_string())
-                } else {
-                    for task in tasks {
-                        let task_id = task.get_id().unwrap();
-                        match import_remote_task(task, no_comments) {
-                            Ok(Some(id)) => println!("Task ID {id} updated"),
-                            Ok(None) => println!("Task ID {task_id} skipped, nothing to update"),
-                            Err(e) => eprintln!("ERROR: {e}"),
-                        }
+                    println!("No tasks found");
+                }
+                let recorded = filter.updated_since.is_some() || !tasks.is_empty();
+                for task in tasks {
+                    let task_id = task.get_id().unwrap();
+                    if checkpoint.is_done(&task_id) {
+                        continue;
+                    }
+                    let outcome = print_pull_outcome(&task_id, import_remote_task(prepare(task), &status_manager, no_comments, prune_properties));
+                    if !matches!(outcome, SyncOutcome::Failed(_)) {
+                        checkpoint.mark_done(&task_id);
+                    }
+                    report.add(&task_id, outcome);
+                }
+
+                // a resumed pull counts from the start of the interrupted one
+                let mut success = true;
+                if complete && recorded && !report.has_failures() {
+                    if let Err(e) = gittask::set_last_pull(&remote_key, checkpoint.started) {
+                        success = error_message(format!("ERROR: {e}"));
                     }
-                    true
                 }
+                report.print(check_no_color(false));
+                checkpoint.finish(&report);
+                pull_result(&report) && success
             }
         },
-        Err(e) => error_message(format!("ERROR: {e}"))
+        Err(e) => error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"))
     }
 }
 
-fn import_remote_task(remote_task: Task, no_comments: bool) -> Result<Option<String>, String> {
-    match gittask::find_task(&remote_task.get_id().unwrap()) {
-        Ok(Some(mut local_task)) => {
-            if local_task.get_property("name") == remote_task.get_property("name")
-                && local_task.get_property("description") == remote_task.get_property("description")
-                && local_task.get_property("status") == remote_task.get_property("status")
-                && (no_comments || comments_are_equal(local_task.get_comments(), remote_task.get_comments())) {
-                Ok(None)
-            } else {
-                local_task.set_property("name", remote_task.get_property("name").unwrap());
-                local_task.set_property("description", remote_task.get_property("description").unwrap());
-                local_task.set_property("status", remote_task.get_property("status").unwrap());
-                if !no_comments {
-                    if let Some(comments) = remote_task.get_comments() {
-                        local_task.set_comments(comments.to_vec());
-                    }
-                }
+fn print_pull_outcome(id: &str, outcome: Result<SyncOutcome, String>) -> SyncOutcome {
+    let outcome = outcome.unwrap_or_else(SyncOutcome::Failed);
+    match &outcome {
+        SyncOutcome::Created => println!("{}", tr("Task ID {id} created", &[("id", &id)])),
+        SyncOutcome::Updated => println!("{}", tr("Task ID {id} updated", &[("id", &id)])),
+        SyncOutcome::Skipped(_) => println!("Task ID {id} skipped, nothing to update"),
+        SyncOutcome::Failed(e) => {
+            error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+        },
+    }
+    outcome
+}
 
-                match gittask::update_task(local_task) {
-                    Ok(id) => Ok(Some(id)),
-                    Err(e) => Err(e),
-                }
-            }
+fn pull_result(report: &SyncReport) -> bool {
+    match report.has_failures() {
+        true => error_message_kind(ErrorKind::Remote, "ERROR: some tasks could not be pulled".to_string()),
+        false => true,
+    }
+}
+
+/// Marks tasks pulled with `--shallow` that haven't got their description, comments and labels yet.
+pub(crate) const SHALLOW: &str = "shallow";
+
+/// Strips the description of a new remote task, a task that has been pulled in full keeps its local description.
+fn make_shallow(mut remote_task: Task) -> Task {
+    match gittask::find_task(&remote_task.get_id().unwrap()) {
+        Ok(Some(local_task)) if local_task.get_property(SHALLOW).is_none() => {
+            remote_task.set_property("description", local_task.get_property("description").map(String::as_str).unwrap_or_default());
         },
-        Ok(None) => match gittask::create_task(remote_task) {
-            Ok(local_task) => Ok(Some(local_task.get_id().unwrap())),
-            Err(e) => Err(e),
+        _ => {
+            remote_task.set_property("description", "");
+            remote_task.set_property(SHALLOW, "true");
         },
-        Err(e) => Err(e)
     }
+    remote_task
 }
 
-fn comments_are_equal(local_comments: &Option<Vec<Comment>>, remote_comments: &Option<Vec<Comment>>) -> bool {
-    (local_comments.is_none() && remote_comments.is_none())
-    || (local_comments.is_some() && remote_comments.is_some()
-        && local_comments.clone().unwrap() == remote_comments.clone().unwrap()
-    )
+/// Fetches the description, comments and labels of a shallow task from the remote.
+fn hydrate_task(mut task: Task) -> Result<Task, String> {
+    let (connector, user, repo) = get_user_repo(&None)?;
+    let task_id = task.get_id().unwrap();
+    let task_statuses = StatusManager::new().get_remote_statuses(connector.name());
+    let remote_task = connector.get_remote_task(&user, &repo, &task_id, true, &CommentWindow::default(), true, &task_statuses)
+        .ok_or_else(|| format!("REMOTE task ID {task_id} not found"))?;
+
+    task.set_property("description", remote_task.get_property("description").map(String::as_str).unwrap_or_default());
+    task.set_comments(remote_task.get_comments().clone().unwrap_or_default());
+    task.set_labels(remote_task.get_labels().clone().unwrap_or_default());
+    task.delete_property(SHALLOW);
+    gittask::update_task(task.clone())?;
+    Ok(task)
 }
 
-fn get_user_repo(remote: &Option<String>) -> Result<(Box<&'static dyn RemoteConnector>, String, String), String> {
+/// Remote update time seen on the last pull, lets unchanged issues skip the comparison entirely.
+pub(crate) const REMOTE_UPDATED: &str = "remote_updated";
+/// Non-default pull options of the last pull, see `PullOptions::pull_options`.
+pub(crate) const PULL_OPTIONS: &str = "pull_options";
+
+/// Remote comments are merged into the local ones, comments and properties that exist only locally are never dropped
+/// unless `prune_properties` asks to mirror the remote properties exactly.
+fn import_remote_task(mut remote_task: Task, status_manager: &StatusManager, no_comments: bool, prune_properties: bool) -> Result<SyncOutcome, String> {
+    remote_task.refresh_mentions();
+    let remote_updated = remote_task.get_property("updated").cloned();
+    if let Some(remote_updated) = &remote_updated {
+        remote_task.set_property(REMOTE_UPDATED, remote_updated);
+        remote_task.delete_property("updated");
+    }
+    match gittask::find_task(&remote_task.get_id().unwrap()) {
+        Ok(Some(mut local_task)) => {
+            // a local status of the same kind is kept, e.g. IN_PROGRESS for an open remote task or DEPLOYED for a closed one
+            let local_status = local_task.status().to_string();
+            if status_manager.get_statuses().iter().any(|status| status.get_name() == local_status)
+                && status_manager.is_done(&local_status) == status_manager.is_done(remote_task.status()) {
+                remote_task.set_property("status", &local_status);
+            }
+
+            let local_comments = local_task.get_comments().clone().unwrap_or_default();
+            let comments = match (no_comments, remote_task.get_comments()) {
+                (false, Some(remote_comments)) => merge_comments(&local_comments, remote_comments),
+                _ => local_comments.clone(),
+            };
+
+            let local_only = match prune_properties {
+                true => get_local_only_properties(&local_task, &remote_task),
+                false => vec![],
+            };
+            let mirrored = !prune_properties || (local_only.is_empty()
+                && remote_task.get_all_properties().iter().all(|(prop, value)| local_task.get_property(prop) == Some(value)));
+
+            let same_depth = local_task.get_property(SHALLOW) == remote_task.get_property(SHALLOW);
+            let same_options = local_task.get_property(PULL_OPTIONS) == remote_task.get_property(PULL_OPTIONS);
+            if same_depth && same_options && mirrored && remote_updated.is_some() && local_task.get_property(REMOTE_UPDATED) == remote_updated.as_ref() {
+                Ok(SyncOutcome::Skipped("not changed since the last pull".to_string()))
+            } else if same_depth && mirrored && local_task.get_property("name") == remote_task.get_property("name")
+                && local_task.get_property("description") == remote_task.get_property("description")
+                && local_task.get_property("status") == remote_task.get_property("status")
+                && remote_task.get_property("type").is_none_or(|remote_type| local_task.get_property("type") == Some(remote_type))
+                && comments == local_comments {
+                Ok(SyncOutcome::Skipped("nothing to update".to_string()))
+            } else {
+                local_task.set_property("name", remote_task.get_property("name").unwrap());
+                local_task.set_property("description", remote_task.get_property("description").unwrap());
+                local_task.set_property("status", remote_task.get_property("status").unwrap());
+                if let Some(remote_type) = remote_task.get_property("type") {
+                    local_task.set_property("type", remote_type);
+                }
+                if let Some(remote_updated) = &remote_updated {
+                    local_task.set_property(REMOTE_UPDATED, remote_updated);
+                }
+                if remote_task.get_property(SHALLOW).is_none() {
+                    local_task.delete_property(SHALLOW);
+                }
+                match remote_task.get_property(PULL_OPTIONS) {
+                    Some(pull_options) => local_task.set_property(PULL_OPTIONS, pull_options),
+                    None => { local_task.delete_property(PULL_OPTIONS); },
+                }
+                if comments != local_comments {
+                    local_task.set_comments(comments);
+                }
+                if prune_properties {
+                    for (prop, value) in remote_task.get_all_properties() {
+                        local_task.set_property(prop, value);
+                    }
+                    local_only.iter().for_each(|prop| { local_task.delete_property(prop); });
+                }
+
+                gittask::update_task(local_task).map(|_| SyncOutcome::Updated)
+            }
+        },
+        Ok(None) => gittask::create_task(remote_task).map(|_| SyncOutcome::Created),
+        Err(e) => Err(e)
+    }
+}
+
+fn get_user_repo(remote: &Option<String>) -> Result<(Box<&'static dyn RemoteConnector>, String, String), String> {
     match gittask::list_remotes(remote) {
         Ok(remotes) => {
             let user_repo = get_matching_remote_connectors(remotes);
@@ -393,17 +1079,23 @@ fn get_user_repo(remote: &Option<String>) -> Result<(Box<&'static dyn RemoteConn
     }
 }
 
-pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, limit: Option<usize>, format: Option<String>, pretty: bool) -> bool {
-    if let Some(format) = format {
-        if format.to_lowercase() != "json" {
-            return error_message("Only JSON format is supported".to_string());
-        }
+pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, properties: Option<Vec<String>>, limit: Option<usize>, format: Option<String>, pretty: bool) -> bool {
+    let format = format.map(|format| format.to_lowercase()).unwrap_or_else(|| "json".to_string());
+    if format != "json" && format != "html" {
+        return error_message_kind(ErrorKind::Validation, "Only JSON and HTML formats are supported".to_string());
     }
 
+    let properties = match PropertyFilter::parse_all(properties) {
+        Ok(properties) => properties,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+
     match gittask::list_tasks() {
         Ok(mut tasks) => {
             let mut result = vec![];
-            tasks.sort_by_key(|task| task.get_id().unwrap().parse::<u64>().unwrap_or(0));
+            if let Err(e) = gittask::sort_by_task_id(&mut tasks, Task::get_id) {
+                return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}"));
+            }
 
             let status_manager = StatusManager::new();
             let statuses = match status {
@@ -412,6 +1104,7 @@ pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, limi
             };
 
             let ids = ids.map(parse_ids);
+            let prop_manager = PropertyManager::new();
 
             let mut count = 0;
             for task in tasks {
@@ -428,6 +1121,10 @@ pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, limi
                     }
                 }
 
+                if !filter::matches_all(&properties, &task, &prop_manager) {
+                    continue;
+                }
+
                 if let Some(limit) = limit {
                     if count >= limit {
                         break;
@@ -438,6 +1135,10 @@ pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, limi
                 count += 1;
             }
 
+            if format == "html" {
+                return success_message(tasks_to_html(&result, &prop_manager));
+            }
+
             let func = if pretty { serde_json::to_string_pretty } else { serde_json::to_string };
 
             if let Ok(result) = func(&result) {
@@ -450,22 +1151,29 @@ pub(crate) fn task_export(ids: Option<String>, status: Option<Vec<String>>, limi
     }
 }
 
-pub(crate) fn task_push(ids: String, remote: &Option<String>, no_comments: bool, no_labels: bool, no_color: bool) -> bool {
-    let ids = parse_ids(ids);
+pub(crate) fn task_push(ids: String, remote: &Option<String>, no_comments: bool, no_labels: bool, no_color: bool, resume: bool, create_missing: bool) -> bool {
+    // reset for every push, `do` sessions run several commands in one process
+    set_create_missing(create_missing);
 
     match get_user_repo(remote) {
         Ok((connector, user, repo)) => {
+            let mut checkpoint = match SyncCheckpoint::start("push", &format!("{user}/{repo} {ids}"), resume) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => return error_message_kind(ErrorKind::NotFound, format!("ERROR: {e}")),
+            };
             let status_manager = StatusManager::new();
-            let task_statuses = vec![
-                status_manager.get_starting_status(),
-                status_manager.get_final_status(),
-            ];
+            let task_statuses = status_manager.get_remote_statuses(connector.name());
             let no_color = check_no_color(no_color);
-            for id in ids {
+            let mut report = SyncReport::default();
+            for id in parse_ids(ids) {
+                if checkpoint.is_done(&id) {
+                    continue;
+                }
+                let reported = report.len();
                 println!("Sync: task ID {id}");
                 if let Ok(Some(local_task)) = gittask::find_task(&id) {
                     println!("Sync: LOCAL task ID {id} found");
-                    let remote_task = connector.get_remote_task(&user, &repo, &id, !no_comments, !no_labels, &task_statuses);
+                    let remote_task = connector.get_remote_task(&user, &repo, &id, !no_comments, &CommentWindow::default(), !no_labels, &task_statuses);
                     if let Some(remote_task) = remote_task {
                         println!("Sync: REMOTE task ID {id} found");
 
@@ -492,25 +1200,33 @@ pub(crate) fn task_push(ids: String, remote: &Option<String>, no_comments: bool,
                             ) {
                                 Ok(_) => {
                                     println!("Sync: REMOTE task ID {id} has been updated");
+                                    report.add(&id, SyncOutcome::Updated);
                                 },
-                                Err(e) => eprintln!("ERROR: {e}")
+                                Err(e) => {
+                                    error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+                                    report.add(&id, SyncOutcome::Failed(e));
+                                }
                             }
                         } else {
+                            let mut comments_updated = false;
+                            let mut comment_errors = vec![];
                             if !no_comments {
-                                let mut comments_updated = false;
                                 let remote_comment_ids: Vec<String> = remote_task.get_comments().as_ref().unwrap_or(&vec![]).iter().map(|comment| comment.get_id().unwrap()).collect();
                                 for comment in local_task.get_comments().as_ref().unwrap_or(&vec![]) {
                                     let local_comment_id = comment.get_id().unwrap();
                                     if !remote_comment_ids.contains(&local_comment_id) {
-                                        create_remote_comment(&connector, &user, &repo, &id, &comment);
+                                        comment_errors.extend(create_remote_comment(*connector, &user, &repo, &id, comment).err());
                                         comments_updated = true;
                                     }
                                 }
-                                if !comments_updated {
+                            }
+                            match (comments_updated, comment_errors.is_empty()) {
+                                (false, _) => {
                                     println!("Nothing to sync");
-                                }
-                            } else {
-                                println!("Nothing to sync");
+                                    report.add(&id, SyncOutcome::Skipped("nothing to sync".to_string()));
+                                },
+                                (true, true) => report.add(&id, SyncOutcome::Updated),
+                                (true, false) => report.add(&id, SyncOutcome::Failed(comment_errors.join("; "))),
                             }
                         }
                     } else {
@@ -528,66 +1244,97 @@ pub(crate) fn task_push(ids: String, remote: &Option<String>, no_comments: bool,
                         match connector.create_remote_task(&user, &repo, &local_task) {
                             Ok(id) => {
                                 println!("Sync: Created REMOTE task ID {id}");
+                                let mut errors = vec![];
                                 if local_task.get_id().unwrap() != id {
                                     match gittask::update_task_id(&local_task.get_id().unwrap(), &id) {
                                         Ok(_) => println!("Task ID {} -> {} updated", local_task.get_id().unwrap(), id),
-                                        Err(e) => eprintln!("ERROR: {e}"),
+                                        Err(e) => {
+                                            error_message(format!("ERROR: {e}"));
+                                            errors.push(e);
+                                        },
                                     }
                                 }
 
                                 if !no_comments {
                                     if let Some(comments) = local_task.get_comments() {
-                                        if !comments.is_empty() {
-                                            for comment in comments {
-                                                create_remote_comment(&connector, &user, &repo, &id, &comment);
-                                            }
+                                        for comment in comments {
+                                            errors.extend(create_remote_comment(*connector, &user, &repo, &id, comment).err());
                                         }
                                     }
                                 }
+                                match errors.is_empty() {
+                                    true => report.add(&id, SyncOutcome::Created),
+                                    false => report.add(&id, SyncOutcome::Failed(errors.join("; "))),
+                                }
                             },
-                            Err(e) => eprintln!("ERROR: {e}")
+                            Err(e) => {
+                                error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+                                report.add(&id, SyncOutcome::Failed(e));
+                            }
                         }
                     }
                 } else {
-                    eprintln!("Sync: LOCAL task ID {id} NOT found")
+                    error_message_kind(ErrorKind::NotFound, format!("Sync: LOCAL task ID {id} NOT found"));
+                    report.add(&id, SyncOutcome::Failed("local task not found".to_string()));
+                }
+                if !report.has_failures_since(reported) {
+                    checkpoint.mark_done(&id);
                 }
             }
-            true
+            report.print(no_color);
+            checkpoint.finish(&report);
+            !report.has_failures()
         },
-        Err(e) => error_message(format!("ERROR: {e}"))
+        Err(e) => error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"))
     }
 }
 
-fn create_remote_comment(connector: &Box<&'static dyn RemoteConnector>, user: &String, repo: &String, id: &String, comment: &Comment) {
+fn create_remote_comment(connector: &dyn RemoteConnector, user: &String, repo: &String, id: &String, comment: &Comment) -> Result<(), String> {
     let local_comment_id = comment.get_id().unwrap();
     match connector.create_remote_comment(user, repo, id, comment) {
         Ok(remote_comment_id) => {
             println!("Created REMOTE comment ID {}", remote_comment_id);
             match gittask::update_comment_id(&id, &local_comment_id, &remote_comment_id) {
-                Ok(_) => println!("Comment ID {} -> {} updated", local_comment_id, remote_comment_id),
-                Err(e) => eprintln!("ERROR: {e}"),
+                Ok(_) => {
+                    println!("Comment ID {} -> {} updated", local_comment_id, remote_comment_id);
+                    Ok(())
+                },
+                Err(e) => {
+                    error_message(format!("ERROR: {e}"));
+                    Err(e)
+                },
             }
         },
-        Err(e) => eprintln!("ERROR creating REMOTE comment: {}", e)
+        Err(e) => {
+            error_message_kind(ErrorKind::Remote, format!("ERROR creating REMOTE comment: {e}"));
+            Err(format!("comment {local_comment_id}: {e}"))
+        }
     }
 }
 
-pub(crate) fn task_delete(ids: Option<String>, status: Option<Vec<String>>, push: bool, remote: &Option<String>) -> bool {
-    let ids = match status {
-        Some(statuses) => {
+pub(crate) fn task_delete(ids: Option<String>, status: Option<Vec<String>>, properties: Option<Vec<String>>, push: bool, remote: &Option<String>) -> bool {
+    let properties = match PropertyFilter::parse_all(properties) {
+        Ok(properties) => properties,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+
+    let ids = match ids {
+        Some(ids) => Ok(parse_ids(ids)),
+        None => {
             match gittask::list_tasks() {
                 Ok(tasks) => {
                     let status_manager = StatusManager::new();
-                    let statuses = statuses.iter().map(|s| status_manager.get_full_status_name(s)).collect::<Vec<_>>();
-                    let ids = tasks.iter().filter(|task| statuses.contains(task.get_property("status").unwrap())).map(|task| task.get_id().unwrap()).collect::<Vec<_>>();
+                    let prop_manager = PropertyManager::new();
+                    let statuses = status.map(|statuses| statuses.iter().map(|s| status_manager.get_full_status_name(s)).collect::<Vec<_>>());
+                    let ids = tasks.iter()
+                        .filter(|task| statuses.as_ref().is_none_or(|statuses| statuses.contains(task.get_property("status").unwrap())))
+                        .filter(|task| filter::matches_all(&properties, task, &prop_manager))
+                        .map(|task| task.get_id().unwrap())
+                        .collect::<Vec<_>>();
                     Ok(ids)
                 },
                 Err(e) => Err(e)
             }
-        },
-        None => {
-            let ids = parse_ids(ids.unwrap());
-            Ok(ids)
         }
     };
 
@@ -596,24 +1343,26 @@ pub(crate) fn task_delete(ids: Option<String>, status: Option<Vec<String>>, push
     }
 
     let ids = ids.unwrap();
+    if ids.is_empty() {
+        return success_message(tr("No matching tasks to delete", &[]));
+    }
     let ids = ids.iter().map(|id| id.as_str()).collect::<Vec<_>>();
 
     match gittask::delete_tasks(&ids) {
         Ok(_) => {
-            println!("Task(s) {} deleted", ids.join(", "));
-            let mut success = false;
+            println!("{}", tr("Task(s) {ids} deleted", &[("ids", &ids.join(", "))]));
+            let mut success = true;
             if push {
                 match get_user_repo(remote) {
                     Ok((connector, user, repo)) => {
                         for id in ids {
                             match connector.delete_remote_task(&user, &repo, &id.to_string()) {
                                 Ok(_) => println!("Sync: REMOTE task ID {id} has been deleted"),
-                                Err(e) => eprintln!("ERROR: {e}")
+                                Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                             }
                         }
-                        success = true;
                     },
-                    Err(e) => eprintln!("ERROR: {e}"),
+                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                 }
             }
 
@@ -625,92 +1374,244 @@ pub(crate) fn task_delete(ids: Option<String>, status: Option<Vec<String>>, push
 
 pub(crate) fn task_clear() -> bool {
     match gittask::clear_tasks() {
-        Ok(task_count) => success_message(format!("{task_count} task(s) deleted")),
+        Ok(task_count) => success_message(tr("{count} task(s) deleted", &[("count", &task_count)])),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
 
-pub(crate) fn task_show(id: String, no_color: bool) -> bool {
+pub(crate) fn task_init(remote: Option<String>, auto_fetch: bool, hooks: bool) -> bool {
+    match gittask::init_tasks_ref() {
+        Ok(true) => println!("Tasks ref {} created", gittask::get_ref_path()),
+        Ok(false) => println!("Tasks ref {} already exists", gittask::get_ref_path()),
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    }
+
+    if gittask::get_config_value("task.statuses").is_err() {
+        match StatusManager::new().set_defaults() {
+            Ok(_) => println!("Default statuses written to task.statuses"),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    if gittask::get_config_value("task.properties").is_err() {
+        match PropertyManager::new().set_defaults() {
+            Ok(_) => println!("Default properties written to task.properties"),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    if (remote.is_some() || auto_fetch) && !remote::task_remote_setup(remote, auto_fetch) {
+        return false;
+    }
+
+    if hooks {
+        match remote::install_push_hook() {
+            Ok(true) => println!("pre-push hook installed, git push publishes tasks too"),
+            Ok(false) => println!("pre-push hook already exists, add git task publish --remote \"$1\" to it to publish tasks on push"),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    success_message(r#"
+Quickstart:
+    git task create "My first task"    create a task
+    git task list                      list tasks
+    git task status 1 i                start working on it
+    git task pull                      import issues from GitHub, GitLab or Jira
+    git task publish                   share tasks through the git remote
+    git task fetch                     get tasks shared by others"#.to_string())
+}
+
+pub(crate) fn task_gc(keep_days: u64, tag: Option<String>, no_repack: bool, dry_run: bool) -> bool {
+    match gittask::compact_history(keep_days, tag.as_deref(), dry_run) {
+        Ok((0, kept)) => return success_message(format!("Nothing to compact, {kept} recent commit(s) kept")),
+        Ok((squashed, kept)) if dry_run => return success_message(format!("{squashed} commit(s) would be squashed, {kept} recent commit(s) kept")),
+        Ok((squashed, kept)) => println!("{squashed} commit(s) squashed, {kept} recent commit(s) kept"),
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    }
+
+    if let Some(tag) = &tag {
+        println!("Old history is preserved under refs/tags/{tag}");
+    }
+
+    if no_repack {
+        return true;
+    }
+
+    let git_dir = match gittask::get_git_dir() {
+        Ok(git_dir) => git_dir,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+    match std::process::Command::new("git").arg("--git-dir").arg(&git_dir).args(["gc", "--quiet"]).status() {
+        Ok(status) if status.success() => success_message("Repository repacked".to_string()),
+        Ok(status) => error_message(format!("ERROR: git gc failed with {status}")),
+        Err(e) => error_message(format!("ERROR: could not run git gc: {e}")),
+    }
+}
+
+pub(crate) fn task_migrate(dry_run: bool, layout: Option<String>, index: Option<String>) -> bool {
+    let version = match gittask::get_format_version() {
+        Ok(None) => return success_message("No tasks found, nothing to migrate".to_string()),
+        Ok(Some(version)) => version,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    if version == gittask::FORMAT_VERSION {
+        println!("Tasks format is up to date (version {version})");
+    } else {
+        match gittask::migrate_tasks(dry_run) {
+            Ok((version, ids)) => {
+                ids.iter().for_each(|id| println!("Task ID {id} {}", if dry_run { "would be updated" } else { "updated" }));
+                match dry_run {
+                    true => println!("Tasks format {version} -> {} can be migrated, {} task(s) would be updated", gittask::FORMAT_VERSION, ids.len()),
+                    false => println!("Tasks format {version} -> {} migrated, {} task(s) updated", gittask::FORMAT_VERSION, ids.len()),
+                }
+            },
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    if let Some(layout) = layout {
+        let sharded = layout == "sharded";
+        let success = match gittask::is_sharded_layout() {
+            Ok(current) if current == sharded => success_message(format!("Tasks layout is already {layout}")),
+            Ok(_) if dry_run => success_message(format!("Tasks layout can be changed to {layout}")),
+            Ok(_) => match gittask::set_layout(sharded) {
+                Ok(task_count) => success_message(format!("Tasks layout changed to {layout}, {task_count} task(s) moved")),
+                Err(e) => error_message(format!("ERROR: {e}")),
+            },
+            Err(e) => error_message(format!("ERROR: {e}")),
+        };
+        if !success {
+            return false;
+        }
+    }
+
+    match index {
+        Some(index) => {
+            let enabled = index == "on";
+            match gittask::has_index() {
+                Ok(false) if !enabled => success_message("Tasks index is already dropped".to_string()),
+                Ok(_) if dry_run => success_message(format!("Tasks index can be {}", if enabled { "built" } else { "dropped" })),
+                Ok(_) => match gittask::set_index(enabled) {
+                    Ok(_) => success_message(format!("Tasks index {}", if enabled { "built" } else { "dropped" })),
+                    Err(e) => error_message(format!("ERROR: {e}")),
+                },
+                Err(e) => error_message(format!("ERROR: {e}")),
+            }
+        },
+        None => true,
+    }
+}
+
+pub(crate) fn task_show(id: String, format: Option<String>, pretty: bool, no_color: bool) -> bool {
     match gittask::find_task(&id) {
         Ok(Some(task)) => {
+            let task = match task.get_property(SHALLOW).is_some() && !gittask::is_readonly() {
+                true => hydrate_task(task.clone()).unwrap_or_else(|e| {
+                    eprintln!("WARNING: could not fetch the rest of task ID {id}: {e}");
+                    task
+                }),
+                false => task,
+            };
+            if format.as_deref() == Some("json") {
+                let func = if pretty { serde_json::to_string_pretty } else { serde_json::to_string };
+                return match PropertyManager::new().describe_task(&task).and_then(|json| func(&json).map_err(|e| e.to_string())) {
+                    Ok(json) => success_message(json),
+                    Err(e) => error_message(format!("ERROR serializing task: {e}")),
+                };
+            }
             let no_color = check_no_color(no_color);
             print_task(task, no_color);
             true
         },
-        Ok(None) => error_message(format!("Task ID {id} not found")),
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &id)])),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
 
-fn print_task(task: Task, no_color: bool) {
+const DEFAULT_SHOW_LAYOUT: &str = "id, created, author, name, labels, status, *, description";
+
+fn print_task(mut task: Task, no_color: bool) {
     let prop_manager = PropertyManager::new();
+    prop_manager.apply_formulas(&mut task);
     let properties = prop_manager.get_properties();
+    let status_manager = StatusManager::new();
+    let theme = Theme::current();
     let context = extract_task_context(&task);
 
-    let id_title = colorize_string("ID", DarkGray, no_color);
-    println!("{}: {}", id_title, task.get_id().unwrap_or("---".to_owned()));
+    let layout = gittask::get_config_value("task.show.layout").ok().filter(|layout| !layout.trim().is_empty()).unwrap_or_else(|| DEFAULT_SHOW_LAYOUT.to_string());
+    let hidden = gittask::get_config_value("task.show.hidden").unwrap_or_default();
+    let task_properties = task.get_all_properties().keys().map(|key| key.as_str()).collect::<Vec<_>>();
 
     let empty_string = String::new();
+    let mut section = None;
 
-    let created = task.get_property("created").unwrap_or(&empty_string);
-    if !created.is_empty() {
-        let created_title = colorize_string("Created", DarkGray, no_color);
-        println!("{}: {}", created_title, prop_manager.format_value("created", created, &context, properties, true));
-    }
-
-    let author = task.get_property("author").unwrap_or(&empty_string);
-    if !author.is_empty() {
-        let author_title = colorize_string("Author", DarkGray, no_color);
-        println!("{}: {}", author_title, prop_manager.format_value("author", author, &context, properties, no_color));
-    }
-
-    let name_title = colorize_string("Name", DarkGray, no_color);
-    println!("{}: {}", name_title, prop_manager.format_value("name", task.get_property("name").unwrap(), &context, properties, no_color));
+    for item in get_show_layout(&layout, &hidden, &task_properties) {
+        if let Some(title) = item.strip_prefix('[').and_then(|item| item.strip_suffix(']')) {
+            section = Some(title.to_string());
+            continue;
+        }
 
-    if let Some(labels) = task.get_labels() {
-        if !labels.is_empty() {
-            let labels_title = colorize_string("Labels", DarkGray, no_color);
-            print!("{labels_title}: ");
+        let value = task.get_property(&item).unwrap_or(&empty_string);
+        let is_empty = match item.as_str() {
+            "id" | "name" => false,
+            "labels" => task.get_labels().as_ref().is_none_or(|labels| labels.is_empty()),
+            _ => value.is_empty(),
+        };
+        if is_empty {
+            continue;
+        }
 
-            for label in labels {
-                print_label(label, no_color);
+        if let Some(title) = section.take() {
+            match is_plain_output() {
+                true => println!("{title}:"),
+                false => println!("{}", colorize_string(&format!("--- {title} ---"), theme.separator(), no_color)),
             }
-
-            println!();
         }
-    }
-
-    let status_manager = StatusManager::new();
-    let status_title = colorize_string("Status", DarkGray, no_color);
-    println!("{}: {}", status_title, status_manager.format_status(task.get_property("status").unwrap(), no_color));
-
-    task.get_all_properties().iter().filter(|entry| {
-        entry.0 != "name" && entry.0 != "status" && entry.0 != "description" && entry.0 != "created" && entry.0 != "author"
-    }).for_each(|entry| {
-        let title = colorize_string(&capitalize(entry.0), DarkGray, no_color);
-        println!("{}: {}", title, prop_manager.format_value(entry.0, entry.1, &context, properties, no_color));
-    });
 
-    let description = task.get_property("description").unwrap_or(&empty_string);
-    if !description.is_empty() {
-        let description_title = colorize_string("Description", DarkGray, no_color);
-        println!("{}: {}", description_title, prop_manager.format_value("description", description, &context, properties, no_color));
+        let title = colorize_string(&tr(&capitalize(&item), &[]), theme.title(), no_color);
+        match item.as_str() {
+            "id" => println!("{}: {}", colorize_string("ID", theme.title(), no_color), task.get_id().unwrap_or("---".to_owned())),
+            "created" => println!("{}: {}", title, prop_manager.format_value("created", value, &context, properties, true)),
+            "status" => println!("{}: {}", title, status_manager.format_status(value, no_color)),
+            "labels" => println!("{title}: {}", format_labels(task.get_labels().iter().flatten(), no_color)),
+            _ => println!("{}: {}", title, prop_manager.format_value(&item, value, &context, properties, no_color)),
+        }
     }
 
     if let Some(comments) = task.get_comments() {
         for comment in comments {
-            print_comment(comment, &prop_manager, no_color);
+            print_comment(comment, &prop_manager, &theme, no_color);
         }
     }
 }
 
-fn print_comment(comment: &Comment, prop_manager: &PropertyManager, no_color: bool) {
-    let separator = colorize_string("---------------", DarkGray, no_color);
-    println!("{}", separator);
+/// Resolves `task.show.layout` into property names and `[Section]` titles in the order of appearance,
+/// `*` stands for the rest of the task properties neither mentioned in the layout nor listed in `task.show.hidden`.
+fn get_show_layout(layout: &str, hidden: &str, properties: &[&str]) -> Vec<String> {
+    let items = layout.split(',').map(str::trim).filter(|item| !item.is_empty()).collect::<Vec<_>>();
+    let hidden = hidden.split(',').map(str::trim).collect::<Vec<_>>();
+
+    let mut rest = properties.iter().filter(|property| !items.contains(property) && !hidden.contains(property)).collect::<Vec<_>>();
+    rest.sort();
+
+    items.iter().flat_map(|item| match *item {
+        "*" => rest.iter().map(|property| property.to_string()).collect::<Vec<_>>(),
+        item => vec![item.to_string()],
+    }).collect()
+}
+
+fn print_comment(comment: &Comment, prop_manager: &PropertyManager, theme: &Theme, no_color: bool) {
+    match is_plain_output() {
+        true => println!(),
+        false => println!("{}", colorize_string("---------------", theme.separator(), no_color)),
+    }
 
     if let Some(id) = comment.get_id() {
-        let id_title = colorize_string("Comment ID", DarkGray, no_color);
-        println!("{}: {}", id_title, id);
+        let id_title = colorize_string(&tr("Comment ID", &[]), theme.title(), no_color);
+        let edited = if comment.is_edited() { colorize_string(&tr(" (edited)", &[]), theme.title(), no_color) } else { String::new() };
+        println!("{}: {}{}", id_title, id, edited);
     }
 
     let empty_string = String::new();
@@ -718,147 +1619,174 @@ fn print_comment(comment: &Comment, prop_manager: &PropertyManager, no_color: bo
 
     let created = comment_properties.get("created").unwrap_or(&empty_string);
     if !created.is_empty() {
-        let created_title = colorize_string("Created", DarkGray, no_color);
+        let created_title = colorize_string(&tr("Created", &[]), theme.title(), no_color);
         println!("{}: {}", created_title, prop_manager.format_value("created", created, comment_properties, prop_manager.get_properties(), true));
     }
 
     let author = comment_properties.get("author").unwrap_or(&empty_string);
     if !author.is_empty() {
-        let author_title = colorize_string("Author", DarkGray, no_color);
+        let author_title = colorize_string(&tr("Author", &[]), theme.title(), no_color);
         println!("{}: {}", author_title, prop_manager.format_value("author", author, comment_properties, prop_manager.get_properties(), no_color));
     }
 
     println!("{}", comment.get_text());
 }
 
-fn print_label(label: &Label, no_color: bool) {
-    match no_color {
-        true => print!("{}", label.get_name()),
-        false => {
-            let color = str_to_color(label.get_color().as_str(), &None);
-            print!("{} ", color.paint(label.get_name()));
-        }
-    }
+/// Label names separated by commas, each one in its own color.
+pub(crate) fn format_labels<'a>(labels: impl Iterator<Item = &'a Label>, no_color: bool) -> String {
+    labels.map(|label| colorize_string(&label.get_name(), str_to_color(&label.get_color(), &None), no_color)).collect::<Vec<_>>().join(", ")
 }
 
-fn make_comparison(first: &Task, second: &Task, prop: &str, value_type: &str) -> Ordering {
-    match prop {
-        "id" => {
-            let first_value = match first.get_id() {
-                Some(value) => value.parse::<u64>().unwrap_or(0),
-                _ => 0,
-            };
-            let second_value = match second.get_id() {
-                Some(value) => value.parse::<u64>().unwrap_or(0),
-                _ => 0,
-            };
-
-            first_value.cmp(&second_value)
+fn make_comparison(first: &Task, second: &Task, prop: &str, value_type: &str, status_manager: &StatusManager) -> Ordering {
+    match (prop, value_type) {
+        ("status", _) => {
+            let order = |task: &Task| task.get_property("status").map(|status| status_manager.get_order(status)).unwrap_or(usize::MAX);
+            order(first).cmp(&order(second))
+        },
+        ("id", _) => {
+            // IDs are checked by sort_by_task_id before
+            let parse = |task: &Task| task.get_id().and_then(|id| gittask::parse_task_id(&id).ok()).unwrap_or_default();
+            parse(first).cmp(&parse(second))
+        },
+        ("updated", _) => {
+            let parse = |task: &Task| task.updated_at().map(|updated| updated.timestamp()).unwrap_or(0);
+            parse(first).cmp(&parse(second))
+        },
+        (_, "integer" | "datetime") => {
+            let parse = |task: &Task| task.get_property(prop).and_then(|value| parse_typed_value::<i64>(prop, value, Some(value_type)).ok()).unwrap_or(0);
+            parse(first).cmp(&parse(second))
         },
         _ => {
-            match value_type {
-                "integer" => {
-                    let first_value = match first.get_property(prop) {
-                        Some(value) => value.parse::<u64>().unwrap_or(0),
-                        _ => 0,
-                    };
-                    let second_value = match second.get_property(prop) {
-                        Some(value) => value.parse::<u64>().unwrap_or(0),
-                        _ => 0,
-                    };
-
-                    first_value.cmp(&second_value)
-                },
-                _ => {
-                    let first_value = match first.get_property(prop) {
-                        Some(value) => value.to_lowercase(),
-                        _ => String::new(),
-                    };
-                    let second_value = match second.get_property(prop) {
-                        Some(value) => value.to_lowercase(),
-                        _ => String::new(),
-                    };
-
-                    first_value.cmp(&second_value)
-                }
-            }
+            let lowercase = |task: &Task| task.get_property(prop).map(|value| value.to_lowercase()).unwrap_or_default();
+            lowercase(first).cmp(&lowercase(second))
         }
     }
 }
 
-pub(crate) fn task_list(status: Option<Vec<String>>,
-             keyword: Option<String>,
-             from: Option<String>,
-             until: Option<String>,
-             author: Option<String>,
-             columns: Option<Vec<String>>,
-             sort: Option<Vec<String>>,
-             limit: Option<usize>,
-             no_color: bool) -> bool {
-    match gittask::list_tasks() {
-        Ok(mut tasks) => {
-            let prop_manager = PropertyManager::new();
-            let sort = match sort {
-                Some(sort) => Some(sort),
-                None => match gittask::get_config_value("task.list.sort") {
-                    Ok(sort) => {
-                        Some(sort.split(",").map(|s| s.trim().to_string()).collect())
-                    },
-                    _ => None
-                }
-            };
-            tasks.sort_by(|a, b| {
-                match &sort {
-                    Some(sort) if !sort.is_empty() => {
-                        let mut ordering = None;
-                        for s in sort {
-                            let mut s = s.trim();
-                            let comparison;
-                            if s.to_lowercase().ends_with(" desc") {
-                                s = s[..(s.len() - "desc".len())].trim();
-                                comparison = make_comparison(b, a, s, &prop_manager.get_parameter(&s, "value_type").unwrap_or_else(|| String::from("")));
-                            } else {
-                                if s.to_lowercase().ends_with(" asc") {
-                                    s = s[..(s.len() - "asc".len())].trim();
-                                }
-                                comparison = make_comparison(a, b, s, &prop_manager.get_parameter(&s, "value_type").unwrap_or_else(|| String::from("")));
-                            }
+/// Filters of `list`.
+#[derive(Default)]
+pub(crate) struct ListOptions {
+    pub status: Option<Vec<String>>,
+    pub keyword: Option<String>,
+    pub from: Option<String>,
+    pub until: Option<String>,
+    pub updated_since: Option<String>,
+    pub author: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub assignee: Option<String>,
+    pub task_type: Option<String>,
+    pub path: Option<String>,
+    pub properties: Option<Vec<String>>,
+    pub include_snoozed: bool,
+    pub stale: Option<Option<i64>>,
+}
 
-                            if ordering.is_none() {
-                                ordering = Some(comparison);
-                            } else {
-                                ordering = Some(ordering.unwrap().then(comparison));
-                            }
-                        }
+pub(crate) fn task_list(options: ListOptions, columns: Option<Vec<String>>, sort: Option<Vec<String>>, limit: Option<usize>, count_only: bool, ids_only: bool, no_color: bool) -> bool {
+    let ListOptions { status, keyword, from, until, updated_since, author, labels, assignee, task_type, path, properties, include_snoozed, stale } = options;
+    let properties = match PropertyFilter::parse_all(properties) {
+        Ok(properties) => properties,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+    // the root of the working tree matches all tasks
+    let path = match path.map(|path| gittask::resolve_path(&path)).transpose() {
+        Ok(path) => path.filter(|path| !path.is_empty()),
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
 
-                        ordering.unwrap()
-                    },
-                    _ => b.get_id().unwrap().parse::<u64>().unwrap_or(0).cmp(&a.get_id().unwrap().parse::<u64>().unwrap_or(0))
-                }
+    let sort = match sort {
+        Some(sort) => Some(sort),
+        None => match gittask::get_config_value("task.list.sort") {
+            Ok(sort) => {
+                Some(sort.split(",").map(|s| s.trim().to_string()).collect())
+            },
+            _ => None
+        }
+    };
+
+    let columns = match columns {
+        Some(columns) => Some(columns),
+        None => match gittask::get_config_value("task.list.columns") {
+            Ok(list_columns) => {
+                Some(list_columns.split(",").map(|s| s.trim().to_string()).collect())
+            },
+            _ => None
+        }
+    };
+
+    let status_manager = StatusManager::new();
+    let statuses = status.map(|statuses| statuses.iter().map(|s| status_manager.get_full_status_name(s)).collect::<Vec<_>>());
+
+    let mut index_filters = vec![];
+    if let Some(statuses) = &statuses {
+        index_filters.push(("status", statuses.clone()));
+    }
+    if let Some(labels) = &labels {
+        index_filters.push(("label", labels.clone()));
+    }
+    if let Some(assignee) = &assignee {
+        index_filters.push(("assignee", vec![assignee.clone()]));
+    }
+
+    let uses_description = |list: &Option<Vec<String>>| list.as_ref().is_some_and(|list| list.iter().any(|s| s.trim().starts_with("description")));
+    let tasks = match gittask::find_tasks_by_index(&index_filters) {
+        Ok(Some(tasks)) if !index_filters.is_empty() => Ok(tasks),
+        _ if keyword.is_some() || uses_description(&columns) || uses_description(&sort) => gittask::list_tasks_without_comments(),
+        _ => gittask::list_task_summaries().map(|summaries| summaries.into_iter().map(|summary| summary.into_task()).collect()),
+    };
+
+    match tasks {
+        Ok(mut tasks) => {
+            let prop_manager = PropertyManager::new();
+            let stale_days = stale.flatten().unwrap_or_else(stale::get_stale_days);
+            let now = chrono::Utc::now();
+            tasks.iter_mut().for_each(|task| {
+                prop_manager.apply_formulas(task);
+                stale::mark_stale(task, stale_days, now, &status_manager);
             });
+            let sort = sort.unwrap_or_default().iter().map(|s| {
+                let s = s.trim();
+                let (prop, desc) = match s.to_lowercase() {
+                    lower if lower.ends_with(" desc") => (s[..(s.len() - "desc".len())].trim(), true),
+                    lower if lower.ends_with(" asc") => (s[..(s.len() - "asc".len())].trim(), false),
+                    _ => (s, false),
+                };
+                let value_type = prop_manager.get_parameter(prop, "value_type")
+                    .or_else(|| gittask::get_property_value_type(prop))
+                    .unwrap_or_default();
+                (prop.to_string(), desc, value_type)
+            }).collect::<Vec<_>>();
+            if let Err(e) = gittask::sort_by_task_id(&mut tasks, Task::get_id) {
+                return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}"));
+            }
+            match sort.is_empty() {
+                true => tasks.reverse(),
+                false => tasks.sort_by(|a, b| sort.iter()
+                    .map(|(prop, desc, value_type)| match desc {
+                        true => make_comparison(b, a, prop, value_type, &status_manager),
+                        false => make_comparison(a, b, prop, value_type, &status_manager),
+                    })
+                    .fold(Ordering::Equal, Ordering::then)),
+            }
 
             let from = parse_date(from);
             let until = parse_date(until);
+            let updated_since = parse_date(updated_since);
 
-            let status_manager = StatusManager::new();
-            let statuses = match status {
-                Some(statuses) => Some(statuses.iter().map(|s| status_manager.get_full_status_name(s)).collect::<Vec<_>>()),
-                None => None
-            };
             let no_color = check_no_color(no_color);
 
-            let columns = match columns {
-                Some(columns) => Some(columns),
-                None => match gittask::get_config_value("task.list.columns") {
-                    Ok(list_columns) => {
-                        Some(list_columns.split(",").map(|s| s.trim().to_string()).collect())
-                    },
-                    _ => None
-                }
-            };
+            let today = Local::now().date_naive();
+            snooze::wake_tasks(&tasks, today);
 
             let mut count = 0;
             for task in tasks {
+                if !include_snoozed && snooze::is_snoozed(&task, today) {
+                    continue;
+                }
+
+                if stale.is_some() && task.get_property(stale::STALE).is_none() {
+                    continue;
+                }
+
                 if let Some(ref statuses) = statuses {
                     let task_status = task.get_property("status").unwrap();
                     if !statuses.contains(&task_status) {
@@ -893,6 +1821,12 @@ pub(crate) fn task_list(status: Option<Vec<String>>,
                     }
                 }
 
+                if let Some(updated_since) = updated_since {
+                    if task.updated_at().is_none_or(|updated| updated < updated_since.earliest().unwrap()) {
+                        continue;
+                    }
+                }
+
                 if author.as_ref().is_some() {
                     if let Some(task_author) = task.get_property("author") {
                         if author.as_ref().unwrap().to_lowercase() != task_author.to_lowercase() {
@@ -901,17 +1835,53 @@ pub(crate) fn task_list(status: Option<Vec<String>>,
                     }
                 }
 
+                if let Some(labels) = &labels {
+                    if !task.get_labels().iter().flatten().any(|label| labels.iter().any(|name| name.eq_ignore_ascii_case(&label.get_name()))) {
+                        continue;
+                    }
+                }
+
+                if let Some(assignee) = &assignee {
+                    if task.get_property("assignee").is_none_or(|task_assignee| task_assignee.to_lowercase() != assignee.to_lowercase()) {
+                        continue;
+                    }
+                }
+
+                if let Some(task_type) = &task_type {
+                    if task.get_property("type").is_none_or(|t| !t.eq_ignore_ascii_case(task_type)) {
+                        continue;
+                    }
+                }
+
+                if let Some(path) = &path {
+                    if !task.get_property("path").is_some_and(|task_path| is_in_path(task_path, path)) {
+                        continue;
+                    }
+                }
+
+                if !filter::matches_all(&properties, &task, &prop_manager) {
+                    continue;
+                }
+
                 if let Some(limit) = limit {
                     if count >= limit {
                         break;
                     }
                 }
 
-                print_task_line(task, &columns, no_color, &prop_manager, &status_manager);
+                if ids_only {
+                    println!("{}", task.get_id().unwrap());
+                } else if !count_only {
+                    print_task_line(task, &columns, no_color, &prop_manager, &status_manager);
+                }
 
                 count += 1;
             }
 
+            if count_only {
+                println!("{count}");
+            }
+
             true
         },
         Err(e) => {
@@ -921,9 +1891,9 @@ pub(crate) fn task_list(status: Option<Vec<String>>,
 }
 
 fn print_task_line(task: Task, columns: &Option<Vec<String>>, no_color: bool, prop_manager: &PropertyManager, status_manager: &StatusManager) {
-    let columns = match columns {
-        Some(columns) => columns,
-        _ => &vec![
+    let mut columns = match columns {
+        Some(columns) => columns.clone(),
+        _ => vec![
             String::from("id"),
             String::from("created"),
             String::from("status"),
@@ -931,31 +1901,51 @@ fn print_task_line(task: Task, columns: &Option<Vec<String>>, no_color: bool, pr
             String::from("labels"),
         ]
     };
+    // plain output doesn't rely on styles of done tasks and highlighted rows, the status is always spelled out
+    let plain = is_plain_output();
+    if plain && !columns.iter().any(|column| column == "status") {
+        let position = columns.iter().position(|column| column == "id").map(|position| position + 1).unwrap_or(0);
+        columns.insert(position, String::from("status"));
+    }
     let context = extract_task_context(&task);
 
-    columns.iter().for_each(|column| {
-        print_column(&task, column, &context, no_color, prop_manager, status_manager);
-    });
+    // highlighted rows and done tasks are painted with a single style, so the columns go without their own colors
+    let done_style = match no_color {
+        true => None,
+        false => prop_manager.find_row_format(&context)
+            .or_else(|| task.get_property("status").and_then(|status| status_manager.get_done_style(status))),
+    };
+    if let Some(style) = done_style {
+        print!("{}", style.prefix());
+    }
+    let values = columns.iter()
+        .map(|column| format_column(&task, column, &context, no_color || done_style.is_some(), prop_manager, status_manager))
+        .collect::<Vec<_>>();
+    match plain {
+        true => print!("{}", values.join("\t")),
+        false => print!("{} ", values.join(" ")),
+    }
+    if let Some(style) = done_style {
+        print!("{}", style.suffix());
+    }
     println!();
 }
 
-fn print_column(
+fn format_column(
     task: &Task,
     column: &String,
     context: &HashMap<String, String>,
     no_color: bool,
     prop_manager: &PropertyManager,
     status_manager: &StatusManager
-) {
+) -> String {
     let empty_string = String::new();
     match column.as_str() {
-        "status" => {
-            print!("{} ", status_manager.format_status(task.get_property(column).unwrap(), no_color))
-        },
-        "labels" => if let Some(labels) = task.get_labels() {
-            for label in labels {
-                print_label(label, no_color);
-            }
+        "status" => status_manager.format_status(task.get_property(column).unwrap(), no_color).to_string(),
+        "labels" => format_labels(task.get_labels().iter().flatten(), no_color),
+        stale::STALE => match task.get_property(column) {
+            Some(days) => colorize_string(&format!("{days}d"), str_to_color("Red", &None), no_color),
+            None => String::new(),
         },
         column => {
             let value = if column == "id" {
@@ -965,20 +1955,31 @@ fn print_column(
                     &empty_string
                 })
             };
-            print!("{} ", prop_manager.format_value(column, value, context, prop_manager.get_properties(), no_color))
+            prop_manager.format_value(column, value, context, prop_manager.get_properties(), no_color).to_string()
         },
     }
 }
 
-pub(crate) fn task_stats(no_color: bool) -> bool {
-    match gittask::list_tasks() {
+pub(crate) fn task_stats(by: Option<String>, cross: Option<String>, cycle_time: bool, no_color: bool) -> bool {
+    match gittask::list_task_summaries().map(|summaries| summaries.into_iter().map(|summary| summary.into_task()).collect::<Vec<_>>()) {
+        Ok(tasks) if cycle_time => match gittask::get_status_history() {
+            Ok(history) => {
+                print_cycle_time(&tasks, &history, by.as_deref());
+                true
+            },
+            Err(e) => error_message(format!("ERROR: {e}"))
+        },
+        Ok(tasks) if by.is_some() => {
+            print_stats_by(&tasks, &by.unwrap(), cross.as_deref(), check_no_color(no_color));
+            true
+        },
         Ok(tasks) => {
             let mut total = 0;
             let mut status_stats = HashMap::<String, i32>::new();
             let mut author_stats = HashMap::<String, i32>::new();
             let no_color = check_no_color(no_color);
 
-            for task in tasks {
+            for task in &tasks {
                 total += 1;
 
                 if let Some(status) = task.get_property("status") {
@@ -994,10 +1995,28 @@ pub(crate) fn task_stats(no_color: bool) -> bool {
             println!();
 
             let status_manager = StatusManager::new();
+            let effort_property = wip::get_effort_property();
             for status in status_manager.get_statuses() {
-                if let Some(count) = status_stats.get(status.get_name()) {
-                    println!("{}: {}", status_manager.format_status(status.get_name(), no_color), count);
+                let limit = wip::get_wip_limit(status.get_name());
+                let count = status_stats.get(status.get_name());
+                if count.is_none() && limit.is_none() {
+                    continue;
                 }
+
+                let wip = limit.map(|limit| {
+                    let load = wip::get_wip_load(&tasks, status.get_name(), &[], &effort_property);
+                    let color = if load > limit { "Red" } else { "Default" };
+                    format!(" (WIP {})", colorize_string(&format!("{load}/{limit}"), str_to_color(color, &None), no_color))
+                }).unwrap_or_default();
+                println!("{}: {}{wip}", status_manager.format_status(status.get_name(), no_color), count.unwrap_or(&0));
+            }
+
+            let stale_days = stale::get_stale_days();
+            let now = chrono::Utc::now();
+            let stale_count = tasks.iter().filter(|task| stale::get_stale_age(task, stale_days, now, &status_manager).is_some()).count();
+            if stale_count > 0 {
+                println!();
+                println!("Stale tasks (not updated in {stale_days} days): {stale_count}");
             }
 
             if !author_stats.is_empty() {
@@ -1020,14 +2039,469 @@ pub(crate) fn task_stats(no_color: bool) -> bool {
     }
 }
 
-fn check_no_color(no_color: bool) -> bool {
-    no_color
-        || gittask::get_config_value("color.ui").unwrap_or_else(|_| "true".to_string()) == "false"
-        || std::env::var("NO_COLOR").unwrap_or_else(|_| "0".to_string()) == "1"
+const STATS_BAR_WIDTH: usize = 30;
+const NO_VALUE: &str = "(none)";
+
+fn print_stats_by(tasks: &[Task], by: &str, cross: Option<&str>, no_color: bool) {
+    let status_manager = StatusManager::new();
+    let prop_manager = PropertyManager::new();
+    let format_value = |property: &str, value: &str| -> String {
+        match (property, value) {
+            (_, NO_VALUE) => value.to_string(),
+            ("status", _) => status_manager.format_status(value, no_color).to_string(),
+            _ => prop_manager.format_value(property, value, &HashMap::new(), &vec![], no_color).to_string(),
+        }
+    };
+
+    println!("Total tasks: {}", tasks.len());
+    println!();
+
+    let mut counts = HashMap::<String, usize>::new();
+    let mut cross_counts = HashMap::<(String, String), usize>::new();
+    let mut cross_values = vec![];
+
+    for task in tasks {
+        let cross_task_values = cross.map(|cross| get_stats_values(task, cross, &prop_manager)).unwrap_or_default();
+        for value in get_stats_values(task, by, &prop_manager) {
+            *counts.entry(value.clone()).or_default() += 1;
+            for cross_value in &cross_task_values {
+                *cross_counts.entry((value.clone(), cross_value.clone())).or_default() += 1;
+                if !cross_values.contains(cross_value) {
+                    cross_values.push(cross_value.clone());
+                }
+            }
+        }
+    }
+
+    let mut values = counts.iter().collect::<Vec<_>>();
+    match by {
+        "status" => values.sort_by_key(|(value, _)| (status_manager.get_order(value), value.to_string())),
+        _ => values.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0))),
+    }
+    let width = values.iter().map(|(value, _)| value.chars().count()).max().unwrap_or(0);
+
+    match cross {
+        None => {
+            let max = values.iter().map(|(_, count)| **count).max().unwrap_or(0);
+            for (value, count) in &values {
+                let percent = **count as f64 * 100.0 / tasks.len().max(1) as f64;
+                let padding = " ".repeat(width - value.chars().count());
+                let line = format!("{}{padding} {count:>5} {percent:>5.1}%", format_value(by, value));
+                match is_plain_output() {
+                    true => println!("{line}"),
+                    false => {
+                        let bar = "█".repeat((**count * STATS_BAR_WIDTH).div_ceil(max.max(1)));
+                        println!("{line} {}", colorize_string(&bar, Theme::current().separator(), no_color));
+                    },
+                }
+            }
+        },
+        Some(cross) => {
+            match cross {
+                "status" => cross_values.sort_by_key(|value| (status_manager.get_order(value), value.clone())),
+                _ => cross_values.sort(),
+            }
+            let column_widths = cross_values.iter().map(|value| value.chars().count().max(5)).collect::<Vec<_>>();
+
+            print!("{}", " ".repeat(width));
+            for (value, column_width) in cross_values.iter().zip(&column_widths) {
+                print!(" {}{}", " ".repeat(column_width - value.chars().count()), format_value(cross, value));
+            }
+            println!(" {:>5}", "Total");
+
+            for (value, count) in &values {
+                print!("{}{}", format_value(by, value), " ".repeat(width - value.chars().count()));
+                for (cross_value, column_width) in cross_values.iter().zip(&column_widths) {
+                    let cross_count = cross_counts.get(&(value.to_string(), cross_value.clone())).unwrap_or(&0);
+                    print!(" {cross_count:>column_width$}");
+                }
+                println!(" {count:>5}");
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FlowTimes {
+    lead_time: Option<u64>,
+    cycle_time: Option<u64>,
+}
+
+fn print_cycle_time(tasks: &[Task], history: &HashMap<String, Vec<(u64, String)>>, by: Option<&str>) {
+    let status_manager = StatusManager::new();
+    let prop_manager = PropertyManager::new();
+    let starting_status = status_manager.get_starting_status();
+    let is_done = |status: &str| status_manager.get_statuses().iter().any(|s| s.get_name() == status && *s.is_done());
+
+    let mut groups = HashMap::<String, Vec<FlowTimes>>::new();
+
+    for task in tasks {
+        let status = task.get_property("status").map(|s| s.as_str()).unwrap_or("");
+        if !is_done(status) {
+            continue;
+        }
+
+        let changes = history.get(&task.get_id().unwrap()).map(|changes| changes.as_slice()).unwrap_or(&[]);
+        let Some(closed) = get_close_time(task, changes, &is_done) else {
+            continue;
+        };
+
+        let created = task.get_property("created").and_then(|created| created.parse::<u64>().ok());
+        let started = changes.iter()
+            .find(|(timestamp, status)| *timestamp <= closed && *status != starting_status && !is_done(status))
+            .map(|(timestamp, _)| *timestamp);
+
+        let times = FlowTimes {
+            lead_time: created.map(|created| closed.saturating_sub(created)),
+            cycle_time: started.map(|started| closed.saturating_sub(started)),
+        };
+
+        let keys = match by {
+            Some("month") => vec![Local.timestamp_opt(closed as i64, 0).unwrap().format("%Y-%m").to_string()],
+            Some(by) => get_stats_values(task, by, &prop_manager),
+            None => vec![String::new()],
+        };
+
+        for key in keys {
+            groups.entry(key).or_default().push(times);
+        }
+    }
+
+    if groups.is_empty() {
+        println!("No closed tasks with known close time");
+        return;
+    }
+
+    let mut keys = groups.keys().cloned().collect::<Vec<_>>();
+    keys.sort();
+
+    for key in keys {
+        let times = &groups[&key];
+        if by.is_some() {
+            println!("{key}:");
+        }
+        print_flow_metric("Lead time (created -> closed)", times.iter().filter_map(|t| t.lead_time).collect());
+        print_flow_metric("Cycle time (started -> closed)", times.iter().filter_map(|t| t.cycle_time).collect());
+        if by.is_some() {
+            println!();
+        }
+    }
+}
+
+/// Close time is taken from `closed` property if present, otherwise from the last transition to a done status.
+fn get_close_time(task: &Task, changes: &[(u64, String)], is_done: &dyn Fn(&str) -> bool) -> Option<u64> {
+    task.get_property("closed").and_then(|closed| closed.parse::<u64>().ok()).or_else(|| {
+        let index = changes.iter().rposition(|(_, status)| !is_done(status))?;
+        changes.get(index + 1).map(|(timestamp, _)| *timestamp)
+    })
+}
+
+const DEFAULT_CHANGELOG_TEMPLATE: &str = "## Changes since {{since}}\n{{#each groups}}\n\n### {{name}}\n\n{{#each tasks}}\n- {{name}} (#{{id}})\n{{/each}}\n{{/each}}\n";
+const CHANGELOG_OTHER_GROUP: &str = "Other";
+
+pub(crate) fn task_changelog(since: String, until: Option<String>, group_by: Option<String>, template: Option<String>) -> bool {
+    let Some(since_time) = parse_changelog_time(&since) else {
+        return error_message_kind(ErrorKind::Validation, format!("ERROR: '{since}' is neither a date (YYYY-MM-DD) nor a git revision"));
+    };
+    let until_time = match &until {
+        Some(until) => match parse_changelog_time(until) {
+            Some(time) => time,
+            None => return error_message_kind(ErrorKind::Validation, format!("ERROR: '{until}' is neither a date (YYYY-MM-DD) nor a git revision")),
+        },
+        None => u64::MAX,
+    };
+
+    let template = match template.or_else(|| gittask::get_config_value("task.changelog.template").ok()) {
+        Some(path) => match read_from_file(&path) {
+            Some(template) => template,
+            None => return error_message(format!("ERROR: Can't read template {path}")),
+        },
+        None => DEFAULT_CHANGELOG_TEMPLATE.to_string(),
+    };
+
+    let (tasks, history) = match (gittask::list_tasks(), gittask::get_status_history()) {
+        (Ok(tasks), Ok(history)) => (tasks, history),
+        (Err(e), _) | (_, Err(e)) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let status_manager = StatusManager::new();
+    let is_done = |status: &str| status_manager.get_statuses().iter().any(|s| s.get_name() == status && *s.is_done());
+
+    let sections = gittask::get_config_value("task.changelog.sections").unwrap_or_default()
+        .split(',').map(|section| section.trim().to_string()).filter(|section| !section.is_empty()).collect::<Vec<_>>();
+    let mut groups = Vec::<(String, Vec<(u64, serde_json::Value)>)>::new();
+
+    for task in tasks {
+        if !task.get_property("status").is_some_and(|status| is_done(status)) {
+            continue;
+        }
+
+        let changes = history.get(&task.get_id().unwrap()).map(|changes| changes.as_slice()).unwrap_or(&[]);
+        let closed = match get_close_time(&task, changes, &is_done) {
+            Some(closed) if closed >= since_time && closed <= until_time => closed,
+            _ => continue,
+        };
+
+        let group = get_changelog_group(&task, group_by.as_deref(), &sections);
+
+        let mut value = serde_json::to_value(task.get_all_properties()).unwrap();
+        value["id"] = task.get_id().unwrap().into();
+        value["closed"] = format_datetime(closed).into();
+        value["labels"] = task.get_labels().as_ref().map(|labels| labels.iter().map(|label| label.get_name()).collect::<Vec<_>>()).unwrap_or_default().into();
+
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, tasks)) => tasks.push((closed, value)),
+            None => groups.push((group, vec![(closed, value)])),
+        }
+    }
+
+    match group_by.as_deref() {
+        Some("status") => groups.sort_by_key(|(name, _)| (status_manager.get_order(name), name.clone())),
+        None | Some("label") => groups.sort_by_key(|(name, _)| (sections.iter().position(|section| section == name).unwrap_or(sections.len()), name == CHANGELOG_OTHER_GROUP, name.clone())),
+        _ => groups.sort_by(|a, b| (a.0 == CHANGELOG_OTHER_GROUP).cmp(&(b.0 == CHANGELOG_OTHER_GROUP)).then(a.0.cmp(&b.0))),
+    }
+
+    let context = serde_json::json!({
+        "since": since,
+        "until": until.unwrap_or_default(),
+        "groups": groups.into_iter().map(|(name, mut tasks)| {
+            tasks.sort_by_key(|(closed, _)| *closed);
+            serde_json::json!({ "name": name, "tasks": tasks.into_iter().map(|(_, task)| task).collect::<Vec<_>>() })
+        }).collect::<Vec<_>>(),
+    });
+
+    match render(&template, &context) {
+        Ok(changelog) => {
+            print!("{changelog}");
+            true
+        },
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+/// Labels group a task under the first one matching a section of `task.changelog.sections`, or else the first one.
+fn get_changelog_group(task: &Task, group_by: Option<&str>, sections: &[String]) -> String {
+    match group_by {
+        None | Some("label") => task.get_labels().as_ref().and_then(|labels| {
+            labels.iter().find_map(|label| sections.iter().find(|section| section.eq_ignore_ascii_case(&label.get_name())).cloned())
+                .or_else(|| labels.first().map(|label| label.get_name()))
+        }),
+        Some(property) => task.get_property(property).cloned(),
+    }.unwrap_or_else(|| CHANGELOG_OTHER_GROUP.to_string())
+}
+
+fn parse_changelog_time(value: &str) -> Option<u64> {
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(_) => parse_date(Some(value.to_string())).and_then(|date| date.earliest()).map(|date| date.timestamp() as u64),
+        Err(_) => gittask::get_revision_time(value).ok(),
+    }
+}
+
+fn print_flow_metric(title: &str, mut values: Vec<u64>) {
+    if values.is_empty() {
+        println!("{title}: no data");
+        return;
+    }
+
+    values.sort();
+    let percentile = |p: usize| values[((values.len() * p).div_ceil(100)).max(1) - 1];
+    let average = values.iter().sum::<u64>() / values.len() as u64;
+
+    println!("{title}, {} task(s): average {}, median {}, 85th percentile {}, 95th percentile {}",
+             values.len(), format_duration(average), format_duration(percentile(50)), format_duration(percentile(85)), format_duration(percentile(95)));
+}
+
+/// Values the task is counted under, labels and list properties count once per value.
+fn get_stats_values(task: &Task, by: &str, prop_manager: &PropertyManager) -> Vec<String> {
+    let values = match by {
+        "label" | "labels" => task.get_labels().as_ref().map(|labels| labels.iter().map(|label| label.get_name()).collect()).unwrap_or_default(),
+        _ => match task.get_property(by) {
+            Some(value) if prop_manager.is_list(by) => Vec::<String>::parse_value(value).unwrap_or_else(|_| vec![value.clone()]),
+            Some(value) => vec![value.clone()],
+            None => vec![],
+        },
+    };
+
+    match values.is_empty() {
+        true => vec![NO_VALUE.to_string()],
+        false => values,
+    }
+}
+
+pub(crate) fn task_inbox(all: bool, no_color: bool) -> bool {
+    match gittask::list_tasks() {
+        Ok(mut tasks) => {
+            let identities = get_user_identities();
+            if identities.is_empty() {
+                return error_message("Could not determine current user, please set up user.name or task.user".to_string());
+            }
+
+            let last_check = match all {
+                true => 0,
+                false => gittask::get_config_value("task.inbox.last").ok().and_then(|s| s.parse::<u64>().ok()).unwrap_or(0),
+            };
+
+            let is_me = |name: &String| identities.iter().any(|identity| identity.eq_ignore_ascii_case(name));
+            let is_newer = |timestamp: Option<&String>| timestamp.and_then(|s| s.parse::<u64>().ok()).unwrap_or(0) > last_check;
+
+            let (assignee_history, mention_history) = match (gittask::get_property_history("assignee"), gittask::get_property_history("mentions")) {
+                (Ok(assignee_history), Ok(mention_history)) => (assignee_history, mention_history),
+                (Err(e), _) | (_, Err(e)) => return error_message(format!("ERROR: {e}")),
+            };
+
+            if let Err(e) = gittask::sort_by_task_id(&mut tasks, Task::get_id) {
+                return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}"));
+            }
+
+            let prop_manager = PropertyManager::new();
+            let status_manager = StatusManager::new();
+            let no_color = check_no_color(no_color);
+            let mut count = 0;
+
+            for task in tasks {
+                let created = task.get_property("created");
+                // when the property last changed according to the tasks ref history, otherwise when the task was updated
+                let changed_at = |history: &HashMap<String, Vec<(u64, String)>>| task.get_id().and_then(|id| history.get(&id)).and_then(|changes| changes.last())
+                    .map(|(timestamp, _)| timestamp.to_string())
+                    .or_else(|| task.get_property("updated").or(created).cloned());
+                let assigned = task.get_property("assignee").is_some_and(is_me) && is_newer(changed_at(&assignee_history).as_ref());
+                let mentioned = extract_mentions(task.get_property("description").map(|s| s.as_str()).unwrap_or("")).iter().any(is_me)
+                    && is_newer(changed_at(&mention_history).as_ref());
+                let mentioned_in_comments = task.get_comments().as_ref().is_some_and(|comments| comments.iter().any(|comment| {
+                    comment.get_mentions().iter().any(is_me) && is_newer(comment.get_all_properties().get("created"))
+                }));
+
+                let watched_changed = task.get_watchers().iter().any(is_me) && (is_newer(created) || task.get_comments().as_ref().is_some_and(|comments| {
+                    comments.iter().any(|comment| is_newer(comment.get_all_properties().get("created")))
+                }));
+
+                if assigned || mentioned || mentioned_in_comments || watched_changed {
+                    print_task_line(task, &None, no_color, &prop_manager, &status_manager);
+                    count += 1;
+                }
+            }
+
+            if count == 0 {
+                println!("No new mentions or assignments");
+            }
+
+            if !all {
+                let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                if let Err(e) = gittask::set_config_value("task.inbox.last", &now.to_string()) {
+                    return error_message(format!("ERROR: {e}"));
+                }
+            }
+
+            true
+        },
+        Err(e) => error_message(format!("ERROR: {e}"))
+    }
+}
+
+pub(crate) fn get_user_identities() -> Vec<String> {
+    let mut result = vec![];
+
+    for key in ["task.user", "user.name"] {
+        if let Ok(name) = gittask::get_config_value(key) {
+            result.push(name);
+        }
+    }
+
+    if let Ok(email) = gittask::get_config_value("user.email") {
+        if let Some((name, _)) = email.split_once('@') {
+            result.push(name.to_string());
+        }
+    }
+
+    result
+}
+
+/// `--no-color` of a command is a shortcut for `--color never`.
+pub(crate) fn check_no_color(no_color: bool) -> bool {
+    no_color || !is_color_enabled()
 }
 
 fn extract_task_context(task: &Task) -> HashMap<String, String> {
     let mut context = task.get_all_properties().to_owned();
     context.insert("id".to_string(), task.get_id().unwrap());
     context
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_stats_values() {
+        let prop_manager = gittask::TaskRepository::temporary().unwrap().run(|| {
+            let mut prop_manager = PropertyManager::new();
+            prop_manager.add_property("reviewers".to_string(), "list".to_string(), "Default".to_string(), None, None, None).unwrap();
+            prop_manager
+        });
+        let task = Task::builder("Review").id("1").status("OPEN")
+            .property("reviewers", r#"["alice","bob"]"#)
+            .property("component", "a,b")
+            .label(Label::new("ui".to_string(), None, None))
+            .label(Label::new("bug".to_string(), None, None))
+            .build().unwrap();
+
+        assert_eq!(get_stats_values(&task, "reviewers", &prop_manager), vec!["alice", "bob"]);
+        assert_eq!(get_stats_values(&task, "component", &prop_manager), vec!["a,b"]);
+        assert_eq!(get_stats_values(&task, "label", &prop_manager), vec!["ui", "bug"]);
+        assert_eq!(get_stats_values(&task, "priority", &prop_manager), vec![NO_VALUE]);
+    }
+
+    #[test]
+    fn test_get_changelog_group() {
+        let task = Task::builder("Fix layout").id("1").status("CLOSED").property("type", "bug")
+            .label(Label::new("ui".to_string(), None, None))
+            .label(Label::new("Bug".to_string(), None, None))
+            .build().unwrap();
+        let sections = vec!["feature".to_string(), "bug".to_string()];
+
+        assert_eq!(get_changelog_group(&task, None, &sections), "bug");
+        assert_eq!(get_changelog_group(&task, Some("label"), &sections[..1]), "ui");
+        assert_eq!(get_changelog_group(&task, None, &[]), "ui");
+        assert_eq!(get_changelog_group(&task, Some("type"), &sections), "bug");
+        assert_eq!(get_changelog_group(&task, Some("priority"), &sections), CHANGELOG_OTHER_GROUP);
+
+        let unlabelled = Task::builder("Docs").id("2").status("CLOSED").build().unwrap();
+        assert_eq!(get_changelog_group(&unlabelled, None, &sections), CHANGELOG_OTHER_GROUP);
+    }
+
+    #[test]
+    fn test_pull_options() {
+        assert_eq!(PullOptions::default().pull_options(), None);
+        let options = PullOptions { no_comments: true, status: Some("OPEN".to_string()), ..Default::default() };
+        assert_eq!(options.pull_options(), Some("status=OPEN comments=off".to_string()));
+
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            let status_manager = StatusManager::new();
+            let remote_task = |comment: bool, pull_options: Option<&str>| {
+                let mut builder = Task::builder("Crash").id("7").status("OPEN").property("updated", "90");
+                if comment {
+                    builder = builder.comment("Reproduced");
+                }
+                if let Some(pull_options) = pull_options {
+                    builder = builder.property(PULL_OPTIONS, pull_options);
+                }
+                builder.build().unwrap()
+            };
+
+            let outcome = import_remote_task(remote_task(false, Some("comments=off")), &status_manager, true, false);
+            assert!(matches!(outcome, Ok(SyncOutcome::Created)));
+            let outcome = import_remote_task(remote_task(true, Some("comments=off")), &status_manager, true, false);
+            assert!(matches!(outcome, Ok(SyncOutcome::Skipped(_))));
+
+            // the remote hasn't changed, but the comments skipped by the previous pull are imported now
+            let outcome = import_remote_task(remote_task(true, None), &status_manager, false, false);
+            assert!(matches!(outcome, Ok(SyncOutcome::Updated)));
+            let task = gittask::find_task("7").unwrap().unwrap();
+            assert_eq!(task.get_comments().as_ref().map(Vec::len), Some(1));
+            assert_eq!(task.get_property(PULL_OPTIONS), None);
+
+            let outcome = import_remote_task(remote_task(true, None), &status_manager, false, false);
+            assert!(matches!(outcome, Ok(SyncOutcome::Skipped(_))));
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use std::process::Command;
+
+use crate::i18n::tr;
+use crate::util::{error_message, error_message_kind, ErrorKind, success_message};
+
+pub(crate) fn task_alias_add(task_id: String, alias: String) -> bool {
+    if alias.is_empty() || alias.parse::<u64>().is_ok() || alias.contains(',') || alias.contains("..") || alias.contains(char::is_whitespace) {
+        return error_message_kind(ErrorKind::Validation, format!("Invalid alias '{alias}', it can't be a number or contain commas, spaces and '..'"));
+    }
+
+    let task_id = match gittask::find_task(&task_id) {
+        Ok(Some(task)) => task.get_id().unwrap(),
+        Ok(None) => return error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let mut aliases = gittask::get_aliases();
+    if let Some(id) = aliases.get(&alias) {
+        if *id != task_id {
+            return error_message_kind(ErrorKind::Conflict, format!("Alias '{alias}' is already assigned to task ID {id}"));
+        }
+    }
+
+    aliases.insert(alias.clone(), task_id.clone());
+    match gittask::set_aliases(&aliases) {
+        Ok(_) => success_message(format!("Alias '{alias}' -> task ID {task_id}")),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_alias_delete(alias: String) -> bool {
+    let mut aliases = gittask::get_aliases();
+    if aliases.remove(&alias).is_none() {
+        return error_message_kind(ErrorKind::NotFound, format!("Alias '{alias}' not found"));
+    }
+
+    match gittask::set_aliases(&aliases) {
+        Ok(_) => success_message(format!("Alias '{alias}' deleted")),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_alias_list(task_id: Option<String>) -> bool {
+    let task_id = task_id.map(|id| gittask::resolve_task_id(&id));
+    gittask::get_aliases().iter()
+        .filter(|(_, id)| task_id.as_ref().is_none_or(|task_id| task_id == *id))
+        .for_each(|(alias, id)| println!("{alias} -> {id}"));
+    true
+}
+
+/// User-defined command shortcut, `task.alias.<name>` parameter, e.g. `list --label bug --status o`.
+pub(crate) fn get_command_alias(name: &str) -> Option<String> {
+    gittask::get_config_value(&format!("task.alias.{name}")).ok().filter(|alias| !alias.trim().is_empty())
+}
+
+/// Runs a shell command alias (`!` prefix stripped) with the arguments appended, returns its exit code.
+pub(crate) fn run_shell_alias(name: &str, command: &str, args: &[String]) -> Result<i32, String> {
+    let status = shell_command(name, command, args).status().map_err(|e| format!("can't run alias {name}: {e}"))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Like git, the arguments are available to the command as `$@` (or appended on Windows).
+fn shell_command(name: &str, command: &str, args: &[String]) -> Command {
+    match cfg!(windows) {
+        true => {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C").arg(std::iter::once(command.to_string()).chain(args.iter().cloned()).collect::<Vec<_>>().join(" "));
+            shell
+        },
+        false => {
+            let mut shell = Command::new("sh");
+            shell.arg("-c").arg(format!("{command} \"$@\"")).arg(name).args(args);
+            shell
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_shell_command() {
+        let command = shell_command("open-bugs", "git log --oneline", &["-n".to_string(), "5".to_string()]);
+        assert_eq!(command.get_program(), "sh");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), vec!["-c", "git log --oneline \"$@\"", "open-bugs", "-n", "5"]);
+    }
+}
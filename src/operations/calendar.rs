@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Local, Months, NaiveDate, Weekday};
+use nu_ansi_term::Color::{Red, Yellow};
+use nu_ansi_term::Style;
+
+use gittask::Task;
+
+use crate::operations::check_no_color;
+use crate::status::StatusManager;
+use crate::theme::Theme;
+use crate::util::{colorize_string, error_message, error_message_kind, is_plain_output, ErrorKind};
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+const CELL_WIDTH: usize = 8;
+
+/// Month grid with the number of due and overdue tasks per day, or a week agenda listing them.
+/// Done tasks are left out. Plain output lists the days of the month instead of the grid.
+pub(crate) fn task_calendar(month: Option<String>, week: Option<Option<String>>, no_color: bool) -> bool {
+    let today = Local::now().date_naive();
+    let no_color = check_no_color(no_color);
+
+    let tasks = match gittask::list_task_summaries() {
+        Ok(summaries) => summaries.into_iter().map(|summary| summary.into_task()).collect::<Vec<_>>(),
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+    let status_manager = StatusManager::new();
+    let due_tasks = match get_due_tasks(tasks, &status_manager) {
+        Ok(due_tasks) => due_tasks,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+
+    match week {
+        Some(date) => {
+            let date = match date.map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d")).transpose() {
+                Ok(date) => date.unwrap_or(today),
+                Err(_) => return error_message_kind(ErrorKind::Validation, "ERROR: Invalid week date, expected YYYY-MM-DD".to_string()),
+            };
+            print_week(&due_tasks, date, today, &status_manager, no_color, is_plain_output());
+        },
+        None => {
+            let first_day = match month.map(|month| NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")).transpose() {
+                Ok(date) => date.unwrap_or(today.with_day(1).unwrap()),
+                Err(_) => return error_message_kind(ErrorKind::Validation, "ERROR: Invalid month, expected YYYY-MM".to_string()),
+            };
+            let lines = match is_plain_output() {
+                true => render_month_plain(&due_tasks, first_day, today),
+                false => render_month(&due_tasks, first_day, today, no_color),
+            };
+            lines.iter().for_each(|line| println!("{line}"));
+        },
+    }
+
+    true
+}
+
+/// Tasks that are not done grouped by their `due` date, `YYYY-MM-DD` or a timestamp.
+fn get_due_tasks(tasks: Vec<Task>, status_manager: &StatusManager) -> Result<BTreeMap<NaiveDate, Vec<Task>>, String> {
+    let mut result = BTreeMap::<NaiveDate, Vec<Task>>::new();
+    for task in tasks {
+        if task.get_property("status").is_some_and(|status| status_manager.is_done(status)) {
+            continue;
+        }
+        if let Some(due) = task.get_property("due").and_then(|due| parse_due(due)) {
+            result.entry(due).or_default().push(task);
+        }
+    }
+    for tasks in result.values_mut() {
+        gittask::sort_by_task_id(tasks, Task::get_id)?;
+    }
+    Ok(result)
+}
+
+fn parse_due(due: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(due.trim(), "%Y-%m-%d").ok()
+        .or_else(|| due.trim().parse::<i64>().ok().and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0)).map(|date| date.with_timezone(&Local).date_naive()))
+}
+
+/// Lines of the month grid: the title, weekday names, weeks starting on Monday and the legend.
+/// Days show `(N)` for tasks due and `!N` for overdue ones, today is marked with `*`.
+fn render_month(due_tasks: &BTreeMap<NaiveDate, Vec<Task>>, first_day: NaiveDate, today: NaiveDate, no_color: bool) -> Vec<String> {
+    let next_month = first_day + Months::new(1);
+    let theme = Theme::current();
+    let mut lines = vec![];
+
+    let title = first_day.format("%B %Y").to_string();
+    let width = CELL_WIDTH * 6 + 2;
+    lines.push(colorize_string(format!("{title:^width$}").trim_end(), theme.title(), no_color));
+    lines.push(WEEKDAYS.iter().map(|weekday| format!("{weekday:<CELL_WIDTH$}")).collect::<String>().trim_end().to_string());
+
+    let mut line = " ".repeat(CELL_WIDTH * first_day.weekday().num_days_from_monday() as usize);
+    let (mut due_count, mut overdue_count) = (0, 0);
+    let mut day = first_day;
+    while day < next_month {
+        let count = due_tasks.get(&day).map(|tasks| tasks.len()).unwrap_or(0);
+        let (marker, color) = match (count, day < today) {
+            (0, _) => (String::new(), Style::default()),
+            (count, true) => {
+                overdue_count += count;
+                (format!("!{count}"), Red.into())
+            },
+            (count, false) => {
+                due_count += count;
+                (format!("({count})"), Yellow.into())
+            },
+        };
+        let today_marker = if day == today { "*" } else { " " };
+        let cell = format!("{:>2}{today_marker}{marker}", day.day());
+        let padding = " ".repeat(CELL_WIDTH.saturating_sub(cell.len()));
+        line.push_str(&colorize_string(&cell, color, no_color || count == 0));
+        line.push_str(&padding);
+
+        if day.weekday() == Weekday::Sun {
+            lines.push(line.trim_end().to_string());
+            line = String::new();
+        }
+        day = day + Days::new(1);
+    }
+    if !line.trim().is_empty() {
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines.push(String::new());
+    lines.push(format!("Due: {due_count}, overdue: {overdue_count}"));
+    lines.push("(N) tasks due, !N overdue tasks, * today".to_string());
+    lines
+}
+
+/// Lines of the month without the grid: the title, every day having due or overdue tasks and the totals.
+fn render_month_plain(due_tasks: &BTreeMap<NaiveDate, Vec<Task>>, first_day: NaiveDate, today: NaiveDate) -> Vec<String> {
+    let mut lines = vec![first_day.format("%B %Y").to_string()];
+    let (mut due_count, mut overdue_count) = (0, 0);
+    for (day, tasks) in due_tasks.range(first_day..first_day + Months::new(1)) {
+        let today_marker = if *day == today { " (today)" } else { "" };
+        let kind = match *day < today {
+            true => {
+                overdue_count += tasks.len();
+                "overdue"
+            },
+            false => {
+                due_count += tasks.len();
+                "due"
+            },
+        };
+        lines.push(format!("{}{today_marker}: {} {kind}", day.format("%a %Y-%m-%d"), tasks.len()));
+    }
+    lines.push(format!("Due: {due_count}, overdue: {overdue_count}"));
+    lines
+}
+
+/// Agenda of the week containing the date: overdue tasks first, then every day from Monday to Sunday.
+/// Plain output separates the columns with tabs and goes without indentation.
+fn print_week(due_tasks: &BTreeMap<NaiveDate, Vec<Task>>, date: NaiveDate, today: NaiveDate, status_manager: &StatusManager, no_color: bool, plain: bool) {
+    let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+    let theme = Theme::current();
+    let print_tasks = |tasks: &[Task], show_due: bool| {
+        for task in tasks {
+            let status = task.get_property("status").map(|status| status_manager.format_status(status, no_color).to_string()).unwrap_or_default();
+            let due = match show_due {
+                true => format!(" (due {})", task.get_property("due").and_then(|due| parse_due(due)).map(|due| due.to_string()).unwrap_or_default()),
+                false => String::new(),
+            };
+            let (id, name) = (task.get_id().unwrap_or_default(), task.get_property("name").cloned().unwrap_or_default());
+            match plain {
+                true => println!("{id}\t{status}\t{name}{due}"),
+                false => println!("  {id} {status} {name}{due}"),
+            }
+        }
+    };
+
+    let overdue = due_tasks.range(..monday.min(today)).flat_map(|(_, tasks)| tasks.iter().cloned()).collect::<Vec<_>>();
+    if !overdue.is_empty() {
+        println!("{}", colorize_string(if plain { "Overdue:" } else { "Overdue" }, Red, no_color));
+        print_tasks(&overdue, true);
+    }
+
+    for offset in 0..7 {
+        let day = monday + Days::new(offset);
+        let title = day.format("%a %Y-%m-%d").to_string() + if day == today { " (today)" } else { "" } + if plain { ":" } else { "" };
+        println!("{}", colorize_string(&title, theme.title(), no_color));
+        match (due_tasks.get(&day), plain) {
+            (Some(tasks), _) => print_tasks(tasks, false),
+            (None, true) => println!("No tasks"),
+            (None, false) => println!("  -"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_month() {
+        let date = |day: u32| NaiveDate::from_ymd_opt(2025, 7, day).unwrap();
+        let task = |id: &str, status: &str, due: &str| Task::builder("Task").id(id).status(status).property("due", due).build().unwrap();
+
+        let due_tasks = gittask::TaskRepository::temporary().unwrap().run(|| {
+            let status_manager = StatusManager::new();
+            get_due_tasks(vec![
+                task("1", "OPEN", "2025-07-03"),
+                task("2", "OPEN", "2025-07-03"),
+                task("3", "CLOSED", "2025-07-03"),
+                task("4", "IN_PROGRESS", "2025-07-21"),
+                task("5", "OPEN", "someday"),
+            ], &status_manager).unwrap()
+        });
+        assert_eq!(due_tasks.len(), 2);
+        assert_eq!(due_tasks[&date(3)].len(), 2);
+        assert_eq!(parse_due("1751500800"), Some(chrono::DateTime::from_timestamp(1751500800, 0).unwrap().with_timezone(&Local).date_naive()));
+
+        let lines = render_month(&due_tasks, date(1), date(15), true);
+        assert_eq!(lines[0].trim(), "July 2025");
+        assert_eq!(lines[1], "Mo      Tu      We      Th      Fr      Sa      Su");
+        assert_eq!(lines[2], "         1       2       3 !2    4       5       6");
+        assert_eq!(lines[4], "14      15*     16      17      18      19      20");
+        assert_eq!(lines[5], "21 (1)  22      23      24      25      26      27");
+        assert_eq!(lines[6], "28      29      30      31");
+        assert_eq!(lines[8], "Due: 1, overdue: 2");
+
+        assert_eq!(render_month_plain(&due_tasks, date(1), date(21)), vec![
+            "July 2025",
+            "Thu 2025-07-03: 2 overdue",
+            "Mon 2025-07-21 (today): 1 due",
+            "Due: 1, overdue: 2",
+        ]);
+    }
+}
@@ -1,47 +1,58 @@
 use std::collections::HashMap;
-use crate::operations::get_user_repo;
-use crate::util::{error_message, get_text_from_editor};
 
-pub(crate) fn task_comment_add(task_id: String, text: Option<String>, push: bool, remote: &Option<String>) -> bool {
-    match gittask::find_task(&task_id) {
-        Ok(Some(mut task)) => {
-            let text = text.or_else(|| get_text_from_editor(None));
-            if text.is_none() {
-                return error_message("No text specified".to_string());
-            }
-            let text = text.unwrap();
+use nu_ansi_term::Color::Yellow;
+use regex::RegexBuilder;
 
-            let comment = task.add_comment(None, HashMap::new(), text);
-            match gittask::update_task(task) {
-                Ok(_) => {
-                    println!("Task ID {task_id} updated");
-                    let mut success = false;
-                    if push {
-                        match get_user_repo(remote) {
-                            Ok((connector, user, repo)) => {
-                                match connector.create_remote_comment(&user, &repo, &task_id, &comment) {
-                                    Ok(remote_comment_id) => {
-                                        println!("Created REMOTE comment ID {}", remote_comment_id);
-                                        match gittask::update_comment_id(&task_id, &comment.get_id().unwrap(), &remote_comment_id) {
-                                            Ok(_) => {
-                                                println!("Comment ID {} -> {} updated", &comment.get_id().unwrap(), remote_comment_id);
-                                                success = true;
-                                            },
-                                            Err(e) => eprintln!("ERROR: {e}"),
-                                        }
-                                    },
-                                    Err(e) => eprintln!("ERROR creating REMOTE comment: {e}")
-                                }
-                            },
-                            Err(e) => eprintln!("ERROR: {e}"),
+use gittask::Task;
+
+use crate::i18n::tr;
+use crate::operations::{check_no_color, get_user_repo};
+use crate::theme::Theme;
+use crate::util::{colorize_string, error_message, error_message_kind, ErrorKind, format_display_datetime, get_text_from_editor, parse_ids};
+
+pub(crate) fn task_comment_add(ids: String, text: Option<String>, push: bool, remote: &Option<String>) -> bool {
+    let mut tasks = vec![];
+    for task_id in parse_ids(ids) {
+        match gittask::find_task(&task_id) {
+            Ok(Some(task)) => tasks.push(task),
+            Ok(None) => return error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    let text = text.or_else(|| get_text_from_editor(None));
+    if text.is_none() {
+        return error_message_kind(ErrorKind::Validation, "No text specified".to_string());
+    }
+    let text = text.unwrap();
+
+    let comments = tasks.iter_mut().map(|task| (task.get_id().unwrap(), task.add_comment(None, HashMap::new(), text.clone()))).collect::<Vec<_>>();
+
+    match gittask::update_tasks(tasks) {
+        Ok(ids) => {
+            ids.iter().for_each(|task_id| println!("{}", tr("Task ID {id} updated", &[("id", &task_id)])));
+            let mut success = true;
+            if push {
+                match get_user_repo(remote) {
+                    Ok((connector, user, repo)) => {
+                        for (task_id, comment) in comments {
+                            match connector.create_remote_comment(&user, &repo, &task_id, &comment) {
+                                Ok(remote_comment_id) => {
+                                    println!("Created REMOTE comment ID {}", remote_comment_id);
+                                    match gittask::update_comment_id(&task_id, &comment.get_id().unwrap(), &remote_comment_id) {
+                                        Ok(_) => println!("Comment ID {} -> {} updated", &comment.get_id().unwrap(), remote_comment_id),
+                                        Err(e) => success = error_message(format!("ERROR: {e}")),
+                                    }
+                                },
+                                Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR creating REMOTE comment: {e}")),
+                            }
                         }
-                    }
-                    success
-                },
-                Err(e) => error_message(format!("ERROR: {e}")),
+                    },
+                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+                }
             }
+            success
         },
-        Ok(None) => error_message(format!("Task ID {task_id} not found")),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
@@ -51,34 +62,31 @@ pub(crate) fn task_comment_edit(task_id: String, comment_id: String, push: bool,
         Ok(Some(mut task)) => {
             let mut comments = task.get_comments().clone();
             if comments.is_none() || comments.as_ref().unwrap().is_empty() {
-                return error_message("Task has no comments".to_string());
+                return error_message_kind(ErrorKind::NotFound, "Task has no comments".to_string());
             }
             let comment = comments.as_mut().unwrap().iter_mut().find(|comment| comment.get_id().unwrap() == comment_id);
             if comment.is_none() {
-                return error_message("Comment not found".to_string());
+                return error_message_kind(ErrorKind::NotFound, "Comment not found".to_string());
             }
             let comment = comment.unwrap();
             match get_text_from_editor(Some(&comment.get_text())) {
                 Some(text) => {
-                    comment.set_text(text.clone());
+                    comment.edit_text(text.clone());
                     task.set_comments(comments.unwrap());
 
                     match gittask::update_task(task) {
                         Ok(_) => {
-                            println!("Task ID {task_id} updated");
-                            let mut success = false;
+                            println!("{}", tr("Task ID {id} updated", &[("id", &task_id)]));
+                            let mut success = true;
                             if push {
                                 match get_user_repo(remote) {
                                     Ok((connector, user, repo)) => {
                                         match connector.update_remote_comment(&user, &repo, &task_id, &comment_id, &text) {
-                                            Ok(_) => {
-                                                println!("Sync: REMOTE comment ID {comment_id} has been updated");
-                                                success = true;
-                                            },
-                                            Err(e) => eprintln!("ERROR: {e}")
+                                            Ok(_) => println!("Sync: REMOTE comment ID {comment_id} has been updated"),
+                                            Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                                         }
                                     },
-                                    Err(e) => eprintln!("ERROR: {e}"),
+                                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                                 }
                             }
                             success
@@ -86,10 +94,10 @@ pub(crate) fn task_comment_edit(task_id: String, comment_id: String, push: bool,
                         Err(e) => error_message(format!("ERROR: {e}")),
                     }
                 },
-                None => error_message("No text specified".to_string())
+                None => error_message_kind(ErrorKind::Validation, "No text specified".to_string())
             }
         },
-        Ok(None) => error_message(format!("Task ID {task_id} not found")),
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
         Err(e) => error_message(format!("ERROR: {e}"))
     }
 }
@@ -101,20 +109,17 @@ pub(crate) fn task_comment_delete(task_id: String, comment_id: String, push: boo
                 Ok(_) => {
                     match gittask::update_task(task) {
                         Ok(_) => {
-                            println!("Task ID {task_id} updated");
-                            let mut success = false;
+                            println!("{}", tr("Task ID {id} updated", &[("id", &task_id)]));
+                            let mut success = true;
                             if push {
                                 match get_user_repo(remote) {
                                     Ok((connector, user, repo)) => {
                                         match connector.delete_remote_comment(&user, &repo, &task_id, &comment_id) {
-                                            Ok(_) => {
-                                                println!("Sync: REMOTE comment ID {comment_id} has been deleted");
-                                                success = true;
-                                            },
-                                            Err(e) => eprintln!("ERROR: {e}")
+                                            Ok(_) => println!("Sync: REMOTE comment ID {comment_id} has been deleted"),
+                                            Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                                         }
                                     },
-                                    Err(e) => eprintln!("ERROR: {e}"),
+                                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                                 }
                             }
                             success
@@ -125,7 +130,83 @@ pub(crate) fn task_comment_delete(task_id: String, comment_id: String, push: boo
                 Err(e) => error_message(format!("ERROR: {e}")),
             }
         },
-        Ok(None) => error_message(format!("Task ID {task_id} not found")),
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
-}
\ No newline at end of file
+}
+pub(crate) fn task_comment_search(pattern: String, ids: Option<String>, regex: bool, ignore_case: bool, no_color: bool) -> bool {
+    let pattern = match regex {
+        true => pattern,
+        false => regex::escape(&pattern),
+    };
+    let regex = match RegexBuilder::new(&pattern).case_insensitive(ignore_case).build() {
+        Ok(regex) => regex,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let mut tasks = match gittask::list_tasks() {
+        Ok(tasks) => tasks,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+    if let Some(ids) = ids.map(parse_ids) {
+        tasks.retain(|task| ids.contains(&task.get_id().unwrap()));
+    }
+    if let Err(e) = gittask::sort_by_task_id(&mut tasks, Task::get_id) {
+        return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}"));
+    }
+
+    let no_color = check_no_color(no_color);
+    let mut count = 0;
+
+    for task in tasks {
+        for comment in task.get_comments().as_ref().unwrap_or(&vec![]) {
+            let text = comment.get_text();
+            let lines = text.lines().filter(|line| regex.is_match(line)).collect::<Vec<_>>();
+            if lines.is_empty() {
+                continue;
+            }
+
+            count += 1;
+            let author = comment.get_all_properties().get("author").map(|author| format!(" by {author}")).unwrap_or_default();
+            println!("{}", colorize_string(&format!("Task ID {}, comment ID {}{author}:", task.get_id().unwrap(), comment.get_id().unwrap_or_default()), Theme::current().title(), no_color));
+            for line in lines {
+                let highlighted = regex.replace_all(line, |caps: &regex::Captures| colorize_string(&caps[0], Yellow, no_color));
+                println!("    {highlighted}");
+            }
+        }
+    }
+
+    if count == 0 {
+        println!("No comments found");
+    }
+
+    true
+}
+
+/// Prints every version of the comment reconstructed from the history of the tasks ref.
+pub(crate) fn task_comment_history(task_id: String, comment_id: String, no_color: bool) -> bool {
+    let history = match gittask::get_comment_history(&task_id, &comment_id) {
+        Ok(history) => history,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+    if history.is_empty() {
+        return error_message_kind(ErrorKind::NotFound, format!("Comment ID {comment_id} of task ID {task_id} not found"));
+    }
+
+    let no_color = check_no_color(no_color);
+    let theme = Theme::current();
+
+    for (n, comment) in history.iter().enumerate() {
+        let props = comment.get_all_properties();
+        let (time, author) = match comment.is_edited() {
+            true => (props.get("edited"), props.get("edited_by")),
+            false => (props.get("created"), props.get("author")),
+        };
+        let time = time.and_then(|time| time.parse::<u64>().ok()).map(|time| format!(", {}", format_display_datetime(time))).unwrap_or_default();
+        let author = author.map(|author| format!(" by {author}")).unwrap_or_default();
+        println!("{}", colorize_string(&format!("--- Version {}{time}{author} ---", n + 1), theme.separator(), no_color));
+        println!("{}", comment.get_text());
+    }
+
+    true
+}
@@ -1,9 +1,19 @@
 use crate::property::PropertyManager;
-use crate::util::{error_message, read_from_pipe, success_message};
+use crate::status::StatusManager;
+use crate::util::{error_message, error_message_kind, read_from_pipe, success_message, ErrorKind};
+
+pub(crate) fn task_config_properties_add(name: String, value_type: String, color: String, style: Option<String>, enum_values: Option<Vec<String>>, cond_format: Option<Vec<String>>, formula: Option<String>) -> bool {
+    if let Some(Err(e)) = formula.as_ref().map(|formula| evalexpr::build_operator_tree(formula)) {
+        return error_message(format!("ERROR: Invalid formula: {e}"));
+    }
 
-pub(crate) fn task_config_properties_add(name: String, value_type: String, color: String, style: Option<String>, enum_values: Option<Vec<String>>, cond_format: Option<Vec<String>>) -> bool {
     let mut prop_manager = PropertyManager::new();
-    match prop_manager.add_property(name.clone(), value_type, color, style, enum_values, cond_format) {
+    let result = prop_manager.add_property(name.clone(), value_type, color, style, enum_values, cond_format)
+        .and_then(|_| match formula {
+            Some(formula) => prop_manager.set_parameter(&name, &"formula".to_string(), &formula),
+            None => Ok(()),
+        });
+    match result {
         Ok(_) => success_message(format!("Property {name} has been added")),
         Err(e) => error_message(format!("ERROR: {e}"))
     }
@@ -40,6 +50,7 @@ pub(crate) fn task_config_properties_set(name: String, param: String, value: Str
     match prop_manager.set_parameter(&name, &param, &value) {
         Ok(_) => {
             println!("{name} {param} has been updated");
+            let mut success = true;
 
             if param.as_str() == "name" {
                 match gittask::list_tasks() {
@@ -50,16 +61,16 @@ pub(crate) fn task_config_properties_set(name: String, param: String, value: Str
                                 task.set_property(&value, &task_prop_value);
                                 task.delete_property(&name);
                                 if let Err(e) = gittask::update_task(task) {
-                                    eprintln!("ERROR: {e}");
+                                    success = error_message(format!("ERROR: {e}"));
                                 }
                             }
                         }
                     },
-                    Err(e) => eprintln!("ERROR: {e}")
+                    Err(e) => success = error_message(format!("ERROR: {e}")),
                 }
             }
 
-            true
+            success
         },
         Err(e) => error_message(format!("ERROR: {e}"))
     }
@@ -67,22 +78,66 @@ pub(crate) fn task_config_properties_set(name: String, param: String, value: Str
 
 pub(crate) fn task_config_properties_list() -> bool {
     let prop_manager = PropertyManager::new();
-    println!("Name\tValue type\tColor\tStyle\tEnum values");
+    println!("Name\tValue type\tColor\tStyle\tEnum values\tFormula");
     prop_manager.get_properties().iter().for_each(|property| {
         let enums = match property.get_enum_values() {
             Some(enum_values) => {
                 enum_values.iter()
-                    .map(|saved_enum| saved_enum.get_name().to_string() + "," + saved_enum.get_color() + (if saved_enum.get_style().is_some() { "," } else { "" }) + saved_enum.get_style().unwrap_or_else(|| ""))
+                    .map(|saved_enum| saved_enum.get_name().to_string() + "," + saved_enum.get_color() + (if saved_enum.get_style().is_some() { "," } else { "" }) + saved_enum.get_style().unwrap_or(""))
                     .collect::<Vec<_>>()
                     .join(";")
             },
             None => String::new()
         };
-        println!("{}\t{}\t{}\t{}\t{}", property.get_name(), property.get_value_type(), property.get_color(), property.get_style().unwrap_or_else(|| ""), enums);
+        println!("{}\t{}\t{}\t{}\t{}\t{}", property.get_name(), property.get_value_type(), property.get_color(), property.get_style().unwrap_or(""), enums, property.get_formula().unwrap_or_default());
     });
     true
 }
 
+pub(crate) fn task_config_properties_describe(name: String) -> bool {
+    let prop_manager = PropertyManager::new();
+    let Some(property) = prop_manager.get_properties().iter().find(|property| property.get_name() == name) else {
+        return error_message_kind(ErrorKind::NotFound, format!("ERROR: property {name} not found"));
+    };
+
+    println!("Name: {}", property.get_name());
+    println!("Type: {}", property.get_value_type());
+    println!("Color: {}", property.get_color());
+    if let Some(style) = property.get_style() {
+        println!("Style: {style}");
+    }
+    if let Some(enum_values) = property.get_enum_values() {
+        println!("Values: {}", enum_values.iter().map(|enum_value| enum_value.get_name()).collect::<Vec<_>>().join(", "));
+    }
+    if let Some(cond_format) = property.get_cond_format().as_ref().filter(|cond_format| !cond_format.is_empty()) {
+        println!("Conditional formatting:");
+        for (n, format) in cond_format.iter().enumerate() {
+            let row = if format.is_row() { " (row)" } else { "" };
+            println!("  {}. {} {} {}{row}", n + 1, format.get_condition(), format.get_color(), format.get_style().unwrap_or_default());
+        }
+    }
+    match property.get_formula() {
+        Some(formula) => println!("Formula: {formula}"),
+        None => if let Ok(tasks) = gittask::list_tasks() {
+            println!("Used by: {} task(s)", tasks.iter().filter(|task| task.has_property(&name)).count());
+        },
+    }
+    true
+}
+
+/// Prints the JSON Schema of tasks in the `export` format.
+pub(crate) fn task_config_properties_schema(pretty: bool) -> bool {
+    let status_manager = StatusManager::new();
+    let statuses = status_manager.get_statuses().iter().map(|status| status.get_name()).collect::<Vec<_>>();
+    let schema = PropertyManager::new().json_schema(&statuses);
+    let func = if pretty { serde_json::to_string_pretty } else { serde_json::to_string };
+
+    match func(&schema) {
+        Ok(result) => success_message(result),
+        Err(e) => error_message(format!("ERROR serializing task schema: {e}")),
+    }
+}
+
 pub(crate) fn task_config_properties_import() -> bool {
     if let Some(input) = read_from_pipe() {
         match PropertyManager::parse_properties(input) {
@@ -127,7 +182,7 @@ pub(crate) fn task_config_properties_enum_list(name: String) -> bool {
             match property.get_enum_values() {
                 Some(enum_values) => {
                     for enum_value in enum_values {
-                        println!("{} {} {}", enum_value.get_name(), enum_value.get_color(), enum_value.get_style().unwrap_or_else(|| ""));
+                        println!("{} {} {}", enum_value.get_name(), enum_value.get_color(), enum_value.get_style().unwrap_or(""));
                     }
                     true
                 },
@@ -177,8 +232,9 @@ pub(crate) fn task_config_properties_cond_format_list(name: String) -> bool {
         Some(property) => {
             match property.get_cond_format() {
                 Some(cond_format) => {
-                    for cond_format_value in cond_format {
-                        println!("{} {} {}", cond_format_value.get_condition(), cond_format_value.get_color(), cond_format_value.get_style().unwrap_or_else(|| ""));
+                    for (n, cond_format_value) in cond_format.iter().enumerate() {
+                        let row = if cond_format_value.is_row() { " row" } else { "" };
+                        println!("{}) {} {} {}{row}", n + 1, cond_format_value.get_condition(), cond_format_value.get_color(), cond_format_value.get_style().unwrap_or(""));
                     }
                     true
                 },
@@ -189,14 +245,30 @@ pub(crate) fn task_config_properties_cond_format_list(name: String) -> bool {
     }
 }
 
-pub(crate) fn task_config_properties_cond_format_add(name: String, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>) -> bool {
+pub(crate) fn task_config_properties_cond_format_add(name: String, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>, row: bool) -> bool {
     let mut prop_manager = PropertyManager::new();
-    match prop_manager.add_cond_format(name, cond_format_expr, cond_format_color, cond_format_style) {
+    match prop_manager.add_cond_format(name, cond_format_expr, cond_format_color, cond_format_style, row) {
         Ok(_) => success_message("Property conditional formatting has been added".to_string()),
         Err(e) => error_message(format!("ERROR: {e}"))
     }
 }
 
+pub(crate) fn task_config_properties_cond_format_set(name: String, number: usize, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>, row: bool) -> bool {
+    let mut prop_manager = PropertyManager::new();
+    match prop_manager.set_cond_format(name, number, cond_format_expr, cond_format_color, cond_format_style, row) {
+        Ok(_) => success_message(format!("Property conditional formatting #{number} has been updated")),
+        Err(e) => error_message(format!("ERROR: {e}"))
+    }
+}
+
+pub(crate) fn task_config_properties_cond_format_delete(name: String, number: usize) -> bool {
+    let mut prop_manager = PropertyManager::new();
+    match prop_manager.delete_cond_format(name, number) {
+        Ok(_) => success_message(format!("Property conditional formatting #{number} has been deleted")),
+        Err(e) => error_message(format!("ERROR: {e}"))
+    }
+}
+
 pub(crate) fn task_config_properties_cond_format_clear(name: String) -> bool {
     let mut prop_manager = PropertyManager::new();
     match prop_manager.clear_cond_format(name) {
@@ -42,6 +42,7 @@ pub(crate) fn task_config_status_set(name: String, param: String, value: String)
     match status_manager.set_property(&name, &param, &value) {
         Ok(prev_value) => {
             println!("{name} {param} has been updated");
+            let mut success = true;
 
             if param.as_str() == "name" && prev_value.is_some() {
                 let prev_status = prev_value.unwrap();
@@ -51,26 +52,35 @@ pub(crate) fn task_config_status_set(name: String, param: String, value: String)
                             if task.get_property("status").unwrap() == prev_status.as_str() {
                                 task.set_property("status", &value);
                                 if let Err(e) = gittask::update_task(task) {
-                                    eprintln!("ERROR: {e}");
+                                    success = error_message(format!("ERROR: {e}"));
                                 }
                             }
                         }
                     },
-                    Err(e) => eprintln!("ERROR: {e}")
+                    Err(e) => success = error_message(format!("ERROR: {e}")),
                 }
             }
 
-            true
+            success
         },
         Err(e) => error_message(format!("ERROR: {e}"))
     }
 }
 
+pub(crate) fn task_config_status_move(name: String, position: usize) -> bool {
+    let mut status_manager = StatusManager::new();
+    let name = status_manager.get_full_status_name(&name);
+    match status_manager.move_status(&name, position) {
+        Ok(_) => success_message(format!("{name} has been moved to position {position}")),
+        Err(e) => error_message(format!("ERROR: {e}"))
+    }
+}
+
 pub(crate) fn task_config_status_list() -> bool {
     let status_manager = StatusManager::new();
     println!("Name\tShortcut\tColor\tStyle\tIs DONE");
     status_manager.get_statuses().iter().for_each(|status| {
-        println!("{}\t{}\t{}\t{}\t{}", status.get_name(), status.get_shortcut(), status.get_color(), status.get_style().unwrap_or_else(|| ""), status.is_done());
+        println!("{}\t{}\t{}\t{}\t{}", status.get_name(), status.get_shortcut(), status.get_color(), status.get_style().unwrap_or(""), status.is_done());
     });
     true
 }
@@ -0,0 +1,64 @@
+use crate::operations::check_no_color;
+use crate::property::PropertyManager;
+use crate::status::StatusManager;
+use crate::theme::{Theme, THEMES};
+use crate::util::{colorize_string, error_message, error_message_kind, str_to_color, success_message, ErrorKind};
+
+pub(crate) fn task_config_theme_list() -> bool {
+    let current = Theme::current();
+    for name in THEMES {
+        let marker = if name == current.get_name() { "*" } else { " " };
+        println!("{marker} {name}");
+    }
+    true
+}
+
+pub(crate) fn task_config_theme_set(name: String) -> bool {
+    let name = name.to_lowercase();
+    let Some(theme) = Theme::get(&name) else {
+        return error_message_kind(ErrorKind::Validation, format!("Unknown theme {name}, expected one of: {}", THEMES.join(", ")));
+    };
+
+    if let Err(e) = gittask::set_config_value("task.theme", &name) {
+        return error_message(format!("ERROR: {e}"));
+    }
+
+    match theme.apply(&mut StatusManager::new(), &mut PropertyManager::new()) {
+        Ok(_) => success_message(format!("Theme {name} has been applied")),
+        Err(e) => error_message(format!("ERROR: {e}"))
+    }
+}
+
+/// Prints sample titles, statuses and property values in the colors of the theme, the current one by default.
+pub(crate) fn task_config_theme_preview(name: Option<String>, no_color: bool) -> bool {
+    let theme = match name.map(|name| name.to_lowercase()) {
+        Some(name) => match Theme::get(&name) {
+            Some(theme) => theme,
+            None => return error_message_kind(ErrorKind::Validation, format!("Unknown theme {name}, expected one of: {}", THEMES.join(", "))),
+        },
+        None => Theme::current(),
+    };
+
+    let no_color = check_no_color(no_color);
+    let status_manager = StatusManager::new();
+    let prop_manager = PropertyManager::new();
+    let paint = |text: &str, property: &str| {
+        let color = theme.get_property_color(property)
+            .or_else(|| prop_manager.get_properties().iter().find(|p| p.get_name() == property).map(|p| p.get_color()))
+            .unwrap_or("Default");
+        colorize_string(text, str_to_color(color, &None), no_color)
+    };
+
+    println!("{}", colorize_string(&format!("--- {} ---", theme.get_name()), theme.separator(), no_color));
+    println!("{}: {}", colorize_string("ID", theme.title(), no_color), paint("1", "id"));
+    println!("{}: {}", colorize_string("Created", theme.title(), no_color), paint("2024-01-01 12:00", "created"));
+    println!("{}: {}", colorize_string("Author", theme.title(), no_color), paint("Jane Doe", "author"));
+    let statuses = status_manager.get_statuses().iter().map(|status| {
+        let color = theme.get_status_color(status.get_name(), &status_manager).unwrap_or(status.get_color());
+        colorize_string(status.get_name(), str_to_color(color, &None), no_color)
+    }).collect::<Vec<_>>();
+    println!("{}: {}", colorize_string("Status", theme.title(), no_color), statuses.join(" "));
+    println!("{}", colorize_string("---------------", theme.separator(), no_color));
+
+    true
+}
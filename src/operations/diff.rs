@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+
+use nu_ansi_term::Color::{Green, Red, Yellow};
+
+use gittask::Task;
+
+use crate::i18n::tr;
+use crate::operations::check_no_color;
+use crate::operations::remote::{delete_bundle_ref, fetch_bundle};
+use crate::util::{colorize_string, error_message_kind, print_diff, ErrorKind};
+
+/// Bookkeeping properties that change with every update.
+const IGNORED_PROPERTIES: [&str; 2] = ["updated", "updated_by"];
+
+#[derive(Debug, PartialEq)]
+enum TaskChange {
+    Created { id: String, name: String },
+    Deleted { id: String, name: String },
+    /// Changed properties, labels and comments with their old and new values
+    Changed { id: String, name: String, changes: Vec<(String, String, String)> },
+}
+
+/// Compares tasks at two revisions of the tasks ref, the current tasks by default.
+/// A revision can be a git bundle made by `export --with-history`.
+pub(crate) fn task_diff(rev1: String, rev2: Option<String>, no_color: bool) -> bool {
+    let old_tasks = match load_revision(&rev1) {
+        Ok(tasks) => tasks,
+        Err(e) => return error_message_kind(ErrorKind::NotFound, format!("ERROR: {rev1}: {e}")),
+    };
+    let new_tasks = match rev2.as_deref().map(load_revision).unwrap_or_else(gittask::list_tasks) {
+        Ok(tasks) => tasks,
+        Err(e) => return error_message_kind(ErrorKind::NotFound, format!("ERROR: {}: {e}", rev2.unwrap_or_default())),
+    };
+
+    let changes = match diff_tasks(&old_tasks, &new_tasks) {
+        Ok(changes) => changes,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+    let no_color = check_no_color(no_color);
+    let (mut created, mut deleted, mut changed) = (0, 0, 0);
+    for change in &changes {
+        match change {
+            TaskChange::Created { id, name } => {
+                created += 1;
+                println!("{}", colorize_string(&format!("+ {id} {name}"), Green, no_color));
+            },
+            TaskChange::Deleted { id, name } => {
+                deleted += 1;
+                println!("{}", colorize_string(&format!("- {id} {name}"), Red, no_color));
+            },
+            TaskChange::Changed { id, name, changes } => {
+                changed += 1;
+                println!("{}", colorize_string(&format!("~ {id} {name}"), Yellow, no_color));
+                for (prop, old_value, new_value) in changes {
+                    match old_value.contains('\n') || new_value.contains('\n') {
+                        true => {
+                            println!("  {prop}:");
+                            print_diff(old_value, new_value, no_color);
+                        },
+                        false => println!("  {prop}: {old_value} -> {new_value}"),
+                    }
+                }
+            },
+        }
+    }
+
+    match changes.is_empty() {
+        true => println!("{}", tr("No changes", &[])),
+        false => println!("Created: {created}, deleted: {deleted}, changed: {changed}"),
+    }
+    true
+}
+
+fn load_revision(revision: &str) -> Result<Vec<Task>, String> {
+    if !std::path::Path::new(revision).is_file() {
+        return gittask::list_tasks_at(revision);
+    }
+
+    let tracking_ref = fetch_bundle(revision)?;
+    let tasks = gittask::list_tasks_at(&tracking_ref);
+    delete_bundle_ref(&tracking_ref);
+    tasks
+}
+
+fn diff_tasks(old_tasks: &[Task], new_tasks: &[Task]) -> Result<Vec<TaskChange>, String> {
+    let find = |tasks: &'_ [Task], id: &str| tasks.iter().find(|task| task.get_id().as_deref() == Some(id)).cloned();
+    let mut ids = old_tasks.iter().chain(new_tasks).filter_map(|task| task.get_id()).collect::<BTreeSet<_>>().into_iter().collect::<Vec<_>>();
+    gittask::sort_by_task_id(&mut ids, |id| Some(id.clone()))?;
+
+    Ok(ids.into_iter().filter_map(|id| {
+        match (find(old_tasks, &id), find(new_tasks, &id)) {
+            (None, Some(task)) => Some(TaskChange::Created { id, name: task.get_property("name").cloned().unwrap_or_default() }),
+            (Some(task), None) => Some(TaskChange::Deleted { id, name: task.get_property("name").cloned().unwrap_or_default() }),
+            (Some(old_task), Some(new_task)) => {
+                let changes = diff_task(&old_task, &new_task);
+                match changes.is_empty() {
+                    true => None,
+                    false => Some(TaskChange::Changed { id, name: new_task.get_property("name").cloned().unwrap_or_default(), changes }),
+                }
+            },
+            (None, None) => None,
+        }
+    }).collect())
+}
+
+fn diff_task(old_task: &Task, new_task: &Task) -> Vec<(String, String, String)> {
+    let mut changes = vec![];
+
+    let props = old_task.get_all_properties().keys().chain(new_task.get_all_properties().keys())
+        .filter(|prop| !IGNORED_PROPERTIES.contains(&prop.as_str()))
+        .collect::<BTreeSet<_>>();
+    for prop in props {
+        let old_value = old_task.get_property(prop).cloned().unwrap_or_default();
+        let new_value = new_task.get_property(prop).cloned().unwrap_or_default();
+        if old_value != new_value {
+            changes.push((prop.clone(), old_value, new_value));
+        }
+    }
+
+    let label_names = |task: &Task| task.get_labels().iter().flatten().map(|label| label.get_name()).collect::<Vec<_>>().join(", ");
+    let (old_labels, new_labels) = (label_names(old_task), label_names(new_task));
+    if old_labels != new_labels {
+        changes.push(("labels".to_string(), old_labels, new_labels));
+    }
+
+    let old_comments = old_task.get_comments().clone().unwrap_or_default();
+    let new_comments = new_task.get_comments().clone().unwrap_or_default();
+    let comment_ids = old_comments.iter().chain(&new_comments).filter_map(|comment| comment.get_id()).collect::<BTreeSet<_>>();
+    for comment_id in comment_ids {
+        let text = |comments: &[gittask::Comment]| comments.iter().find(|comment| comment.get_id() == Some(comment_id.clone())).map(|comment| comment.get_text());
+        let (old_text, new_text) = (text(&old_comments), text(&new_comments));
+        if old_text != new_text {
+            changes.push((format!("comment {comment_id}"), old_text.unwrap_or_default(), new_text.unwrap_or_default()));
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_tasks() {
+        let task = |id: &str, name: &str, status: &str| {
+            let mut task = Task::new(name.to_string(), String::new(), status.to_string()).unwrap();
+            task.set_id(id.to_string());
+            task.set_property("created", "1700000000");
+            task.set_property("updated", id);
+            task
+        };
+
+        let mut closed = task("2", "Two", "CLOSED");
+        closed.add_comment(Some("1".to_string()), HashMap::new(), "Done".to_string());
+        closed.set_property("updated", "changed");
+        let old_tasks = vec![task("1", "One", "OPEN"), task("2", "Two", "OPEN"), task("10", "Ten", "OPEN")];
+        let new_tasks = vec![closed, task("3", "Three", "OPEN"), task("10", "Ten", "OPEN")];
+
+        assert_eq!(diff_tasks(&old_tasks, &new_tasks).unwrap(), vec![
+            TaskChange::Deleted { id: "1".to_string(), name: "One".to_string() },
+            TaskChange::Changed { id: "2".to_string(), name: "Two".to_string(), changes: vec![
+                ("status".to_string(), "OPEN".to_string(), "CLOSED".to_string()),
+                ("comment 1".to_string(), String::new(), "Done".to_string()),
+            ] },
+            TaskChange::Created { id: "3".to_string(), name: "Three".to_string() },
+        ]);
+        assert!(diff_tasks(&old_tasks, &old_tasks).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+
+use nu_ansi_term::Color::{Green, Red, Yellow};
+
+use crate::connectors::get_matching_remote_connectors;
+use crate::operations::check_no_color;
+use crate::property::PropertyManager;
+use crate::status::parse_statuses;
+use crate::util::{colorize_string, format_display_datetime, get_editor, split_args};
+
+/// Tolerated clock skew for task timestamps, in seconds.
+const CLOCK_SKEW: i64 = 5 * 60;
+
+#[derive(PartialEq)]
+enum Check {
+    Ok,
+    Warning,
+    Failure,
+}
+
+struct Doctor {
+    no_color: bool,
+    failures: usize,
+    warnings: usize,
+}
+
+impl Doctor {
+    fn report(&mut self, check: Check, message: String, fix: Option<String>) {
+        let mark = match check {
+            Check::Ok => colorize_string("[ OK ]", Green, self.no_color),
+            Check::Warning => {
+                self.warnings += 1;
+                colorize_string("[WARN]", Yellow, self.no_color)
+            },
+            Check::Failure => {
+                self.failures += 1;
+                colorize_string("[FAIL]", Red, self.no_color)
+            },
+        };
+        println!("{mark} {message}");
+        if let Some(fix) = fix {
+            println!("       fix: {fix}");
+        }
+    }
+}
+
+pub(crate) fn task_doctor(offline: bool, no_color: bool) -> bool {
+    let mut doctor = Doctor { no_color: check_no_color(no_color), failures: 0, warnings: 0 };
+
+    check_tasks_ref(&mut doctor);
+    check_config(&mut doctor);
+    check_remotes(&mut doctor, offline);
+    check_editor(&mut doctor);
+
+    match (doctor.failures, doctor.warnings) {
+        (0, 0) => println!("\nEverything looks fine"),
+        (failures, warnings) => println!("\n{failures} problem(s), {warnings} warning(s) found"),
+    }
+
+    doctor.failures == 0
+}
+
+fn check_tasks_ref(doctor: &mut Doctor) {
+    let ref_path = gittask::get_ref_path();
+    match gittask::check_tasks_ref() {
+        Ok(None) => doctor.report(Check::Warning, format!("Tasks ref {ref_path} doesn't exist yet"), Some("create a task with `git task create` or fetch tasks from a remote".to_string())),
+        Ok(Some((count, time, problems))) => {
+            match gittask::get_format_version() {
+                Ok(Some(version)) if version < gittask::FORMAT_VERSION => doctor.report(Check::Warning, format!("Tasks format version {version} is outdated"), Some("git task migrate".to_string())),
+                Ok(Some(version)) if version > gittask::FORMAT_VERSION => doctor.report(Check::Failure, format!("Tasks format version {version} is newer than supported"), Some("upgrade git-task".to_string())),
+                Ok(_) => {},
+                Err(e) => doctor.report(Check::Failure, e, Some("git task migrate".to_string())),
+            }
+            if problems.is_empty() {
+                doctor.report(Check::Ok, format!("Tasks ref {ref_path} contains {count} task(s)"), None);
+            } else {
+                for problem in problems {
+                    doctor.report(Check::Failure, format!("Tasks ref {ref_path}: {problem}"), Some(format!("inspect the entry with `git ls-tree {ref_path}` and remove or fix it")));
+                }
+            }
+            check_clock(doctor, time);
+        },
+        Err(e) => doctor.report(Check::Failure, format!("Tasks ref {ref_path} can't be read: {e}"), Some("make sure you're inside a git repository, or use -C / --git-dir".to_string())),
+    }
+}
+
+fn check_clock(doctor: &mut Doctor, last_commit_time: i64) {
+    let now = chrono::Utc::now().timestamp();
+    if last_commit_time > now + CLOCK_SKEW {
+        doctor.report(Check::Warning, format!("Latest tasks commit is in the future ({})", format_display_datetime(last_commit_time as u64)), Some("check the system clock and time zone settings".to_string()));
+        return;
+    }
+
+    let future_tasks = gittask::list_tasks().unwrap_or_default().iter()
+        .filter(|task| task.created_at().is_some_and(|created| created.timestamp() > now + CLOCK_SKEW))
+        .map(|task| task.get_id().unwrap())
+        .collect::<Vec<_>>();
+    if future_tasks.is_empty() {
+        doctor.report(Check::Ok, "Clock is consistent with task timestamps".to_string(), None);
+    } else {
+        doctor.report(Check::Warning, format!("Task(s) {} have creation time in the future", future_tasks.join(", ")), Some("check the system clock, then fix the tasks with `git task set <ID> created <TIMESTAMP>`".to_string()));
+    }
+}
+
+fn check_config(doctor: &mut Doctor) {
+    let ref_path = gittask::get_ref_path();
+    if !git2::Reference::is_valid_name(&ref_path) || !ref_path.starts_with("refs/") {
+        doctor.report(Check::Failure, format!("task.ref '{ref_path}' is not a valid reference name"), Some("git task config set task.ref refs/tasks/tasks".to_string()));
+    }
+
+    if let Ok(statuses) = gittask::get_config_value("task.statuses") {
+        match parse_statuses(statuses) {
+            Ok(_) => doctor.report(Check::Ok, "task.statuses is valid".to_string(), None),
+            Err(e) => doctor.report(Check::Failure, format!("task.statuses can't be parsed: {e}"), Some("git task config status reset".to_string())),
+        }
+    }
+
+    if let Ok(properties) = gittask::get_config_value("task.properties") {
+        match PropertyManager::parse_properties(properties) {
+            Ok(_) => doctor.report(Check::Ok, "task.properties is valid".to_string(), None),
+            Err(e) => doctor.report(Check::Failure, format!("task.properties can't be parsed: {e}"), Some("git task config properties reset".to_string())),
+        }
+    }
+
+    if let Ok(aliases) = gittask::get_config_value("task.aliases") {
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&aliases) {
+            doctor.report(Check::Failure, format!("task.aliases can't be parsed: {e}"), Some("git config --unset task.aliases".to_string()));
+        }
+    }
+
+    for param in ["task.readonly", "task.assign.auto"] {
+        if let Ok(value) = gittask::get_config_value(param) {
+            if value.parse::<bool>().is_err() {
+                doctor.report(Check::Failure, format!("{param} has invalid value '{value}'"), Some(format!("git task config set {param} true|false")));
+            }
+        }
+    }
+}
+
+fn check_remotes(doctor: &mut Doctor, offline: bool) {
+    let remotes = gittask::list_remotes(&None).unwrap_or_default();
+    let connectors = get_matching_remote_connectors(remotes);
+    if connectors.is_empty() {
+        doctor.report(Check::Ok, "No remotes with a supported connector, sync is not configured".to_string(), None);
+        return;
+    }
+
+    for (connector, user, repo) in connectors {
+        if offline {
+            doctor.report(Check::Ok, format!("Remote {user}/{repo} found, connection check skipped"), None);
+            continue;
+        }
+        match connector.check_connection(&user, &repo) {
+            Ok(message) => doctor.report(Check::Ok, message, None),
+            Err(e) => doctor.report(Check::Failure, format!("Remote {user}/{repo}: {e}"), Some("export a valid API token, e.g. GITHUB_TOKEN, GITLAB_TOKEN or JIRA_TOKEN".to_string())),
+        }
+    }
+}
+
+fn check_editor(doctor: &mut Doctor) {
+    let editor = get_editor();
+    match find_editor(&editor) {
+        true => doctor.report(Check::Ok, format!("Editor '{editor}' found"), None),
+        false => doctor.report(Check::Warning, format!("Editor '{editor}' not found"), Some("set GIT_EDITOR, core.editor, VISUAL or EDITOR to an installed editor".to_string())),
+    }
+}
+
+/// The editor may come with arguments, e.g. `code --wait`, only the program itself is looked up.
+fn find_editor(editor: &str) -> bool {
+    split_args(editor).ok().and_then(|args| args.into_iter().next()).is_some_and(|program| find_executable(&program))
+}
+
+fn find_executable(program: &str) -> bool {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file();
+    }
+
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".cmd", ".bat"] } else { &[""] };
+    std::env::var_os("PATH").is_some_and(|paths| std::env::split_paths(&paths).any(|dir| {
+        extensions.iter().any(|extension| dir.join(format!("{program}{extension}")).is_file())
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_executable() {
+        assert!(find_executable(if cfg!(windows) { "cmd" } else { "sh" }));
+        assert!(!find_executable("surely-not-an-installed-editor"));
+        assert!(!find_executable("./no/such/editor"));
+    }
+
+    #[test]
+    fn test_find_editor() {
+        let shell = if cfg!(windows) { "cmd" } else { "sh" };
+        assert!(find_editor(shell));
+        assert!(find_editor(&format!("{shell} --wait")));
+        assert!(find_editor(&format!("  \"{shell}\" -n ")));
+        assert!(!find_editor("surely-not-an-installed-editor --wait"));
+        assert!(!find_editor(""));
+    }
+}
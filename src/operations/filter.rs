@@ -0,0 +1,102 @@
+use regex::Regex;
+
+use gittask::{PropertyValue, Task};
+
+use crate::property::PropertyManager;
+
+enum FilterOp {
+    Equals,
+    NotEquals,
+    Matches(Regex),
+}
+
+/// Property filter of `--prop`: `KEY=VALUE`, `KEY!=VALUE` or `KEY~=REGEX`.
+/// Values are compared case-insensitively, list properties have to contain a matching value.
+pub(crate) struct PropertyFilter {
+    prop: String,
+    op: FilterOp,
+    value: String,
+}
+
+impl PropertyFilter {
+    pub(crate) fn parse(filter: &str) -> Result<PropertyFilter, String> {
+        let invalid = || format!("Invalid property filter '{filter}', expected KEY=VALUE, KEY!=VALUE or KEY~=REGEX");
+        let (key, value) = filter.split_once('=').ok_or_else(invalid)?;
+        let (prop, op) = match (key.strip_suffix('!'), key.strip_suffix('~')) {
+            (Some(prop), _) => (prop, FilterOp::NotEquals),
+            (_, Some(prop)) => (prop, FilterOp::Matches(Regex::new(value).map_err(|e| format!("Invalid regex in '{filter}': {e}"))?)),
+            _ => (key, FilterOp::Equals),
+        };
+        if prop.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(PropertyFilter { prop: prop.trim().to_string(), op, value: value.to_string() })
+    }
+
+    /// Parses all filters, the first invalid one is an error.
+    pub(crate) fn parse_all(filters: Option<Vec<String>>) -> Result<Vec<PropertyFilter>, String> {
+        filters.unwrap_or_default().iter().map(|filter| PropertyFilter::parse(filter)).collect()
+    }
+
+    pub(crate) fn matches(&self, task: &Task, prop_manager: &PropertyManager) -> bool {
+        let values = match task.get_property(&self.prop) {
+            Some(value) if prop_manager.is_list(&self.prop) => Vec::<String>::parse_value(value).unwrap_or_default(),
+            Some(value) => vec![value.clone()],
+            None => vec![String::new()],
+        };
+        let equals = || values.iter().any(|value| value.eq_ignore_ascii_case(&self.value));
+        match &self.op {
+            FilterOp::Equals => equals(),
+            FilterOp::NotEquals => !equals(),
+            FilterOp::Matches(regex) => values.iter().any(|value| regex.is_match(value)),
+        }
+    }
+}
+
+/// Checks the task against all filters.
+pub(crate) fn matches_all(filters: &[PropertyFilter], task: &Task, prop_manager: &PropertyManager) -> bool {
+    filters.iter().all(|filter| filter.matches(task, prop_manager))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_property_filter() {
+        let prop_manager = gittask::TaskRepository::temporary().unwrap().run(|| {
+            let mut prop_manager = PropertyManager::new();
+            prop_manager.add_property("tags".to_string(), "list".to_string(), "Default".to_string(), None, None, None).unwrap();
+            prop_manager
+        });
+        let task = Task::builder("[WIP] Crash").id("1").status("OPEN")
+            .property("component", "Parser")
+            .property("tags", r#"["ui","api"]"#)
+            .property("note", "[draft]")
+            .build().unwrap();
+        let matches = |filter: &str| PropertyFilter::parse(filter).unwrap().matches(&task, &prop_manager);
+
+        assert!(matches("component=parser"));
+        assert!(!matches("component=lexer"));
+        assert!(matches("component!=lexer"));
+        assert!(!matches("component!=Parser"));
+        assert!(matches("component~=^Par"));
+        assert!(!matches("component~=^par"));
+        assert!(matches("tags=API"));
+        assert!(matches("tags!=db"));
+        assert!(matches("tags~=^a"));
+        assert!(matches("assignee="));
+        assert!(matches("assignee!=alice"));
+        assert!(!matches("assignee~=."));
+        assert!(matches("name=[WIP] Crash"));
+        assert!(matches("name~=^\\[WIP\\]"));
+        assert!(matches("note=[draft]"));
+        assert!(!matches("note=draft"));
+
+        assert!(PropertyFilter::parse("component").is_err());
+        assert!(PropertyFilter::parse("!=x").is_err());
+        assert!(PropertyFilter::parse("component~=(").is_err());
+        assert_eq!(PropertyFilter::parse_all(Some(vec!["a=1".to_string(), "b!=2".to_string()])).unwrap().len(), 2);
+    }
+}
@@ -1,70 +1,191 @@
-use crate::operations::get_user_repo;
-use crate::util::{error_message};
-
-pub(crate) fn task_label_add(task_id: String, name: String, color: Option<String>, description: Option<String>, push: bool, remote: &Option<String>) -> bool {
-    match gittask::find_task(&task_id) {
-        Ok(Some(mut task)) => {
-            let label = task.add_label(name.clone(), description.clone(), color.clone());
-            match gittask::update_task(task) {
-                Ok(_) => {
-                    println!("Task ID {task_id} updated");
-                    let mut success = false;
-                    if push {
-                        match get_user_repo(remote) {
-                            Ok((connector, user, repo)) => {
-                                match connector.create_remote_label(&user, &repo, &task_id, &label) {
-                                    Ok(_) => {
-                                        println!("Added REMOTE label {}", label.get_name());
-                                        success = true;
-                                    },
-                                    Err(e) => eprintln!("ERROR adding REMOTE label: {e}")
-                                }
-                            },
-                            Err(e) => eprintln!("ERROR: {e}"),
+use gittask::{Label, Task};
+
+use crate::i18n::tr;
+use crate::operations::{check_no_color, get_user_repo};
+use crate::status::StatusManager;
+use crate::util::{colorize_string, error_message, error_message_kind, parse_ids, str_to_color, ErrorKind};
+
+fn find_tasks(ids: String) -> Result<Vec<Task>, bool> {
+    let mut tasks = vec![];
+    for task_id in parse_ids(ids) {
+        match gittask::find_task(&task_id) {
+            Ok(Some(task)) => tasks.push(task),
+            Ok(None) => return Err(error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)]))),
+            Err(e) => return Err(error_message(format!("ERROR: {e}"))),
+        }
+    }
+    Ok(tasks)
+}
+
+pub(crate) fn task_label_add(ids: String, name: String, color: Option<String>, description: Option<String>, push: bool, remote: &Option<String>) -> bool {
+    let mut tasks = match find_tasks(ids) {
+        Ok(tasks) => tasks,
+        Err(result) => return result,
+    };
+
+    // tasks that already have the label are left as they are
+    tasks.retain(|task| !task.get_labels().iter().flatten().any(|label| label.get_name() == name));
+    if tasks.is_empty() {
+        return error_message_kind(ErrorKind::Validation, format!("All tasks already have label '{name}'"));
+    }
+
+    let labels = tasks.iter_mut().map(|task| (task.get_id().unwrap(), task.add_label(name.clone(), description.clone(), color.clone()))).collect::<Vec<_>>();
+
+    match gittask::update_tasks(tasks) {
+        Ok(ids) => {
+            ids.iter().for_each(|task_id| println!("{}", tr("Task ID {id} updated", &[("id", &task_id)])));
+            let mut success = true;
+            if push {
+                match get_user_repo(remote) {
+                    Ok((connector, user, repo)) => {
+                        for (task_id, label) in labels {
+                            match connector.create_remote_label(&user, &repo, &task_id, &label) {
+                                Ok(_) => println!("Added REMOTE label {} to task ID {task_id}", label.get_name()),
+                                Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR adding REMOTE label: {e}")),
+                            }
                         }
-                    }
-                    success
-                },
-                Err(e) => error_message(format!("ERROR: {e}")),
+                    },
+                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+                }
             }
+            success
         },
-        Ok(None) => error_message(format!("Task ID {task_id} not found")),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
 }
 
-pub(crate) fn task_label_delete(task_id: String, name: String, push: bool, remote: &Option<String>) -> bool {
-    match gittask::find_task(&task_id) {
-        Ok(Some(mut task)) => {
-            match task.delete_label(&name) {
-                Ok(_) => {
-                    match gittask::update_task(task) {
-                        Ok(_) => {
-                            println!("Task ID {task_id} updated");
-                            let mut success = false;
-                            if push {
-                                match get_user_repo(remote) {
-                                    Ok((connector, user, repo)) => {
-                                        match connector.delete_remote_label(&user, &repo, &task_id, &name) {
-                                            Ok(_) => {
-                                                println!("Sync: REMOTE label '{name}' has been deleted");
-                                                success = true;
-                                            },
-                                            Err(e) => eprintln!("ERROR: {e}")
-                                        }
-                                    },
-                                    Err(e) => eprintln!("ERROR: {e}"),
-                                }
+pub(crate) fn task_label_delete(ids: String, name: String, push: bool, remote: &Option<String>) -> bool {
+    let mut tasks = match find_tasks(ids) {
+        Ok(tasks) => tasks,
+        Err(result) => return result,
+    };
+
+    // tasks without the label are left as they are
+    tasks.retain(|task| task.get_labels().iter().flatten().any(|label| label.get_name() == name));
+    if tasks.is_empty() {
+        return error_message_kind(ErrorKind::NotFound, format!("No tasks have label '{name}'"));
+    }
+
+    for task in tasks.iter_mut() {
+        if let Err(e) = task.delete_label(&name) {
+            return error_message(format!("ERROR: task ID {}: {e}", task.get_id().unwrap()));
+        }
+    }
+
+    match gittask::update_tasks(tasks) {
+        Ok(ids) => {
+            ids.iter().for_each(|task_id| println!("{}", tr("Task ID {id} updated", &[("id", &task_id)])));
+            let mut success = true;
+            if push {
+                match get_user_repo(remote) {
+                    Ok((connector, user, repo)) => {
+                        for task_id in &ids {
+                            match connector.delete_remote_label(&user, &repo, task_id, &name) {
+                                Ok(_) => println!("Sync: REMOTE label '{name}' of task ID {task_id} has been deleted"),
+                                Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
                             }
-                            success
-                        },
-                        Err(e) => error_message(format!("ERROR: {e}")),
-                    }
-                },
-                Err(e) => error_message(format!("ERROR: {e}")),
+                        }
+                    },
+                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+                }
             }
+            success
         },
-        Ok(None) => error_message(format!("Task ID {task_id} not found")),
         Err(e) => error_message(format!("ERROR: {e}")),
     }
-}
\ No newline at end of file
+}
+
+/// Renames the label on every task carrying it in a single commit, tasks in final statuses only with `all`.
+/// The remote label is replaced with the new one on each task.
+pub(crate) fn task_label_rename(name: String, new_name: String, all: bool, push: bool, remote: &Option<String>) -> bool {
+    let tasks = match gittask::list_tasks() {
+        Ok(tasks) => tasks,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let status_manager = StatusManager::new();
+    let mut tasks = tasks.into_iter()
+        .filter(|task| all || !task.get_property("status").is_some_and(|status| status_manager.is_done(status)))
+        .filter(|task| task.get_labels().iter().flatten().any(|label| label.get_name() == name))
+        .collect::<Vec<_>>();
+    if tasks.is_empty() {
+        return error_message_kind(ErrorKind::NotFound, format!("No tasks with label '{name}' found"));
+    }
+
+    let labels = tasks.iter_mut().map(|task| {
+        task.rename_label(&name, &new_name);
+        let label = task.get_labels().iter().flatten().find(|label| label.get_name() == new_name).cloned().unwrap();
+        (task.get_id().unwrap(), label)
+    }).collect::<Vec<_>>();
+
+    match gittask::update_tasks(tasks) {
+        Ok(ids) => {
+            println!("Label '{name}' has been renamed to '{new_name}' in {} task(s)", ids.len());
+            let mut success = true;
+            if push {
+                match get_user_repo(remote) {
+                    Ok((connector, user, repo)) => {
+                        for (task_id, label) in labels {
+                            let result = connector.delete_remote_label(&user, &repo, &task_id, &name)
+                                .and_then(|_| connector.create_remote_label(&user, &repo, &task_id, &label));
+                            match result {
+                                Ok(_) => println!("Sync: REMOTE label of task ID {task_id} has been renamed"),
+                                Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR renaming REMOTE label of task ID {task_id}: {e}")),
+                            }
+                        }
+                    },
+                    Err(e) => success = error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+                }
+            }
+            success
+        },
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+/// Lists labels of the task or, without a task ID, all labels in use with the number of tasks carrying them.
+pub(crate) fn task_label_list(task_id: Option<String>, no_color: bool) -> bool {
+    let no_color = check_no_color(no_color);
+    let paint = |label: &Label| colorize_string(&label.get_name(), str_to_color(&label.get_color(), &None), no_color);
+
+    if let Some(task_id) = task_id {
+        return match gittask::find_task(&task_id) {
+            Ok(Some(task)) => {
+                for label in task.get_labels().iter().flatten() {
+                    println!("{}\t{}\t{}", paint(label), label.get_color(), label.get_description().unwrap_or_default());
+                }
+                true
+            },
+            Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+            Err(e) => error_message(format!("ERROR: {e}")),
+        };
+    }
+
+    let summaries = match gittask::list_task_summaries() {
+        Ok(summaries) => summaries,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    // the first label with a color represents all labels of the same name
+    let mut labels: Vec<(Label, usize)> = vec![];
+    for label in summaries.iter().flat_map(|summary| summary.get_labels().iter().flatten()) {
+        match labels.iter_mut().find(|(saved_label, _)| saved_label.get_name() == label.get_name()) {
+            Some((saved_label, count)) => {
+                *count += 1;
+                if saved_label.get_color().is_empty() && !label.get_color().is_empty() {
+                    saved_label.set_color(label.get_color());
+                }
+            },
+            None => labels.push((label.clone(), 1)),
+        }
+    }
+    labels.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.get_name().cmp(&b.get_name())));
+
+    let width = labels.iter().map(|(label, _)| label.get_name().chars().count()).max().unwrap_or(0);
+    for (label, count) in &labels {
+        let padding = " ".repeat(width - label.get_name().chars().count());
+        println!("{}{padding} {count:>5} {}", paint(label), label.get_color());
+    }
+
+    true
+}
@@ -0,0 +1,163 @@
+use std::process::Command;
+
+use gittask::FetchResult;
+use crate::util::{error_message, error_message_kind, success_message, ErrorKind};
+
+pub(crate) fn task_remote_setup(remote: Option<String>, auto_fetch: bool) -> bool {
+    let remote = gittask::get_tasks_remote(&remote);
+    if let Err(e) = gittask::set_config_value("task.remote", &remote) {
+        return error_message(format!("ERROR: {e}"));
+    }
+
+    match gittask::set_auto_fetch(&remote, auto_fetch) {
+        Ok(_) if auto_fetch => success_message(format!("Tasks are published to and fetched from {remote}, git fetch brings them along")),
+        Ok(_) => success_message(format!("Tasks are published to and fetched from {remote} only with git task publish and git task fetch")),
+        Err(e) => error_message_kind(ErrorKind::NotFound, format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_remote_show() -> bool {
+    let remote = gittask::get_tasks_remote(&None);
+    match gittask::is_auto_fetch(&remote) {
+        Ok(auto_fetch) => success_message(format!("remote: {remote}\nref: {}\nauto fetch: {auto_fetch}", gittask::get_ref_path())),
+        Err(e) => error_message_kind(ErrorKind::NotFound, format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_publish(remote: Option<String>, force: bool) -> bool {
+    let remote = gittask::get_tasks_remote(&remote);
+    let ref_path = gittask::get_ref_path();
+    let refspec = match force {
+        true => format!("+{ref_path}:{ref_path}"),
+        false => format!("{ref_path}:{ref_path}"),
+    };
+
+    match run_git(&["push", &remote, &refspec]) {
+        Ok(_) => success_message(format!("Tasks published to {remote}")),
+        Err(e) => error_message_kind(ErrorKind::Remote, format!("ERROR: {e}")),
+    }
+}
+
+pub(crate) fn task_fetch(remote: Option<String>, force: bool) -> bool {
+    let remote = gittask::get_tasks_remote(&remote);
+    let ref_path = gittask::get_ref_path();
+    let tracking_ref = gittask::get_remote_tracking_ref(&remote);
+
+    if let Err(e) = run_git(&["fetch", "--refmap=", &remote, &format!("+{ref_path}:{tracking_ref}")]) {
+        return error_message_kind(ErrorKind::Remote, format!("ERROR: {e}"));
+    }
+
+    match gittask::fast_forward_tasks(&tracking_ref, force) {
+        Ok(FetchResult::UpToDate) => success_message("Tasks are up to date".to_string()),
+        Ok(FetchResult::Created) | Ok(FetchResult::FastForwarded) => success_message(format!("Tasks fetched from {remote}")),
+        Ok(FetchResult::Diverged) => error_message_kind(ErrorKind::Conflict, format!("Local tasks have diverged from {remote}, use --force to replace them or git task publish --force to overwrite the remote")),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+/// Writes the tasks ref with its whole history into a git bundle, e.g. for a backup or moving tasks to another repository.
+pub(crate) fn task_export_bundle(path: String) -> bool {
+    let ref_path = gittask::get_ref_path();
+    match run_git(&["bundle", "create", "--quiet", &path, &ref_path]) {
+        Ok(_) => success_message(format!("Tasks with history exported to {path}")),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+/// Fast-forwards the tasks ref to the one in the bundle, with `force` replaces diverged local tasks.
+/// The bundle can come from a repository with another `task.ref`.
+pub(crate) fn task_import_bundle(path: String, force: bool) -> bool {
+    let tracking_ref = match fetch_bundle(&path) {
+        Ok(tracking_ref) => tracking_ref,
+        Err(e) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {e}")),
+    };
+
+    let result = gittask::fast_forward_tasks(&tracking_ref, force);
+    delete_bundle_ref(&tracking_ref);
+    match result {
+        Ok(FetchResult::UpToDate) => success_message("Tasks are up to date".to_string()),
+        Ok(FetchResult::Created) | Ok(FetchResult::FastForwarded) => success_message(format!("Tasks with history imported from {path}")),
+        Ok(FetchResult::Diverged) => error_message_kind(ErrorKind::Conflict, format!("Local tasks have diverged from {path}, use --force to replace them")),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+/// Fetches the tasks ref of the bundle into a temporary ref, which has to be deleted with [`delete_bundle_ref`].
+pub(crate) fn fetch_bundle(path: &str) -> Result<String, String> {
+    let heads = git_output(&["bundle", "list-heads", path])?;
+    let bundle_ref = pick_bundle_ref(&heads, &gittask::get_ref_path()).ok_or_else(|| format!("no tasks ref found in {path}"))?;
+
+    let tracking_ref = gittask::get_remote_tracking_ref("bundle");
+    run_git(&["fetch", "--quiet", "--refmap=", path, &format!("+{bundle_ref}:{tracking_ref}")])?;
+    Ok(tracking_ref)
+}
+
+pub(crate) fn delete_bundle_ref(tracking_ref: &str) {
+    let _ = run_git(&["update-ref", "-d", tracking_ref]);
+}
+
+/// The only ref of the bundle or the one matching the local tasks ref.
+fn pick_bundle_ref(heads: &str, ref_path: &str) -> Option<String> {
+    let refs = heads.lines().filter_map(|line| line.split_whitespace().nth(1)).collect::<Vec<_>>();
+    match refs[..] {
+        [bundle_ref] => Some(bundle_ref.to_string()),
+        _ => refs.into_iter().find(|bundle_ref| *bundle_ref == ref_path).map(String::from),
+    }
+}
+
+fn git_output(args: &[&str]) -> Result<String, String> {
+    let git_dir = gittask::get_git_dir()?;
+    match Command::new("git").arg("--git-dir").arg(&git_dir).args(args).output() {
+        Ok(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        Ok(output) => Err(format!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim())),
+        Err(e) => Err(format!("could not run git: {e}")),
+    }
+}
+
+fn run_git(args: &[&str]) -> Result<(), String> {
+    let git_dir = gittask::get_git_dir()?;
+    match Command::new("git").arg("--git-dir").arg(&git_dir).args(args).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("git {} failed with {status}", args[0])),
+        Err(e) => Err(format!("could not run git: {e}")),
+    }
+}
+
+const PUSH_HOOK: &str = r#"#!/bin/sh
+# Installed by git task init: publish tasks along with every push
+[ -n "$GIT_TASK_HOOK" ] && exit 0
+GIT_TASK_HOOK=1 git task publish --remote "$1" || echo "git task publish failed, run git task fetch and try again" >&2
+exit 0
+"#;
+
+/// Installs a pre-push hook publishing tasks, returns false if there's a hook already.
+pub(crate) fn install_push_hook() -> Result<bool, String> {
+    let hooks_dir = gittask::get_hooks_dir()?;
+    let hook_path = hooks_dir.join("pre-push");
+    if hook_path.exists() {
+        return Ok(false);
+    }
+
+    std::fs::create_dir_all(&hooks_dir).map_err(|e| e.to_string())?;
+    std::fs::write(&hook_path, PUSH_HOOK).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pick_bundle_ref() {
+        assert_eq!(pick_bundle_ref("1234abcd refs/heads/tasks\n", "refs/tasks/tasks"), Some("refs/heads/tasks".to_string()));
+        assert_eq!(pick_bundle_ref("1234abcd refs/heads/main\n5678ef01 refs/tasks/tasks\n", "refs/tasks/tasks"), Some("refs/tasks/tasks".to_string()));
+        assert_eq!(pick_bundle_ref("1234abcd refs/heads/main\n5678ef01 refs/heads/dev\n", "refs/tasks/tasks"), None);
+        assert_eq!(pick_bundle_ref("", "refs/tasks/tasks"), None);
+    }
+}
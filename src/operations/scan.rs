@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+
+use gittask::Task;
+
+use crate::i18n::tr;
+use crate::status::StatusManager;
+use crate::util::{error_message, is_in_path, normalize_path};
+
+const DEFAULT_KEYWORDS: &str = "TODO,FIXME,HACK";
+
+struct Candidate {
+    path: String,
+    line: usize,
+    keyword: String,
+    text: String,
+    fingerprint: String,
+}
+
+pub(crate) fn task_scan(path: Option<String>, keywords: Option<String>, apply: bool) -> bool {
+    let keywords = keywords.unwrap_or_else(|| DEFAULT_KEYWORDS.to_string());
+    let keywords = keywords.split(',').map(|k| regex::escape(k.trim())).filter(|k| !k.is_empty()).collect::<Vec<_>>();
+    let regex = match Regex::new(&format!(r"\b({})\b[\s:(\-]*(.*)$", keywords.join("|"))) {
+        Ok(regex) => regex,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let files = match gittask::list_tracked_files() {
+        Ok(files) => files,
+        Err(e) => return error_message(format!("ERROR: {e}")),
+    };
+
+    let tracked = match gittask::list_tasks() {
+        Ok(tasks) => tasks.iter().filter_map(|task| task.get_property("fingerprint").cloned()).collect::<HashSet<_>>(),
+        Err(_) => HashSet::new(),
+    };
+
+    let path = path.map(|path| normalize_path(&path));
+    let mut candidates = vec![];
+
+    for (file, full_path) in files {
+        if path.as_ref().is_some_and(|path| !is_in_path(&file, path)) {
+            continue;
+        }
+
+        let Ok(content) = std::fs::read(&full_path) else {
+            continue;
+        };
+        if content.contains(&0) {
+            continue;
+        }
+
+        for (n, line) in String::from_utf8_lossy(&content).lines().enumerate() {
+            if let Some(caps) = regex.captures(line) {
+                let keyword = caps[1].to_string();
+                let text = caps[2].trim().trim_end_matches("*/").trim_end_matches("-->").trim().to_string();
+                let fingerprint = gittask::get_fingerprint(&format!("{file}\n{keyword}\n{text}"));
+                candidates.push(Candidate { path: file.clone(), line: n + 1, keyword, text, fingerprint });
+            }
+        }
+    }
+
+    let status_manager = StatusManager::new();
+    let mut new_count = 0;
+    let mut success = true;
+
+    for candidate in candidates {
+        let location = format!("{}:{}", candidate.path, candidate.line);
+        if tracked.contains(&candidate.fingerprint) {
+            println!("{location}: {} {} (already tracked)", candidate.keyword, candidate.text);
+            continue;
+        }
+
+        new_count += 1;
+        println!("{location}: {} {}", candidate.keyword, candidate.text);
+
+        if apply {
+            let name = match candidate.text.is_empty() {
+                true => format!("{} in {location}", candidate.keyword),
+                false => candidate.text.clone(),
+            };
+            let mut task = match Task::new(name, format!("{} at {location}", candidate.keyword), status_manager.get_starting_status()) {
+                Ok(task) => task,
+                Err(e) => return error_message(format!("ERROR: {e}")),
+            };
+            task.set_property("source_file", &candidate.path);
+            task.set_property("source_line", &candidate.line.to_string());
+            task.set_property("fingerprint", &candidate.fingerprint);
+            task.add_label(candidate.keyword.to_lowercase(), None, None);
+
+            match gittask::create_task(task) {
+                Ok(task) => println!("{}", tr("Task ID {id} created", &[("id", &task.get_id().unwrap())])),
+                Err(e) => success = error_message(format!("ERROR: {e}")),
+            }
+        }
+    }
+
+    if !apply && new_count > 0 {
+        println!("Found {new_count} new item(s), use --apply to create tasks");
+    }
+
+    success
+}
@@ -0,0 +1,90 @@
+use chrono::{Local, NaiveDate};
+
+use gittask::Task;
+
+use crate::i18n::tr;
+use crate::util::{error_message, error_message_kind, parse_ids, ErrorKind};
+
+/// Date (YYYY-MM-DD) until which the task is hidden from `list`.
+pub(crate) const SNOOZED_UNTIL: &str = "snoozed_until";
+
+pub(crate) fn task_snooze(ids: String, until: String) -> bool {
+    let until = match NaiveDate::parse_from_str(&until, "%Y-%m-%d") {
+        Ok(until) if until > Local::now().date_naive() => until,
+        Ok(_) => return error_message_kind(ErrorKind::Validation, format!("ERROR: {until} is not in the future")),
+        Err(_) => return error_message_kind(ErrorKind::Validation, format!("ERROR: Invalid date '{until}', expected YYYY-MM-DD")),
+    };
+
+    let mut tasks = vec![];
+    for task_id in parse_ids(ids) {
+        match gittask::find_task(&task_id) {
+            Ok(Some(mut task)) => {
+                task.set_property(SNOOZED_UNTIL, &until.to_string());
+                tasks.push(task);
+            },
+            Ok(None) => return error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+            Err(e) => return error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    match gittask::update_tasks(tasks) {
+        Ok(ids) => {
+            ids.iter().for_each(|task_id| println!("Task ID {task_id} snoozed until {until}"));
+            true
+        },
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+fn get_snoozed_until(task: &Task) -> Option<NaiveDate> {
+    task.get_property(SNOOZED_UNTIL).and_then(|until| NaiveDate::parse_from_str(until, "%Y-%m-%d").ok())
+}
+
+pub(crate) fn is_snoozed(task: &Task, today: NaiveDate) -> bool {
+    get_snoozed_until(task).is_some_and(|until| until > today)
+}
+
+/// Clears `snoozed_until` of the tasks whose date has come, nothing is changed in a readonly repository.
+pub(crate) fn wake_tasks(tasks: &[Task], today: NaiveDate) {
+    let ids = tasks.iter()
+        .filter(|task| get_snoozed_until(task).is_some_and(|until| until <= today))
+        .filter_map(|task| task.get_id())
+        .collect::<Vec<_>>();
+    if ids.is_empty() || gittask::is_readonly() {
+        return;
+    }
+
+    // tasks of the list can be summaries without descriptions and comments
+    let mut woken = vec![];
+    for task_id in ids {
+        if let Ok(Some(mut task)) = gittask::find_task(&task_id) {
+            task.delete_property(SNOOZED_UNTIL);
+            woken.push(task);
+        }
+    }
+
+    if let Err(e) = gittask::update_tasks(woken) {
+        error_message(format!("ERROR waking up snoozed tasks: {e}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_snoozed() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let task = |until: &str| {
+            let mut task = Task::new("Task".to_string(), String::new(), "OPEN".to_string()).unwrap();
+            task.set_property(SNOOZED_UNTIL, until);
+            task
+        };
+
+        assert!(is_snoozed(&task("2024-05-11"), today));
+        assert!(!is_snoozed(&task("2024-05-10"), today));
+        assert!(!is_snoozed(&task("2024-05-01"), today));
+        assert!(!is_snoozed(&task("someday"), today));
+        assert!(!is_snoozed(&Task::new("Task".to_string(), String::new(), "OPEN".to_string()).unwrap(), today));
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Utc};
+
+use gittask::Task;
+
+use crate::status::StatusManager;
+
+/// Computed property with the number of days a stale task hasn't been updated.
+pub(crate) const STALE: &str = "stale";
+
+const DEFAULT_STALE_DAYS: i64 = 30;
+
+/// Days without updates after which a task is stale, `task.stale.days` parameter.
+pub(crate) fn get_stale_days() -> i64 {
+    gittask::get_config_value("task.stale.days").ok()
+        .and_then(|days| days.trim().parse().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_STALE_DAYS)
+}
+
+/// Days since the last update of the task that is not done and hasn't been updated for `days`.
+pub(crate) fn get_stale_age(task: &Task, days: i64, now: DateTime<Utc>, status_manager: &StatusManager) -> Option<i64> {
+    if task.get_property("status").is_some_and(|status| status_manager.is_done(status)) {
+        return None;
+    }
+
+    task.updated_at().map(|updated| (now - updated).num_days()).filter(|idle| *idle >= days)
+}
+
+/// Sets `stale` property of the stale task, so it can be listed, sorted and highlighted like any other property.
+pub(crate) fn mark_stale(task: &mut Task, days: i64, now: DateTime<Utc>, status_manager: &StatusManager) {
+    if let Some(idle) = get_stale_age(task, days, now, status_manager) {
+        task.set_property(STALE, &idle.to_string());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_mark_stale() {
+        let status_manager = StatusManager::new();
+        let now = Utc.with_ymd_and_hms(2024, 6, 30, 12, 0, 0).unwrap();
+        let task = |status: &str, updated: u32| {
+            let mut task = Task::new("Task".to_string(), String::new(), status.to_string()).unwrap();
+            task.set_property("updated", &Utc.with_ymd_and_hms(2024, 6, updated, 12, 0, 0).unwrap().timestamp().to_string());
+            task
+        };
+
+        let mut old = task(status_manager.get_starting_status().as_str(), 1);
+        mark_stale(&mut old, 14, now, &status_manager);
+        assert_eq!(old.get_property(STALE).map(String::as_str), Some("29"));
+
+        let mut recent = task(status_manager.get_starting_status().as_str(), 20);
+        mark_stale(&mut recent, 14, now, &status_manager);
+        assert!(recent.get_property(STALE).is_none());
+
+        let mut done = task(status_manager.get_final_status().as_str(), 1);
+        mark_stale(&mut done, 14, now, &status_manager);
+        assert!(done.get_property(STALE).is_none());
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use nu_ansi_term::Color::{Green, Red, Yellow};
+use serde::{Deserialize, Serialize};
+
+use gittask::{Comment, Task};
+
+use crate::operations::{PULL_OPTIONS, REMOTE_UPDATED, SHALLOW};
+use crate::util::colorize_string;
+
+/// Properties maintained by git-task itself, never pruned.
+const SYNC_PROPERTIES: [&str; 5] = ["updated", "updated_by", REMOTE_UPDATED, PULL_OPTIONS, SHALLOW];
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum SyncOutcome {
+    Created,
+    Updated,
+    Skipped(String),
+    Failed(String),
+}
+
+/// Outcomes of a pull or push per task, printed as a summary table at the end of bulk runs
+/// so failures don't get lost among the progress lines.
+#[derive(Default)]
+pub(crate) struct SyncReport {
+    entries: Vec<(String, SyncOutcome)>,
+}
+
+impl SyncReport {
+    pub(crate) fn add(&mut self, id: &str, outcome: SyncOutcome) {
+        self.entries.push((id.to_string(), outcome));
+    }
+
+    pub(crate) fn has_failures(&self) -> bool {
+        self.has_failures_since(0)
+    }
+
+    /// Checks the outcomes added after the first `start` ones, e.g. of the task being synced.
+    pub(crate) fn has_failures_since(&self, start: usize) -> bool {
+        self.entries.iter().skip(start).any(|(_, outcome)| matches!(outcome, SyncOutcome::Failed(_)))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Created, updated, skipped and failed counts.
+    fn counts(&self) -> (usize, usize, usize, usize) {
+        self.entries.iter().fold((0, 0, 0, 0), |(created, updated, skipped, failed), (_, outcome)| match outcome {
+            SyncOutcome::Created => (created + 1, updated, skipped, failed),
+            SyncOutcome::Updated => (created, updated + 1, skipped, failed),
+            SyncOutcome::Skipped(_) => (created, updated, skipped + 1, failed),
+            SyncOutcome::Failed(_) => (created, updated, skipped, failed + 1),
+        })
+    }
+
+    /// Prints the table when more than one task was processed, a single one is clear from its log lines.
+    pub(crate) fn print(&self, no_color: bool) {
+        if self.entries.len() < 2 {
+            return;
+        }
+
+        let id_width = self.entries.iter().map(|(id, _)| id.len()).max().unwrap_or_default().max(2);
+        println!();
+        println!("{:id_width$}  {:8} DETAILS", "ID", "RESULT");
+        for (id, outcome) in &self.entries {
+            let (result, color, details) = match outcome {
+                SyncOutcome::Created => ("created", Green, ""),
+                SyncOutcome::Updated => ("updated", Green, ""),
+                SyncOutcome::Skipped(reason) => ("skipped", Yellow, reason.as_str()),
+                SyncOutcome::Failed(reason) => ("failed", Red, reason.as_str()),
+            };
+            let line = format!("{id:id_width$}  {} {details}", colorize_string(&format!("{result:8}"), color, no_color));
+            println!("{}", line.trim_end());
+        }
+
+        let (created, updated, skipped, failed) = self.counts();
+        let failed = format!("failed: {failed}");
+        let failed = if self.has_failures() { colorize_string(&failed, Red, no_color) } else { failed };
+        println!("Created: {created}, updated: {updated}, skipped: {skipped}, {failed}");
+    }
+}
+
+/// Progress of a bulk pull or push, saved after every task to `.git/task-<operation>-checkpoint.json`,
+/// so an interrupted run can be continued with `--resume`. Failed tasks are not recorded and get retried.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SyncCheckpoint {
+    /// Remote and task selection of the run, a checkpoint of another run can't be resumed
+    key: String,
+    /// Start time of the original run, the starting point for the next incremental pull
+    pub started: i64,
+    done: BTreeSet<String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl SyncCheckpoint {
+    /// Continues the checkpoint of the same run with `resume`, otherwise starts a new one.
+    pub(crate) fn start(operation: &str, key: &str, resume: bool) -> Result<SyncCheckpoint, String> {
+        let path = gittask::get_git_dir()?.join(format!("task-{operation}-checkpoint.json"));
+        let saved = std::fs::read_to_string(&path).ok().and_then(|content| serde_json::from_str::<SyncCheckpoint>(&content).ok());
+
+        let checkpoint = match (saved, resume) {
+            (Some(saved), true) if saved.key == key => {
+                println!("Resuming interrupted {operation}, {} task(s) already done", saved.done.len());
+                saved
+            },
+            (_, true) => return Err(format!("No interrupted {operation} of {key} to resume")),
+            (saved, false) => {
+                if saved.is_some_and(|saved| saved.key == key) {
+                    println!("Previous {operation} of {key} was interrupted, starting over (use --resume to continue it)");
+                }
+                SyncCheckpoint { key: key.to_string(), started: chrono::Utc::now().timestamp(), ..Default::default() }
+            },
+        };
+
+        Ok(SyncCheckpoint { path: Some(path), ..checkpoint })
+    }
+
+    pub(crate) fn is_done(&self, id: &str) -> bool {
+        self.done.contains(id)
+    }
+
+    /// Records the processed task, a checkpoint that can't be saved only prints a warning.
+    pub(crate) fn mark_done(&mut self, id: &str) {
+        self.done.insert(id.to_string());
+        if let Some(path) = &self.path {
+            let result = serde_json::to_string(self).map_err(|e| e.to_string())
+                .and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+            if let Err(e) = result {
+                eprintln!("WARNING: could not save sync checkpoint {}: {e}", path.display());
+            }
+        }
+    }
+
+    /// Removes the checkpoint once the run completed, it's kept if some tasks failed.
+    pub(crate) fn finish(self, report: &SyncReport) {
+        if let Some(path) = self.path.filter(|_| !report.has_failures()) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Unions pulled comments with the local ones. A remote comment replaces the local one with the same ID,
+/// or with the same author and text if it was pushed without its ID being updated; new remote comments are added.
+/// Local comments that were never pushed are kept, the result is ordered by creation time.
+pub(crate) fn merge_comments(local_comments: &[Comment], remote_comments: &[Comment]) -> Vec<Comment> {
+    let remote_ids = remote_comments.iter().filter_map(|comment| comment.get_id()).collect::<BTreeSet<_>>();
+    let same_text = |local: &Comment, remote: &Comment| local.get_all_properties().get("author") == remote.get_all_properties().get("author")
+        && local.get_text().trim() == remote.get_text().trim();
+
+    let mut result = local_comments.to_vec();
+    for remote_comment in remote_comments {
+        let position = result.iter().position(|local| local.get_id() == remote_comment.get_id())
+            .or_else(|| result.iter().position(|local| local.get_id().is_none_or(|id| !remote_ids.contains(&id)) && same_text(local, remote_comment)));
+        match position {
+            Some(position) => result[position] = remote_comment.clone(),
+            None => result.push(remote_comment.clone()),
+        }
+    }
+
+    result.sort_by_key(|comment| comment.get_all_properties().get("created").and_then(|created| created.parse::<i64>().ok()).unwrap_or(i64::MAX));
+    result
+}
+
+/// Local properties the remote task doesn't have, removed by `pull --prune-properties`.
+pub(crate) fn get_local_only_properties(local_task: &Task, remote_task: &Task) -> Vec<String> {
+    let mut result = local_task.get_all_properties().keys()
+        .filter(|prop| !SYNC_PROPERTIES.contains(&prop.as_str()) && remote_task.get_property(prop).is_none())
+        .cloned()
+        .collect::<Vec<_>>();
+    result.sort();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_sync_report() {
+        let mut report = SyncReport::default();
+        report.add("1", SyncOutcome::Created);
+        report.add("2", SyncOutcome::Skipped("nothing to update".to_string()));
+        assert!(!report.has_failures());
+
+        report.add("3", SyncOutcome::Updated);
+        report.add("4", SyncOutcome::Failed("rate limit".to_string()));
+        report.add("5", SyncOutcome::Updated);
+        assert!(report.has_failures());
+        assert_eq!(report.counts(), (1, 2, 1, 1));
+    }
+
+    #[test]
+    fn test_merge_comments() {
+        let comment = |id: &str, author: &str, created: &str, text: &str| Comment::new(id.to_string(), HashMap::from([
+            ("author".to_string(), author.to_string()),
+            ("created".to_string(), created.to_string()),
+        ]), text.to_string());
+        let ids = |comments: Vec<Comment>| comments.iter().map(|comment| comment.get_id().unwrap()).collect::<Vec<_>>();
+
+        let local = vec![
+            comment("1001", "alice", "100", "Reproduced"),
+            comment("2", "bob", "150", "Local note"),
+            comment("3", "bob", "300", "Pushed before"),
+        ];
+        let remote = vec![
+            comment("1001", "alice", "100", "Reproduced on 1.2"),
+            comment("1002", "carol", "200", "Fixed"),
+            comment("1003", "bob", "300", "Pushed before\n"),
+        ];
+
+        let merged = merge_comments(&local, &remote);
+        assert_eq!(ids(merged.clone()), vec!["1001", "2", "1002", "1003"]);
+        assert_eq!(merged[0].get_text(), "Reproduced on 1.2");
+        assert!(merge_comments(&merged, &remote) == merged);
+        assert!(merge_comments(&merged, &remote[1..2]) == merged);
+        assert_eq!(ids(merge_comments(&[], &remote)), vec!["1001", "1002", "1003"]);
+    }
+
+    #[test]
+    fn test_get_local_only_properties() {
+        let local = Task::builder("Crash").id("1").status("OPEN")
+            .property("priority", "HIGH")
+            .property("estimate", "3")
+            .property("updated", "100")
+            .property(REMOTE_UPDATED, "90")
+            .build().unwrap();
+        let remote = Task::builder("Crash").id("1").status("CLOSED").property("priority", "LOW").build().unwrap();
+
+        assert_eq!(get_local_only_properties(&local, &remote), vec!["estimate".to_string()]);
+        assert!(get_local_only_properties(&remote, &local).is_empty());
+    }
+
+    #[test]
+    fn test_sync_checkpoint() {
+        gittask::TaskRepository::temporary().unwrap().run(|| {
+            assert!(SyncCheckpoint::start("pull", "user/repo", true).is_err());
+
+            let mut checkpoint = SyncCheckpoint::start("pull", "user/repo", false).unwrap();
+            checkpoint.mark_done("1");
+            checkpoint.mark_done("2");
+            let started = checkpoint.started;
+
+            assert!(SyncCheckpoint::start("pull", "user/other", true).is_err());
+            assert!(SyncCheckpoint::start("push", "user/repo", true).is_err());
+            let resumed = SyncCheckpoint::start("pull", "user/repo", true).unwrap();
+            assert!(resumed.is_done("1") && resumed.is_done("2") && !resumed.is_done("3"));
+            assert_eq!(resumed.started, started);
+
+            let mut report = SyncReport::default();
+            report.add("3", SyncOutcome::Failed("rate limit".to_string()));
+            resumed.finish(&report);
+            let resumed = SyncCheckpoint::start("pull", "user/repo", true).unwrap();
+            resumed.finish(&SyncReport::default());
+            assert!(SyncCheckpoint::start("pull", "user/repo", true).is_err());
+            assert!(!SyncCheckpoint::start("pull", "user/repo", false).unwrap().is_done("1"));
+        });
+    }
+}
@@ -0,0 +1,59 @@
+use crate::i18n::tr;
+use crate::operations::get_user_identities;
+use crate::util::{error_message, error_message_kind, ErrorKind, parse_ids};
+
+pub(crate) fn task_watch_add(ids: String, user: Option<String>) -> bool {
+    update_watchers(ids, user, true)
+}
+
+pub(crate) fn task_watch_remove(ids: String, user: Option<String>) -> bool {
+    update_watchers(ids, user, false)
+}
+
+pub(crate) fn task_watch_list(task_id: String) -> bool {
+    match gittask::find_task(&task_id) {
+        Ok(Some(task)) => {
+            task.get_watchers().iter().for_each(|watcher| println!("{watcher}"));
+            true
+        },
+        Ok(None) => error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+        Err(e) => error_message(format!("ERROR: {e}")),
+    }
+}
+
+fn update_watchers(ids: String, user: Option<String>, add: bool) -> bool {
+    let user = match user.or_else(|| get_user_identities().into_iter().next()) {
+        Some(user) => user,
+        None => return error_message("Could not determine current user, please set up user.name or task.user".to_string()),
+    };
+
+    let mut success = true;
+
+    for task_id in parse_ids(ids) {
+        match gittask::find_task(&task_id) {
+            Ok(Some(mut task)) => {
+                let changed = match add {
+                    true => task.add_watcher(&user),
+                    false => task.remove_watcher(&user),
+                };
+
+                if !changed {
+                    match add {
+                        true => println!("Task ID {task_id} is already watched by {user}"),
+                        false => println!("Task ID {task_id} is not watched by {user}"),
+                    }
+                    continue;
+                }
+
+                match gittask::update_task(task) {
+                    Ok(_) => println!("{}", tr("Task ID {id} updated", &[("id", &task_id)])),
+                    Err(e) => success = error_message(format!("ERROR: {e}")),
+                }
+            },
+            Ok(None) => success = error_message_kind(ErrorKind::NotFound, tr("Task ID {id} not found", &[("id", &task_id)])),
+            Err(e) => success = error_message(format!("ERROR: {e}")),
+        }
+    }
+
+    success
+}
@@ -0,0 +1,90 @@
+use gittask::Task;
+
+/// Limit of the work in progress in the status, `task.wip.<STATUS>` parameter.
+pub(crate) fn get_wip_limit(status: &str) -> Option<f64> {
+    gittask::get_config_value(&get_wip_limit_key(status)).ok()?.trim().parse().ok().filter(|limit| *limit > 0.0)
+}
+
+/// Git config keys are case-insensitive and can't contain underscores, `IN_PROGRESS` is kept as `task.wip.in-progress`.
+pub(crate) fn get_wip_limit_key(status: &str) -> String {
+    format!("task.wip.{}", status.to_lowercase().replace('_', "-"))
+}
+
+/// Numeric property the tasks are weighed by, e.g. story points, `task.wip.effort` parameter.
+/// Without it every task counts as one.
+pub(crate) fn get_effort_property() -> Option<String> {
+    gittask::get_config_value("task.wip.effort").ok().filter(|prop| !prop.trim().is_empty())
+}
+
+fn get_effort(task: &Task, effort_property: &Option<String>) -> f64 {
+    effort_property.as_ref()
+        .and_then(|prop| task.get_property(prop))
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(1.0)
+}
+
+/// Load of the status once the `moving` tasks get there, tasks that are already in the status are not counted twice.
+pub(crate) fn get_wip_load(tasks: &[Task], status: &str, moving: &[String], effort_property: &Option<String>) -> f64 {
+    tasks.iter()
+        .filter(|task| task.get_property("status").is_some_and(|task_status| task_status == status) || task.get_id().is_some_and(|id| moving.contains(&id)))
+        .map(|task| get_effort(task, effort_property))
+        .sum()
+}
+
+/// Checks the limit of the status before the tasks are moved there. Exceeding it is a warning,
+/// with `task.wip.strict` an error unless forced.
+pub(crate) fn check_wip_limit(ids: &[String], status: &str, force: bool) -> Result<(), String> {
+    let Some(limit) = get_wip_limit(status) else {
+        return Ok(());
+    };
+
+    let tasks = gittask::list_task_summaries()?.into_iter().map(|summary| summary.into_task()).collect::<Vec<_>>();
+    let load = get_wip_load(&tasks, status, ids, &get_effort_property());
+    if load <= limit {
+        return Ok(());
+    }
+
+    let message = format!("WIP limit of {status} exceeded: {load}/{limit}");
+    let strict = gittask::get_config_value("task.wip.strict").is_ok_and(|strict| strict == "true");
+    match strict && !force {
+        true => Err(format!("{message}, use --force to override")),
+        false => {
+            eprintln!("WARNING: {message}");
+            Ok(())
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_wip_limit_key() {
+        assert_eq!(get_wip_limit_key("IN_PROGRESS"), "task.wip.in-progress");
+        assert_eq!(get_wip_limit_key("Review"), "task.wip.review");
+    }
+
+    #[test]
+    fn test_get_wip_load() {
+        let task = |id: &str, status: &str, points: Option<&str>| {
+            let mut task = Task::new("Task".to_string(), String::new(), status.to_string()).unwrap();
+            task.set_id(id.to_string());
+            if let Some(points) = points {
+                task.set_property("points", points);
+            }
+            task
+        };
+        let tasks = vec![
+            task("1", "IN_PROGRESS", Some("3")),
+            task("2", "IN_PROGRESS", None),
+            task("3", "OPEN", Some("5")),
+            task("4", "OPEN", Some("0.5")),
+        ];
+
+        assert_eq!(get_wip_load(&tasks, "IN_PROGRESS", &[], &None), 2.0);
+        assert_eq!(get_wip_load(&tasks, "IN_PROGRESS", &["1".to_string(), "3".to_string()], &None), 3.0);
+        assert_eq!(get_wip_load(&tasks, "IN_PROGRESS", &["4".to_string()], &Some("points".to_string())), 4.5);
+        assert_eq!(get_wip_load(&tasks, "CLOSED", &[], &None), 0.0);
+    }
+}
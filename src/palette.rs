@@ -0,0 +1,173 @@
+use std::io::{stderr, stdin, Write};
+
+use gittask::TaskRepository;
+
+use crate::property::PropertyManager;
+use crate::status::StatusManager;
+use crate::util::split_args;
+
+const PROMPT: &str = "git-task> ";
+
+/// Interactive session of `git task do`. The whole session runs inside [`TaskRepository::run`], so the repository
+/// is opened once and every line runs against it, task IDs and property names for completions are kept in memory
+/// until a command changes something.
+struct Palette {
+    commands: Vec<String>,
+    tasks: Option<Vec<(String, String)>>,
+    names: Option<Vec<String>>,
+}
+
+impl Palette {
+    fn get_tasks(&mut self) -> &[(String, String)] {
+        if self.tasks.is_none() {
+            let mut tasks = gittask::list_task_summaries().unwrap_or_default().into_iter()
+                .map(|summary| (summary.get_id().unwrap_or_default(), summary.get_property("name").cloned().unwrap_or_default()))
+                .collect::<Vec<_>>();
+            if let Err(e) = gittask::sort_by_task_id(&mut tasks, |(id, _)| Some(id.clone())) {
+                eprintln!("{e}");
+            }
+            self.tasks = Some(tasks);
+        }
+        self.tasks.as_deref().unwrap()
+    }
+
+    /// Names of the configured properties and statuses.
+    fn get_names(&mut self) -> &[String] {
+        if self.names.is_none() {
+            let properties = PropertyManager::new().get_properties().iter().map(|property| property.get_name().to_string()).collect::<Vec<_>>();
+            let statuses = StatusManager::new().get_statuses().iter().map(|status| status.get_name().to_string()).collect::<Vec<_>>();
+            self.names = Some(properties.into_iter().chain(statuses).collect());
+        }
+        self.names.as_deref().unwrap()
+    }
+
+    fn print_completions(&mut self, line: &str) {
+        let mut words = match split_args(line) {
+            Ok(words) => words,
+            Err(e) => return eprintln!("{e}"),
+        };
+        let partial = match line.ends_with(char::is_whitespace) || words.is_empty() {
+            true => String::new(),
+            false => words.pop().unwrap(),
+        };
+
+        let commands = self.commands.clone();
+        let tasks = self.get_tasks().to_vec();
+        let names = self.get_names().to_vec();
+        let completions = complete(&words, &partial, &commands, &tasks, &names);
+        match completions.is_empty() {
+            true => eprintln!("No completions"),
+            false => completions.iter().for_each(|completion| eprintln!("  {completion}")),
+        }
+    }
+
+    /// Expands an unambiguous prefix of the command, e.g. `sh 1` to `show 1`.
+    fn expand_command(&self, mut words: Vec<String>) -> Vec<String> {
+        if let Some(first) = words.first() {
+            if !self.commands.contains(first) {
+                let matches = self.commands.iter().filter(|command| command.starts_with(first.as_str())).collect::<Vec<_>>();
+                if let [command] = matches[..] {
+                    words[0] = command.clone();
+                }
+            }
+        }
+        words
+    }
+}
+
+/// Reads commands until `exit`, `quit` or end of input and runs them with `run`.
+pub fn run_palette(commands: Vec<String>, run: impl Fn(Vec<String>) -> bool) -> Result<(), String> {
+    let repository = match std::env::var("GIT_DIR") {
+        Ok(git_dir) => TaskRepository::open(git_dir)?,
+        Err(_) => TaskRepository::open(".")?,
+    };
+    repository.run(|| read_commands(Palette { commands, tasks: None, names: None }, run))
+}
+
+fn read_commands(mut palette: Palette, run: impl Fn(Vec<String>) -> bool) -> Result<(), String> {
+    eprintln!("Type a command without `git task`, end a line with ? or Tab to list completions, `help` for commands, `exit` to quit");
+    loop {
+        eprint!("{PROMPT}");
+        let _ = stderr().flush();
+
+        let mut line = String::new();
+        if stdin().read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            eprintln!();
+            return Ok(());
+        }
+        let line = line.trim_end_matches(['\n', '\r']).trim_start();
+
+        match line.trim_end() {
+            "" => continue,
+            "exit" | "quit" => return Ok(()),
+            "help" => {
+                eprintln!("{}", palette.commands.join(" "));
+                continue;
+            },
+            _ => {},
+        }
+
+        if let Some(line) = line.strip_suffix('?').or_else(|| line.strip_suffix('\t')) {
+            palette.print_completions(line);
+            continue;
+        }
+
+        match split_args(line) {
+            Ok(words) => {
+                let words = palette.expand_command(words);
+                run(words);
+                palette.tasks = None;
+                palette.names = None;
+            },
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+/// Completions of the partial word: commands for the first word, task IDs for numbers or after the command,
+/// property and status names after a task ID.
+fn complete(words: &[String], partial: &str, commands: &[String], tasks: &[(String, String)], names: &[String]) -> Vec<String> {
+    let is_id = |word: &str| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit() || c == ',' || c == '.');
+
+    if words.is_empty() {
+        let mut result = commands.iter().filter(|command| command.starts_with(partial)).cloned().collect::<Vec<_>>();
+        result.sort();
+        result.dedup();
+        return result;
+    }
+
+    let previous_is_id = words.last().is_some_and(|word| is_id(word));
+    if is_id(partial) || (partial.is_empty() && !previous_is_id) {
+        let prefix = partial.rsplit([',', '.']).next().unwrap_or_default();
+        return tasks.iter()
+            .filter(|(id, _)| id.starts_with(prefix))
+            .map(|(id, name)| format!("{id}\t{name}"))
+            .collect();
+    }
+
+    names.iter()
+        .filter(|name| name.to_lowercase().starts_with(&partial.to_lowercase()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let commands = ["list", "show", "status", "stats", "set"].map(String::from).to_vec();
+        let tasks = [("1", "Login fails"), ("12", "Add dark theme"), ("2", "Fix docs")].map(|(id, name)| (id.to_string(), name.to_string())).to_vec();
+        let names = ["priority", "name", "OPEN", "CLOSED"].map(String::from).to_vec();
+        let words = |line: &str| split_args(line).unwrap();
+
+        assert_eq!(complete(&[], "st", &commands, &tasks, &names), vec!["stats", "status"]);
+        assert_eq!(complete(&words("show"), "1", &commands, &tasks, &names), vec!["1\tLogin fails", "12\tAdd dark theme"]);
+        assert_eq!(complete(&words("show"), "", &commands, &tasks, &names).len(), 3);
+        assert_eq!(complete(&words("set 2"), "pr", &commands, &tasks, &names), vec!["priority"]);
+        assert_eq!(complete(&words("status 1,2"), "", &commands, &tasks, &names), vec!["priority", "name", "OPEN", "CLOSED"]);
+        assert_eq!(complete(&words("status 1..1"), "c", &commands, &tasks, &names), vec!["CLOSED"]);
+        assert_eq!(complete(&words("show"), "2,1", &commands, &tasks, &names), vec!["1\tLogin fails", "12\tAdd dark theme"]);
+    }
+}
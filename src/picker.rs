@@ -0,0 +1,122 @@
+use std::io::{stderr, stdin, Write};
+
+use gittask::Task;
+
+const PICKER_SIZE: usize = 10;
+
+/// Interactively picks a task: the user types a query to narrow down the list
+/// by fuzzy matching of task names and selects one of the matches by its number.
+pub fn pick_task() -> Result<Option<String>, String> {
+    let mut tasks = gittask::list_tasks()?;
+    gittask::sort_by_task_id(&mut tasks, Task::get_id)?;
+    tasks.reverse();
+
+    let mut query = String::new();
+    loop {
+        let matches = find_matches(&tasks, &query);
+        if matches.is_empty() {
+            eprintln!("No tasks match '{query}'");
+        }
+        for (n, task) in matches.iter().enumerate() {
+            eprintln!("{:>3}) {:>5}  {}", n + 1, task.get_id().unwrap(), task.get_property("name").map(|s| s.as_str()).unwrap_or(""));
+        }
+
+        eprint!("Filter, number to select or empty to cancel: ");
+        let _ = stderr().flush();
+
+        let mut input = String::new();
+        if stdin().read_line(&mut input).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+
+        match input.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= matches.len() => return Ok(matches[n - 1].get_id()),
+            _ if input.is_empty() => return Ok(None),
+            _ => query = input.to_string(),
+        }
+    }
+}
+
+/// Interactively picks one of the values, e.g. enum values of a property, narrowing them down by fuzzy matching.
+pub fn pick_value(values: &[&str]) -> Result<Option<String>, String> {
+    let mut query = String::new();
+    loop {
+        let mut matches = values.iter()
+            .filter_map(|value| fuzzy_score(&query, value).map(|score| (score, *value)))
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.truncate(PICKER_SIZE);
+        if matches.is_empty() {
+            eprintln!("No values match '{query}'");
+        }
+        for (n, (_, value)) in matches.iter().enumerate() {
+            eprintln!("{:>3}) {value}", n + 1);
+        }
+
+        eprint!("Filter, number to select or empty to cancel: ");
+        let _ = stderr().flush();
+
+        let mut input = String::new();
+        if stdin().read_line(&mut input).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+
+        match input.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= matches.len() => return Ok(Some(matches[n - 1].1.to_string())),
+            _ if input.is_empty() => return Ok(None),
+            _ => query = input.to_string(),
+        }
+    }
+}
+
+fn find_matches<'a>(tasks: &'a [Task], query: &str) -> Vec<&'a Task> {
+    let mut matches = tasks.iter()
+        .filter_map(|task| fuzzy_score(query, task.get_property("name")?).map(|score| (score, task)))
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().take(PICKER_SIZE).map(|(_, task)| task).collect()
+}
+
+/// Scores the text if all characters of the query appear in it in the same order (case-insensitive).
+/// Consecutive characters and matches at word starts score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    if query.trim().is_empty() {
+        return Some(0);
+    }
+
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|c| *c == q)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    Some(score * 100 - text.len() as i64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("lgn", "Login fails").is_some());
+        assert!(fuzzy_score("ngl", "Login fails").is_none());
+        assert!(fuzzy_score("", "Anything").is_some());
+        assert!(fuzzy_score("login", "Login fails") > fuzzy_score("login", "Let others go in"));
+        assert!(fuzzy_score("fail", "Login fails") > fuzzy_score("fail", "Fix a telemetry bug later"));
+    }
+}
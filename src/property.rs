@@ -1,10 +1,17 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
 
-use evalexpr::{ContextWithMutableVariables, HashMapContext};
-use nu_ansi_term::AnsiString;
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value};
+use gittask::{parse_typed_value, Task};
+use nu_ansi_term::{AnsiString, Style};
 use serde::{Deserialize, Serialize};
 
-use crate::util::{format_datetime, str_to_color};
+use crate::util::{format_display_datetime, parse_date, str_to_color, suggest};
+
+thread_local! {
+    /// Last parsed `task.properties`, commands of a `git task do` session don't parse the same JSON again.
+    static PARSED_CONFIG: RefCell<Option<(String, Vec<Property>)>> = const { RefCell::new(None) };
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -13,6 +20,7 @@ pub(crate) enum PropertyValueType {
     Text,
     Integer,
     DateTime,
+    List,
 }
 
 impl std::fmt::Display for PropertyValueType {
@@ -22,6 +30,7 @@ impl std::fmt::Display for PropertyValueType {
             PropertyValueType::Text => write!(formatter, "text"),
             PropertyValueType::Integer => write!(formatter, "integer"),
             PropertyValueType::DateTime => write!(formatter, "datetime"),
+            PropertyValueType::List => write!(formatter, "list"),
         }
     }
 }
@@ -35,7 +44,8 @@ impl std::str::FromStr for PropertyValueType {
             "text" => Ok(PropertyValueType::Text),
             "integer" => Ok(PropertyValueType::Integer),
             "datetime" => Ok(PropertyValueType::DateTime),
-            _ => Err("Error parsing property value type. Supported types are: string, text, integer, datetime".to_string()),
+            "list" => Ok(PropertyValueType::List),
+            _ => Err("Error parsing property value type. Supported types are: string, text, integer, datetime, list".to_string()),
         }
     }
 }
@@ -48,9 +58,22 @@ pub struct Property {
     style: Option<String>,
     enum_values: Option<Vec<PropertyEnumValue>>,
     cond_format: Option<Vec<PropertyCondFormat>>,
+    formula: Option<String>,
 }
 
 impl Property {
+    pub(crate) fn new(name: String, value_type: PropertyValueType, color: String, enum_values: Option<Vec<PropertyEnumValue>>) -> Property {
+        Property {
+            name,
+            value_type,
+            color,
+            style: None,
+            enum_values,
+            cond_format: None,
+            formula: None,
+        }
+    }
+
     pub(crate) fn get_name(&self) -> &str {
         &self.name
     }
@@ -74,6 +97,10 @@ impl Property {
     pub(crate) fn get_cond_format(&self) -> &Option<Vec<PropertyCondFormat>> {
         &self.cond_format
     }
+
+    pub(crate) fn get_formula(&self) -> Option<&str> {
+        self.formula.as_deref()
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -84,16 +111,18 @@ pub struct PropertyEnumValue {
 }
 
 impl PropertyEnumValue {
-    fn from(source: Vec<String>) -> Vec<PropertyEnumValue> {
-        let mut result = vec![];
-        for i in 0..=source.len()/2 {
-            result.push(PropertyEnumValue{
-                name: source[i * 2].clone(),
-                color: source[i * 2 + 1].clone(),
-                style: None,
-            })
+    pub(crate) fn new(name: String, color: String) -> PropertyEnumValue {
+        PropertyEnumValue {
+            name,
+            color,
+            style: None,
         }
-        result
+    }
+
+    fn from(source: Vec<String>) -> Vec<PropertyEnumValue> {
+        to_pairs(source).into_iter()
+            .map(|(name, color)| PropertyEnumValue { name, color, style: None })
+            .collect()
     }
 
     pub(crate) fn get_name(&self) -> &str {
@@ -114,19 +143,14 @@ pub struct PropertyCondFormat {
     condition: String,
     color: String,
     style: Option<String>,
+    row: Option<bool>,
 }
 
 impl PropertyCondFormat {
     fn from(source: Vec<String>) -> Vec<PropertyCondFormat> {
-        let mut result = vec![];
-        for i in 0..=source.len()/2 {
-            result.push(PropertyCondFormat{
-                condition: source[i * 2].clone(),
-                color: source[i * 2 + 1].clone(),
-                style: None,
-            })
-        }
-        result
+        to_pairs(source).into_iter()
+            .map(|(condition, color)| PropertyCondFormat { condition, color, style: None, row: None })
+            .collect()
     }
 
     pub(crate) fn get_condition(&self) -> &str {
@@ -140,6 +164,16 @@ impl PropertyCondFormat {
     pub(crate) fn get_style(&self) -> Option<&str> {
         self.style.as_deref()
     }
+
+    /// Whether the whole list row is highlighted instead of the property value.
+    pub(crate) fn is_row(&self) -> bool {
+        self.row.unwrap_or(false)
+    }
+}
+
+/// Splits `[name, color, name, color, ...]` into pairs, a trailing value without a color is ignored.
+fn to_pairs(source: Vec<String>) -> Vec<(String, String)> {
+    source.chunks_exact(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect()
 }
 
 pub struct PropertyManager {
@@ -164,6 +198,7 @@ impl PropertyManager {
                 style: None,
                 enum_values: None,
                 cond_format: None,
+                formula: None,
             },
             Property {
                 name: "name".to_string(),
@@ -172,6 +207,7 @@ impl PropertyManager {
                 style: None,
                 enum_values: None,
                 cond_format: None,
+                formula: None,
             },
             Property {
                 name: "created".to_string(),
@@ -180,6 +216,16 @@ impl PropertyManager {
                 style: None,
                 enum_values: None,
                 cond_format: None,
+                formula: None,
+            },
+            Property {
+                name: "updated".to_string(),
+                value_type: PropertyValueType::DateTime,
+                color: "239".to_string(),
+                style: None,
+                enum_values: None,
+                cond_format: None,
+                formula: None,
             },
             Property {
                 name: "author".to_string(),
@@ -188,6 +234,7 @@ impl PropertyManager {
                 style: None,
                 enum_values: None,
                 cond_format: None,
+                formula: None,
             },
             Property {
                 name: "description".to_string(),
@@ -196,6 +243,7 @@ impl PropertyManager {
                 style: None,
                 enum_values: None,
                 cond_format: None,
+                formula: None,
             },
         ]
     }
@@ -215,10 +263,13 @@ impl PropertyManager {
     }
 
     fn read_config() -> Result<Vec<Property>, String> {
-        match gittask::get_config_value("task.properties") {
-            Ok(s) => Self::parse_properties(s),
-            Err(e) => Err(e)
+        let input = gittask::get_config_value("task.properties")?;
+        if let Some(properties) = PARSED_CONFIG.with_borrow(|parsed| parsed.as_ref().filter(|(raw, _)| *raw == input).map(|(_, properties)| properties.clone())) {
+            return Ok(properties);
         }
+        let properties = Self::parse_properties(input.clone())?;
+        PARSED_CONFIG.set(Some((input, properties.clone())));
+        Ok(properties)
     }
 
     fn save_config(properties: &Vec<Property>) -> Result<(), String> {
@@ -238,7 +289,14 @@ impl PropertyManager {
         match self.properties.iter().find(|p| p.name == property) {
             Some(property) => {
                 let value = match property.value_type {
-                    PropertyValueType::DateTime => format_datetime(value.parse().unwrap_or(0)),
+                    PropertyValueType::DateTime => match parse_typed_value::<u64>(&property.name, value, Some("datetime")) {
+                        Ok(timestamp) => format_display_datetime(timestamp),
+                        Err(_) => value.to_string(),
+                    },
+                    PropertyValueType::List => match parse_typed_value::<Vec<String>>(&property.name, value, Some("list")) {
+                        Ok(values) => values.join(", "),
+                        Err(_) => value.to_string(),
+                    },
                     _ => value.to_string()
                 };
                 match no_color {
@@ -257,29 +315,12 @@ impl PropertyManager {
     }
 
     fn find_cond_format<'a>(cond_format: &'a Option<Vec<PropertyCondFormat>>, context: &'a HashMap<String, String>, properties: &Vec<Property>) -> Option<(&'a String, &'a Option<String>)> {
-        let mut eval_context = HashMapContext::new();
-        context.into_iter().for_each(|(k, v)| {
-            let property = properties.iter().find(|p| p.name == k.as_str());
-            match property {
-                Some(property) => {
-                    match property.value_type {
-                        PropertyValueType::Integer => {
-                            eval_context.set_value(k.into(), v.clone().parse::<i64>().unwrap_or(0).into()).unwrap();
-                        },
-                        _ => {
-                            eval_context.set_value(k.into(), v.clone().into()).unwrap();
-                        }
-                    }
-                },
-                None => {
-                    eval_context.set_value(k.into(), v.clone().into()).unwrap();
-                }
-            }
-        });
+        let eval_context = Self::make_eval_context(context, properties);
 
         match cond_format {
             Some(cond_format) => {
                 cond_format.iter()
+                    .filter(|cf| !cf.is_row())
                     .find(|cf| evalexpr::eval_boolean_with_context(&cf.condition, &eval_context).unwrap_or(false))
                     .map(|cf| Some((&cf.color, &cf.style)))
                     .unwrap_or_else(|| None)
@@ -288,6 +329,229 @@ impl PropertyManager {
         }
     }
 
+    /// Integer and datetime properties are numbers in expressions (datetimes can also be YYYY-MM-DD dates),
+    /// lists are tuples, `now` is the current timestamp.
+    fn make_eval_context(context: &HashMap<String, String>, properties: &[Property]) -> HashMapContext {
+        let mut eval_context = HashMapContext::new();
+        context.iter().for_each(|(k, v)| {
+            let property = properties.iter().find(|p| p.name == k.as_str());
+            match property.map(|property| &property.value_type) {
+                Some(PropertyValueType::Integer) => {
+                    let value = parse_typed_value::<i64>(k, v, Some("integer")).unwrap_or(0);
+                    eval_context.set_value(k.into(), value.into()).unwrap();
+                },
+                Some(PropertyValueType::DateTime) => {
+                    let value = parse_typed_value::<i64>(k, v, Some("datetime")).ok()
+                        .or_else(|| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").ok()
+                            .and_then(|_| parse_date(Some(v.clone()))?.earliest())
+                            .map(|date| date.timestamp()))
+                        .unwrap_or(0);
+                    eval_context.set_value(k.into(), value.into()).unwrap();
+                },
+                Some(PropertyValueType::List) => {
+                    let values = parse_typed_value::<Vec<String>>(k, v, Some("list")).unwrap_or_default();
+                    eval_context.set_value(k.into(), Value::Tuple(values.into_iter().map(Value::from).collect())).unwrap();
+                },
+                _ => {
+                    eval_context.set_value(k.into(), v.clone().into()).unwrap();
+                }
+            }
+        });
+        eval_context.set_value("now".into(), chrono::Utc::now().timestamp().into()).unwrap();
+        eval_context
+    }
+
+    /// Sets the values of the computed properties, those having a formula, in the order of their definition,
+    /// so a formula can refer to the properties computed before. The task must not be saved afterward.
+    pub fn apply_formulas(&self, task: &mut Task) {
+        let mut context = task.get_all_properties().to_owned();
+        if let Some(id) = task.get_id() {
+            context.insert("id".to_string(), id);
+        }
+
+        for property in &self.properties {
+            let Some(formula) = &property.formula else {
+                continue;
+            };
+            let eval_context = Self::make_eval_context(&context, &self.properties);
+            let value = match evalexpr::eval_with_context(formula, &eval_context) {
+                Ok(Value::String(value)) => value,
+                Ok(Value::Int(value)) => value.to_string(),
+                Ok(Value::Float(value)) => value.to_string(),
+                Ok(Value::Boolean(value)) => value.to_string(),
+                _ => continue,
+            };
+            task.set_property(&property.name, &value);
+            context.insert(property.name.clone(), value);
+        }
+    }
+
+    /// Full task as JSON for scripts and editor plugins: the export format with the computed properties filled in,
+    /// their names under `computed` and the configuration of every property the task has under `properties`.
+    pub fn describe_task(&self, task: &Task) -> Result<serde_json::Value, String> {
+        let mut task = task.clone();
+        self.apply_formulas(&mut task);
+
+        let mut result = serde_json::to_value(&task).map_err(|e| e.to_string())?;
+        let computed = self.properties.iter()
+            .filter(|property| property.formula.is_some() && task.has_property(&property.name))
+            .map(|property| property.name.clone())
+            .collect::<Vec<_>>();
+        let properties = self.properties.iter()
+            .filter(|property| property.name == "id" || task.has_property(&property.name))
+            .map(|property| serde_json::to_value(property).map(|value| (property.name.clone(), value)))
+            .collect::<Result<serde_json::Map<_, _>, _>>()
+            .map_err(|e| e.to_string())?;
+
+        if let Some(object) = result.as_object_mut() {
+            object.insert("computed".to_string(), serde_json::json!(computed));
+            object.insert("properties".to_string(), serde_json::Value::Object(properties));
+        }
+        Ok(result)
+    }
+
+    /// JSON Schema of a task in the `export` format, so external tools can validate or generate tasks.
+    /// All property values are strings, the configured properties get their format, enum values and description.
+    pub fn json_schema(&self, statuses: &[&str]) -> serde_json::Value {
+        let mut props = serde_json::Map::new();
+        props.insert("status".to_string(), serde_json::json!({ "type": "string", "enum": statuses }));
+        for property in self.properties.iter().filter(|property| property.name != "id") {
+            props.insert(property.name.clone(), Self::property_schema(property));
+        }
+
+        let string_map = serde_json::json!({ "type": "object", "additionalProperties": { "type": "string" } });
+        serde_json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "git-task task",
+            "type": "object",
+            "required": ["props"],
+            "properties": {
+                "id": { "type": ["string", "null"], "pattern": "^[0-9]+$" },
+                "props": {
+                    "type": "object",
+                    "required": ["name", "status"],
+                    "properties": props,
+                    "additionalProperties": { "type": "string" },
+                },
+                "comments": {
+                    "type": ["array", "null"],
+                    "items": {
+                        "type": "object",
+                        "required": ["text"],
+                        "properties": {
+                            "id": { "type": ["string", "null"] },
+                            "props": string_map,
+                            "text": { "type": "string" },
+                        },
+                    },
+                },
+                "labels": {
+                    "type": ["array", "null"],
+                    "items": {
+                        "type": "object",
+                        "required": ["name"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "color": { "type": ["string", "null"] },
+                            "description": { "type": ["string", "null"] },
+                        },
+                    },
+                },
+            },
+        })
+    }
+
+    fn property_schema(property: &Property) -> serde_json::Value {
+        let enum_names = property.enum_values.as_ref().map(|enum_values| enum_values.iter().map(|enum_value| enum_value.name.clone()).collect::<Vec<_>>());
+        let mut schema = match property.value_type {
+            PropertyValueType::String | PropertyValueType::Text => serde_json::json!({ "type": "string" }),
+            PropertyValueType::Integer => serde_json::json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+            PropertyValueType::DateTime => serde_json::json!({ "type": "string", "pattern": "^-?[0-9]+$", "description": "Unix timestamp in seconds" }),
+            PropertyValueType::List => serde_json::json!({
+                "type": "string",
+                "contentMediaType": "application/json",
+                "contentSchema": { "type": "array", "items": { "type": "string" } },
+                "description": "JSON array of strings",
+            }),
+        };
+        if let Some(enum_names) = enum_names {
+            match property.value_type {
+                PropertyValueType::List => schema["contentSchema"]["items"]["enum"] = serde_json::json!(enum_names),
+                _ => schema["enum"] = serde_json::json!(enum_names),
+            }
+        }
+        if let Some(formula) = &property.formula {
+            schema["readOnly"] = serde_json::json!(true);
+            schema["description"] = serde_json::json!(format!("Computed: {formula}"));
+        }
+        schema
+    }
+
+    /// Enum values defined for the property, empty if any value is allowed.
+    pub fn get_enum_names(&self, property: &str) -> Vec<&str> {
+        self.properties.iter()
+            .find(|p| p.name == property)
+            .and_then(|p| p.enum_values.as_ref())
+            .map(|enum_values| enum_values.iter().map(|enum_value| enum_value.name.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Checks that the value (every one of them for list properties) is one of the enum values,
+    /// returns the value with the case of the enum values.
+    pub fn check_enum_value(&self, property: &str, value: &str) -> Result<String, String> {
+        let names = self.get_enum_names(property);
+        if names.is_empty() || value.is_empty() {
+            return Ok(value.to_string());
+        }
+
+        let check = |value: &str| match names.iter().find(|name| name.eq_ignore_ascii_case(value)) {
+            Some(name) => Ok(name.to_string()),
+            None => match suggest(value, &names) {
+                Some(suggestion) => Err(format!("'{value}' is not a valid {property}, did you mean {suggestion}?")),
+                None => Err(format!("'{value}' is not a valid {property}, expected one of: {}", names.join(", "))),
+            },
+        };
+        match self.is_list(property) {
+            true => {
+                let values = parse_typed_value::<Vec<String>>(property, value, Some("list"))?;
+                values.iter().map(|value| check(value)).collect::<Result<Vec<_>, _>>().map(|values| gittask::format_list_value(&values))
+            },
+            false => check(value),
+        }
+    }
+
+    /// Checks the value against the type of the property and its enum values,
+    /// returns the value with the case of the enum values.
+    pub fn validate_value(&self, property: &str, value: &str) -> Result<String, String> {
+        if !value.is_empty() {
+            match self.properties.iter().find(|p| p.name == property).map(|p| &p.value_type) {
+                Some(PropertyValueType::Integer) => parse_typed_value::<i64>(property, value, Some("integer")).map(|_| ())?,
+                Some(PropertyValueType::DateTime) => parse_typed_value::<u64>(property, value, Some("datetime")).map(|_| ())?,
+                Some(PropertyValueType::List) => parse_typed_value::<Vec<String>>(property, value, Some("list")).map(|_| ())?,
+                _ => {},
+            }
+        }
+        self.check_enum_value(property, value)
+    }
+
+    pub fn is_list(&self, property: &str) -> bool {
+        self.properties.iter().any(|p| p.name == property && matches!(p.value_type, PropertyValueType::List))
+    }
+
+    pub fn is_computed(&self, property: &str) -> bool {
+        self.properties.iter().any(|p| p.name == property && p.formula.is_some())
+    }
+
+    /// Style of the whole list row from the first matching conditional formatting marked as a row one.
+    pub fn find_row_format(&self, context: &HashMap<String, String>) -> Option<Style> {
+        let eval_context = Self::make_eval_context(context, &self.properties);
+        self.properties.iter()
+            .flat_map(|property| property.cond_format.iter().flatten())
+            .filter(|cf| cf.is_row())
+            .find(|cf| evalexpr::eval_boolean_with_context(&cf.condition, &eval_context).unwrap_or(false))
+            .map(|cf| str_to_color(&cf.color, &cf.style))
+    }
+
     fn find_enum_value<'a>(enum_values: &'a Option<Vec<PropertyEnumValue>>, value: &'a String) -> Option<(&'a String, &'a Option<String>)> {
         match enum_values {
             Some(enum_values) => {
@@ -308,6 +572,7 @@ impl PropertyManager {
                     "value_type" => Some(saved_prop.value_type.to_string()),
                     "color" => Some(saved_prop.color.clone()),
                     "style" => saved_prop.style.clone(),
+                    "formula" => saved_prop.formula.clone(),
                     _ => None
                 }
             } else { None }
@@ -346,6 +611,15 @@ impl PropertyManager {
                     "style" => {
                         saved_prop.style = Some(value.clone()); Ok(())
                     },
+                    "formula" => {
+                        match value.as_str() {
+                            "" => { saved_prop.formula = None; Ok(()) },
+                            value => match evalexpr::build_operator_tree(value) {
+                                Ok(_) => { saved_prop.formula = Some(value.to_string()); Ok(()) },
+                                Err(e) => Err(format!("Invalid formula: {e}")),
+                            }
+                        }
+                    },
                     _ => Err("Unknown property".to_string())
                 };
                 match set_result {
@@ -374,6 +648,7 @@ impl PropertyManager {
             color,
             enum_values: enum_values.map_or_else(|| None, |enum_values| Some(PropertyEnumValue::from(enum_values))),
             cond_format: cond_format.map_or_else(|| None, |cond_format| Some(PropertyCondFormat::from(cond_format))),
+            formula: None,
         };
         self.properties.push(property);
         Self::save_config(&self.properties)
@@ -388,6 +663,16 @@ impl PropertyManager {
         }
     }
 
+    /// Changes the colors of several properties at once, the others keep their colors.
+    pub fn set_colors(&mut self, colors: &[(String, String)]) -> Result<(), String> {
+        for property in self.properties.iter_mut() {
+            if let Some((_, color)) = colors.iter().find(|(name, _)| *name == property.name) {
+                property.color = color.clone();
+            }
+        }
+        Self::save_config(&self.properties)
+    }
+
     pub fn add_enum_property(&mut self, name: String, enum_value_name: String, enum_value_color: String, enum_value_style: Option<String>) -> Result<(), String> {
         let property = self.properties.iter_mut().find(|saved_prop| saved_prop.name == name);
         match property {
@@ -469,7 +754,8 @@ impl PropertyManager {
         }
     }
 
-    pub fn add_cond_format(&mut self, name: String, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>) -> Result<(), String> {
+    pub fn add_cond_format(&mut self, name: String, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>, row: bool) -> Result<(), String> {
+        evalexpr::build_operator_tree(&cond_format_expr).map_err(|e| format!("Invalid expression: {e}"))?;
         let property = self.properties.iter_mut().find(|saved_prop| saved_prop.name == name);
         match property {
             Some(property) => {
@@ -478,6 +764,7 @@ impl PropertyManager {
                     condition: cond_format_expr,
                     color: cond_format_color,
                     style: cond_format_style,
+                    row: if row { Some(true) } else { None },
                 });
                 property.cond_format = Some(cond_format);
                 Self::save_config(&self.properties)
@@ -486,6 +773,44 @@ impl PropertyManager {
         }
     }
 
+    /// Replaces the conditional formatting by its number starting from 1.
+    pub fn set_cond_format(&mut self, name: String, number: usize, cond_format_expr: String, cond_format_color: String, cond_format_style: Option<String>, row: bool) -> Result<(), String> {
+        evalexpr::build_operator_tree(&cond_format_expr).map_err(|e| format!("Invalid expression: {e}"))?;
+        let property = self.properties.iter_mut().find(|saved_prop| saved_prop.name == name);
+        match property {
+            Some(property) => {
+                match property.cond_format.iter_mut().flatten().nth(number.wrapping_sub(1)) {
+                    Some(cond_format) => {
+                        cond_format.condition = cond_format_expr;
+                        cond_format.color = cond_format_color;
+                        cond_format.style = cond_format_style;
+                        cond_format.row = if row { Some(true) } else { None };
+                        Self::save_config(&self.properties)
+                    },
+                    None => Err(format!("Conditional formatting #{number} not found")),
+                }
+            },
+            None => Err("Property not found".to_string())
+        }
+    }
+
+    /// Deletes the conditional formatting by its number starting from 1.
+    pub fn delete_cond_format(&mut self, name: String, number: usize) -> Result<(), String> {
+        let property = self.properties.iter_mut().find(|saved_prop| saved_prop.name == name);
+        match property {
+            Some(property) => {
+                let mut cond_format = property.cond_format.clone().unwrap_or_default();
+                if number == 0 || number > cond_format.len() {
+                    return Err(format!("Conditional formatting #{number} not found"));
+                }
+                cond_format.remove(number - 1);
+                property.cond_format = if cond_format.is_empty() { None } else { Some(cond_format) };
+                Self::save_config(&self.properties)
+            },
+            None => Err("Property not found".to_string())
+        }
+    }
+
     pub fn clear_cond_format(&mut self, name: String) -> Result<(), String> {
         let property = self.properties.iter_mut().find(|saved_prop| saved_prop.name == name);
         match property {
@@ -496,4 +821,110 @@ impl PropertyManager {
             None => Err("Property not found".to_string())
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply_formulas() {
+        let estimate = Property::new("estimate".to_string(), PropertyValueType::Integer, "Default".to_string(), None);
+        let spent = Property::new("spent".to_string(), PropertyValueType::Integer, "Default".to_string(), None);
+        let mut remaining = Property::new("remaining".to_string(), PropertyValueType::Integer, "Default".to_string(), None);
+        let mut overdue = Property::new("overdue".to_string(), PropertyValueType::String, "Default".to_string(), None);
+        remaining.formula = Some("estimate - spent".to_string());
+        overdue.formula = Some("remaining < 0".to_string());
+        let prop_manager = PropertyManager { properties: vec![estimate, spent, remaining, overdue] };
+
+        let mut task = Task::builder("Refactoring").id("1").status("OPEN").property("estimate", "5").property("spent", "8").build().unwrap();
+        prop_manager.apply_formulas(&mut task);
+        assert_eq!(task.get_property("remaining").unwrap(), "-3");
+        assert_eq!(task.get_property("overdue").unwrap(), "true");
+        assert!(prop_manager.is_computed("remaining"));
+        assert!(!prop_manager.is_computed("estimate"));
+    }
+
+    #[test]
+    fn test_describe_task() {
+        let estimate = Property::new("estimate".to_string(), PropertyValueType::Integer, "Default".to_string(), None);
+        let mut remaining = Property::new("remaining".to_string(), PropertyValueType::Integer, "Default".to_string(), None);
+        let priority = Property::new("priority".to_string(), PropertyValueType::String, "Red".to_string(), None);
+        remaining.formula = Some("estimate - 2".to_string());
+        let prop_manager = PropertyManager { properties: vec![estimate, remaining, priority] };
+
+        let mut task = Task::builder("Refactoring").id("1").status("OPEN").property("estimate", "5").build().unwrap();
+        task.add_comment(Some("1".to_string()), HashMap::new(), "Started".to_string());
+        let json = prop_manager.describe_task(&task).unwrap();
+
+        assert_eq!(json["id"], "1");
+        assert_eq!(json["props"]["remaining"], "3");
+        assert_eq!(json["comments"][0]["text"], "Started");
+        assert_eq!(json["computed"], serde_json::json!(["remaining"]));
+        assert_eq!(json["properties"]["estimate"]["value_type"], "integer");
+        assert_eq!(json["properties"]["remaining"]["formula"], "estimate - 2");
+        assert!(json["properties"].get("priority").is_none());
+        assert!(!task.has_property("remaining"));
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let enum_values = PropertyEnumValue::from(["LOW", "Green", "HIGH", "Red"].map(String::from).to_vec());
+        let id = Property::new("id".to_string(), PropertyValueType::Integer, "DarkGray".to_string(), None);
+        let priority = Property::new("priority".to_string(), PropertyValueType::String, "Default".to_string(), Some(enum_values.clone()));
+        let platforms = Property::new("platforms".to_string(), PropertyValueType::List, "Default".to_string(), Some(enum_values));
+        let mut created = Property::new("created".to_string(), PropertyValueType::DateTime, "Default".to_string(), None);
+        created.formula = Some("0".to_string());
+        let prop_manager = PropertyManager { properties: vec![id, priority, platforms, created] };
+
+        let schema = prop_manager.json_schema(&["OPEN", "CLOSED"]);
+        let props = &schema["properties"]["props"]["properties"];
+        assert!(props.get("id").is_none());
+        assert_eq!(props["status"]["enum"], serde_json::json!(["OPEN", "CLOSED"]));
+        assert_eq!(props["priority"]["enum"], serde_json::json!(["LOW", "HIGH"]));
+        assert_eq!(props["platforms"]["contentSchema"]["items"]["enum"], serde_json::json!(["LOW", "HIGH"]));
+        assert_eq!(props["created"]["pattern"], "^-?[0-9]+$");
+        assert_eq!(props["created"]["readOnly"], true);
+        assert_eq!(schema["properties"]["props"]["required"], serde_json::json!(["name", "status"]));
+    }
+
+    #[test]
+    fn test_to_pairs() {
+        let source = ["LOW", "Green", "HIGH", "Red"].map(String::from).to_vec();
+        assert_eq!(to_pairs(source), vec![("LOW".to_string(), "Green".to_string()), ("HIGH".to_string(), "Red".to_string())]);
+
+        let odd = ["LOW", "Green", "HIGH"].map(String::from).to_vec();
+        assert_eq!(PropertyEnumValue::from(odd.clone()).iter().map(|value| value.get_name()).collect::<Vec<_>>(), vec!["LOW"]);
+        assert_eq!(PropertyCondFormat::from(odd).iter().map(|format| format.get_condition()).collect::<Vec<_>>(), vec!["LOW"]);
+        assert!(to_pairs(vec!["LOW".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_check_enum_value() {
+        let enum_values = PropertyEnumValue::from(["LOW", "Green", "HIGH", "Red"].map(String::from).to_vec());
+        let priority = Property::new("priority".to_string(), PropertyValueType::String, "Default".to_string(), Some(enum_values.clone()));
+        let platforms = Property::new("platforms".to_string(), PropertyValueType::List, "Default".to_string(), Some(enum_values));
+        let prop_manager = PropertyManager { properties: vec![priority, platforms] };
+
+        assert_eq!(prop_manager.check_enum_value("priority", "high"), Ok("HIGH".to_string()));
+        assert_eq!(prop_manager.check_enum_value("priority", "HIHG"), Err("'HIHG' is not a valid priority, did you mean HIGH?".to_string()));
+        assert!(prop_manager.check_enum_value("priority", "urgent").unwrap_err().ends_with("expected one of: LOW, HIGH"));
+        assert_eq!(prop_manager.check_enum_value("platforms", r#"["low","HIGH"]"#), Ok(r#"["LOW","HIGH"]"#.to_string()));
+        assert!(prop_manager.check_enum_value("platforms", r#"["LOW","lwo2"]"#).is_err());
+        assert_eq!(prop_manager.check_enum_value("estimate", "3"), Ok("3".to_string()));
+    }
+
+    #[test]
+    fn test_find_row_format() {
+        let mut due = Property::new("due".to_string(), PropertyValueType::DateTime, "Default".to_string(), None);
+        due.cond_format = Some(vec![
+            PropertyCondFormat { condition: "due < now".to_string(), color: "Red".to_string(), style: None, row: Some(true) },
+            PropertyCondFormat { condition: "due > now".to_string(), color: "Green".to_string(), style: None, row: None },
+        ]);
+        let prop_manager = PropertyManager { properties: vec![due] };
+
+        let overdue = HashMap::from([("due".to_string(), "2000-01-01".to_string())]);
+        let upcoming = HashMap::from([("due".to_string(), "2999-01-01".to_string())]);
+        assert!(prop_manager.find_row_format(&overdue).is_some());
+        assert!(prop_manager.find_row_format(&upcoming).is_none());
+    }
+}
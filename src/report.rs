@@ -0,0 +1,123 @@
+use chrono::Local;
+
+use gittask::Task;
+
+use crate::i18n::date_format;
+use crate::property::PropertyManager;
+use crate::util::capitalize;
+
+/// Print stylesheet: every task starts on a new page, headers of the summary table repeat on page breaks,
+/// pages are numbered in the footer.
+const STYLE: &str = r#"
+body { font-family: sans-serif; font-size: 11pt; color: #000; margin: 0 auto; max-width: 60em; }
+h1 { font-size: 18pt; margin-bottom: 0; }
+h2 { font-size: 14pt; border-bottom: 1px solid #888; padding-bottom: 2pt; }
+.meta { color: #555; margin-top: 2pt; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #bbb; padding: 3pt 6pt; text-align: left; vertical-align: top; }
+th { background: #eee; }
+thead { display: table-header-group; }
+tr { page-break-inside: avoid; }
+table.properties th { width: 25%; }
+pre { white-space: pre-wrap; font-family: inherit; margin: 6pt 0; }
+.task { page-break-before: always; }
+.comment { border-left: 3px solid #bbb; padding-left: 8pt; margin: 8pt 0; page-break-inside: avoid; }
+.comment .meta { font-size: 9pt; }
+@page { size: A4; margin: 2cm; @bottom-right { content: "Page " counter(page) " of " counter(pages); } }
+@media screen { body { margin: 2em auto; } .task { border-top: 2px solid #888; margin-top: 2em; } }
+"#;
+
+/// Renders the tasks as a self-contained HTML report ready for printing or saving as PDF from a browser:
+/// a summary table followed by a page per task with its properties, description and comments.
+pub fn tasks_to_html(tasks: &[Task], prop_manager: &PropertyManager) -> String {
+    let properties = prop_manager.get_properties();
+    let format = |task: &Task, prop: &str, value: &str| -> String {
+        let mut context = task.get_all_properties().clone();
+        context.insert("id".to_string(), task.get_id().unwrap_or_default());
+        prop_manager.format_value(prop, value, &context, properties, true).to_string()
+    };
+    let generated = Local::now().format(date_format()).to_string();
+
+    let mut result = String::new();
+    result.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Task report</title>\n");
+    result.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    result.push_str("<h1>Task report</h1>\n");
+    result.push_str(&format!("<p class=\"meta\">Generated {generated}, {} task(s)</p>\n", tasks.len()));
+
+    result.push_str("<table class=\"summary\">\n<thead><tr><th>ID</th><th>Status</th><th>Name</th><th>Created</th></tr></thead>\n<tbody>\n");
+    for task in tasks {
+        let cell = |prop: &str| task.get_property(prop).map(|value| escape(&format(task, prop, value))).unwrap_or_default();
+        result.push_str(&format!("<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&task.get_id().unwrap_or_default()), cell("status"), cell("name"), cell("created")));
+    }
+    result.push_str("</tbody>\n</table>\n");
+
+    for task in tasks {
+        let id = escape(&task.get_id().unwrap_or_default());
+        let name = escape(task.get_property("name").map(String::as_str).unwrap_or_default());
+        result.push_str(&format!("<section class=\"task\">\n<h2>#{id} {name}</h2>\n<table class=\"properties\">\n"));
+
+        let mut props = task.get_all_properties().iter()
+            .filter(|(prop, _)| !["name", "description"].contains(&prop.as_str()))
+            .collect::<Vec<_>>();
+        props.sort_by_key(|(prop, _)| match prop.as_str() {
+            "status" => (0, String::new()),
+            prop => (1, prop.to_string()),
+        });
+        for (prop, value) in props {
+            result.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", escape(&capitalize(prop)), escape(&format(task, prop, value))));
+        }
+        if let Some(labels) = task.get_labels().as_ref().filter(|labels| !labels.is_empty()) {
+            let labels = labels.iter().map(|label| label.get_name()).collect::<Vec<_>>().join(", ");
+            result.push_str(&format!("<tr><th>Labels</th><td>{}</td></tr>\n", escape(&labels)));
+        }
+        result.push_str("</table>\n");
+
+        if let Some(description) = task.get_property("description").filter(|description| !description.trim().is_empty()) {
+            result.push_str(&format!("<h3>Description</h3>\n<pre>{}</pre>\n", escape(description)));
+        }
+
+        if let Some(comments) = task.get_comments().as_ref().filter(|comments| !comments.is_empty()) {
+            result.push_str("<h3>Comments</h3>\n");
+            for comment in comments {
+                let comment_properties = comment.get_all_properties();
+                let author = comment_properties.get("author").cloned().unwrap_or_default();
+                let created = comment_properties.get("created").map(|created| format(task, "created", created)).unwrap_or_default();
+                result.push_str(&format!("<div class=\"comment\">\n<p class=\"meta\">{} {}</p>\n<pre>{}</pre>\n</div>\n",
+                    escape(&author), escape(&created), escape(comment.get_text().trim())));
+            }
+        }
+
+        result.push_str("</section>\n");
+    }
+
+    result.push_str("</body>\n</html>");
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tasks_to_html() {
+        let prop_manager = gittask::TaskRepository::temporary().unwrap().run(PropertyManager::new);
+        let tasks = vec![
+            Task::builder("Crash in <parser>").id("1").status("OPEN").description("Steps:\n1. run & fail").property("priority", "HIGH").build().unwrap(),
+            Task::builder("Docs").id("2").status("CLOSED").build().unwrap(),
+        ];
+
+        let html = tasks_to_html(&tasks, &prop_manager);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("2 task(s)"));
+        assert!(html.contains("<h2>#1 Crash in &lt;parser&gt;</h2>"));
+        assert!(html.contains("<pre>Steps:\n1. run &amp; fail</pre>"));
+        assert!(html.contains("<tr><th>Priority</th><td>HIGH</td></tr>"));
+        assert_eq!(html.matches("<section class=\"task\">").count(), 2);
+        assert_eq!(html.matches("<h3>Description</h3>").count(), 1);
+    }
+}
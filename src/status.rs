@@ -1,7 +1,14 @@
-use nu_ansi_term::AnsiString;
+use std::cell::RefCell;
+
+use nu_ansi_term::{AnsiString, Style};
 use serde::{Deserialize, Serialize};
 
-use crate::util::str_to_color;
+use crate::util::{is_valid_style, str_to_color, STYLES};
+
+thread_local! {
+    /// Last parsed `task.statuses`, commands of a `git task do` session don't parse the same JSON again.
+    static PARSED_CONFIG: RefCell<Option<(String, Vec<Status>)>> = const { RefCell::new(None) };
+}
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Status {
@@ -13,6 +20,16 @@ pub struct Status {
 }
 
 impl Status {
+    pub(crate) fn new(name: String, shortcut: String, color: String, is_done: bool) -> Status {
+        Status {
+            name,
+            shortcut,
+            color,
+            style: None,
+            is_done,
+        }
+    }
+
     pub(crate) fn get_name(&self) -> &str {
         &self.name
     }
@@ -36,14 +53,17 @@ impl Status {
 
 pub struct StatusManager {
     statuses: Vec<Status>,
+    done_style: Option<String>,
 }
 
 impl StatusManager {
     pub fn new() -> StatusManager {
         let statuses = read_config().unwrap_or_else(|_| Self::get_defaults());
+        let done_style = gittask::get_config_value("task.list.done.style").ok().filter(|style| !style.is_empty());
 
         StatusManager {
-            statuses
+            statuses,
+            done_style,
         }
     }
 
@@ -117,6 +137,32 @@ impl StatusManager {
         }
     }
 
+    /// Moves the status to the 1-based position, statuses are presented in this workflow order.
+    pub fn move_status(&mut self, name: &str, position: usize) -> Result<(), String> {
+        if position == 0 || position > self.statuses.len() {
+            return Err(format!("Position must be between 1 and {}", self.statuses.len()));
+        }
+        let index = self.statuses.iter().position(|s| s.name == name).ok_or_else(|| "No such status".to_string())?;
+        let status = self.statuses.remove(index);
+        self.statuses.insert(position - 1, status);
+        save_config(&self.statuses)
+    }
+
+    /// Changes the colors of several statuses at once, the others keep their colors.
+    pub fn set_colors(&mut self, colors: &[(String, String)]) -> Result<(), String> {
+        for status in self.statuses.iter_mut() {
+            if let Some((_, color)) = colors.iter().find(|(name, _)| *name == status.name) {
+                status.color = color.clone();
+            }
+        }
+        save_config(&self.statuses)
+    }
+
+    /// Position of the status in the workflow, unknown statuses go last.
+    pub fn get_order(&self, status: &str) -> usize {
+        self.statuses.iter().position(|s| s.name == status).unwrap_or(self.statuses.len())
+    }
+
     pub fn format_status<'a>(&self, status: &'a str, no_color: bool) -> AnsiString<'a> {
         match no_color {
             false => {
@@ -130,6 +176,14 @@ impl StatusManager {
         }
     }
 
+    /// Style of the whole list line for tasks in final statuses, `task.list.done.style` parameter.
+    pub fn get_done_style(&self, status: &str) -> Option<Style> {
+        match self.is_done(status) {
+            true => self.done_style.as_ref().map(|style| str_to_color("default", &Some(style.clone()))),
+            false => None,
+        }
+    }
+
     pub fn get_full_status_name(&self, status: &String) -> String {
         self.statuses.iter().find_map(|saved_status| {
             if status == saved_status.shortcut.as_str() { Some(saved_status.name.clone()) } else { None }
@@ -137,15 +191,15 @@ impl StatusManager {
     }
 
     pub fn get_starting_status(&self) -> String {
-        match gittask::get_config_value("task.status.open") {
-            Ok(s) => s,
+        match self.pick_status("task.status.open") {
+            Some(s) => s,
             _ => self.statuses.first().unwrap().name.clone()
         }
     }
 
     pub fn get_final_status(&self) -> String {
-        match gittask::get_config_value("task.status.closed") {
-            Ok(s) => s,
+        match self.pick_status("task.status.closed") {
+            Some(s) => s,
             _ => {
                 self.statuses.iter().find_map(|saved_status| {
                     if saved_status.is_done { Some(saved_status.name.clone()) } else { None }
@@ -154,6 +208,25 @@ impl StatusManager {
         }
     }
 
+    /// Local statuses for open and closed tasks of the remote connector,
+    /// `task.<connector>.status.open` and `task.<connector>.status.closed` override the common ones.
+    pub fn get_remote_statuses(&self, connector: &str) -> Vec<String> {
+        vec![
+            self.pick_status(&format!("task.{connector}.status.open")).unwrap_or_else(|| self.get_starting_status()),
+            self.pick_status(&format!("task.{connector}.status.closed")).unwrap_or_else(|| self.get_final_status()),
+        ]
+    }
+
+    /// The parameter is a priority list of statuses, the first defined one is picked.
+    fn pick_status(&self, param: &str) -> Option<String> {
+        let value = gittask::get_config_value(param).ok()?;
+        let candidates = value.split(',').map(str::trim).filter(|status| !status.is_empty()).collect::<Vec<_>>();
+        candidates.iter()
+            .find(|candidate| self.statuses.iter().any(|saved_status| saved_status.name == **candidate))
+            .or(candidates.first())
+            .map(|status| status.to_string())
+    }
+
     pub fn is_done(&self, status: &str) -> bool {
         self.statuses.iter().find_map(|saved_status| {
             if saved_status.name == status { Some(saved_status.is_done) } else { None }
@@ -161,14 +234,15 @@ impl StatusManager {
     }
 
     pub fn get_property(&self, status: &str, property: &str) -> Option<String> {
-        self.statuses.iter().find_map(|saved_status| {
+        self.statuses.iter().enumerate().find_map(|(index, saved_status)| {
             if status == saved_status.name.as_str() {
                 match property {
-                    "name" => return Some(saved_status.name.clone()),
-                    "shortcut" => return Some(saved_status.shortcut.clone()),
-                    "color" => return Some(saved_status.color.clone()),
-                    "style" => return Some(saved_status.style.clone().unwrap_or_else(|| String::new())),
-                    "is_done" => return Some(saved_status.is_done.to_string()),
+                    "name" => Some(saved_status.name.clone()),
+                    "shortcut" => Some(saved_status.shortcut.clone()),
+                    "color" => Some(saved_status.color.clone()),
+                    "style" => Some(saved_status.style.clone().unwrap_or_default()),
+                    "is_done" => Some(saved_status.is_done.to_string()),
+                    "order" => Some((index + 1).to_string()),
                     _ => None
                 }
             } else { None }
@@ -176,6 +250,11 @@ impl StatusManager {
     }
 
     pub fn set_property(&mut self, status: &String, property: &String, value: &String) -> Result<Option<String>, String> {
+        if property == "order" {
+            let position = value.parse::<usize>().map_err(|_| "Order must be a positive number".to_string())?;
+            return self.move_status(status, position).map(|_| None);
+        }
+
         let statuses = self.statuses.clone();
         let status = self.statuses.iter_mut().find(|saved_status| {
             status == saved_status.name.as_str()
@@ -211,7 +290,11 @@ impl StatusManager {
                         saved_status.color = value.clone(); Ok(None)
                     },
                     "style" => {
-                        saved_status.style = Some(value.clone()); Ok(None)
+                        match value.as_str() {
+                            "" => { saved_status.style = None; Ok(None) },
+                            value if is_valid_style(value) => { saved_status.style = Some(value.to_string()); Ok(None) },
+                            _ => Err(format!("Unknown style, supported styles are: {}", STYLES.join(", ")))
+                        }
                     },
                     "is_done" => {
                         saved_status.is_done = value.parse::<bool>().unwrap(); Ok(None)
@@ -234,10 +317,13 @@ impl StatusManager {
 }
 
 fn read_config() -> Result<Vec<Status>, String> {
-    match gittask::get_config_value("task.statuses") {
-        Ok(s) => parse_statuses(s),
-        Err(e) => Err(e)
+    let input = gittask::get_config_value("task.statuses")?;
+    if let Some(statuses) = PARSED_CONFIG.with_borrow(|parsed| parsed.as_ref().filter(|(raw, _)| *raw == input).map(|(_, statuses)| statuses.clone())) {
+        return Ok(statuses);
     }
+    let statuses = parse_statuses(input.clone())?;
+    PARSED_CONFIG.set(Some((input, statuses.clone())));
+    Ok(statuses)
 }
 
 fn save_config(statuses: &Vec<Status>) -> Result<(), String> {
@@ -0,0 +1,133 @@
+use serde_json::Value;
+
+/// Renders a Handlebars-style template: `{{name}}`, `{{task.name}}`, `{{this}}`,
+/// `{{#each list}}...{{/each}}` and `{{#if value}}...{{/if}}` blocks are supported.
+pub fn render(template: &str, context: &Value) -> Result<String, String> {
+    let mut output = String::new();
+    render_into(template, &[context], &mut output)?;
+    Ok(output)
+}
+
+fn render_into(template: &str, scopes: &[&Value], output: &mut String) -> Result<(), String> {
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let end = rest[start..].find("}}").ok_or_else(|| "Unclosed {{ in template".to_string())? + start;
+        let tag = rest[start + 2..end].trim();
+        let at_line_start = output.is_empty() || output.ends_with('\n');
+        rest = &rest[end + 2..];
+
+        if let Some(block) = tag.strip_prefix('#') {
+            let (kind, arg) = block.split_once(' ').unwrap_or((block, ""));
+            let (mut body, after) = split_block(rest, kind)?;
+            if at_line_start {
+                body = body.strip_prefix('\n').unwrap_or(body);
+            }
+            rest = match body.is_empty() || body.ends_with('\n') {
+                true => after.strip_prefix('\n').unwrap_or(after),
+                false => after,
+            };
+
+            let value = lookup(scopes, arg.trim());
+            match kind {
+                "each" => {
+                    if let Some(Value::Array(items)) = value {
+                        for item in items {
+                            let mut scopes = scopes.to_vec();
+                            scopes.push(item);
+                            render_into(body, &scopes, output)?;
+                        }
+                    }
+                },
+                "if" => {
+                    if is_truthy(value) {
+                        render_into(body, scopes, output)?;
+                    }
+                },
+                _ => return Err(format!("Unknown template block: {kind}")),
+            }
+        } else if tag.starts_with('/') {
+            return Err(format!("Unexpected {{{{{tag}}}}} in template"));
+        } else {
+            match lookup(scopes, tag) {
+                Some(Value::String(s)) => output.push_str(s),
+                Some(Value::Null) | None => {},
+                Some(value) => output.push_str(&value.to_string()),
+            }
+        }
+    }
+
+    output.push_str(rest);
+    Ok(())
+}
+
+fn split_block<'a>(template: &'a str, kind: &str) -> Result<(&'a str, &'a str), String> {
+    let mut depth = 0;
+    let mut position = 0;
+
+    while let Some(start) = template[position..].find("{{") {
+        let start = position + start;
+        let end = template[start..].find("}}").ok_or_else(|| "Unclosed {{ in template".to_string())? + start;
+        let tag = template[start + 2..end].trim();
+        position = end + 2;
+
+        if tag.starts_with('#') {
+            depth += 1;
+        } else if tag.starts_with('/') {
+            if depth == 0 {
+                return Ok((&template[..start], &template[position..]));
+            }
+            depth -= 1;
+        }
+    }
+
+    Err(format!("Missing {{{{/{kind}}}}} in template"))
+}
+
+fn lookup<'a>(scopes: &[&'a Value], path: &str) -> Option<&'a Value> {
+    if path == "this" || path == "." {
+        return scopes.last().copied();
+    }
+
+    scopes.iter().rev().find_map(|scope| {
+        path.split('.').try_fold(*scope, |value, key| value.get(key))
+    })
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let context = json!({
+            "title": "Release",
+            "groups": [
+                { "name": "bug", "tasks": [{ "id": "1", "name": "Crash" }, { "id": "2", "name": "Typo" }] },
+                { "name": "feature", "tasks": [] },
+            ],
+        });
+        let template = "# {{title}}\n{{#each groups}}\n## {{name}}\n{{#each tasks}}\n- {{name}} (#{{id}}, {{title}})\n{{/each}}\n{{#if tasks}}\n---\n{{/if}}\n{{/each}}\n";
+        assert_eq!(render(template, &context).unwrap(), "# Release\n## bug\n- Crash (#1, Release)\n- Typo (#2, Release)\n---\n## feature\n");
+    }
+
+    #[test]
+    fn test_render_errors() {
+        assert!(render("{{#each items}}", &json!({})).is_err());
+        assert!(render("{{name", &json!({})).is_err());
+        assert!(render("{{#unknown x}}{{/unknown}}", &json!({})).is_err());
+    }
+}
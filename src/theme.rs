@@ -0,0 +1,116 @@
+use nu_ansi_term::Style;
+
+use crate::property::PropertyManager;
+use crate::status::StatusManager;
+use crate::util::str_to_color;
+
+/// Known themes, `custom` keeps the individually configured colors.
+pub const THEMES: [&str; 4] = ["dark", "light", "solarized", "custom"];
+
+/// Colors of the titles, separators, statuses and default properties in one place, `task.theme` parameter.
+/// Titles and separators can be overridden with `task.theme.title` and `task.theme.separator`.
+pub struct Theme {
+    name: String,
+    title: String,
+    separator: String,
+    /// colors of the starting, intermediate and final statuses
+    statuses: Option<[String; 3]>,
+    properties: Vec<(String, String)>,
+}
+
+impl Theme {
+    pub fn get(name: &str) -> Option<Theme> {
+        let (title, separator, statuses, properties) = match name {
+            "dark" => ("DarkGray", "DarkGray", Some(["Red", "Yellow", "Green"]), [("id", "DarkGray"), ("created", "239"), ("updated", "239"), ("author", "Cyan")]),
+            "light" => ("Blue", "245", Some(["160", "130", "28"]), [("id", "240"), ("created", "244"), ("updated", "244"), ("author", "25")]),
+            "solarized" => ("#586e75", "#586e75", Some(["#dc322f", "#b58900", "#859900"]), [("id", "#657b83"), ("created", "#93a1a1"), ("updated", "#93a1a1"), ("author", "#2aa198")]),
+            "custom" => ("DarkGray", "DarkGray", None, [("id", ""), ("created", ""), ("updated", ""), ("author", "")]),
+            _ => return None,
+        };
+
+        Some(Theme {
+            name: name.to_string(),
+            title: title.to_string(),
+            separator: separator.to_string(),
+            statuses: statuses.map(|colors| colors.map(String::from)),
+            properties: properties.iter()
+                .filter(|(_, color)| !color.is_empty())
+                .map(|(property, color)| (property.to_string(), color.to_string()))
+                .collect(),
+        })
+    }
+
+    /// The configured theme with the overridden title and separator colors, `dark` by default.
+    pub fn current() -> Theme {
+        let name = gittask::get_config_value("task.theme").unwrap_or_default();
+        let mut theme = Self::get(&name.to_lowercase()).unwrap_or_else(|| Self::get("dark").unwrap());
+        if let Some(title) = gittask::get_config_value("task.theme.title").ok().filter(|color| !color.is_empty()) {
+            theme.title = title;
+        }
+        if let Some(separator) = gittask::get_config_value("task.theme.separator").ok().filter(|color| !color.is_empty()) {
+            theme.separator = separator;
+        }
+        theme
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn title(&self) -> Style {
+        str_to_color(&self.title, &None)
+    }
+
+    pub fn separator(&self) -> Style {
+        str_to_color(&self.separator, &None)
+    }
+
+    /// Color the theme gives to the status, `None` if the theme keeps the configured one.
+    pub fn get_status_color(&self, status: &str, status_manager: &StatusManager) -> Option<&str> {
+        let [starting, in_progress, done] = self.statuses.as_ref()?;
+        match status {
+            status if status_manager.is_done(status) => Some(done),
+            status if status == status_manager.get_starting_status() => Some(starting),
+            _ => Some(in_progress),
+        }
+    }
+
+    pub fn get_property_color(&self, property: &str) -> Option<&str> {
+        self.properties.iter().find(|(name, _)| name == property).map(|(_, color)| color.as_str())
+    }
+
+    /// Recolors the statuses and the default properties, `custom` theme leaves them as they are.
+    pub fn apply(&self, status_manager: &mut StatusManager, prop_manager: &mut PropertyManager) -> Result<(), String> {
+        let status_colors = status_manager.get_statuses().iter()
+            .filter_map(|status| self.get_status_color(status.get_name(), status_manager).map(|color| (status.get_name().to_string(), color.to_string())))
+            .collect::<Vec<_>>();
+        if !status_colors.is_empty() {
+            status_manager.set_colors(&status_colors)?;
+        }
+        if !self.properties.is_empty() {
+            prop_manager.set_colors(&self.properties)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_theme() {
+        for name in THEMES {
+            assert_eq!(Theme::get(name).unwrap().get_name(), name);
+        }
+        assert!(Theme::get("neon").is_none());
+
+        let solarized = Theme::get("solarized").unwrap();
+        assert_eq!(solarized.get_property_color("author"), Some("#2aa198"));
+        assert_eq!(solarized.get_property_color("name"), None);
+
+        let custom = Theme::get("custom").unwrap();
+        assert!(custom.statuses.is_none());
+        assert!(custom.get_property_color("id").is_none());
+    }
+}
@@ -3,9 +3,12 @@ use std::fs::File;
 use std::io::{IsTerminal, Read, Write};
 use std::iter::Iterator;
 use std::process::Command;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::time::{Duration, UNIX_EPOCH};
 
 use chrono::{DateTime, Local, MappedLocalTime, NaiveDate, TimeZone, Utc};
+use gittask::PropertyValue;
 use nu_ansi_term::{Color, Style};
 use nu_ansi_term::Color::{Black, Blue, Cyan, DarkGray, Default, Fixed, Green, LightBlue, LightCyan, LightGray, LightGreen, LightMagenta, LightPurple, LightRed, LightYellow, Magenta, Purple, Red, White, Yellow};
 
@@ -36,9 +39,109 @@ pub fn parse_ids(ids: String) -> Vec<String> {
         .filter(|s| !s.is_empty())
         .map(|s| s.to_string())
         .expand_range()
+        .map(|id| gittask::resolve_task_id(&id))
         .collect::<Vec<_>>()
 }
 
+/// Splits a command line into arguments like a shell does: whitespace separates them,
+/// single and double quotes group words, a backslash escapes the next character outside single quotes.
+pub fn split_args(line: &str) -> Result<Vec<String>, String> {
+    let mut args = vec![];
+    let mut current: Option<String> = None;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some('\'')) => current.get_or_insert_with(String::new).push(c),
+            ('\\', _) => match chars.next() {
+                Some(next) => current.get_or_insert_with(String::new).push(next),
+                None => return Err("Unfinished escape at the end of the line".to_string()),
+            },
+            ('\'' | '"', None) => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            },
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if c.is_whitespace() => args.extend(current.take()),
+            (c, _) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(q) = quote {
+        return Err(format!("Unmatched {q} quote"));
+    }
+    args.extend(current);
+    Ok(args)
+}
+
+pub fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(format!("Invalid property '{s}', expected KEY=VALUE")),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ListEdit {
+    Set,
+    Add,
+    Remove,
+}
+
+/// Applies comma separated values (or a JSON array) to the current value of a list property,
+/// returns the new value as stored.
+pub fn edit_list_value(current: &str, values: &str, edit: ListEdit) -> Result<String, String> {
+    let values = match values.trim_start().starts_with('[') {
+        true => Vec::<String>::parse_value(values)?,
+        false => values.split(',').map(str::trim).filter(|value| !value.is_empty()).map(String::from).collect(),
+    };
+    let mut list = match edit {
+        ListEdit::Set => vec![],
+        _ => Vec::<String>::parse_value(current)?,
+    };
+    match edit {
+        ListEdit::Set | ListEdit::Add => values.into_iter().for_each(|value| if !list.contains(&value) { list.push(value) }),
+        ListEdit::Remove => list.retain(|value| !values.contains(value)),
+    }
+    Ok(gittask::format_list_value(&list))
+}
+
+/// Closest candidate for a mistyped value, if it's close enough to be a typo.
+pub fn suggest<'a>(value: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    candidates.iter()
+        .map(|candidate| (edit_distance(&value, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.chars().count() / 2).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let current = row[j + 1];
+            row[j + 1] = (previous + usize::from(a != b[j])).min(row[j] + 1).min(current + 1);
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.split('/').filter(|s| !s.is_empty() && *s != ".").collect::<Vec<_>>().join("/")
+}
+
+pub fn is_in_path(path: &str, parent: &str) -> bool {
+    let parent = normalize_path(parent);
+    parent.is_empty() || path == parent || path.starts_with(&(parent + "/"))
+}
+
 pub fn capitalize(s: &str) -> String {
     let mut c = s.chars();
     match c.next() {
@@ -47,6 +150,18 @@ pub fn capitalize(s: &str) -> String {
     }
 }
 
+/// Text styles supported in `style` settings, several can be combined with commas.
+pub const STYLES: [&str; 6] = ["bold", "dimmed", "italic", "normal", "strikethrough", "underline"];
+
+pub fn is_valid_style(style: &str) -> bool {
+    style.split(',').all(|value| STYLES.contains(&value.trim()))
+}
+
+/// Named colors, 0-255 and #RRGGBB are valid, anything else falls back to the default color.
+pub fn is_valid_color(color: &str) -> bool {
+    color.eq_ignore_ascii_case("default") || str_to_color(color, &None) != str_to_color("default", &None)
+}
+
 pub fn str_to_color(color: &str, style: &Option<String>) -> Style {
     let color = match color.to_lowercase().as_str() {
         "black" => Black,
@@ -408,18 +523,37 @@ fn fixed_to_rgb_str(color: u8) -> &'static str {
     }
 }
 
-pub fn colorize_string(s: &str, color: Color, no_color: bool) -> String {
-    if no_color { s.to_string() } else { color.paint(s).to_string() }
+pub fn colorize_string(s: &str, color: impl Into<Style>, no_color: bool) -> String {
+    if no_color { s.to_string() } else { color.into().paint(s).to_string() }
 }
 
+/// Local date and time in the fixed `YYYY-MM-DD HH:MM` format, for templates and other machine-readable output.
 pub fn format_datetime(seconds: u64) -> String {
+    format_datetime_as(seconds, crate::i18n::DEFAULT_DATE_FORMAT)
+}
+
+/// Local date and time in the format of the output language, for displaying only.
+pub fn format_display_datetime(seconds: u64) -> String {
+    format_datetime_as(seconds, crate::i18n::date_format())
+}
+
+fn format_datetime_as(seconds: u64, format: &str) -> String {
     if seconds == 0 {
         return String::new();
     }
 
     let seconds = UNIX_EPOCH + Duration::from_secs(seconds);
     let datetime = DateTime::<Local>::from(seconds);
-    datetime.format("%Y-%m-%d %H:%M").to_string()
+    datetime.format(format).to_string()
+}
+
+pub fn format_duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m"),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
 }
 
 pub fn parse_date(date: Option<String>) -> Option<MappedLocalTime<DateTime<Local>>> {
@@ -444,6 +578,44 @@ pub fn read_from_pipe() -> Option<String> {
     }
 }
 
+pub fn read_from_file(path: &str) -> Option<String> {
+    match path {
+        "-" => read_from_pipe(),
+        path => std::fs::read_to_string(path).ok(),
+    }
+}
+
+pub fn unescape(s: &str) -> String {
+    s.replace("\\n", "\n").replace("\\t", "\t")
+}
+
+pub fn join_text(existing: &str, addition: &str, separator: &str, prepend: bool) -> String {
+    let existing = existing.trim_end_matches(['\r', '\n']);
+    let addition = addition.trim_end_matches(['\r', '\n']);
+
+    if existing.is_empty() {
+        return addition.to_string();
+    }
+
+    if addition.is_empty() {
+        return existing.to_string();
+    }
+
+    match prepend {
+        true => format!("{addition}{separator}{existing}"),
+        false => format!("{existing}{separator}{addition}"),
+    }
+}
+
+pub fn get_editor() -> String {
+    std::env::var("GIT_EDITOR")
+        .or_else(|_| gittask::get_config_value("core.editor"))
+        .or_else(|_| std::env::var("VISUAL"))
+        .or_else(|_| std::env::var("EDITOR"))
+        .or_else(|_| Ok::<String, VarError>("vi".to_string()))
+        .unwrap()
+}
+
 pub fn get_text_from_editor(text: Option<&String>) -> Option<String> {
     let tmp_file = tempfile::Builder::new().prefix("git-task").suffix(".txt").keep(true).tempfile().ok()?;
     let mut file = File::create(tmp_file.path()).unwrap();
@@ -452,14 +624,11 @@ pub fn get_text_from_editor(text: Option<&String>) -> Option<String> {
         write!(file, "{}", text).ok()?;
     }
 
-    let editor = std::env::var("GIT_EDITOR")
-        .or_else(|_| gittask::get_config_value("core.editor"))
-        .or_else(|_| std::env::var("VISUAL"))
-        .or_else(|_| std::env::var("EDITOR"))
-        .or_else(|_| Ok::<String, VarError>("vi".to_string()))
-        .unwrap();
+    let editor = split_args(&get_editor()).unwrap_or_default();
+    let (program, args) = editor.split_first().map(|(program, args)| (program.as_str(), args)).unwrap_or(("vi", &[]));
 
-    let mut status = Command::new(editor)
+    let mut status = Command::new(program)
+        .args(args)
         .arg(tmp_file.path().to_str()?)
         .status();
 
@@ -484,20 +653,274 @@ pub fn get_text_from_editor(text: Option<&String>) -> Option<String> {
     Some(contents)
 }
 
+/// Line-based diff of two texts, each line is marked with ' ', '-' or '+'.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<(char, &'a str)> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = match old[i] == new[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut result = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            result.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            result.push(('-', old[i]));
+            i += 1;
+        } else {
+            result.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    result
+}
+
+pub fn print_diff(old: &str, new: &str, no_color: bool) {
+    for (mark, line) in diff_lines(old, new) {
+        let line = format!("{mark} {line}");
+        match mark {
+            '-' => println!("{}", colorize_string(&line, Red, no_color)),
+            '+' => println!("{}", colorize_string(&line, Green, no_color)),
+            _ => println!("{}", colorize_string(&line, DarkGray, no_color)),
+        }
+    }
+}
+
+/// Failure categories, each reported with its own process exit code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    General,
+    NotFound,
+    Validation,
+    Remote,
+    Conflict,
+}
+
+impl ErrorKind {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ErrorKind::General => 1,
+            ErrorKind::NotFound => 3,
+            ErrorKind::Validation => 4,
+            ErrorKind::Remote => 5,
+            ErrorKind::Conflict => 6,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::General => "error",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Validation => "validation",
+            ErrorKind::Remote => "remote",
+            ErrorKind::Conflict => "conflict",
+        }
+    }
+}
+
+static EXIT_CODE: AtomicU8 = AtomicU8::new(1);
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+static COLOR_MODE: AtomicU8 = AtomicU8::new(0);
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// When to colorize the output, `--color` option.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ColorMode, String> {
+        match s {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            _ => Err(format!("Unknown color mode {s}, expected auto, always or never")),
+        }
+    }
+}
+
+pub fn set_color_mode(mode: ColorMode) {
+    COLOR_MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+fn get_color_mode() -> ColorMode {
+    match COLOR_MODE.load(Ordering::Relaxed) {
+        1 => ColorMode::Always,
+        2 => ColorMode::Never,
+        _ => ColorMode::Auto,
+    }
+}
+
+pub fn set_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+}
+
+/// ASCII-only output friendly to screen readers: `--plain` option or `task.output.ascii` parameter.
+pub fn is_plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Whether the output is colorized: an explicit `--color` wins, otherwise plain output, `NO_COLOR` and `color.ui` are respected
+/// and colors are on only when stdout is a terminal.
+pub fn is_color_enabled() -> bool {
+    let no_color_env = std::env::var("NO_COLOR").ok();
+    let color_ui = gittask::get_config_value("color.ui").ok();
+    resolve_color_mode(get_color_mode(), is_plain_output(), no_color_env.as_deref(), color_ui.as_deref(), std::io::stdout().is_terminal())
+}
+
+fn resolve_color_mode(mode: ColorMode, plain: bool, no_color_env: Option<&str>, color_ui: Option<&str>, is_terminal: bool) -> bool {
+    match mode {
+        ColorMode::Always => true,
+        ColorMode::Never => false,
+        ColorMode::Auto if plain => false,
+        ColorMode::Auto if no_color_env.is_some_and(|value| !value.is_empty()) => false,
+        ColorMode::Auto => match color_ui.map(str::to_lowercase).as_deref() {
+            Some("false" | "never" | "off" | "no") => false,
+            Some("always") => true,
+            _ => is_terminal,
+        },
+    }
+}
+
+pub fn set_json_errors(json: bool) {
+    JSON_ERRORS.store(json, Ordering::Relaxed);
+}
+
+/// Exit code of the most recently reported error.
+pub fn get_exit_code() -> u8 {
+    EXIT_CODE.load(Ordering::Relaxed)
+}
+
+/// Reports the exit code of a failed external command, e.g. a shell alias.
+pub fn set_exit_code(code: u8) {
+    EXIT_CODE.store(code, Ordering::Relaxed);
+}
+
 pub fn success_message(message: String) -> bool {
     println!("{message}");
     true
 }
 
 pub fn error_message(message: String) -> bool {
-    eprintln!("{message}");
+    let kind = match message.contains(gittask::CONFLICT_ERROR) {
+        true => ErrorKind::Conflict,
+        false => ErrorKind::General,
+    };
+    error_message_kind(kind, message)
+}
+
+pub fn error_message_kind(kind: ErrorKind, message: String) -> bool {
+    EXIT_CODE.store(kind.exit_code(), Ordering::Relaxed);
+    if JSON_ERRORS.load(Ordering::Relaxed) {
+        eprintln!("{}", format_json_error(kind, &message));
+    } else {
+        eprintln!("{message}");
+    }
     false
 }
 
+fn format_json_error(kind: ErrorKind, message: &str) -> String {
+    let message = message.strip_prefix("ERROR: ").unwrap_or(message);
+    serde_json::json!({ "error": { "kind": kind.name(), "code": kind.exit_code(), "message": message } }).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_valid_style() {
+        assert!(is_valid_style("bold"));
+        assert!(is_valid_style("dimmed, strikethrough"));
+        assert!(!is_valid_style("blinking"));
+        assert!(!is_valid_style(""));
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args("set 1 name \"Fix login page\"").unwrap(), vec!["set", "1", "name", "Fix login page"]);
+        assert_eq!(split_args("  comment add 2  'C:\\temp' ").unwrap(), vec!["comment", "add", "2", "C:\\temp"]);
+        assert_eq!(split_args("create Don\\'t \"\"").unwrap(), vec!["create", "Don't", ""]);
+        assert!(split_args("create \"Unfinished").is_err());
+        assert!(split_args("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_color_mode() {
+        assert!(resolve_color_mode(ColorMode::Always, false, Some("1"), Some("false"), false));
+        assert!(!resolve_color_mode(ColorMode::Never, false, None, Some("always"), true));
+        assert!(resolve_color_mode(ColorMode::Auto, false, None, None, true));
+        assert!(!resolve_color_mode(ColorMode::Auto, true, None, None, true));
+        assert!(resolve_color_mode(ColorMode::Always, true, None, None, true));
+        assert!(!resolve_color_mode(ColorMode::Auto, false, None, None, false));
+        assert!(!resolve_color_mode(ColorMode::Auto, false, Some("1"), None, true));
+        assert!(resolve_color_mode(ColorMode::Auto, false, Some(""), None, true));
+        assert!(!resolve_color_mode(ColorMode::Auto, false, None, Some("false"), true));
+        assert!(resolve_color_mode(ColorMode::Auto, false, None, Some("always"), false));
+        assert_eq!("never".parse::<ColorMode>(), Ok(ColorMode::Never));
+        assert!("sometimes".parse::<ColorMode>().is_err());
+    }
+
+    #[test]
+    fn test_is_valid_color() {
+        assert!(is_valid_color("LightBlue"));
+        assert!(is_valid_color("239"));
+        assert!(is_valid_color("Default"));
+        assert!(!is_valid_color("blurple"));
+    }
+
+    #[test]
+    fn test_format_json_error() {
+        assert_eq!(format_json_error(ErrorKind::NotFound, "Task ID 7 not found"), r#"{"error":{"code":3,"kind":"not_found","message":"Task ID 7 not found"}}"#);
+        assert_eq!(format_json_error(ErrorKind::General, "ERROR: boom"), r#"{"error":{"code":1,"kind":"error","message":"boom"}}"#);
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\nc", "a\nB\nc\nd"), vec![(' ', "a"), ('-', "b"), ('+', "B"), (' ', "c"), ('+', "d")]);
+        assert_eq!(diff_lines("", "new"), vec![('+', "new")]);
+        assert_eq!(diff_lines("same", "same"), vec![(' ', "same")]);
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        assert_eq!(format_datetime(0), "");
+        assert!(chrono::NaiveDateTime::parse_from_str(&format_datetime(1751364000), "%Y-%m-%d %H:%M").is_ok());
+        assert!(chrono::NaiveDateTime::parse_from_str(&format_datetime_as(1751364000, "%d.%m.%Y %H:%M"), "%d.%m.%Y %H:%M").is_ok());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(59), "0m");
+        assert_eq!(format_duration(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_duration(2 * 86400 + 7 * 3600 + 60), "2d 7h");
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("./src//frontend/"), "src/frontend");
+        assert_eq!(normalize_path("src\\backend"), "src/backend");
+        assert!(is_in_path("src/frontend/app", "src/frontend/"));
+        assert!(is_in_path("src/frontend", "src/frontend"));
+        assert!(!is_in_path("src/frontend-old", "src/frontend"));
+    }
+
     #[test]
     fn test_expand_range_single() {
         let input = vec!["1".to_string()];
@@ -580,6 +1003,56 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_suggest() {
+        assert_eq!(suggest("HIHG", &["LOW", "MEDIUM", "HIGH"]), Some("HIGH"));
+        assert_eq!(suggest("meduim", &["LOW", "MEDIUM", "HIGH"]), Some("MEDIUM"));
+        assert_eq!(suggest("urgent", &["LOW", "MEDIUM", "HIGH"]), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_edit_list_value() {
+        assert_eq!(edit_list_value("", "ui, api,ui", ListEdit::Set), Ok(r#"["ui","api"]"#.to_string()));
+        assert_eq!(edit_list_value(r#"["ui"]"#, "api", ListEdit::Add), Ok(r#"["ui","api"]"#.to_string()));
+        assert_eq!(edit_list_value("ui", r#"["ui"]"#, ListEdit::Add), Ok(r#"["ui"]"#.to_string()));
+        assert_eq!(edit_list_value(r#"["ui","api"]"#, "ui", ListEdit::Remove), Ok(r#"["api"]"#.to_string()));
+        assert!(edit_list_value("[broken", "ui", ListEdit::Add).is_err());
+    }
+
+    #[test]
+    fn test_parse_key_value() {
+        assert_eq!(parse_key_value("priority=HIGH"), Ok(("priority".to_string(), "HIGH".to_string())));
+        assert_eq!(parse_key_value("expr=a=b"), Ok(("expr".to_string(), "a=b".to_string())));
+        assert_eq!(parse_key_value("empty="), Ok(("empty".to_string(), "".to_string())));
+        assert!(parse_key_value("novalue").is_err());
+        assert!(parse_key_value("=value").is_err());
+    }
+
+    #[test]
+    fn test_join_text_append() {
+        let result = join_text("First line\n", "Second line\n", "\n", false);
+        assert_eq!(result, "First line\nSecond line".to_string());
+    }
+
+    #[test]
+    fn test_join_text_prepend() {
+        let result = join_text("First line", "Zero line", "\n---\n", true);
+        assert_eq!(result, "Zero line\n---\nFirst line".to_string());
+    }
+
+    #[test]
+    fn test_join_text_empty_existing() {
+        let result = join_text("", "New text", "\n", false);
+        assert_eq!(result, "New text".to_string());
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(unescape("\\n\\n"), "\n\n".to_string());
+        assert_eq!(unescape(" | "), " | ".to_string());
+    }
+
     #[test]
     fn test_capitalize_lowercase() {
         let input = "hello";
@@ -0,0 +1,203 @@
+use std::io::{stderr, BufRead, Write};
+
+use crate::property::{Property, PropertyEnumValue, PropertyManager, PropertyValueType};
+use crate::status::{Status, StatusManager};
+use crate::util::{is_valid_color, str_to_color};
+
+const COLORS: [&str; 12] = ["Red", "Yellow", "Green", "Blue", "Purple", "Cyan", "LightRed", "LightYellow", "LightGreen", "LightBlue", "LightPurple", "DarkGray"];
+
+/// Interactively defines statuses and common properties with previews of their colors,
+/// then saves them to `task.statuses` and `task.properties` if confirmed.
+pub fn run_wizard(input: &mut impl BufRead, no_color: bool) -> Result<bool, String> {
+    let mut wizard = Wizard { input, no_color };
+
+    let mut statuses = StatusManager::new().get_statuses().clone();
+    eprintln!("Current statuses:");
+    statuses.iter().for_each(|status| eprintln!("  {}", wizard.preview_status(status)));
+    if wizard.confirm("Define statuses from scratch?", false)? {
+        statuses = wizard.ask_statuses()?;
+    }
+
+    let current_properties = PropertyManager::new().get_properties().clone();
+    let properties = wizard.ask_properties(current_properties)?;
+
+    eprintln!("Statuses:");
+    statuses.iter().for_each(|status| eprintln!("  {}", wizard.preview_status(status)));
+    eprintln!("Properties:");
+    properties.iter().for_each(|property| eprintln!("  {}", wizard.preview_property(property)));
+    if !wizard.confirm("Save configuration?", true)? {
+        return Ok(false);
+    }
+
+    StatusManager::new().set_statuses(statuses)?;
+    PropertyManager::new().set_properties(properties)?;
+    Ok(true)
+}
+
+struct Wizard<'a, R: BufRead> {
+    input: &'a mut R,
+    no_color: bool,
+}
+
+impl<R: BufRead> Wizard<'_, R> {
+    fn ask(&mut self, question: &str, default: &str) -> Result<String, String> {
+        match default.is_empty() {
+            true => eprint!("{question}: "),
+            false => eprint!("{question} [{default}]: "),
+        }
+        let _ = stderr().flush();
+
+        let mut answer = String::new();
+        if self.input.read_line(&mut answer).map_err(|e| e.to_string())? == 0 {
+            return Err("Wizard cancelled".to_string());
+        }
+        match answer.trim() {
+            "" => Ok(default.to_string()),
+            answer => Ok(answer.to_string()),
+        }
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool, String> {
+        loop {
+            let answer = self.ask(&format!("{question} {}", if default { "[Y/n]" } else { "[y/N]" }), "")?;
+            match answer.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => {},
+            }
+        }
+    }
+
+    fn ask_color(&mut self, question: &str, default: &str) -> Result<String, String> {
+        loop {
+            let color = self.ask(question, default)?;
+            match is_valid_color(&color) {
+                true => return Ok(color),
+                false => eprintln!("Unknown color {color}, use a name, a number 0-255 or #RRGGBB"),
+            }
+        }
+    }
+
+    fn ask_statuses(&mut self) -> Result<Vec<Status>, String> {
+        eprintln!("Colors: {}, also 0-255 and #RRGGBB", COLORS.iter().map(|color| self.paint(color, color)).collect::<Vec<_>>().join(" "));
+
+        let mut statuses: Vec<Status> = vec![];
+        loop {
+            let name = self.ask(&format!("Status #{} name (empty to finish)", statuses.len() + 1), "")?;
+            if name.is_empty() {
+                match statuses.iter().filter(|status| *status.is_done()).count() {
+                    _ if statuses.len() < 2 => eprintln!("Define at least two statuses"),
+                    0 => eprintln!("Define at least one final status"),
+                    _ => return Ok(statuses),
+                }
+                continue;
+            }
+            if name.contains(',') || statuses.iter().any(|status| status.get_name() == name) {
+                eprintln!("Status name must be unique and can't contain comma");
+                continue;
+            }
+
+            let default_shortcut = name.chars().next().map(|c| c.to_lowercase().to_string())
+                .filter(|shortcut| statuses.iter().all(|status| status.get_shortcut() != shortcut))
+                .unwrap_or_default();
+            let shortcut = loop {
+                let shortcut = self.ask("  Shortcut", &default_shortcut)?;
+                match shortcut.is_empty() || shortcut.contains(',') || statuses.iter().any(|status| status.get_shortcut() == shortcut) {
+                    true => eprintln!("Shortcut must be unique and can't be empty or contain comma"),
+                    false => break shortcut,
+                }
+            };
+            let color = self.ask_color("  Color", COLORS[statuses.len() % COLORS.len()])?;
+            let is_done = self.confirm("  Is it a final status?", false)?;
+
+            let status = Status::new(name, shortcut, color, is_done);
+            eprintln!("  {}", self.preview_status(&status));
+            statuses.push(status);
+        }
+    }
+
+    fn ask_properties(&mut self, mut properties: Vec<Property>) -> Result<Vec<Property>, String> {
+        for (property, description) in get_common_properties() {
+            if properties.iter().any(|existing| existing.get_name() == property.get_name()) {
+                continue;
+            }
+            if self.confirm(&format!("Add property {}, {description}?", self.preview_property(&property)), false)? {
+                properties.push(property);
+            }
+        }
+
+        loop {
+            let name = self.ask("Custom property name (empty to finish)", "")?;
+            if name.is_empty() {
+                return Ok(properties);
+            }
+            if ["id", "labels"].contains(&name.to_lowercase().as_str()) || properties.iter().any(|property| property.get_name() == name) {
+                eprintln!("Property {name} already exists or is reserved");
+                continue;
+            }
+
+            let value_type = loop {
+                match self.ask("  Type (string, text, integer, datetime, list)", "string")?.parse::<PropertyValueType>() {
+                    Ok(value_type) => break value_type,
+                    Err(e) => eprintln!("{e}"),
+                }
+            };
+            let color = self.ask_color("  Color", "Default")?;
+
+            let property = Property::new(name, value_type, color, None);
+            eprintln!("  {}", self.preview_property(&property));
+            properties.push(property);
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        match self.no_color {
+            true => text.to_string(),
+            false => str_to_color(color, &None).paint(text).to_string(),
+        }
+    }
+
+    fn preview_status(&self, status: &Status) -> String {
+        let done = if *status.is_done() { ", final" } else { "" };
+        format!("{} ({}{done})", self.paint(status.get_name(), status.get_color()), status.get_shortcut())
+    }
+
+    fn preview_property(&self, property: &Property) -> String {
+        let enum_values = property.get_enum_values().iter().flatten()
+            .map(|enum_value| self.paint(enum_value.get_name(), enum_value.get_color()))
+            .collect::<Vec<_>>();
+        match enum_values.is_empty() {
+            true => format!("{} ({})", self.paint(property.get_name(), property.get_color()), property.get_value_type()),
+            false => format!("{} ({}: {})", self.paint(property.get_name(), property.get_color()), property.get_value_type(), enum_values.join(" ")),
+        }
+    }
+}
+
+fn get_common_properties() -> Vec<(Property, &'static str)> {
+    let enum_values = [("LOW", "Green"), ("MEDIUM", "Yellow"), ("HIGH", "Red")]
+        .map(|(name, color)| PropertyEnumValue::new(name.to_string(), color.to_string()))
+        .to_vec();
+    vec![
+        (Property::new("priority".to_string(), PropertyValueType::String, "Default".to_string(), Some(enum_values)), "importance of the task"),
+        (Property::new("assignee".to_string(), PropertyValueType::String, "Cyan".to_string(), None), "who works on the task"),
+        (Property::new("due".to_string(), PropertyValueType::String, "Purple".to_string(), None), "due date"),
+        (Property::new("type".to_string(), PropertyValueType::String, "Blue".to_string(), None), "bug, feature, etc."),
+        (Property::new("estimate".to_string(), PropertyValueType::Integer, "Default".to_string(), None), "effort in hours"),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ask_statuses() {
+        let mut input = "TODO\n\n\nn\nDONE\nt\nx\nBlue\ny\n\n".as_bytes();
+        let mut wizard = Wizard { input: &mut input, no_color: true };
+        let statuses = wizard.ask_statuses().unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!((statuses[0].get_name(), statuses[0].get_shortcut(), statuses[0].get_color(), *statuses[0].is_done()), ("TODO", "t", "Red", false));
+        assert_eq!((statuses[1].get_name(), statuses[1].get_shortcut(), statuses[1].get_color(), *statuses[1].is_done()), ("DONE", "x", "Blue", true));
+    }
+}